@@ -0,0 +1,152 @@
+//! Changed-path Bloom filters, which allow to quickly determine that a commit did not change a path.
+//!
+//! Each filter is computed from the paths of all files changed between a commit and its first parent, including all of their
+//! leading directories. A filter never fails to report a path that was changed, but may claim that a path was changed even though it
+//! was not.
+use bstr::{BStr, ByteSlice};
+
+/// The amount of bits to set for each path added to a filter.
+pub const DEFAULT_NUM_HASHES: u32 = 7;
+/// The amount of bits reserved in a filter for each changed path.
+pub const DEFAULT_BITS_PER_ENTRY: u32 = 10;
+/// The amount of changed paths at which a filter is replaced by one that contains all paths.
+pub const MAX_CHANGED_PATHS: usize = 512;
+
+const SEED0: u32 = 0x293a_e76f;
+const SEED1: u32 = 0x7e64_6e2c;
+const BITS_PER_WORD: u64 = 8;
+
+/// The parameters used to compute all filters of a commit-graph file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    /// The version of the hash function used to compute keys.
+    ///
+    /// Version 1 hashes bytes with the high bit set as if they were sign-extended, just like git does on most platforms,
+    /// while version 2 hashes them correctly. Paths consisting only of ASCII characters hash the same in both versions.
+    pub hash_version: u32,
+    /// The amount of bits set for each path.
+    pub num_hashes: u32,
+    /// The amount of bits reserved in a filter for each changed path.
+    pub bits_per_entry: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            hash_version: 1,
+            num_hashes: DEFAULT_NUM_HASHES,
+            bits_per_entry: DEFAULT_BITS_PER_ENTRY,
+        }
+    }
+}
+
+/// The hashes of a path for lookup in [filters][Filter].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key for `path` using the given `settings`.
+    pub fn new(path: &BStr, settings: &Settings) -> Self {
+        let hash0 = murmur3(SEED0, path, settings.hash_version);
+        let hash1 = murmur3(SEED1, path, settings.hash_version);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|i| hash0.wrapping_add(i.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+
+    /// Compute the keys for `path` and all of its leading directories, with `path` first.
+    pub fn with_leading_directories(path: &BStr, settings: &Settings) -> Vec<Self> {
+        let mut keys = vec![Key::new(path, settings)];
+        let mut path = path.as_bytes();
+        while let Some(pos) = path.rfind_byte(b'/') {
+            path = &path[..pos];
+            keys.push(Key::new(path.as_bstr(), settings));
+        }
+        keys
+    }
+}
+
+/// A changed-path Bloom filter of a single commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Filter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Filter<'a> {
+    /// Create a new instance from the raw `data` of a filter.
+    pub fn from_bytes(data: &'a [u8]) -> Self {
+        Filter { data }
+    }
+
+    /// Return the raw data of this filter.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Return false if the path that `key` was computed for was definitely not changed, or true if it might have been changed.
+    pub fn contains(&self, key: &Key) -> bool {
+        if self.data.is_empty() {
+            return true;
+        }
+        let num_bits = self.data.len() as u64 * BITS_PER_WORD;
+        key.hashes.iter().all(|hash| {
+            let bit = u64::from(*hash) % num_bits;
+            self.data[(bit / BITS_PER_WORD) as usize] & (1 << (bit % BITS_PER_WORD)) != 0
+        })
+    }
+}
+
+/// Compute the data of a filter for the given `paths`, which must be unique and include all leading directories of changed files.
+pub fn filter_data<'a>(paths: impl ExactSizeIterator<Item = &'a BStr>, settings: &Settings) -> Vec<u8> {
+    if paths.len() > MAX_CHANGED_PATHS {
+        return vec![0xff];
+    }
+    let num_bits = paths.len() as u64 * u64::from(settings.bits_per_entry);
+    let num_bytes = num_bits.saturating_sub(1) / BITS_PER_WORD + 1;
+    let mut data = vec![0u8; num_bytes as usize];
+    let num_bits = data.len() as u64 * BITS_PER_WORD;
+    for path in paths {
+        for hash in Key::new(path, settings).hashes {
+            let bit = u64::from(hash) % num_bits;
+            data[(bit / BITS_PER_WORD) as usize] |= 1 << (bit % BITS_PER_WORD);
+        }
+    }
+    data
+}
+
+/// The 32 bit variant of the murmur3 hash as used by git, with `version` 1 reproducing git's handling of bytes with the high bit set.
+fn murmur3(mut seed: u32, data: &[u8], version: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let byte = |b: u8| if version == 1 { b as i8 as u32 } else { u32::from(b) };
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in chunks.by_ref() {
+        let mut k = byte(chunk[0]) | byte(chunk[1]) << 8 | byte(chunk[2]) << 16 | byte(chunk[3]) << 24;
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        seed ^= k;
+        seed = seed.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0u32;
+        for (index, b) in tail.iter().enumerate().rev() {
+            k ^= byte(*b) << (8 * index);
+        }
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        seed ^= k;
+    }
+
+    seed ^= data.len() as u32;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85eb_ca6b);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xc2b2_ae35);
+    seed ^= seed >> 16;
+    seed
+}
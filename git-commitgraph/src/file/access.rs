@@ -1,7 +1,11 @@
-use crate::file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE};
+use crate::{
+    bloom,
+    file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE, GENERATION_DATA_OVERFLOW_ENTRY_SIZE},
+};
+use byteorder::{BigEndian, ByteOrder};
 use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
 use std::{
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     fmt::{Debug, Formatter},
     path::Path,
 };
@@ -13,6 +17,35 @@ impl File {
         self.base_graph_count
     }
 
+    /// Returns the changed-path Bloom filter of the commit located at the given lexigraphical position, or `None` if this file
+    /// doesn't contain Bloom filters or the filter is out of bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds.
+    pub fn bloom_filter_at(&self, pos: file::Position) -> Option<bloom::Filter<'_>> {
+        assert!(
+            pos.0 < self.num_commits(),
+            "expected lexigraphical position less than {}, got {}",
+            self.num_commits(),
+            pos.0
+        );
+        let filters = self.bloom_filters.as_ref()?;
+        let end_at = |pos: u32| {
+            let start = filters.index_offset + pos as usize * 4;
+            BigEndian::read_u32(&self.data[start..start + 4]) as usize
+        };
+        let start = if pos.0 == 0 { 0 } else { end_at(pos.0 - 1) };
+        let end = end_at(pos.0);
+        let data = &self.data[filters.data_range.clone()];
+        data.get(start..end).map(bloom::Filter::from_bytes)
+    }
+
+    /// The settings used to compute the changed-path Bloom filters in this file, if it contains any.
+    pub fn bloom_filter_settings(&self) -> Option<bloom::Settings> {
+        self.bloom_filters.as_ref().map(|f| f.settings)
+    }
+
     /// Returns the commit data for the commit located at the given lexigraphical position.
     ///
     /// `pos` must range from 0 to self.num_commits().
@@ -24,6 +57,13 @@ impl File {
         Commit::new(self, pos)
     }
 
+    /// Returns true if this file stores corrected commit dates, i.e. generation numbers of version 2, for all of its commits.
+    ///
+    /// See [`Commit::corrected_commit_date()`] for details.
+    pub fn has_generation_data(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// The kind of hash used in this File.
     ///
     /// Note that it is always conforming to the hash used in the owning repository.
@@ -125,6 +165,27 @@ impl File {
         &self.data[start..start + COMMIT_DATA_ENTRY_SIZE]
     }
 
+    /// Returns the entry of the commit at `pos` in this file's Generation Data (GDA2) chunk, if there is one.
+    pub(crate) fn generation_data_at(&self, pos: file::Position) -> Option<u32> {
+        let pos: usize = pos
+            .0
+            .try_into()
+            .expect("an architecture able to hold 32 bits of integer");
+        let start = self.generation_data_offset? + pos * 4;
+        Some(BigEndian::read_u32(&self.data[start..start + 4]))
+    }
+
+    /// Returns the entry at `index` in this file's Generation Data Overflow (GDO2) chunk, if there is one.
+    pub(crate) fn generation_data_overflow_at(&self, index: u32) -> Option<u64> {
+        let overflow_data = &self.data[self.generation_data_overflow_range.clone()?];
+        let start = usize::try_from(index)
+            .ok()?
+            .checked_mul(GENERATION_DATA_OVERFLOW_ENTRY_SIZE)?;
+        overflow_data
+            .get(start..start + GENERATION_DATA_OVERFLOW_ENTRY_SIZE)
+            .map(BigEndian::read_u64)
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
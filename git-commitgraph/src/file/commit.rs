@@ -1,5 +1,6 @@
 //! Low-level operations on individual commits.
 use crate::{
+    bloom,
     file::{self, File},
     graph,
 };
@@ -19,6 +20,8 @@ pub enum Error {
     ExtraEdgesListOverflow(git_hash::ObjectId),
     #[error("commit {0}'s first parent is an extra edge index, which is invalid")]
     FirstParentIsExtraEdgeIndex(git_hash::ObjectId),
+    #[error("commit {0}'s corrected commit date overflows the commit-graph file's generation data overflow list")]
+    GenerationDataOverflow(git_hash::ObjectId),
     #[error("commit {0} has extra edges, but commit-graph file has no extra edges list")]
     MissingExtraEdgesList(git_hash::ObjectId),
     #[error("commit {0} has a second parent but not a first parent")]
@@ -27,8 +30,10 @@ pub enum Error {
 
 // Note that git's commit-graph-format.txt as of v2.28.0 gives an incorrect value 0x0700_0000 for
 // NO_PARENT. Fixed in https://github.com/git/git/commit/4d515253afcef985e94400adbfed7044959f9121 .
-const NO_PARENT: u32 = 0x7000_0000;
-const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
+pub(crate) const NO_PARENT: u32 = 0x7000_0000;
+pub(crate) const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
+pub(crate) const CORRECTED_COMMIT_DATE_OFFSET_OVERFLOW: u32 = 0x8000_0000;
+pub(crate) const CORRECTED_COMMIT_DATE_OFFSET_MAX: u64 = 0x7fff_ffff;
 
 /// A commit as stored in a [`File`].
 pub struct Commit<'a> {
@@ -56,6 +61,41 @@ impl<'a> Commit<'a> {
         }
     }
 
+    /// Returns the changed-path Bloom filter of this commit, if the file containing it has Bloom filters.
+    pub fn bloom_filter(&self) -> Option<bloom::Filter<'a>> {
+        self.file.bloom_filter_at(self.pos)
+    }
+
+    /// Returns the settings used to compute the [changed-path Bloom filter][Commit::bloom_filter()] of this commit, if the
+    /// file containing it has Bloom filters.
+    pub fn bloom_filter_settings(&self) -> Option<bloom::Settings> {
+        self.file.bloom_filter_settings()
+    }
+
+    /// Returns the corrected commit date of this commit, also known as generation number of version 2, or `None` if the file
+    /// containing it doesn't store them.
+    ///
+    /// The corrected commit date of a commit is the larger one of its [committer timestamp][Commit::committer_timestamp()]
+    /// and the corrected commit dates of its parents + 1. Unlike [topological levels][Commit::generation()] they remain
+    /// meaningful for pruning traversals even if committer clocks are skewed.
+    ///
+    /// Note that corrected commit dates are only comparable between commits of a [`Graph`][crate::Graph] whose files
+    /// [all have them][crate::Graph::has_generation_data()].
+    pub fn corrected_commit_date(&self) -> Result<Option<u64>, Error> {
+        let offset = match self.file.generation_data_at(self.pos) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let offset = if offset & CORRECTED_COMMIT_DATE_OFFSET_OVERFLOW != 0 {
+            self.file
+                .generation_data_overflow_at(offset & !CORRECTED_COMMIT_DATE_OFFSET_OVERFLOW)
+                .ok_or_else(|| Error::GenerationDataOverflow(self.id().into()))?
+        } else {
+            u64::from(offset)
+        };
+        Ok(Some(self.commit_timestamp.saturating_add(offset)))
+    }
+
     /// Returns the committer timestamp of this commit.
     ///
     /// The value is the number of seconds since 1970-01-01 00:00:00 UTC.
@@ -63,10 +103,12 @@ impl<'a> Commit<'a> {
         self.commit_timestamp
     }
 
-    /// Returns the generation number of this commit.
+    /// Returns the generation number of this commit, also known as its topological level.
     ///
     /// Commits without parents have generation number 1. Commits with parents have a generation
     /// number that is the max of their parents' generation numbers + 1.
+    ///
+    /// See [`corrected_commit_date()`][Commit::corrected_commit_date()] for generation numbers of version 2.
     pub fn generation(&self) -> u32 {
         self.generation
     }
@@ -238,7 +280,7 @@ impl ParentEdge {
     }
 }
 
-const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
+pub(crate) const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
 
 enum ExtraEdge {
     Internal(graph::Position),
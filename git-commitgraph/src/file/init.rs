@@ -1,4 +1,7 @@
-use crate::file::{File, COMMIT_DATA_ENTRY_SIZE, FAN_LEN, SIGNATURE};
+use crate::{
+    bloom,
+    file::{BloomFilters, File, COMMIT_DATA_ENTRY_SIZE, FAN_LEN, GENERATION_DATA_OVERFLOW_ENTRY_SIZE, SIGNATURE},
+};
 use bstr::ByteSlice;
 use byteorder::{BigEndian, ByteOrder};
 use filebuffer::FileBuffer;
@@ -9,7 +12,7 @@ use std::{
     path::Path,
 };
 
-type ChunkId = [u8; 4];
+pub(crate) type ChunkId = [u8; 4];
 
 /// The error used in [`File::at()`].
 #[derive(thiserror::Error, Debug)]
@@ -49,20 +52,27 @@ pub enum Error {
     UnsupportedVersion(u8),
 }
 
-const CHUNK_LOOKUP_SIZE: usize = 12;
-const HEADER_LEN: usize = 8;
+pub(crate) const CHUNK_LOOKUP_SIZE: usize = 12;
+pub(crate) const HEADER_LEN: usize = 8;
 const TRAILER_LEN: usize = SHA1_SIZE;
 const MIN_FILE_SIZE: usize = HEADER_LEN + ((MIN_CHUNKS + 1) * CHUNK_LOOKUP_SIZE) + TRAILER_LEN;
 const OID_LOOKUP_ENTRY_SIZE: usize = SHA1_SIZE;
 
 // Required chunks: OIDF, OIDL, CDAT
 const MIN_CHUNKS: usize = 3;
-const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
-const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
-const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
-const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
-const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
-const SENTINEL_CHUNK_ID: ChunkId = [0u8; 4];
+pub(crate) const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
+pub(crate) const BLOOM_FILTER_INDEX_CHUNK_ID: ChunkId = *b"BIDX";
+pub(crate) const BLOOM_FILTER_DATA_CHUNK_ID: ChunkId = *b"BDAT";
+pub(crate) const BLOOM_FILTER_DATA_HEADER_LEN: usize = 12;
+pub(crate) const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
+pub(crate) const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
+// Git 2.36 and later ignore the `GDAT` and `GDOV` chunks written by earlier versions, as their overflow entries can be
+// incorrect, and use these instead. We do the same.
+pub(crate) const GENERATION_DATA_CHUNK_ID: ChunkId = *b"GDA2";
+pub(crate) const GENERATION_DATA_OVERFLOW_CHUNK_ID: ChunkId = *b"GDO2";
+pub(crate) const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
+pub(crate) const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
+pub(crate) const SENTINEL_CHUNK_ID: ChunkId = [0u8; 4];
 
 impl File {
     /// Try to parse the commit graph file at `path`.
@@ -127,10 +137,16 @@ impl TryFrom<&Path> for File {
         }
 
         let mut base_graphs_list_offset: Option<usize> = None;
+        let mut bloom_filter_index_offset: Option<usize> = None;
+        let mut bloom_filter_index_count = 0u32;
+        let mut bloom_filter_data: Option<(Range<usize>, bloom::Settings)> = None;
         let mut commit_data_offset: Option<usize> = None;
         let mut commit_data_count = 0u32;
         let mut extra_edges_list_range: Option<Range<usize>> = None;
         let mut fan_offset: Option<usize> = None;
+        let mut generation_data_offset: Option<usize> = None;
+        let mut generation_data_count = 0u32;
+        let mut generation_data_overflow_range: Option<Range<usize>> = None;
         let mut oid_lookup_offset: Option<usize> = None;
         let mut oid_lookup_count = 0u32;
 
@@ -193,6 +209,44 @@ impl TryFrom<&Path> for File {
                     }
                     base_graphs_list_offset = Some(chunk_offset);
                 }
+                BLOOM_FILTER_INDEX_CHUNK_ID => {
+                    if bloom_filter_index_offset.is_some() {
+                        return Err(Error::DuplicateChunk(chunk_id));
+                    }
+                    if chunk_size % 4 != 0 {
+                        return Err(Error::InvalidChunkSize {
+                            id: chunk_id,
+                            msg: format!("chunk size {} is not a multiple of 4", chunk_size),
+                        });
+                    }
+                    bloom_filter_index_offset = Some(chunk_offset);
+                    bloom_filter_index_count = (chunk_size / 4)
+                        .try_into()
+                        .expect("number of commits in BIDX chunk to fit in 32 bits");
+                }
+                BLOOM_FILTER_DATA_CHUNK_ID => {
+                    if bloom_filter_data.is_some() {
+                        return Err(Error::DuplicateChunk(chunk_id));
+                    }
+                    if chunk_size < BLOOM_FILTER_DATA_HEADER_LEN {
+                        return Err(Error::InvalidChunkSize {
+                            id: chunk_id,
+                            msg: format!(
+                                "chunk size {} is too small to hold the header of {} bytes",
+                                chunk_size, BLOOM_FILTER_DATA_HEADER_LEN
+                            ),
+                        });
+                    }
+                    let header = &data[chunk_offset..chunk_offset + BLOOM_FILTER_DATA_HEADER_LEN];
+                    bloom_filter_data = Some((
+                        chunk_offset + BLOOM_FILTER_DATA_HEADER_LEN..next_chunk_offset,
+                        bloom::Settings {
+                            hash_version: BigEndian::read_u32(&header[..4]),
+                            num_hashes: BigEndian::read_u32(&header[4..8]),
+                            bits_per_entry: BigEndian::read_u32(&header[8..12]),
+                        },
+                    ));
+                }
                 COMMIT_DATA_CHUNK_ID => {
                     if commit_data_offset.is_some() {
                         return Err(Error::DuplicateChunk(chunk_id));
@@ -221,6 +275,36 @@ impl TryFrom<&Path> for File {
                         end: next_chunk_offset,
                     })
                 }
+                GENERATION_DATA_CHUNK_ID => {
+                    if generation_data_offset.is_some() {
+                        return Err(Error::DuplicateChunk(chunk_id));
+                    }
+                    if chunk_size % 4 != 0 {
+                        return Err(Error::InvalidChunkSize {
+                            id: chunk_id,
+                            msg: format!("chunk size {} is not a multiple of 4", chunk_size),
+                        });
+                    }
+                    generation_data_offset = Some(chunk_offset);
+                    generation_data_count = (chunk_size / 4)
+                        .try_into()
+                        .expect("number of commits in GDA2 chunk to fit in 32 bits");
+                }
+                GENERATION_DATA_OVERFLOW_CHUNK_ID => {
+                    if generation_data_overflow_range.is_some() {
+                        return Err(Error::DuplicateChunk(chunk_id));
+                    }
+                    if chunk_size % GENERATION_DATA_OVERFLOW_ENTRY_SIZE != 0 {
+                        return Err(Error::InvalidChunkSize {
+                            id: chunk_id,
+                            msg: format!(
+                                "chunk size {} is not a multiple of {}",
+                                chunk_size, GENERATION_DATA_OVERFLOW_ENTRY_SIZE
+                            ),
+                        });
+                    }
+                    generation_data_overflow_range = Some(chunk_offset..next_chunk_offset);
+                }
                 OID_FAN_CHUNK_ID => {
                     if fan_offset.is_some() {
                         return Err(Error::DuplicateChunk(chunk_id));
@@ -306,13 +390,43 @@ impl TryFrom<&Path> for File {
                 chunk2_commits: commit_data_count,
             });
         }
+        if generation_data_offset.is_some() && generation_data_count != fan[255] {
+            return Err(Error::CommitCountMismatch {
+                chunk1_id: OID_FAN_CHUNK_ID,
+                chunk1_commits: fan[255],
+                chunk2_id: GENERATION_DATA_CHUNK_ID,
+                chunk2_commits: generation_data_count,
+            });
+        }
+        if bloom_filter_index_offset.is_some() && bloom_filter_index_count != fan[255] {
+            return Err(Error::CommitCountMismatch {
+                chunk1_id: OID_FAN_CHUNK_ID,
+                chunk1_commits: fan[255],
+                chunk2_id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                chunk2_commits: bloom_filter_index_count,
+            });
+        }
+        // Like git, ignore Bloom filters unless both chunks are present and use a hash version we know.
+        let bloom_filters = match (bloom_filter_index_offset, bloom_filter_data) {
+            (Some(index_offset), Some((data_range, settings))) if matches!(settings.hash_version, 1 | 2) => {
+                Some(BloomFilters {
+                    index_offset,
+                    data_range,
+                    settings,
+                })
+            }
+            _ => None,
+        };
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filters,
             commit_data_offset,
             data,
             extra_edges_list_range,
             fan,
+            generation_data_offset,
+            generation_data_overflow_range,
             oid_lookup_offset,
             path: path.to_owned(),
         })
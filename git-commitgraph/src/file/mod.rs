@@ -7,6 +7,7 @@ pub use commit::Commit;
 
 mod init;
 pub mod verify;
+pub(crate) mod write;
 
 pub use init::Error;
 
@@ -20,6 +21,7 @@ use std::{
 
 const COMMIT_DATA_ENTRY_SIZE: usize = SHA1_SIZE + 16;
 const FAN_LEN: usize = 256;
+const GENERATION_DATA_OVERFLOW_ENTRY_SIZE: usize = 8;
 const SIGNATURE: &[u8] = b"CGPH";
 
 /// A single commit-graph file.
@@ -29,14 +31,24 @@ const SIGNATURE: &[u8] = b"CGPH";
 pub struct File {
     base_graph_count: u8,
     base_graphs_list_offset: Option<usize>,
+    bloom_filters: Option<BloomFilters>,
     commit_data_offset: usize,
     data: FileBuffer,
     extra_edges_list_range: Option<Range<usize>>,
     fan: [u32; FAN_LEN],
+    generation_data_offset: Option<usize>,
+    generation_data_overflow_range: Option<Range<usize>>,
     oid_lookup_offset: usize,
     path: PathBuf,
 }
 
+/// The location of the changed-path Bloom filters within a [`File`].
+struct BloomFilters {
+    index_offset: usize,
+    data_range: Range<usize>,
+    settings: crate::bloom::Settings,
+}
+
 /// The position of a given commit within a graph file, starting at 0.
 ///
 /// Commits within a graph file are sorted in lexicographical order by OID; a commit's lexigraphical position
@@ -0,0 +1,213 @@
+use crate::{
+    bloom,
+    file::{
+        commit::{
+            CORRECTED_COMMIT_DATE_OFFSET_MAX, CORRECTED_COMMIT_DATE_OFFSET_OVERFLOW, EXTENDED_EDGES_MASK,
+            LAST_EXTENDED_EDGE_MASK, NO_PARENT,
+        },
+        init::{
+            ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_DATA_HEADER_LEN,
+            BLOOM_FILTER_INDEX_CHUNK_ID, CHUNK_LOOKUP_SIZE, COMMIT_DATA_CHUNK_ID, EXTENDED_EDGES_LIST_CHUNK_ID,
+            GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN, OID_FAN_CHUNK_ID,
+            OID_LOOKUP_CHUNK_ID, SENTINEL_CHUNK_ID,
+        },
+        COMMIT_DATA_ENTRY_SIZE, FAN_LEN, GENERATION_DATA_OVERFLOW_ENTRY_SIZE, SIGNATURE,
+    },
+    graph,
+};
+use git_hash::{ObjectId, SIZE_OF_SHA1_DIGEST as SHA1_SIZE};
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+/// A commit to be written into a commit-graph file.
+pub(crate) struct Entry {
+    pub id: ObjectId,
+    pub root_tree_id: ObjectId,
+    /// The positions of all parents in the graph that the written file will be a part of.
+    pub parents: Vec<graph::Position>,
+    pub generation: u32,
+    pub commit_timestamp: u64,
+    /// The corrected commit date, only used if generation data is written.
+    pub corrected_commit_date: u64,
+    /// The data of the changed-path Bloom filter, only used if Bloom filters are written.
+    pub bloom_filter: Vec<u8>,
+}
+
+/// Write a commit-graph file with all `entries`, which must be sorted by id, to `out`,
+/// based on the graph files identified by their checksums in `base_graphs`.
+/// If `generation_data` is true, the corrected commit dates of all entries are written as well, and if `bloom_settings`
+/// are set, the same is true for their changed-path Bloom filters.
+///
+/// Returns the checksum of the written file.
+pub(crate) fn write_to(
+    entries: &[Entry],
+    generation_data: bool,
+    bloom_settings: Option<&bloom::Settings>,
+    base_graphs: &[ObjectId],
+    out: impl io::Write,
+) -> io::Result<ObjectId> {
+    let extra_edges: usize = entries
+        .iter()
+        .filter(|e| e.parents.len() > 2)
+        .map(|e| e.parents.len() - 1)
+        .sum();
+    let generation_data_overflows = if generation_data {
+        entries
+            .iter()
+            .filter(|e| e.corrected_commit_date - e.commit_timestamp > CORRECTED_COMMIT_DATE_OFFSET_MAX)
+            .count()
+    } else {
+        0
+    };
+    let mut chunks: Vec<(ChunkId, usize)> = vec![
+        (OID_FAN_CHUNK_ID, FAN_LEN * 4),
+        (OID_LOOKUP_CHUNK_ID, entries.len() * SHA1_SIZE),
+        (COMMIT_DATA_CHUNK_ID, entries.len() * COMMIT_DATA_ENTRY_SIZE),
+    ];
+    if generation_data {
+        chunks.push((GENERATION_DATA_CHUNK_ID, entries.len() * 4));
+    }
+    if generation_data_overflows != 0 {
+        chunks.push((
+            GENERATION_DATA_OVERFLOW_CHUNK_ID,
+            generation_data_overflows * GENERATION_DATA_OVERFLOW_ENTRY_SIZE,
+        ));
+    }
+    if extra_edges != 0 {
+        chunks.push((EXTENDED_EDGES_LIST_CHUNK_ID, extra_edges * 4));
+    }
+    if bloom_settings.is_some() {
+        chunks.push((BLOOM_FILTER_INDEX_CHUNK_ID, entries.len() * 4));
+        chunks.push((
+            BLOOM_FILTER_DATA_CHUNK_ID,
+            BLOOM_FILTER_DATA_HEADER_LEN + entries.iter().map(|e| e.bloom_filter.len()).sum::<usize>(),
+        ));
+    }
+    if !base_graphs.is_empty() {
+        chunks.push((BASE_GRAPHS_LIST_CHUNK_ID, base_graphs.len() * SHA1_SIZE));
+    }
+
+    let mut out = HashWrite {
+        inner: out,
+        hash: git_features::hash::Sha1::default(),
+    };
+    out.write_all(SIGNATURE)?;
+    out.write_all(&[
+        1, /* version */
+        1, /* hash version */
+        chunks.len() as u8,
+        u8::try_from(base_graphs.len()).expect("at most 255 base graphs"),
+    ])?;
+
+    let mut offset = (HEADER_LEN + (chunks.len() + 1) * CHUNK_LOOKUP_SIZE) as u64;
+    for (id, size) in chunks.iter() {
+        out.write_all(id)?;
+        out.write_all(&offset.to_be_bytes())?;
+        offset += *size as u64;
+    }
+    out.write_all(&SENTINEL_CHUNK_ID)?;
+    out.write_all(&offset.to_be_bytes())?;
+
+    let mut fan = [0u32; FAN_LEN];
+    for entry in entries {
+        fan[usize::from(entry.id.first_byte())] += 1;
+    }
+    let mut count = 0;
+    for slot in fan.iter_mut() {
+        count += *slot;
+        out.write_all(&count.to_be_bytes())?;
+    }
+
+    for entry in entries {
+        out.write_all(entry.id.as_slice())?;
+    }
+
+    let mut extra_edge_index = 0u32;
+    for entry in entries {
+        out.write_all(entry.root_tree_id.as_slice())?;
+        let parent1 = entry.parents.first().map(|p| p.0).unwrap_or(NO_PARENT);
+        let parent2 = match entry.parents.len() {
+            0 | 1 => NO_PARENT,
+            2 => entry.parents[1].0,
+            num_parents => {
+                let index = extra_edge_index | EXTENDED_EDGES_MASK;
+                extra_edge_index += num_parents as u32 - 1;
+                index
+            }
+        };
+        out.write_all(&parent1.to_be_bytes())?;
+        out.write_all(&parent2.to_be_bytes())?;
+        let generation_and_time = (u64::from(entry.generation) << 34) | (entry.commit_timestamp & 0x0003_ffff_ffff);
+        out.write_all(&generation_and_time.to_be_bytes())?;
+    }
+
+    if generation_data {
+        let mut overflow_index = 0u32;
+        for entry in entries {
+            let offset = entry.corrected_commit_date - entry.commit_timestamp;
+            let offset = if offset > CORRECTED_COMMIT_DATE_OFFSET_MAX {
+                overflow_index += 1;
+                (overflow_index - 1) | CORRECTED_COMMIT_DATE_OFFSET_OVERFLOW
+            } else {
+                offset as u32
+            };
+            out.write_all(&offset.to_be_bytes())?;
+        }
+        for entry in entries {
+            let offset = entry.corrected_commit_date - entry.commit_timestamp;
+            if offset > CORRECTED_COMMIT_DATE_OFFSET_MAX {
+                out.write_all(&offset.to_be_bytes())?;
+            }
+        }
+    }
+
+    for entry in entries.iter().filter(|e| e.parents.len() > 2) {
+        let (last, edges) = entry.parents[1..].split_last().expect("at least two extra edges");
+        for edge in edges {
+            out.write_all(&edge.0.to_be_bytes())?;
+        }
+        out.write_all(&(last.0 | LAST_EXTENDED_EDGE_MASK).to_be_bytes())?;
+    }
+
+    if let Some(settings) = bloom_settings {
+        let mut end = 0u32;
+        for entry in entries {
+            end += entry.bloom_filter.len() as u32;
+            out.write_all(&end.to_be_bytes())?;
+        }
+        for field in &[settings.hash_version, settings.num_hashes, settings.bits_per_entry] {
+            out.write_all(&field.to_be_bytes())?;
+        }
+        for entry in entries {
+            out.write_all(&entry.bloom_filter)?;
+        }
+    }
+
+    for id in base_graphs {
+        out.write_all(id.as_slice())?;
+    }
+
+    let checksum = ObjectId::new_sha1(out.hash.digest());
+    out.inner.write_all(checksum.as_slice())?;
+    out.inner.flush()?;
+    Ok(checksum)
+}
+
+struct HashWrite<W> {
+    inner: W,
+    hash: git_features::hash::Sha1,
+}
+
+impl<W: io::Write> io::Write for HashWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hash.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    bloom,
     file::{self, Commit, File},
     graph::{self, Graph},
 };
@@ -20,6 +21,14 @@ impl Graph {
         Some(r.file.commit_at(r.file_pos))
     }
 
+    /// Returns true if all files of this graph store [corrected commit dates][Commit::corrected_commit_date()], which is
+    /// when they can be compared between any of its commits.
+    ///
+    /// Like git, corrected commit dates should be ignored entirely if only some layers of a split commit-graph have them.
+    pub fn has_generation_data(&self) -> bool {
+        self.files.iter().all(File::has_generation_data)
+    }
+
     /// Returns the `hash` at the given position `pos`.
     ///
     /// # Panics
@@ -29,6 +38,23 @@ impl Graph {
         r.file.id_at(r.pos)
     }
 
+    /// Return `Some(false)` if the commit with `id` definitely didn't change `path` compared to its first parent, `Some(true)` if
+    /// it might have, or `None` if the commit isn't contained in this graph or has no changed-path Bloom filter.
+    ///
+    /// Note that keys are computed for `path` and all of its leading directories on each call. If many commits are queried
+    /// for the same `path`, consider using [`Key::with_leading_directories()`][bloom::Key::with_leading_directories()]
+    /// with [`Commit::bloom_filter_settings()`] and [`Commit::bloom_filter()`] instead.
+    pub fn might_have_changed_path(&self, id: impl AsRef<git_hash::oid>, path: &bstr::BStr) -> Option<bool> {
+        let r = self.lookup_by_id(id.as_ref())?;
+        let filter = r.file.bloom_filter_at(r.file_pos)?;
+        let settings = r.file.bloom_filter_settings()?;
+        Some(
+            bloom::Key::with_leading_directories(path, &settings)
+                .iter()
+                .all(|key| filter.contains(key)),
+        )
+    }
+
     /// Iterate over commits in unsorted order.
     pub fn iter_commits(&self) -> impl Iterator<Item = Commit<'_>> {
         self.files.iter().flat_map(|file| file.iter_commits())
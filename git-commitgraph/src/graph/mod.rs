@@ -2,6 +2,9 @@
 mod access;
 mod init;
 pub mod verify;
+pub mod write;
+
+pub use init::Error;
 
 use crate::file::File;
 use std::fmt;
@@ -25,6 +25,12 @@ pub enum Error<E: std::error::Error + 'static> {
     },
     #[error(transparent)]
     Commit(#[from] commit::Error),
+    #[error("Commit {id}'s corrected commit date should be {expected} but is {actual}")]
+    CorrectedCommitDate {
+        actual: u64,
+        expected: u64,
+        id: git_hash::ObjectId,
+    },
     #[error("{}: {err}", .path.display())]
     File {
         // Use zero-size error type. We will never return
@@ -92,6 +98,7 @@ impl Graph {
             parent_counts: BTreeMap::new(),
         };
         let mut max_generation = 0u32;
+        let has_generation_data = self.has_generation_data();
 
         // TODO: Detect duplicate commit IDs across different files. Not sure how to do this without
         //   a separate loop, e.g. self.iter_sorted_ids().
@@ -130,6 +137,7 @@ impl Graph {
             let file_stats = file
                 .traverse(|commit| {
                     let mut max_parent_generation = 0u32;
+                    let mut max_parent_corrected_commit_date = 0u64;
                     for parent_pos in commit.iter_parents() {
                         let parent_pos = parent_pos.map_err(Error::Commit)?;
                         if parent_pos >= next_file_start_pos {
@@ -141,6 +149,13 @@ impl Graph {
                         }
                         let parent = self.commit_at(parent_pos);
                         max_parent_generation = max(max_parent_generation, parent.generation());
+                        if has_generation_data {
+                            let date = parent
+                                .corrected_commit_date()
+                                .map_err(Error::Commit)?
+                                .expect("all files to have generation data");
+                            max_parent_corrected_commit_date = max(max_parent_corrected_commit_date, date);
+                        }
                     }
 
                     // If the max parent generation is GENERATION_NUMBER_MAX, then this commit's
@@ -154,6 +169,21 @@ impl Graph {
                         });
                     }
 
+                    if has_generation_data {
+                        let actual = commit
+                            .corrected_commit_date()
+                            .map_err(Error::Commit)?
+                            .expect("all files to have generation data");
+                        let expected = max(commit.committer_timestamp(), max_parent_corrected_commit_date + 1);
+                        if actual != expected {
+                            return Err(Error::CorrectedCommitDate {
+                                actual,
+                                expected,
+                                id: commit.id().into(),
+                            });
+                        }
+                    }
+
                     processor(commit).map_err(Error::Processor)?;
 
                     Ok(())
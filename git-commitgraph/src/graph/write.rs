@@ -0,0 +1,496 @@
+//! Write commit-graph files, either as a single file or as a new layer of a split commit-graph.
+use crate::{
+    bloom,
+    file::{self, write::Entry, File},
+    graph, Graph, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+use bstr::{BString, ByteSlice};
+use git_hash::{oid, ObjectId};
+use git_object::{
+    immutable::{self, commit::iter::Token},
+    tree::EntryMode,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The error returned by [`Graph::write_to_info_dir()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The object {oid} could not be found")]
+    NotFound { oid: ObjectId },
+    #[error("A commit could not be decoded")]
+    Decode(#[from] git_object::immutable::object::decode::Error),
+    #[error("The existing commit-graph could not be opened")]
+    Graph(#[from] graph::Error),
+    #[error("The parents of a commit could not be read from the existing commit-graph")]
+    ExistingCommit(#[from] file::commit::Error),
+    #[error(
+        "The commit-graph would contain {0} commits, but only {} commits are allowed",
+        MAX_COMMITS
+    )]
+    TooManyCommits(u64),
+    #[error("Could not write commit-graph file at '{}'", .path.display())]
+    Io {
+        #[source]
+        err: io::Error,
+        path: PathBuf,
+    },
+}
+
+/// Configure how commit-graph files are written in [`Graph::write_to_info_dir()`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// If set, only commits not yet contained in the commit-graph are written into a new layer on top of the
+    /// `commit-graph-chain`, similar to `git commit-graph write --split`.
+    ///
+    /// Otherwise a single `commit-graph` file is written which replaces all existing commit-graph files.
+    pub split: Option<Split>,
+    /// If true, write corrected commit dates, i.e. generation numbers of version 2, along with topological levels,
+    /// similar to git's default of `commitGraph.generationVersion=2`.
+    ///
+    /// Note that they are not written into a new layer of a split commit-graph if any of the layers below it
+    /// doesn't have them either, as they would be ignored anyway.
+    pub generation_data: bool,
+    /// If set, compute changed-path Bloom filters for all written commits with the given settings, similar to
+    /// `git commit-graph write --changed-paths`.
+    pub changed_paths: Option<bloom::Settings>,
+}
+
+/// Configure when layers of a split commit-graph are merged into the newly written one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Split {
+    /// Merge the topmost layer into the new one as long as it contains no more than `size_multiple` times the commits
+    /// of the new layer, similar to `--size-multiple`.
+    pub size_multiple: u32,
+    /// If set, merge layers into the new one as long as it contains more than `max_commits`, similar to `--max-commits`.
+    pub max_commits: Option<u32>,
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Split {
+            size_multiple: 2,
+            max_commits: None,
+        }
+    }
+}
+
+/// The outcome of [`Graph::write_to_info_dir()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The path to the written commit-graph file, or `None` if all commits were already contained in the existing
+    /// commit-graph when [splitting][Options::split].
+    pub path: Option<PathBuf>,
+    /// The amount of commits in the written file.
+    pub num_commits: u32,
+    /// The amount of files the commit-graph consists of after writing.
+    pub num_layers: usize,
+}
+
+/// A commit as needed to write it into a commit-graph file.
+struct Info {
+    root_tree_id: ObjectId,
+    parents: Vec<ObjectId>,
+    commit_timestamp: u64,
+}
+
+/// Writing
+impl Graph {
+    /// Write a commit-graph with all commits reachable from `tips` into `info_dir`, the `objects/info` directory of a
+    /// repository, using `find(id, buf) -> Option<data>` to lookup the data of commits and trees, writing it into `buf`.
+    ///
+    /// Unless [splitting][Options::split], all existing commit-graph files in `info_dir` are replaced by a single
+    /// `commit-graph` file. Otherwise commits not yet contained in the existing commit-graph are written into a new layer of the
+    /// `commit-graphs/commit-graph-chain`, which is merged with existing layers just like `git commit-graph write --split` would.
+    /// An existing `commit-graph` file becomes the bottommost layer of the chain in the process, and layers are merged
+    /// as needed to keep the chain at no more than 256 files.
+    pub fn write_to_info_dir<Find>(
+        info_dir: impl AsRef<Path>,
+        tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+        mut find: Find,
+        options: Options,
+    ) -> Result<Outcome, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<&'a [u8]>,
+    {
+        let info_dir = info_dir.as_ref();
+        let graphs_dir = info_dir.join("commit-graphs");
+        let single_file_path = info_dir.join("commit-graph");
+        let existing = match options.split {
+            Some(_) if single_file_path.is_file() => Some(Graph::from_file(&single_file_path)?),
+            Some(_) if graphs_dir.join("commit-graph-chain").is_file() => {
+                Some(Graph::from_commit_graphs_dir(&graphs_dir)?)
+            }
+            Some(_) | None => None,
+        };
+
+        let mut commits = collect_commits(tips, existing.as_ref(), &mut find)?;
+        let existing_files: &[File] = existing.as_ref().map(|g| g.files.as_slice()).unwrap_or(&[]);
+        let mut num_base_files = existing_files.len();
+        if let (Some(split), Some(existing)) = (options.split, existing.as_ref()) {
+            let mut num_commits = commits.len() as u64;
+            while let Some(top) = num_base_files.checked_sub(1).map(|index| &existing_files[index]) {
+                let top_commits = u64::from(top.num_commits());
+                let merge = top_commits <= u64::from(split.size_multiple) * num_commits
+                    || matches!(split.max_commits, Some(max) if num_commits > u64::from(max))
+                    || num_base_files > usize::from(u8::MAX);
+                if !merge {
+                    break;
+                }
+                num_commits += top_commits;
+                num_base_files -= 1;
+            }
+            for commit in existing_files[num_base_files..].iter().flat_map(|f| f.iter_commits()) {
+                let parents = commit
+                    .iter_parents()
+                    .map(|pos| pos.map(|pos| existing.id_at(pos).to_owned()))
+                    .collect::<Result<_, _>>()?;
+                commits.insert(
+                    commit.id().to_owned(),
+                    Info {
+                        root_tree_id: commit.root_tree_id().to_owned(),
+                        parents,
+                        commit_timestamp: commit.committer_timestamp(),
+                    },
+                );
+            }
+        }
+        let (base_files, merged_files) = existing_files.split_at(num_base_files);
+        if options.split.is_some() && commits.is_empty() {
+            return Ok(Outcome {
+                path: None,
+                num_commits: 0,
+                num_layers: base_files.len(),
+            });
+        }
+
+        let num_base_commits: u32 = base_files.iter().map(|f| f.num_commits()).sum();
+        let num_commits = u64::from(num_base_commits) + commits.len() as u64;
+        if num_commits > u64::from(MAX_COMMITS) {
+            return Err(Error::TooManyCommits(num_commits));
+        }
+        let bloom_filters = match options.changed_paths {
+            Some(settings) => bloom_filters(&commits, existing.as_ref(), &mut find, &settings)?,
+            None => HashMap::new(),
+        };
+        let entries = to_entries(&commits, bloom_filters, existing.as_ref(), num_base_commits)?;
+        let generation_data = options.generation_data && base_files.iter().all(File::has_generation_data);
+        drop(commits);
+
+        let base_checksums: Vec<_> = base_files.iter().map(|f| f.checksum().to_owned()).collect();
+        let merged_paths: Vec<_> = merged_files.iter().map(|f| f.path().to_owned()).collect();
+        let num_layers = num_base_files + 1;
+        drop(existing);
+
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |err| Error::Io { err, path }
+        };
+        let out_dir = if options.split.is_some() { &graphs_dir } else { info_dir };
+        std::fs::create_dir_all(out_dir).map_err(io_err(out_dir))?;
+        let mut tempfile = tempfile::NamedTempFile::new_in(out_dir).map_err(io_err(out_dir))?;
+        let checksum = file::write::write_to(
+            &entries,
+            generation_data,
+            options.changed_paths.as_ref(),
+            &base_checksums,
+            io::BufWriter::new(tempfile.as_file_mut()),
+        )
+        .map_err(io_err(tempfile.path()))?;
+
+        let path = if options.split.is_some() {
+            let path = graph_file_path(&graphs_dir, &checksum);
+            tempfile.persist(&path).map_err(|err| io_err(&path)(err.error))?;
+            if num_base_files == 1 && single_file_path.is_file() {
+                let base_path = graph_file_path(&graphs_dir, &base_checksums[0]);
+                std::fs::rename(&single_file_path, &base_path).map_err(io_err(&base_path))?;
+            }
+
+            let chain_path = graphs_dir.join("commit-graph-chain");
+            let mut chain = tempfile::NamedTempFile::new_in(&graphs_dir).map_err(io_err(&graphs_dir))?;
+            for checksum in base_checksums.iter().chain(std::iter::once(&checksum)) {
+                checksum
+                    .write_hex_to(chain.as_file_mut())
+                    .and_then(|_| io::Write::write_all(chain.as_file_mut(), b"\n"))
+                    .map_err(io_err(chain.path()))?;
+            }
+            chain
+                .persist(&chain_path)
+                .map_err(|err| io_err(&chain_path)(err.error))?;
+            for merged_path in merged_paths {
+                std::fs::remove_file(&merged_path).map_err(io_err(&merged_path))?;
+            }
+            path
+        } else {
+            tempfile
+                .persist(&single_file_path)
+                .map_err(|err| io_err(&single_file_path)(err.error))?;
+            remove_split_graph(&graphs_dir).map_err(io_err(&graphs_dir))?;
+            single_file_path
+        };
+
+        Ok(Outcome {
+            path: Some(path),
+            num_commits: entries.len() as u32,
+            num_layers,
+        })
+    }
+}
+
+/// Find all commits reachable from `tips` which are not yet contained in the `existing` commit-graph.
+fn collect_commits<Find>(
+    tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+    existing: Option<&Graph>,
+    find: &mut Find,
+) -> Result<HashMap<ObjectId, Info>, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<&'a [u8]>,
+{
+    let mut commits = HashMap::new();
+    let mut buf = Vec::new();
+    let mut queue: Vec<ObjectId> = tips.into_iter().map(Into::into).collect();
+    while let Some(id) = queue.pop() {
+        if commits.contains_key(&id) || existing.and_then(|graph| graph.lookup(id)).is_some() {
+            continue;
+        }
+        let mut info = Info {
+            root_tree_id: ObjectId::null_sha1(),
+            parents: Vec::new(),
+            commit_timestamp: 0,
+        };
+        let data = find(&id, &mut buf).ok_or(Error::NotFound { oid: id })?;
+        for token in immutable::CommitIter::from_bytes(data) {
+            match token? {
+                Token::Tree { id } => info.root_tree_id = id,
+                Token::Parent { id } => info.parents.push(id),
+                Token::Author { .. } => {}
+                Token::Committer { signature } => {
+                    info.commit_timestamp = u64::from(signature.time.time);
+                    break;
+                }
+                _a_token_past_the_committer => break,
+            }
+        }
+        queue.extend(info.parents.iter().filter(|id| !commits.contains_key(*id)));
+        commits.insert(id, info);
+    }
+    Ok(commits)
+}
+
+/// Compute the changed-path Bloom filter data for all `commits` with the given `settings`, looking up trees of parents
+/// in the `base` graph if they are not part of `commits`.
+fn bloom_filters<Find>(
+    commits: &HashMap<ObjectId, Info>,
+    base: Option<&Graph>,
+    find: &mut Find,
+    settings: &bloom::Settings,
+) -> Result<HashMap<ObjectId, Vec<u8>>, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<&'a [u8]>,
+{
+    let mut filters = HashMap::with_capacity(commits.len());
+    let mut buf = Vec::new();
+    let mut paths = BTreeSet::new();
+    for (id, info) in commits {
+        let parent_tree_id = info.parents.first().map(|parent| match commits.get(parent) {
+            Some(parent) => parent.root_tree_id,
+            None => base
+                .and_then(|g| g.commit_by_id(parent))
+                .expect("parents are either written or part of the base graph")
+                .root_tree_id()
+                .to_owned(),
+        });
+        paths.clear();
+        let data = if changed_paths(
+            find,
+            &mut buf,
+            parent_tree_id,
+            Some(info.root_tree_id),
+            &mut BString::default(),
+            &mut paths,
+        )? {
+            bloom::filter_data(paths.iter().map(|p| p.as_bstr()), settings)
+        } else {
+            vec![0xff]
+        };
+        filters.insert(*id, data);
+    }
+    Ok(filters)
+}
+
+/// Insert the paths of all files that differ between the trees `lhs` and `rhs` into `out`, along with their leading
+/// directories, with all paths prefixed by `prefix`.
+///
+/// Return false if this would insert more than [`bloom::MAX_CHANGED_PATHS`].
+fn changed_paths<Find>(
+    find: &mut Find,
+    buf: &mut Vec<u8>,
+    lhs: Option<ObjectId>,
+    rhs: Option<ObjectId>,
+    prefix: &mut BString,
+    out: &mut BTreeSet<BString>,
+) -> Result<bool, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<&'a [u8]>,
+{
+    let mut entries = BTreeMap::<BString, [Option<(EntryMode, ObjectId)>; 2]>::new();
+    for (index, tree_id) in [lhs, rhs].iter().enumerate() {
+        if let Some(tree_id) = tree_id {
+            let data = find(tree_id, buf).ok_or(Error::NotFound { oid: *tree_id })?;
+            for entry in immutable::TreeIter::from_bytes_with_hash_kind(data, tree_id.kind()) {
+                let entry = entry?;
+                entries.entry(entry.filename.to_owned()).or_default()[index] = Some((entry.mode, entry.oid.to_owned()));
+            }
+        }
+    }
+
+    let prefix_len = prefix.len();
+    for (name, [lhs, rhs]) in entries {
+        if lhs == rhs {
+            continue;
+        }
+        if prefix_len != 0 {
+            prefix.push(b'/');
+        }
+        prefix.extend_from_slice(&name);
+        let tree = |side: Option<(EntryMode, ObjectId)>| side.filter(|(mode, _)| mode.is_tree()).map(|(_, id)| id);
+        let is_file = |side: Option<(EntryMode, ObjectId)>| matches!(side, Some((mode, _)) if mode.is_no_tree());
+        let (lhs_tree, rhs_tree) = (tree(lhs), tree(rhs));
+        if (lhs_tree.is_some() || rhs_tree.is_some()) && !changed_paths(find, buf, lhs_tree, rhs_tree, prefix, out)? {
+            return Ok(false);
+        }
+        if is_file(lhs) || is_file(rhs) {
+            let mut path = prefix.as_bytes();
+            while out.insert(path.into()) {
+                match path.rfind_byte(b'/') {
+                    Some(pos) => path = &path[..pos],
+                    None => break,
+                }
+            }
+            if out.len() > bloom::MAX_CHANGED_PATHS {
+                return Ok(false);
+            }
+        }
+        prefix.truncate(prefix_len);
+    }
+    Ok(true)
+}
+
+/// Turn `commits` into entries sorted by id, with parents, generation numbers and corrected commit dates resolved either
+/// within `commits` or within the `base` graph whose first `num_base_commits` remain in place.
+fn to_entries(
+    commits: &HashMap<ObjectId, Info>,
+    mut bloom_filters: HashMap<ObjectId, Vec<u8>>,
+    base: Option<&Graph>,
+    num_base_commits: u32,
+) -> Result<Vec<Entry>, file::commit::Error> {
+    let mut ids: Vec<_> = commits.keys().copied().collect();
+    ids.sort();
+
+    // Like git, use the commit date in place of the corrected commit date of base commits which don't have one.
+    // This only happens if no generation data will be written.
+    let base_generations = |commit: file::Commit<'_>| -> Result<(u32, u64), file::commit::Error> {
+        let date = commit.corrected_commit_date()?;
+        Ok((
+            commit.generation(),
+            date.unwrap_or_else(|| commit.committer_timestamp()),
+        ))
+    };
+    let mut generations = HashMap::<ObjectId, (u32, u64)>::with_capacity(ids.len());
+    let mut stack = Vec::new();
+    for id in &ids {
+        stack.push(*id);
+        while let Some(id) = stack.last().copied() {
+            if generations.contains_key(&id) {
+                stack.pop();
+                continue;
+            }
+            let mut max_parent_generation = 0;
+            let mut max_parent_corrected_commit_date = 0;
+            let mut parents_done = true;
+            for parent in &commits[&id].parents {
+                let generations = match commits.get(parent) {
+                    Some(_) => generations.get(parent).copied(),
+                    None => base
+                        .and_then(|g| g.commit_by_id(parent))
+                        .map(base_generations)
+                        .transpose()?,
+                };
+                match generations {
+                    Some((generation, corrected_commit_date)) => {
+                        max_parent_generation = max_parent_generation.max(generation);
+                        max_parent_corrected_commit_date = max_parent_corrected_commit_date.max(corrected_commit_date);
+                    }
+                    None => {
+                        stack.push(*parent);
+                        parents_done = false;
+                    }
+                }
+            }
+            if parents_done {
+                generations.insert(
+                    id,
+                    (
+                        max_parent_generation.saturating_add(1).min(GENERATION_NUMBER_MAX),
+                        commits[&id]
+                            .commit_timestamp
+                            .max(max_parent_corrected_commit_date.saturating_add(1)),
+                    ),
+                );
+                stack.pop();
+            }
+        }
+    }
+
+    let position = |id: &ObjectId| match ids.binary_search(id) {
+        Ok(index) => graph::Position(num_base_commits + index as u32),
+        Err(_) => base
+            .and_then(|g| g.lookup(id))
+            .expect("parents are either written or part of the base graph"),
+    };
+    Ok(ids
+        .iter()
+        .map(|id| {
+            let info = &commits[id];
+            let (generation, corrected_commit_date) = generations[id];
+            Entry {
+                id: *id,
+                root_tree_id: info.root_tree_id,
+                parents: info.parents.iter().map(position).collect(),
+                generation,
+                commit_timestamp: info.commit_timestamp,
+                corrected_commit_date,
+                bloom_filter: bloom_filters.remove(id).unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+fn graph_file_path(graphs_dir: &Path, checksum: &ObjectId) -> PathBuf {
+    graphs_dir.join(format!("graph-{}.graph", checksum.to_sha1_hex_string()))
+}
+
+/// Remove the chain and all graph files of a split commit-graph in `graphs_dir`, if there is one.
+fn remove_split_graph(graphs_dir: &Path) -> io::Result<()> {
+    let entries = match std::fs::read_dir(graphs_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name == "commit-graph-chain" || (name.starts_with("graph-") && name.ends_with(".graph")) {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
@@ -68,3 +68,82 @@ fn two_parents() -> crate::Result {
 
     Ok(())
 }
+
+#[test]
+fn corrected_commit_dates() -> crate::Result {
+    let repo_dir = make_readonly_repo("generation_data.sh");
+    let refs = inspect_refs(
+        &repo_dir,
+        &["c1", "c2", "c3", "future", "skewed", "side1", "side2", "merge"],
+    );
+    let cg = Graph::from_file(repo_dir.join("expected").join("single"))?;
+    check_common(&cg, &refs);
+    assert!(cg.has_generation_data());
+
+    for (name, expected_timestamp, expected_date) in &[
+        ("c1", 1000000000, 1000000000),
+        ("c2", 1000000100, 1000000100),
+        ("c3", 1000000200, 1000000200),
+        ("future", 4200000000, 4200000000),
+        ("skewed", 1000000400, 4200000001),
+        ("side1", 999999000, 1000000201),
+        ("side2", 1000000300, 1000000300),
+        ("merge", 1000000500, 4200000002),
+    ] {
+        let commit = cg.commit_at(refs[*name].pos());
+        assert_eq!(commit.committer_timestamp(), *expected_timestamp, "{}", name);
+        assert_eq!(commit.corrected_commit_date()?, Some(*expected_date), "{}", name);
+    }
+
+    let cg = Graph::from_commit_graphs_dir(repo_dir.join("expected").join("split-after-future"))?;
+    assert!(
+        !cg.has_generation_data(),
+        "the base layer was written without generation data, and so was the layer on top"
+    );
+    let future = cg.commit_by_id(refs["future"].id()).expect("present");
+    assert_eq!(future.corrected_commit_date()?, None);
+    Ok(())
+}
+
+#[test]
+fn changed_path_bloom_filters() -> crate::Result {
+    let repo_dir = make_readonly_repo("changed_paths.sh");
+    let cg = Graph::from_file(repo_dir.join("expected").join("commit-graph"))?;
+    let changed = |commit: &str, path: &str| {
+        cg.might_have_changed_path(crate::hex_to_id(commit.as_bytes()), path.into())
+            .expect("all commits have filters")
+    };
+
+    let change_c = "2f12cec3e4e73bf29cd7592972ea8ace995a3cb1";
+    for path in &["dir/sub/c", "dir/sub", "dir"] {
+        assert!(changed(change_c, path), "{} was changed", path);
+    }
+    for path in &["a", "dir/b", "dir/sub/d", "other"] {
+        assert!(!changed(change_c, path), "{} was not changed", path);
+    }
+
+    let change_nothing = "45c67c458e460eeb9e079fb0caa5a86f4d4d82f2";
+    for path in &["a", "dir", "dir/sub/c"] {
+        assert!(!changed(change_nothing, path), "nothing was changed");
+    }
+
+    let add_umlaut = "03dde5fd50ebb615f90e68f5ec045c0c8889c5f9";
+    assert!(changed(add_umlaut, "dir/ümlaut"), "non-ascii paths hash like git does");
+    assert!(!changed(add_umlaut, "dir/b"));
+
+    let add_many_files = "88c09c355a1fd24012d2e220cf67f654f054c836";
+    assert!(
+        changed(add_many_files, "anything"),
+        "filters with too many changes contain everything"
+    );
+
+    let single_parent_repo = make_readonly_repo("single_parent.sh");
+    let refs = inspect_refs(&single_parent_repo, &["child"]);
+    let cg = Graph::from_info_dir(single_parent_repo.join(".git").join("objects").join("info"))?;
+    assert_eq!(
+        cg.might_have_changed_path(refs["child"].id(), "a".into()),
+        None,
+        "there are no filters without the respective chunks"
+    );
+    Ok(())
+}
@@ -11,6 +11,7 @@ use std::{
 type Result = std::result::Result<(), Box<dyn std::error::Error>>;
 
 mod access;
+mod write;
 
 pub fn check_common(cg: &Graph, expected: &HashMap<String, RefInfo, impl BuildHasher>) {
     assert_eq!(
@@ -0,0 +1,48 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config core.quotepath false
+git config commitGraph.generationVersion 1
+
+git checkout -q -b main
+mkdir -p dir/sub
+echo a > a
+echo b > dir/b
+echo c > dir/sub/c
+git add -A
+git commit -q -m "add files"
+
+echo changed > dir/sub/c
+git commit -q -am "change dir/sub/c"
+
+git commit -q --allow-empty -m "change nothing"
+
+echo ü > "dir/ümlaut"
+git add -A
+git commit -q -m "add dir/ümlaut"
+
+git checkout -q -b side
+rm a
+mkdir a
+echo x > a/x
+git add -A
+git commit -q -m "turn a into a directory"
+
+git checkout -q main
+chmod +x dir/b
+git commit -q -am "make dir/b executable"
+git merge -q -m "merge side" side
+
+mkdir many
+for n in $(seq 600); do
+  echo $n > many/$n
+done
+git add -A
+git commit -q -m "add many files"
+
+git commit-graph write --no-progress --reachable --changed-paths
+mkdir expected
+mv .git/objects/info/commit-graph expected/
+git repack -adq
@@ -0,0 +1,43 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local message=${1:?first argument is the commit message}
+  local date=${2:?second argument is the commit date}
+  GIT_COMMITTER_DATE="@$date +0000" GIT_AUTHOR_DATE="@$date +0000" git commit -q --allow-empty -m "$message"
+  git branch "$message"
+}
+
+git init -q
+git config commit.gpgsign false
+git config commitGraph.generationVersion 2
+
+git checkout -q -b main
+commit_at c1 1000000000
+commit_at c2 1000000100
+commit_at c3 1000000200
+# a committer clock far in the future makes the corrected commit dates of all descendants overflow
+commit_at future 4200000000
+commit_at skewed 1000000400
+
+git checkout -q -b side c3
+# a committer clock in the past
+commit_at side1 999999000
+commit_at side2 1000000300
+
+git checkout -q main
+GIT_COMMITTER_DATE="1000000500 +0000" GIT_AUTHOR_DATE="1000000500 +0000" git merge -q --no-ff side -m merge
+git branch merge
+
+git commit-graph write --no-progress --reachable
+mkdir -p expected
+mv .git/objects/info/commit-graph expected/single
+
+git show-ref -s --heads c3 | git -c commitGraph.generationVersion=1 commit-graph write --no-progress --split --stdin-commits
+cp -R .git/objects/info/commit-graphs expected/split-after-c3
+for tip in future merge; do
+  git show-ref -s --heads $tip | git commit-graph write --no-progress --split --stdin-commits
+  cp -R .git/objects/info/commit-graphs expected/split-after-$tip
+done
+rm -R .git/objects/info/commit-graphs
+git repack -adq
@@ -0,0 +1,36 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config commitGraph.generationVersion 1
+
+git checkout -q -b main
+git commit -q --allow-empty -m c1
+git commit -q --allow-empty -m c2
+git commit -q --allow-empty -m c3
+git branch c3
+
+for branch in a b c; do
+  git checkout -q -b $branch c3
+  git commit -q --allow-empty -m $branch
+done
+
+git checkout -q main
+git merge -q -m m --no-ff a b c >/dev/null
+git branch m
+git commit -q --allow-empty -m c4
+git branch c4
+git commit -q --allow-empty -m c5
+git branch c5
+
+git show-ref -s c5 | git commit-graph write --no-progress --stdin-commits
+mkdir -p expected
+mv .git/objects/info/commit-graph expected/single
+
+for tip in c3 a m c4 c5; do
+  git show-ref -s --heads $tip | git commit-graph write --no-progress --split --stdin-commits
+  cp -R .git/objects/info/commit-graphs expected/split-after-$tip
+done
+rm -R .git/objects/info/commit-graphs
+git repack -adq
@@ -0,0 +1,210 @@
+use crate::{inspect_refs, make_readonly_repo};
+use git_commitgraph::{
+    graph::write::{Options, Split},
+    Graph,
+};
+use std::path::Path;
+
+fn db_and_tip(
+    repo_dir: &Path,
+    tip: &'static str,
+) -> Result<(git_odb::linked::Db, git_hash::ObjectId), Box<dyn std::error::Error>> {
+    let db = git_odb::linked::Db::at(repo_dir.join(".git").join("objects"))?;
+    let id = inspect_refs(repo_dir, &[tip])[tip].id().to_owned();
+    Ok((db, id))
+}
+
+fn find(db: &git_odb::linked::Db) -> impl for<'a> FnMut(&git_hash::oid, &'a mut Vec<u8>) -> Option<&'a [u8]> + '_ {
+    move |id, buf| {
+        git_odb::Find::find(db, id, buf, &mut git_odb::pack::cache::Never)
+            .ok()
+            .flatten()
+            .map(|obj| obj.data)
+    }
+}
+
+/// Return the names and contents of all files in `dir`, sorted by name.
+fn dir_contents(dir: impl AsRef<Path>) -> std::io::Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        files.push((
+            entry.file_name().to_string_lossy().into_owned(),
+            std::fs::read(entry.path())?,
+        ));
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn split() -> Options {
+    Options {
+        split: Some(Split::default()),
+        ..Default::default()
+    }
+}
+
+fn verify(info_dir: &Path) -> crate::Result {
+    Graph::from_info_dir(info_dir)?.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    Ok(())
+}
+
+#[test]
+fn single_file_is_identical_to_the_one_written_by_git() -> crate::Result {
+    let repo_dir = make_readonly_repo("write.sh");
+    let (db, tip) = db_and_tip(&repo_dir, "c5")?;
+    let info_dir = tempfile::tempdir()?;
+
+    let outcome = Graph::write_to_info_dir(info_dir.path(), Some(tip), find(&db), Options::default())?;
+    let expected_path = info_dir.path().join("commit-graph");
+    assert_eq!(outcome.path.as_ref(), Some(&expected_path));
+    assert_eq!(outcome.num_commits, 9);
+    assert_eq!(outcome.num_layers, 1);
+    assert_eq!(
+        std::fs::read(expected_path)?,
+        std::fs::read(repo_dir.join("expected").join("single"))?
+    );
+    verify(info_dir.path())
+}
+
+#[test]
+fn split_layers_are_merged_like_git_does() -> crate::Result {
+    let repo_dir = make_readonly_repo("write.sh");
+    let info_dir = tempfile::tempdir()?;
+    let graphs_dir = info_dir.path().join("commit-graphs");
+
+    for (tip, expected_new_commits, expected_layers) in
+        &[("c3", 3, 1), ("a", 1, 2), ("m", 7, 1), ("c4", 1, 2), ("c5", 2, 2)]
+    {
+        let (db, tip_id) = db_and_tip(&repo_dir, tip)?;
+        let outcome = Graph::write_to_info_dir(info_dir.path(), Some(tip_id), find(&db), split())?;
+        assert_eq!(outcome.num_commits, *expected_new_commits, "after writing {}", tip);
+        assert_eq!(outcome.num_layers, *expected_layers, "after writing {}", tip);
+        assert_eq!(
+            outcome.path.as_ref().map(|p| p.parent().expect("parent dir")),
+            Some(graphs_dir.as_path())
+        );
+        assert_eq!(
+            dir_contents(&graphs_dir)?,
+            dir_contents(repo_dir.join("expected").join(format!("split-after-{}", tip)))?,
+            "after writing {}",
+            tip
+        );
+        verify(info_dir.path())?;
+    }
+
+    let (db, tip_id) = db_and_tip(&repo_dir, "c4")?;
+    let outcome = Graph::write_to_info_dir(info_dir.path(), Some(tip_id), find(&db), split())?;
+    assert_eq!(outcome.path, None, "nothing is written if all commits are known");
+    assert_eq!(outcome.num_layers, 2);
+
+    let (db, tip_id) = db_and_tip(&repo_dir, "c5")?;
+    Graph::write_to_info_dir(info_dir.path(), Some(tip_id), find(&db), Options::default())?;
+    assert_eq!(
+        dir_contents(&graphs_dir)?,
+        Vec::new(),
+        "writing a single file removes all layers"
+    );
+    assert_eq!(
+        std::fs::read(info_dir.path().join("commit-graph"))?,
+        std::fs::read(repo_dir.join("expected").join("single"))?
+    );
+    verify(info_dir.path())
+}
+
+#[test]
+fn split_turns_an_existing_single_file_into_the_base_layer() -> crate::Result {
+    let repo_dir = make_readonly_repo("write.sh");
+    let info_dir = tempfile::tempdir()?;
+
+    let (db, tip_id) = db_and_tip(&repo_dir, "m")?;
+    Graph::write_to_info_dir(info_dir.path(), Some(tip_id), find(&db), Options::default())?;
+    assert_eq!(Graph::from_info_dir(info_dir.path())?.num_commits(), 7);
+
+    let (db, tip_id) = db_and_tip(&repo_dir, "c5")?;
+    let outcome = Graph::write_to_info_dir(info_dir.path(), Some(tip_id), find(&db), split())?;
+    assert_eq!(outcome.num_commits, 2);
+    assert_eq!(outcome.num_layers, 2);
+    assert!(!info_dir.path().join("commit-graph").exists());
+    assert_eq!(
+        dir_contents(info_dir.path().join("commit-graphs"))?,
+        dir_contents(repo_dir.join("expected").join("split-after-c5"))?,
+        "the single file is identical to the base layer written by git"
+    );
+    verify(info_dir.path())
+}
+
+#[test]
+fn changed_path_bloom_filters_are_identical_to_the_ones_written_by_git() -> crate::Result {
+    let repo_dir = make_readonly_repo("changed_paths.sh");
+    let (db, tip) = db_and_tip(&repo_dir, "main")?;
+    let info_dir = tempfile::tempdir()?;
+
+    let outcome = Graph::write_to_info_dir(
+        info_dir.path(),
+        Some(tip),
+        find(&db),
+        Options {
+            changed_paths: Some(Default::default()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.num_commits, 8);
+    assert_eq!(
+        std::fs::read(info_dir.path().join("commit-graph"))?,
+        std::fs::read(repo_dir.join("expected").join("commit-graph"))?
+    );
+    verify(info_dir.path())
+}
+
+#[test]
+fn generation_data_is_identical_to_the_one_written_by_git() -> crate::Result {
+    let repo_dir = make_readonly_repo("generation_data.sh");
+    let (db, tip) = db_and_tip(&repo_dir, "merge")?;
+    let info_dir = tempfile::tempdir()?;
+
+    let outcome = Graph::write_to_info_dir(
+        info_dir.path(),
+        Some(tip),
+        find(&db),
+        Options {
+            generation_data: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.num_commits, 8);
+    assert_eq!(
+        std::fs::read(info_dir.path().join("commit-graph"))?,
+        std::fs::read(repo_dir.join("expected").join("single"))?
+    );
+    verify(info_dir.path())
+}
+
+#[test]
+fn split_layers_have_generation_data_only_if_all_layers_below_have_it() -> crate::Result {
+    let repo_dir = make_readonly_repo("generation_data.sh");
+    let info_dir = tempfile::tempdir()?;
+    let graphs_dir = info_dir.path().join("commit-graphs");
+
+    for (tip, generation_data, expected_layers) in &[("c3", false, 1), ("future", true, 2), ("merge", true, 1)] {
+        let (db, tip_id) = db_and_tip(&repo_dir, tip)?;
+        let outcome = Graph::write_to_info_dir(
+            info_dir.path(),
+            Some(tip_id),
+            find(&db),
+            Options {
+                generation_data: *generation_data,
+                ..split()
+            },
+        )?;
+        assert_eq!(outcome.num_layers, *expected_layers, "after writing {}", tip);
+        assert_eq!(
+            dir_contents(&graphs_dir)?,
+            dir_contents(repo_dir.join("expected").join(format!("split-after-{}", tip)))?,
+            "after writing {}",
+            tip
+        );
+        verify(info_dir.path())?;
+    }
+    Ok(())
+}
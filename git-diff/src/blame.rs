@@ -0,0 +1,435 @@
+use crate::{history, lines, tree};
+use git_hash::{oid, ObjectId};
+use git_object::bstr::{BStr, BString};
+use quick_error::quick_error;
+use std::{
+    borrow::BorrowMut,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    ops::Range,
+};
+
+quick_error! {
+    /// The error returned by [Blame::new()] and as part of the items returned by the [Blame] iterator.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        PathNotFound { path: BString, commit: ObjectId } {
+            display("The file '{}' does not exist in commit {}", path, commit)
+        }
+        Lookup(err: history::Error) {
+            display("Commits, trees or blobs could not be looked up or compared")
+            source(err)
+            from()
+        }
+    }
+}
+
+/// Options for use in [Blame::new()].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Options {
+    /// If true, detect lines moved or copied within the same file, similar to `git blame -M`.
+    pub moves: bool,
+    /// If true, detect lines moved or copied from other files modified in the same commit, similar to `git blame -C`.
+    pub copies: bool,
+}
+
+/// The minimal amount of alphanumeric characters a run of moved or copied lines must have to be attributed to its origin.
+const MOVE_SCORE: usize = 20;
+
+/// A run of lines in the blamed file attributed to the commit that introduced them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// The lines in the blamed file, starting at 0.
+    pub range_in_blamed_file: Range<u32>,
+    /// The commit which introduced the lines.
+    pub commit_id: ObjectId,
+    /// The path of the file in which the lines were introduced, which differs from the blamed path for renames,
+    /// or for lines [copied from other files][Options::copies].
+    pub path: BString,
+    /// The lines in the file at `path` of `commit_id`, starting at 0.
+    pub range_in_source_file: Range<u32>,
+}
+
+/// Lines of the blamed file which are yet to be attributed.
+#[derive(Clone, Copy)]
+struct Unblamed {
+    /// The first line in the blamed file.
+    blamed_start: u32,
+    /// The first line in the file of the suspect.
+    suspect_start: u32,
+    len: u32,
+}
+
+/// The state used and potentially shared by multiple blame runs.
+#[derive(Default)]
+pub struct State {
+    buf1: Vec<u8>,
+    buf2: Vec<u8>,
+    commits: HashMap<ObjectId, history::Info>,
+    /// Lines yet to be attributed for each suspected file in a commit.
+    suspects: HashMap<(ObjectId, BString), Vec<Unblamed>>,
+    /// Suspects to process, by committer time, newest first, and by insertion order if the time is equal.
+    queue: BinaryHeap<(u32, Reverse<usize>, ObjectId, BString)>,
+    seq: usize,
+    out: VecDeque<Entry>,
+    diff: tree::State<usize>,
+}
+
+impl State {
+    fn clear(&mut self) {
+        self.buf1.clear();
+        self.buf2.clear();
+        self.commits.clear();
+        self.suspects.clear();
+        self.queue.clear();
+        self.seq = 0;
+        self.out.clear();
+    }
+}
+
+/// An iterator attributing each line of a file to the commit which introduced it, similar to `git blame --incremental`.
+///
+/// [Entries][Entry] are returned as soon as they are known, so the order of lines is undefined. To obtain all lines in order,
+/// collect all entries and sort them by their [range in the blamed file][Entry::range_in_blamed_file].
+///
+/// Renames of the blamed file are followed. A commit identical to one of its parents in the suspected file passes
+/// all blame to that parent, similar to the history simplification of [`history::Commits`].
+pub struct Blame<Find, StateMut> {
+    find: Find,
+    state: StateMut,
+    options: Options,
+}
+
+impl<Find, StateMut> Blame<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<(git_object::Kind, &'a [u8])>,
+    StateMut: BorrowMut<State>,
+{
+    /// Create a new instance to blame the file at `path` in `commit`.
+    ///
+    /// * `options` - configure move and copy detection.
+    /// * `state` - all state used for the traversal. If multiple blames are performed, allocations can be minimized by reusing
+    ///   this state.
+    /// * `find` - a way to lookup objects by their id, see [`history::Commits::new()`] for details.
+    pub fn new(
+        commit: impl Into<ObjectId>,
+        path: impl Into<BString>,
+        options: Options,
+        mut state: StateMut,
+        find: Find,
+    ) -> Result<Self, Error> {
+        let (commit, path) = (commit.into(), path.into());
+        state.borrow_mut().clear();
+        let mut blame = Blame { find, state, options };
+        let num_lines = lines::split(&blame.blob(commit, path.as_ref())?).len() as u32;
+        if num_lines != 0 {
+            blame.suspect(
+                commit,
+                path,
+                Some(Unblamed {
+                    blamed_start: 0,
+                    suspect_start: 0,
+                    len: num_lines,
+                }),
+            )?;
+        }
+        Ok(blame)
+    }
+
+    fn load(&mut self, id: ObjectId) -> Result<&history::Info, Error> {
+        let state = self.state.borrow_mut();
+        if !state.commits.contains_key(&id) {
+            let info = history::decode_commit(&mut self.find, &mut state.buf1, id)?;
+            state.commits.insert(id, info);
+        }
+        Ok(&state.commits[&id])
+    }
+
+    /// Return the id of the file at `path` in `commit`, if there is one.
+    fn blob_id(&mut self, commit: ObjectId, path: &BStr) -> Result<Option<ObjectId>, Error> {
+        let tree_id = self.load(commit)?.tree_id;
+        Ok(
+            history::entry_at_path(&mut self.find, &mut self.state.borrow_mut().buf1, tree_id, path)?
+                .filter(|(mode, _)| mode.is_no_tree())
+                .map(|(_, id)| id),
+        )
+    }
+
+    fn blob(&mut self, commit: ObjectId, path: &BStr) -> Result<Vec<u8>, Error> {
+        let id = self.blob_id(commit, path)?.ok_or_else(|| Error::PathNotFound {
+            path: path.to_owned(),
+            commit,
+        })?;
+        Ok(history::find_blob(&mut self.find, &id, &mut self.state.borrow_mut().buf1)?.to_owned())
+    }
+
+    /// Make `lines` of the file at `path` in `commit` suspects, and queue them for processing.
+    fn suspect(
+        &mut self,
+        commit: ObjectId,
+        path: BString,
+        lines: impl IntoIterator<Item = Unblamed>,
+    ) -> Result<(), Error> {
+        let committer_time = self.load(commit)?.committer_time;
+        let state = self.state.borrow_mut();
+        let key = (commit, path);
+        if !state.suspects.contains_key(&key) {
+            state
+                .queue
+                .push((committer_time, Reverse(state.seq), key.0, key.1.clone()));
+            state.seq += 1;
+        }
+        state.suspects.entry(key).or_default().extend(lines);
+        Ok(())
+    }
+
+    /// Find the file in `parent` that `path` in `commit` originates from, following renames.
+    fn origin(
+        &mut self,
+        parent: ObjectId,
+        commit: ObjectId,
+        path: &BStr,
+    ) -> Result<Option<(BString, ObjectId)>, Error> {
+        if let Some(id) = self.blob_id(parent, path)? {
+            return Ok(Some((path.to_owned(), id)));
+        }
+        let blob_id = match self.blob_id(commit, path)? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let trees = (self.load(parent)?.tree_id, self.load(commit)?.tree_id);
+        let state = self.state.borrow_mut();
+        let source = history::rename_source(
+            &mut self.find,
+            &mut state.buf1,
+            &mut state.buf2,
+            &mut state.diff,
+            trees,
+            (git_object::tree::EntryMode::Blob, blob_id),
+        )?;
+        Ok(match source {
+            Some(source) => self.blob_id(parent, source.as_ref())?.map(|id| (source, id)),
+            None => None,
+        })
+    }
+
+    /// Pass blame for all lines of `path` in `commit` to its parents where possible, and take the blame for the remaining ones.
+    fn process(&mut self, commit: ObjectId, path: BString) -> Result<(), Error> {
+        let mut unblamed = self
+            .state
+            .borrow_mut()
+            .suspects
+            .remove(&(commit, path.clone()))
+            .expect("queued suspects have lines");
+        let blob_id = self.blob_id(commit, path.as_ref())?.expect("suspects exist");
+        let parents = self.load(commit)?.parents.clone();
+
+        let mut origins = Vec::with_capacity(parents.len());
+        for parent in parents.iter() {
+            if let Some((origin_path, origin_id)) = self.origin(*parent, commit, path.as_ref())? {
+                if origin_id == blob_id {
+                    return self.suspect(*parent, origin_path, unblamed);
+                }
+                origins.push((*parent, origin_path, origin_id));
+            }
+        }
+
+        let data = self.blob(commit, path.as_ref())?;
+        let lines = lines::split(&data);
+        for (parent, origin_path, origin_id) in origins.iter() {
+            let origin_data =
+                history::find_blob(&mut self.find, origin_id, &mut self.state.borrow_mut().buf1)?.to_owned();
+            let origin_lines = lines::split(&origin_data);
+            let (passed, remaining) = pass_blame(unblamed, &lines::matches(&origin_lines, &lines));
+            unblamed = remaining;
+            if !passed.is_empty() {
+                self.suspect(*parent, origin_path.clone(), passed)?;
+            }
+        }
+
+        if self.options.moves {
+            for (parent, origin_path, origin_id) in origins.iter() {
+                let origin_data =
+                    history::find_blob(&mut self.find, origin_id, &mut self.state.borrow_mut().buf1)?.to_owned();
+                unblamed = self.find_moved_lines(*parent, origin_path, &origin_data, &lines, unblamed)?;
+            }
+        }
+
+        if self.options.copies {
+            for parent in parents.iter() {
+                let trees = (self.load(*parent)?.tree_id, self.load(commit)?.tree_id);
+                let state = self.state.borrow_mut();
+                let changes =
+                    history::tree_changes(&mut self.find, &mut state.buf1, &mut state.buf2, &mut state.diff, trees)?;
+                for change in changes {
+                    let (source_path, source_id) = match change {
+                        tree::recorder::Change::Modification {
+                            previous_entry_mode,
+                            previous_oid,
+                            path: source_path,
+                            ..
+                        }
+                        | tree::recorder::Change::Deletion {
+                            entry_mode: previous_entry_mode,
+                            oid: previous_oid,
+                            path: source_path,
+                        } if previous_entry_mode.is_no_tree() => (source_path, previous_oid),
+                        _ => continue,
+                    };
+                    if source_path == path || unblamed.is_empty() {
+                        continue;
+                    }
+                    let source_data =
+                        history::find_blob(&mut self.find, &source_id, &mut self.state.borrow_mut().buf1)?.to_owned();
+                    unblamed = self.find_moved_lines(*parent, &source_path, &source_data, &lines, unblamed)?;
+                }
+            }
+        }
+
+        let out = &mut self.state.borrow_mut().out;
+        for lines in unblamed {
+            out.push_back(Entry {
+                range_in_blamed_file: lines.blamed_start..lines.blamed_start + lines.len,
+                commit_id: commit,
+                path: path.clone(),
+                range_in_source_file: lines.suspect_start..lines.suspect_start + lines.len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Pass blame for runs of `unblamed` lines which are also present anywhere in `source_data` to `source_path` in `parent`,
+    /// and return the remaining ones.
+    fn find_moved_lines(
+        &mut self,
+        parent: ObjectId,
+        source_path: &BString,
+        source_data: &[u8],
+        lines: &[&[u8]],
+        mut unblamed: Vec<Unblamed>,
+    ) -> Result<Vec<Unblamed>, Error> {
+        let source_lines = lines::split(source_data);
+        let mut positions = HashMap::<&[u8], Vec<u32>>::new();
+        for (index, line) in source_lines.iter().enumerate() {
+            positions.entry(line).or_default().push(index as u32);
+        }
+
+        let mut passed = Vec::new();
+        loop {
+            let mut found = Vec::new();
+            for u in unblamed.iter() {
+                if let Some(m) = longest_moved_run(u, lines, &source_lines, &positions) {
+                    found.push(m);
+                }
+            }
+            if found.is_empty() {
+                break;
+            }
+            found.sort_by_key(|m| m.new_start);
+            let (moved, remaining) = pass_blame(unblamed, &found);
+            passed.extend(moved);
+            unblamed = remaining;
+        }
+        if !passed.is_empty() {
+            self.suspect(parent, source_path.clone(), passed)?;
+        }
+        Ok(unblamed)
+    }
+}
+
+impl<Find, StateMut> Iterator for Blame<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<(git_object::Kind, &'a [u8])>,
+    StateMut: BorrowMut<State>,
+{
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.state.borrow_mut().out.pop_front() {
+                return Some(Ok(entry));
+            }
+            let (_, _, commit, path) = self.state.borrow_mut().queue.pop()?;
+            if let Err(err) = self.process(commit, path) {
+                self.state.borrow_mut().clear();
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Return the longest run of lines within `unblamed` that also appears in `source_lines`, if it is significant enough.
+fn longest_moved_run(
+    unblamed: &Unblamed,
+    lines: &[&[u8]],
+    source_lines: &[&[u8]],
+    positions: &HashMap<&[u8], Vec<u32>>,
+) -> Option<lines::Match> {
+    let end = unblamed.suspect_start + unblamed.len;
+    let mut best: Option<(usize, lines::Match)> = None;
+    for start in unblamed.suspect_start..end {
+        for source_start in positions.get(lines[start as usize]).into_iter().flatten() {
+            let len = (0..)
+                .take_while(|offset| {
+                    start + offset < end
+                        && ((source_start + offset) as usize) < source_lines.len()
+                        && lines[(start + offset) as usize] == source_lines[(source_start + offset) as usize]
+                })
+                .count() as u32;
+            let score = lines[start as usize..(start + len) as usize]
+                .iter()
+                .map(|line| line.iter().filter(|b| b.is_ascii_alphanumeric()).count())
+                .sum::<usize>();
+            if score >= MOVE_SCORE && best.as_ref().map(|(best_score, _)| score > *best_score).unwrap_or(true) {
+                best = Some((
+                    score,
+                    lines::Match {
+                        old_start: *source_start,
+                        new_start: start,
+                        len,
+                    },
+                ));
+            }
+        }
+    }
+    best.map(|(_, m)| m)
+}
+
+/// Split `unblamed` lines into those covered by `matches`, translated into lines of the matches' old version,
+/// and those not covered by any of them.
+fn pass_blame(unblamed: Vec<Unblamed>, matches: &[lines::Match]) -> (Vec<Unblamed>, Vec<Unblamed>) {
+    let mut passed = Vec::new();
+    let mut remaining = Vec::new();
+    for u in unblamed {
+        let mut pos = u.suspect_start;
+        let end = u.suspect_start + u.len;
+        for m in matches {
+            let (overlap_start, overlap_end) = (m.new_start.max(pos), (m.new_start + m.len).min(end));
+            if overlap_start >= overlap_end {
+                continue;
+            }
+            if overlap_start > pos {
+                remaining.push(Unblamed {
+                    blamed_start: u.blamed_start + (pos - u.suspect_start),
+                    suspect_start: pos,
+                    len: overlap_start - pos,
+                });
+            }
+            passed.push(Unblamed {
+                blamed_start: u.blamed_start + (overlap_start - u.suspect_start),
+                suspect_start: m.old_start + (overlap_start - m.new_start),
+                len: overlap_end - overlap_start,
+            });
+            pos = overlap_end;
+        }
+        if pos < end {
+            remaining.push(Unblamed {
+                blamed_start: u.blamed_start + (pos - u.suspect_start),
+                suspect_start: pos,
+                len: end - pos,
+            });
+        }
+    }
+    (passed, remaining)
+}
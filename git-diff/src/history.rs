@@ -0,0 +1,462 @@
+use crate::tree;
+use git_commitgraph::bloom;
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BStr, BString, ByteSlice},
+    immutable,
+    tree::EntryMode,
+};
+use quick_error::quick_error;
+use std::{
+    borrow::BorrowMut,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+quick_error! {
+    /// The error is part of the item returned by the [Commits] iterator.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NotFound{oid: ObjectId} {
+            display("The object {} could not be found", oid)
+        }
+        ObjectDecode(err: immutable::object::decode::Error) {
+            display("An object could not be decoded")
+            source(err)
+            from()
+        }
+        Diff(err: tree::changes::Error) {
+            display("The trees of a commit and its parent could not be compared")
+            source(err)
+            from()
+        }
+    }
+}
+
+/// Options for use in [Commits::new()].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Options {
+    /// If true and if the path is a file, follow it across renames similar to `git log --follow`.
+    ///
+    /// A file is considered renamed if a file deleted in the same commit has the same content, or if at least half of it is
+    /// made of the same lines.
+    pub follow: bool,
+}
+
+/// A commit which changed the tracked path, as returned by the [Commits] iterator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Item {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The path as it is named in this commit, which differs from the path the iteration started with if renames are followed.
+    pub path: BString,
+}
+
+pub(crate) struct Info {
+    pub tree_id: ObjectId,
+    pub parents: Vec<ObjectId>,
+    pub committer_time: u32,
+}
+
+/// The state used and potentially shared by multiple path-limited history traversals.
+#[derive(Default)]
+pub struct State {
+    buf1: Vec<u8>,
+    buf2: Vec<u8>,
+    commits: HashMap<ObjectId, Info>,
+    /// The path to track in each commit, for all commits that were ever queued.
+    paths: HashMap<ObjectId, BString>,
+    /// The changed-path Bloom filter keys of each tracked path and its leading directories, along with the settings used
+    /// to compute them.
+    bloom_keys: HashMap<BString, (bloom::Settings, Vec<bloom::Key>)>,
+    /// Commits to process, by committer time, newest first, and by insertion order if the time is equal.
+    queue: BinaryHeap<(u32, Reverse<usize>, ObjectId)>,
+    seq: usize,
+    diff: tree::State<usize>,
+}
+
+impl State {
+    fn clear(&mut self) {
+        self.buf1.clear();
+        self.buf2.clear();
+        self.commits.clear();
+        self.paths.clear();
+        self.bloom_keys.clear();
+        self.queue.clear();
+        self.seq = 0;
+    }
+}
+
+/// An iterator over all commits that changed a path, similar to `git log -- <path>`, newest first.
+///
+/// It applies git's default history simplification: if a commit has the same content at the path as one of its parents, it is
+/// _TREESAME_ to that parent, won't be returned, and only the first of these parents is followed. Otherwise the commit is returned
+/// and all of its parents are followed.
+pub struct Commits<Find, StateMut> {
+    find: Find,
+    state: StateMut,
+    options: Options,
+    cache: Option<git_commitgraph::Graph>,
+}
+
+impl<Find, StateMut> Commits<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<(git_object::Kind, &'a [u8])>,
+    StateMut: BorrowMut<State>,
+{
+    /// Create a new instance.
+    ///
+    /// * `tips` - the commits to start the traversal at.
+    /// * `path` - the slash-separated path of a file or directory to track, relative to the root of the repository.
+    /// * `options` - additional configuration, like whether or not to follow renames.
+    /// * `state` - all state used for the traversal. If multiple traversals are performed, allocations can be minimized by reusing
+    ///   this state.
+    /// * `find` - a way to lookup objects by their id, writing their data into the buffer and returning their kind along with their
+    ///   data if the object is present. Commits, trees and blobs are looked up, the latter only if renames are followed.
+    pub fn new(
+        tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+        path: impl Into<BString>,
+        options: Options,
+        mut state: StateMut,
+        find: Find,
+    ) -> Result<Self, Error> {
+        let path = path.into();
+        let mut commits = {
+            state.borrow_mut().clear();
+            Commits {
+                find,
+                state,
+                options,
+                cache: None,
+            }
+        };
+        for tip in tips {
+            commits.enqueue(tip.into(), path.as_ref())?;
+        }
+        Ok(commits)
+    }
+
+    /// Use the changed-path Bloom filters of the given commit-graph, if present, to skip looking at the trees of commits
+    /// which definitely didn't change the tracked path compared to their first parent.
+    pub fn with_commit_graph(mut self, graph: Option<git_commitgraph::Graph>) -> Self {
+        self.cache = graph;
+        self
+    }
+
+    /// Queue commit `id` for processing, tracking `path` in it, unless it was queued before.
+    fn enqueue(&mut self, id: ObjectId, path: &BStr) -> Result<(), Error> {
+        if self.state.borrow_mut().paths.contains_key(&id) {
+            return Ok(());
+        }
+        self.load(id)?;
+        let state = self.state.borrow_mut();
+        state.paths.insert(id, path.to_owned());
+        state
+            .queue
+            .push((state.commits[&id].committer_time, Reverse(state.seq), id));
+        state.seq += 1;
+        Ok(())
+    }
+
+    /// Make sure the information about commit `id` is available.
+    fn load(&mut self, id: ObjectId) -> Result<(), Error> {
+        let state = self.state.borrow_mut();
+        if state.commits.contains_key(&id) {
+            return Ok(());
+        }
+        let info = decode_commit(&mut self.find, &mut state.buf1, id)?;
+        state.commits.insert(id, info);
+        Ok(())
+    }
+
+    /// Return true if the changed-path Bloom filter of commit `id` says it definitely didn't change `path` compared to its first
+    /// parent.
+    fn is_unchanged_according_to_bloom_filter(&mut self, id: &oid, path: &BString) -> bool {
+        let commit = match self.cache.as_ref().and_then(|graph| graph.commit_by_id(id)) {
+            Some(commit) => commit,
+            None => return false,
+        };
+        let (filter, settings) = match (commit.bloom_filter(), commit.bloom_filter_settings()) {
+            (Some(filter), Some(settings)) => (filter, settings),
+            _ => return false,
+        };
+        let bloom_keys = &mut self.state.borrow_mut().bloom_keys;
+        let needs_keys = match bloom_keys.get(path) {
+            Some((keys_settings, _)) => *keys_settings != settings,
+            None => true,
+        };
+        if needs_keys {
+            let normalized_path = normalized(path.as_ref());
+            let keys = if normalized_path.is_empty() {
+                Vec::new()
+            } else {
+                bloom::Key::with_leading_directories(normalized_path.as_ref(), &settings)
+            };
+            bloom_keys.insert(path.to_owned(), (settings, keys));
+        }
+        let keys = &bloom_keys[path].1;
+        !keys.is_empty() && !keys.iter().all(|key| filter.contains(key))
+    }
+
+    /// Return the entry at `path` in the tree of commit `id`.
+    fn entry(&mut self, id: &oid, path: &BStr) -> Result<Option<(EntryMode, ObjectId)>, Error> {
+        let tree_id = self.state.borrow_mut().commits[id].tree_id;
+        entry_at_path(&mut self.find, &mut self.state.borrow_mut().buf1, tree_id, path)
+    }
+
+    /// Find the path of the file in the tree of `parent` that was renamed to `path` with `entry` in the tree of `commit`.
+    fn rename_source(
+        &mut self,
+        parent: &oid,
+        commit: &oid,
+        (entry_mode, entry_id): (EntryMode, ObjectId),
+    ) -> Result<Option<BString>, Error> {
+        let find = &mut self.find;
+        let state = self.state.borrow_mut();
+        rename_source(
+            find,
+            &mut state.buf1,
+            &mut state.buf2,
+            &mut state.diff,
+            (state.commits[parent].tree_id, state.commits[commit].tree_id),
+            (entry_mode, entry_id),
+        )
+    }
+
+    fn next_inner(&mut self) -> Result<Option<Item>, Error> {
+        while let Some((_, _, id)) = self.state.borrow_mut().queue.pop() {
+            let path = self.state.borrow_mut().paths[&id].clone();
+            let parents = self.state.borrow_mut().commits[&id].parents.clone();
+            if let Some(first_parent) = parents.first() {
+                if self.is_unchanged_according_to_bloom_filter(&id, &path) {
+                    self.enqueue(*first_parent, path.as_ref())?;
+                    continue;
+                }
+            }
+
+            let entry = self.entry(&id, path.as_ref())?;
+            if parents.is_empty() {
+                if entry.is_some() {
+                    return Ok(Some(Item { id, path }));
+                }
+                continue;
+            }
+
+            let mut parent_entries = Vec::with_capacity(parents.len());
+            for parent_id in parents.iter() {
+                self.load(*parent_id)?;
+                parent_entries.push(self.entry(parent_id, path.as_ref())?);
+            }
+            if let Some(same_parent) = parent_entries.iter().position(|parent_entry| *parent_entry == entry) {
+                self.enqueue(parents[same_parent], path.as_ref())?;
+                continue;
+            }
+
+            for (parent_id, parent_entry) in parents.iter().zip(parent_entries) {
+                let mut parent_path = None;
+                if let (true, Some(entry), None) = (self.options.follow, entry, parent_entry) {
+                    parent_path = self.rename_source(parent_id, &id, entry)?;
+                }
+                self.enqueue(*parent_id, parent_path.as_ref().unwrap_or(&path).as_ref())?;
+            }
+            return Ok(Some(Item { id, path }));
+        }
+        Ok(None)
+    }
+}
+
+impl<Find, StateMut> Iterator for Commits<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<(git_object::Kind, &'a [u8])>,
+    StateMut: BorrowMut<State>,
+{
+    type Item = Result<Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_inner().transpose()
+    }
+}
+
+pub(crate) fn find_tree<'a, Find>(
+    find: &mut Find,
+    id: &oid,
+    buf: &'a mut Vec<u8>,
+) -> Result<immutable::TreeIter<'a>, Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<(git_object::Kind, &'b [u8])>,
+{
+    match find(id, buf) {
+        Some((git_object::Kind::Tree, data)) => Ok(immutable::TreeIter::from_bytes_with_hash_kind(data, id.kind())),
+        _ => Err(Error::NotFound { oid: id.to_owned() }),
+    }
+}
+
+pub(crate) fn find_blob<'a, Find>(find: &mut Find, id: &oid, buf: &'a mut Vec<u8>) -> Result<&'a [u8], Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<(git_object::Kind, &'b [u8])>,
+{
+    match find(id, buf) {
+        Some((git_object::Kind::Blob, data)) => Ok(data),
+        _ => Err(Error::NotFound { oid: id.to_owned() }),
+    }
+}
+
+/// Return the non-empty components of the slash-separated `path`.
+fn components(path: &BStr) -> impl Iterator<Item = &[u8]> {
+    path.split_str("/").filter(|c| !c.is_empty())
+}
+
+/// Return `path` with all of its [components] joined by single slashes, the way paths are stored in changed-path Bloom
+/// filters.
+fn normalized(path: &BStr) -> BString {
+    let mut out = BString::default();
+    for component in components(path) {
+        if !out.is_empty() {
+            out.push(b'/');
+        }
+        out.extend_from_slice(component);
+    }
+    out
+}
+
+/// Return the mode and id of the entry at the slash-separated `path` within the tree with `tree_id`, if present.
+pub(crate) fn entry_at_path<Find>(
+    find: &mut Find,
+    buf: &mut Vec<u8>,
+    mut tree_id: ObjectId,
+    path: &BStr,
+) -> Result<Option<(EntryMode, ObjectId)>, Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<(git_object::Kind, &'b [u8])>,
+{
+    let mut components = components(path).peekable();
+    while let Some(component) = components.next() {
+        let mut found = None;
+        for entry in find_tree(find, &tree_id, buf)? {
+            let entry = entry?;
+            if entry.filename == component {
+                found = Some((entry.mode, entry.oid.to_owned()));
+                break;
+            }
+        }
+        match found {
+            Some(entry) if components.peek().is_none() => return Ok(Some(entry)),
+            Some((EntryMode::Tree, id)) => tree_id = id,
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some((EntryMode::Tree, tree_id)))
+}
+
+/// Return the portion of bytes in the larger of `old` and `new` which are part of lines present in both, between 0.0 and 1.0.
+fn similarity(old: &[u8], new: &[u8]) -> f32 {
+    let max_len = old.len().max(new.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+    let mut old_lines = HashMap::<&[u8], usize>::new();
+    for line in old.lines_with_terminator() {
+        *old_lines.entry(line).or_default() += 1;
+    }
+    let mut common_bytes = 0;
+    for line in new.lines_with_terminator() {
+        if let Some(count) = old_lines.get_mut(line).filter(|count| **count > 0) {
+            *count -= 1;
+            common_bytes += line.len();
+        }
+    }
+    common_bytes as f32 / max_len as f32
+}
+
+pub(crate) fn decode_commit<Find>(find: &mut Find, buf: &mut Vec<u8>, id: ObjectId) -> Result<Info, Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<(git_object::Kind, &'b [u8])>,
+{
+    let commit_iter = match find(&id, buf) {
+        Some((git_object::Kind::Commit, data)) => immutable::CommitIter::from_bytes(data),
+        _ => return Err(Error::NotFound { oid: id }),
+    };
+    let mut info = Info {
+        tree_id: ObjectId::null_sha1(),
+        parents: Vec::new(),
+        committer_time: 0,
+    };
+    for token in commit_iter {
+        match token? {
+            immutable::commit::iter::Token::Tree { id } => info.tree_id = id,
+            immutable::commit::iter::Token::Parent { id } => info.parents.push(id),
+            immutable::commit::iter::Token::Author { .. } => {}
+            immutable::commit::iter::Token::Committer { signature } => {
+                info.committer_time = signature.time.time;
+                break;
+            }
+            _a_token_past_the_committer => break,
+        }
+    }
+    Ok(info)
+}
+
+/// Return all changes needed to turn the tree `lhs_tree` into `rhs_tree`.
+pub(crate) fn tree_changes<Find>(
+    find: &mut Find,
+    buf1: &mut Vec<u8>,
+    buf2: &mut Vec<u8>,
+    state: &mut tree::State<usize>,
+    (lhs_tree, rhs_tree): (ObjectId, ObjectId),
+) -> Result<Vec<tree::recorder::Change>, Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<(git_object::Kind, &'b [u8])>,
+{
+    let mut recorder = tree::Recorder::default();
+    let lhs = find_tree(find, &lhs_tree, buf1)?;
+    let rhs = find_tree(find, &rhs_tree, buf2)?;
+    tree::Changes::from(lhs).needed_to_obtain(
+        rhs,
+        state,
+        |id, buf| match find(id, buf) {
+            Some((git_object::Kind::Tree, data)) => {
+                Some(immutable::TreeIter::from_bytes_with_hash_kind(data, id.kind()))
+            }
+            _ => None,
+        },
+        &mut recorder,
+    )?;
+    Ok(recorder.records)
+}
+
+/// Find the path of the file in `parent_tree` that was renamed to become `entry` in `commit_tree`.
+pub(crate) fn rename_source<Find>(
+    find: &mut Find,
+    buf1: &mut Vec<u8>,
+    buf2: &mut Vec<u8>,
+    state: &mut tree::State<usize>,
+    (parent_tree, commit_tree): (ObjectId, ObjectId),
+    (entry_mode, entry_id): (EntryMode, ObjectId),
+) -> Result<Option<BString>, Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<(git_object::Kind, &'b [u8])>,
+{
+    if entry_mode.is_tree() {
+        return Ok(None);
+    }
+    let changes = tree_changes(find, buf1, buf2, state, (parent_tree, commit_tree))?;
+    let deleted_files = changes.into_iter().filter_map(|change| match change {
+        tree::recorder::Change::Deletion { entry_mode, oid, path } if entry_mode.is_no_tree() => Some((oid, path)),
+        _ => None,
+    });
+
+    let mut best: Option<(f32, BString)> = None;
+    let new_data = find_blob(find, &entry_id, buf1)?;
+    for (deleted_id, deleted_path) in deleted_files {
+        if deleted_id == entry_id {
+            return Ok(Some(deleted_path));
+        }
+        let old_data = find_blob(find, &deleted_id, buf2)?;
+        let score = similarity(old_data, new_data);
+        if score >= 0.5 && best.as_ref().map(|(best_score, _)| score > *best_score).unwrap_or(true) {
+            best = Some((score, deleted_path));
+        }
+    }
+    Ok(best.map(|(_, path)| path))
+}
@@ -4,3 +4,12 @@
 
 ///
 pub mod tree;
+
+/// Path-limited commit history with history simplification, similar to `git log -- <path>`.
+pub mod history;
+
+/// Line-based diffs between two versions of a file.
+pub mod lines;
+
+/// Attribute lines of a file to the commits that introduced them, similar to `git blame`.
+pub mod blame;
@@ -0,0 +1,147 @@
+use git_object::bstr::ByteSlice;
+use std::collections::HashMap;
+
+/// A run of lines which is unchanged between the old and the new version of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Match {
+    /// The index of the first line of the run in the old version.
+    pub old_start: u32,
+    /// The index of the first line of the run in the new version.
+    pub new_start: u32,
+    /// The amount of lines in the run.
+    pub len: u32,
+}
+
+/// Split `data` into lines, each including its line terminator if there is one.
+pub fn split(data: &[u8]) -> Vec<&[u8]> {
+    data.lines_with_terminator().collect()
+}
+
+/// Return all runs of lines which are unchanged when turning `old` lines into `new` lines, ordered by their position.
+///
+/// The result is a shortest edit script as computed by the linear space variant of [Myers' algorithm][myers].
+///
+/// [myers]: http://www.xmailserver.org/diff2.pdf
+pub fn matches<'a>(old: &[&'a [u8]], new: &[&'a [u8]]) -> Vec<Match> {
+    let mut interned = HashMap::<&'a [u8], u32>::new();
+    let mut intern = |line: &&'a [u8]| {
+        let next_id = interned.len() as u32;
+        *interned.entry(*line).or_insert(next_id)
+    };
+    let old: Vec<_> = old.iter().map(&mut intern).collect();
+    let new: Vec<_> = new.iter().map(&mut intern).collect();
+
+    let mut out = Vec::new();
+    diff(&old, &new, 0, 0, &mut out);
+
+    let mut merged: Vec<Match> = Vec::with_capacity(out.len());
+    for m in out.into_iter().filter(|m| m.len != 0) {
+        match merged.last_mut() {
+            Some(last) if last.old_start + last.len == m.old_start && last.new_start + last.len == m.new_start => {
+                last.len += m.len
+            }
+            _ => merged.push(m),
+        }
+    }
+    merged
+}
+
+fn diff(old: &[u32], new: &[u32], old_offset: usize, new_offset: usize, out: &mut Vec<Match>) {
+    let prefix = old.iter().zip(new.iter()).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+    push(out, old_offset, new_offset, prefix);
+
+    let (old_offset, new_offset) = (old_offset + prefix, new_offset + prefix);
+    if !old.is_empty() && !new.is_empty() {
+        let (edits, (x_start, y_start), (x_end, y_end)) = middle_snake(old, new);
+        if edits > 1 {
+            diff(&old[..x_start], &new[..y_start], old_offset, new_offset, out);
+            push(out, old_offset + x_start, new_offset + y_start, x_end - x_start);
+            diff(
+                &old[x_end..],
+                &new[y_end..],
+                old_offset + x_end,
+                new_offset + y_end,
+                out,
+            );
+        }
+    }
+    push(out, old_offset + old.len(), new_offset + new.len(), suffix);
+}
+
+fn push(out: &mut Vec<Match>, old_start: usize, new_start: usize, len: usize) {
+    out.push(Match {
+        old_start: old_start as u32,
+        new_start: new_start as u32,
+        len: len as u32,
+    });
+}
+
+/// Return the amount of edits needed to turn `old` into `new`, along with the start and end of the snake in the middle of
+/// the edit path, as `(x, y)` coordinates into `old` and `new` respectively.
+fn middle_snake(old: &[u32], new: &[u32]) -> (usize, (usize, usize), (usize, usize)) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    // `forward[k]` is the furthest x on diagonal k from the start, `backward[k]` the same from the end in reversed coordinates.
+    let mut forward = vec![0isize; 2 * offset as usize + 1];
+    let mut backward = vec![0isize; 2 * offset as usize + 1];
+    let index = |k: isize| (k + offset) as usize;
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[index(k - 1)] < forward[index(k + 1)]) {
+                forward[index(k + 1)]
+            } else {
+                forward[index(k - 1)] + 1
+            };
+            let (x_start, y_start) = (x, x - k);
+            let mut y = y_start;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[index(k)] = x;
+            let k_backward = delta - k;
+            if odd && k_backward > -d && k_backward < d && x + backward[index(k_backward)] >= n {
+                return (
+                    (2 * d - 1) as usize,
+                    (x_start as usize, y_start as usize),
+                    (x as usize, y as usize),
+                );
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[index(k - 1)] < backward[index(k + 1)]) {
+                backward[index(k + 1)]
+            } else {
+                backward[index(k - 1)] + 1
+            };
+            let (x_start, y_start) = (x, x - k);
+            let mut y = y_start;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[index(k)] = x;
+            let k_forward = delta - k;
+            if !odd && k_forward >= -d && k_forward <= d && x + forward[index(k_forward)] >= n {
+                return (
+                    (2 * d) as usize,
+                    ((n - x) as usize, (m - y) as usize),
+                    ((n - x_start) as usize, (m - y_start) as usize),
+                );
+            }
+        }
+    }
+    unreachable!("BUG: there is always a path from the start to the end")
+}
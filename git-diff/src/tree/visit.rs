@@ -76,7 +76,7 @@ mod tests {
     fn size_of_change() {
         assert_eq!(
             std::mem::size_of::<Change>(),
-            46,
+            70,
             "this type shouldn't grow without us knowing"
         )
     }
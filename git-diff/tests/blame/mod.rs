@@ -0,0 +1,140 @@
+use crate::hex_to_id;
+use git_diff::blame;
+use git_hash::ObjectId;
+use git_odb::{linked, pack, Find};
+
+fn db() -> crate::Result<linked::Db> {
+    linked::Db::at(
+        git_testtools::scripted_fixture_repo_read_only("make_blame_repo.sh")?
+            .join(".git")
+            .join("objects"),
+    )
+    .map_err(Into::into)
+}
+
+const HEAD: &str = "cf75a7a3ce15e3450d32b5541e247a8968e902e5";
+
+fn by_message(message: &str) -> ObjectId {
+    hex_to_id(match message {
+        "move lines from other" => HEAD,
+        "move first two lines down" => "2e7282adf85e1df12fdef2058dca5e4589c0aadd",
+        "rename file" => "7c9decc73eaef2cc4c049c621bf18f662dd70830",
+        "append fifth line" => "2d412774ca2702478c9de556018d95f579c290c1",
+        "modify second line" => "137aabd089298ee406dc2efcda8e352198bdc769",
+        "add file and other" => "53d1f4c42b03f82ad4bc19f63d3fd6e811d88fbe",
+        _ => unreachable!("unknown commit message: {}", message),
+    })
+}
+
+/// Return the commit, path and line in the source file for each line of `path` at `HEAD`.
+fn blame_lines(path: &str, options: blame::Options) -> crate::Result<Vec<(ObjectId, String, u32)>> {
+    let db = db()?;
+    let mut entries = blame::Blame::new(hex_to_id(HEAD), path, options, blame::State::default(), |oid, buf| {
+        db.find(oid, buf, &mut pack::cache::Never)
+            .ok()
+            .flatten()
+            .map(|obj| (obj.kind, obj.data))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.range_in_blamed_file.start);
+
+    let mut lines = Vec::new();
+    for entry in entries {
+        assert_eq!(
+            entry.range_in_blamed_file.len(),
+            entry.range_in_source_file.len(),
+            "ranges always have the same length"
+        );
+        assert_eq!(
+            entry.range_in_blamed_file.start as usize,
+            lines.len(),
+            "entries cover all lines without gaps or overlaps"
+        );
+        for line in entry.range_in_source_file {
+            lines.push((entry.commit_id, entry.path.to_string(), line));
+        }
+    }
+    Ok(lines)
+}
+
+fn assert_blame(options: blame::Options, expected: &[(&str, &str, u32)]) -> crate::Result {
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(message, path, line)| (by_message(message), path.to_string(), *line))
+        .collect();
+    assert_eq!(blame_lines("renamed", options)?, expected);
+    Ok(())
+}
+
+#[test]
+fn without_move_and_copy_detection() -> crate::Result {
+    assert_blame(
+        Default::default(),
+        &[
+            ("add file and other", "file", 2),
+            ("add file and other", "file", 3),
+            ("move first two lines down", "renamed", 2),
+            ("move first two lines down", "renamed", 3),
+            ("append fifth line", "file", 4),
+            ("move lines from other", "renamed", 5),
+            ("move lines from other", "renamed", 6),
+        ],
+    )
+}
+
+#[test]
+fn with_move_detection() -> crate::Result {
+    assert_blame(
+        blame::Options {
+            moves: true,
+            copies: false,
+        },
+        &[
+            ("add file and other", "file", 2),
+            ("add file and other", "file", 3),
+            ("add file and other", "file", 0),
+            ("modify second line", "file", 1),
+            ("append fifth line", "file", 4),
+            ("move lines from other", "renamed", 5),
+            ("move lines from other", "renamed", 6),
+        ],
+    )
+}
+
+#[test]
+fn with_move_and_copy_detection() -> crate::Result {
+    assert_blame(
+        blame::Options {
+            moves: true,
+            copies: true,
+        },
+        &[
+            ("add file and other", "file", 2),
+            ("add file and other", "file", 3),
+            ("add file and other", "file", 0),
+            ("modify second line", "file", 1),
+            ("append fifth line", "file", 4),
+            ("add file and other", "other", 1),
+            ("add file and other", "other", 2),
+        ],
+    )
+}
+
+#[test]
+fn missing_path() -> crate::Result {
+    let db = db()?;
+    let res = blame::Blame::new(
+        hex_to_id(HEAD),
+        "file",
+        Default::default(),
+        blame::State::default(),
+        |oid, buf| {
+            db.find(oid, buf, &mut pack::cache::Never)
+                .ok()
+                .flatten()
+                .map(|obj| (obj.kind, obj.data))
+        },
+    );
+    assert!(matches!(res, Err(blame::Error::PathNotFound { .. })));
+    Ok(())
+}
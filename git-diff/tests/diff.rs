@@ -4,4 +4,7 @@ pub fn hex_to_id(hex: &str) -> git_hash::ObjectId {
     git_hash::ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
 }
 
+mod blame;
+mod history;
+mod lines;
 mod visit;
@@ -0,0 +1,59 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local message=${1:?first argument is the commit message}
+  local date=${2:?second argument is the commit date}
+  git add -A
+  GIT_COMMITTER_DATE="$date +0000" GIT_AUTHOR_DATE="$date +0000" git commit -q -m "$message"
+}
+
+git init -q
+git config commit.gpgsign false
+git config core.autocrlf false
+
+git checkout -q -b main
+cat > file <<CONTENT
+first line of the file
+second line of the file
+third line of the file
+fourth line of the file
+CONTENT
+cat > other <<CONTENT
+alpha line in the other file
+beta line in the other file
+gamma line in the other file
+CONTENT
+commit_at "add file and other" 1000000000
+
+cat > file <<CONTENT
+first line of the file
+second line of the file, modified
+third line of the file
+fourth line of the file
+CONTENT
+commit_at "modify second line" 1000000100
+
+echo "fifth line, appended to the file" >> file
+commit_at "append fifth line" 1000000200
+
+git mv file renamed
+commit_at "rename file" 1000000300
+
+cat > renamed <<CONTENT
+third line of the file
+fourth line of the file
+first line of the file
+second line of the file, modified
+fifth line, appended to the file
+CONTENT
+commit_at "move first two lines down" 1000000400
+
+cat > other <<CONTENT
+alpha line in the other file
+CONTENT
+cat >> renamed <<CONTENT
+beta line in the other file
+gamma line in the other file
+CONTENT
+commit_at "move lines from other" 1000000500
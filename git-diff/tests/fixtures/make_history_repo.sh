@@ -0,0 +1,63 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local message=${1:?first argument is the commit message}
+  local date=${2:?second argument is the commit date}
+  GIT_COMMITTER_DATE="$date +0000" GIT_AUTHOR_DATE="$date +0000" git commit -q -am "$message"
+}
+
+function merge_at() {
+  local branch=${1:?first argument is the branch to merge}
+  local message=${2:?second argument is the commit message}
+  local date=${3:?third argument is the commit date}
+  GIT_COMMITTER_DATE="$date +0000" GIT_AUTHOR_DATE="$date +0000" git merge -q --no-ff "$branch" -m "$message"
+}
+
+git init -q
+git config commit.gpgsign false
+git config core.autocrlf false
+
+git checkout -q -b main
+mkdir dir
+seq 1 10 > dir/a
+echo other > other
+git add .
+commit_at "add dir/a and other" 1000000000
+
+echo changed > other
+commit_at "modify other" 1000000100
+
+seq 1 11 > dir/a
+commit_at "modify dir/a" 1000000200
+
+git checkout -q -b side
+echo "changed on side" > other
+commit_at "modify other on side" 1000000300
+
+git checkout -q main
+merge_at side "merge side without touching dir" 1000000400
+
+git checkout -q -b side2
+echo b > dir/b
+git add dir/b
+commit_at "add dir/b on side2" 1000000500
+
+git checkout -q main
+echo "also other" > other
+commit_at "modify other again" 1000000600
+merge_at side2 "merge side2" 1000000700
+
+git mv dir/a renamed
+commit_at "rename dir/a to renamed" 1000000800
+
+seq 1 12 > renamed
+commit_at "modify renamed" 1000000900
+
+git mv renamed final
+seq 0 12 > final
+commit_at "rename renamed to final with modification" 1000001000
+
+if [ "${1:-}" = "changed-paths" ]; then
+  git commit-graph write --no-progress --reachable --changed-paths
+fi
@@ -0,0 +1,188 @@
+use crate::hex_to_id;
+use git_diff::history;
+use git_hash::ObjectId;
+use git_odb::{linked, pack, Find};
+
+fn db() -> crate::Result<linked::Db> {
+    linked::Db::at(
+        git_testtools::scripted_fixture_repo_read_only("make_history_repo.sh")?
+            .join(".git")
+            .join("objects"),
+    )
+    .map_err(Into::into)
+}
+
+const HEAD: &str = "e4b00ccdb452d6f5709d9b396d1cd3bfe1ead7ff";
+
+fn by_message(message: &str) -> ObjectId {
+    hex_to_id(match message {
+        "rename renamed to final with modification" => HEAD,
+        "modify renamed" => "28979585daabf55d579b0e98fdbf392181e5f44e",
+        "rename dir/a to renamed" => "81db10f3929a4aac431baf2b15debcbf334dff52",
+        "merge side2" => "3a4315b54e887ab97db22e8c06793aafa437c158",
+        "modify other again" => "1c051a380fe34da299bc8de882eb57a5d5c990ca",
+        "add dir/b on side2" => "a30a8e9ba6810d81cfaa6a6fad17e424e3bffc9e",
+        "merge side without touching dir" => "5b9dbf84bc181aba474424474b8b1bd8ec40c40c",
+        "modify other on side" => "87e6c448fd4d7a377f1b273d6a32b218874bca60",
+        "modify dir/a" => "2f4b7b8d534c5f7a0a3896a9d92f8d3d4e81c344",
+        "modify other" => "c116342d25b559806cce847c3430cf26a1e21387",
+        "add dir/a and other" => "3aaefff1b073a72f02bb1668acefacfaaf55787c",
+        _ => unreachable!("unknown commit message: {}", message),
+    })
+}
+
+fn history(path: &str, options: history::Options) -> crate::Result<Vec<history::Item>> {
+    let db = db()?;
+    let items: Result<Vec<_>, _> = history::Commits::new(
+        Some(hex_to_id(HEAD)),
+        path,
+        options,
+        history::State::default(),
+        |oid, buf| {
+            db.find(oid, buf, &mut pack::cache::Never)
+                .ok()
+                .flatten()
+                .map(|obj| (obj.kind, obj.data))
+        },
+    )?
+    .collect();
+    Ok(items?)
+}
+
+fn assert_history(path: &str, expected: &[&str]) -> crate::Result {
+    let expected: Vec<_> = expected.iter().copied().map(by_message).collect();
+    let actual: Vec<_> = history(path, Default::default())?
+        .into_iter()
+        .map(|item| {
+            assert_eq!(item.path, path, "the path never changes without following renames");
+            item.id
+        })
+        .collect();
+    assert_eq!(actual, expected, "path: {}", path);
+    Ok(())
+}
+
+#[test]
+fn directory() -> crate::Result {
+    assert_history(
+        "dir",
+        &[
+            "rename dir/a to renamed",
+            "add dir/b on side2",
+            "modify dir/a",
+            "add dir/a and other",
+        ],
+    )
+}
+
+#[test]
+fn file_in_directory() -> crate::Result {
+    assert_history(
+        "dir/a",
+        &["rename dir/a to renamed", "modify dir/a", "add dir/a and other"],
+    )
+}
+
+#[test]
+fn merges_treesame_to_a_parent_follow_only_that_parent() -> crate::Result {
+    assert_history(
+        "other",
+        &[
+            "modify other again",
+            "modify other on side",
+            "modify other",
+            "add dir/a and other",
+        ],
+    )
+}
+
+#[test]
+fn file_without_following_renames() -> crate::Result {
+    assert_history(
+        "renamed",
+        &[
+            "rename renamed to final with modification",
+            "modify renamed",
+            "rename dir/a to renamed",
+        ],
+    )?;
+    assert_history("final", &["rename renamed to final with modification"])
+}
+
+#[test]
+fn file_following_renames() -> crate::Result {
+    let actual: Vec<_> = history("final", history::Options { follow: true })?
+        .into_iter()
+        .map(|item| (item.id, item.path))
+        .collect();
+    let expected: Vec<_> = [
+        ("rename renamed to final with modification", "final"),
+        ("modify renamed", "renamed"),
+        ("rename dir/a to renamed", "renamed"),
+        ("modify dir/a", "dir/a"),
+        ("add dir/a and other", "dir/a"),
+    ]
+    .iter()
+    .map(|(message, path)| (by_message(message), (*path).into()))
+    .collect();
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[test]
+fn missing_path() -> crate::Result {
+    assert_history("does-not-exist", &[])
+}
+
+#[test]
+fn changed_path_bloom_filters_avoid_tree_lookups() -> crate::Result {
+    let repo_dir =
+        git_testtools::scripted_fixture_repo_read_only_with_args("make_history_repo.sh", Some("changed-paths"))?;
+    let db = linked::Db::at(repo_dir.join(".git").join("objects"))?;
+    let history_with_lookups = |path: &str, options: history::Options, use_graph: bool| -> crate::Result<_> {
+        let graph = if use_graph {
+            Some(git_commitgraph::Graph::from_info_dir(
+                repo_dir.join(".git").join("objects").join("info"),
+            )?)
+        } else {
+            None
+        };
+        let mut lookups = 0;
+        let items: Result<Vec<_>, _> = history::Commits::new(
+            Some(hex_to_id(HEAD)),
+            path,
+            options,
+            history::State::default(),
+            |oid, buf| {
+                lookups += 1;
+                db.find(oid, buf, &mut pack::cache::Never)
+                    .ok()
+                    .flatten()
+                    .map(|obj| (obj.kind, obj.data))
+            },
+        )?
+        .with_commit_graph(graph)
+        .collect();
+        let items: Vec<_> = items?.into_iter().map(|item| (item.id, item.path)).collect();
+        Ok((items, lookups))
+    };
+
+    for path in &["dir", "dir/a", "other", "final", "dir/", "dir//a"] {
+        for follow in &[false, true] {
+            let options = history::Options { follow: *follow };
+            let (expected, lookups_without_graph) = history_with_lookups(path, options, false)?;
+            let (actual, lookups_with_graph) = history_with_lookups(path, options, true)?;
+            assert!(!expected.is_empty());
+            assert_eq!(actual, expected, "path: {}, follow: {}", path, follow);
+            assert!(
+                lookups_with_graph < lookups_without_graph,
+                "path: {}, follow: {}: {} < {}",
+                path,
+                follow,
+                lookups_with_graph,
+                lookups_without_graph
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use git_diff::lines::{self, Match};
+
+fn matches(old: &str, new: &str) -> Vec<Match> {
+    let (old, new): (Vec<_>, Vec<_>) = (old.bytes().collect(), new.bytes().collect());
+    let old: Vec<_> = old.iter().map(std::slice::from_ref).collect();
+    let new: Vec<_> = new.iter().map(std::slice::from_ref).collect();
+    lines::matches(&old, &new)
+}
+
+fn m(old_start: u32, new_start: u32, len: u32) -> Match {
+    Match {
+        old_start,
+        new_start,
+        len,
+    }
+}
+
+/// The length of the longest common subsequence, computed the slow way.
+fn lcs_len(old: &[u8], new: &[u8]) -> u32 {
+    let mut table = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for (x, a) in old.iter().enumerate() {
+        for (y, b) in new.iter().enumerate() {
+            table[x + 1][y + 1] = if a == b {
+                table[x][y] + 1
+            } else {
+                table[x][y + 1].max(table[x + 1][y])
+            };
+        }
+    }
+    table[old.len()][new.len()]
+}
+
+#[test]
+fn split_keeps_line_terminators() {
+    assert_eq!(lines::split(b"a\nb\r\nc"), vec![&b"a\n"[..], b"b\r\n", b"c"]);
+    assert!(lines::split(b"").is_empty());
+}
+
+#[test]
+fn identical_and_disjoint_inputs() {
+    assert_eq!(matches("abc", "abc"), vec![m(0, 0, 3)]);
+    assert_eq!(matches("abc", "xyz"), vec![]);
+    assert_eq!(matches("", "abc"), vec![]);
+    assert_eq!(matches("abc", ""), vec![]);
+}
+
+#[test]
+fn insertions_and_deletions() {
+    assert_eq!(matches("ac", "abc"), vec![m(0, 0, 1), m(1, 2, 1)]);
+    assert_eq!(matches("abc", "ac"), vec![m(0, 0, 1), m(2, 1, 1)]);
+    assert_eq!(matches("abcd", "xbcy"), vec![m(1, 1, 2)]);
+}
+
+#[test]
+fn matches_form_a_longest_common_subsequence() {
+    let inputs = [
+        ("abcabba", "cbabac"),
+        ("abgdef", "gh"),
+        ("aaaabbbb", "bbbbaaaa"),
+        ("the quick brown fox", "a quick brown dog jumps"),
+        ("xaxbxcx", "abc"),
+        ("abcdefghij", "jihgfedcba"),
+    ];
+    for (old, new) in inputs.iter() {
+        let actual = matches(old, new);
+        for window in actual.windows(2) {
+            assert!(
+                window[0].old_start + window[0].len < window[1].old_start
+                    || window[0].new_start + window[0].len < window[1].new_start,
+                "adjacent runs are merged and ordered"
+            );
+        }
+        for run in &actual {
+            for offset in 0..run.len {
+                assert_eq!(
+                    old.as_bytes()[(run.old_start + offset) as usize],
+                    new.as_bytes()[(run.new_start + offset) as usize]
+                );
+            }
+        }
+        assert_eq!(
+            actual.iter().map(|run| run.len).sum::<u32>(),
+            lcs_len(old.as_bytes(), new.as_bytes()),
+            "{:?} -> {:?}",
+            old,
+            new
+        );
+    }
+}
@@ -2,11 +2,17 @@
 //!
 //! With the `fast-sha1` feature, the [`Sha1`] hash type will use a more elaborate implementation utilizing hardware support
 //! in case it is available.
+//! With the `sha1-collision-detection` feature, which takes precedence, the [`Sha1`] hash type detects the use of known
+//! collision attacks like SHAttered in the hashed data, similar to what git does by default, at the cost of performance.
+//! Use [`Sha1::try_digest()`] or [`Hasher::try_digest()`] to learn about detected collisions.
 //! Otherwise, a minimal yet performant implementation is used instead for a decent trade-off between compile times and run-time performance.
+//!
+//! Repositories using the Sha256 object format are supported by the [`Sha256`] hash type, and [`hasher()`] provides a hash
+//! of any [kind][git_hash::Kind].
 
-#[cfg(not(feature = "fast-sha1"))]
+#[cfg(not(any(feature = "fast-sha1", feature = "sha1-collision-detection")))]
 mod _impl {
-    use super::Sha1Digest;
+    use super::{Collision, Sha1Digest};
 
     /// A implementation of the Sha1 hash, which can be used once.
     #[derive(Default, Clone)]
@@ -21,15 +27,19 @@ mod _impl {
         pub fn digest(self) -> Sha1Digest {
             self.0.digest().bytes()
         }
+        /// Finalize the hash and produce a digest, which never fails as this implementation doesn't detect collisions.
+        pub fn try_digest(self) -> Result<Sha1Digest, Collision> {
+            Ok(self.digest())
+        }
     }
 }
 
 /// A 20 bytes digest produced by a [`Sha1`] hash implementation.
 pub type Sha1Digest = [u8; 20];
 
-#[cfg(feature = "fast-sha1")]
+#[cfg(all(feature = "fast-sha1", not(feature = "sha1-collision-detection")))]
 mod _impl {
-    use super::Sha1Digest;
+    use super::{Collision, Sha1Digest};
     use fastsha1::Digest;
 
     /// A implementation of the Sha1 hash, which can be used once.
@@ -45,11 +55,124 @@ mod _impl {
         pub fn digest(self) -> Sha1Digest {
             self.0.finalize().into()
         }
+        /// Finalize the hash and produce a digest, which never fails as this implementation doesn't detect collisions.
+        pub fn try_digest(self) -> Result<Sha1Digest, Collision> {
+            Ok(self.digest())
+        }
+    }
+}
+
+#[cfg(feature = "sha1-collision-detection")]
+mod _impl {
+    use super::{Collision, Sha1Digest};
+    use sha1collisiondetection::Sha1CD;
+
+    /// A implementation of the Sha1 hash detecting collision attacks, which can be used once.
+    #[derive(Clone)]
+    pub struct Sha1(Sha1CD);
+
+    impl Default for Sha1 {
+        fn default() -> Self {
+            // Without mitigation, the digest is always the actual Sha1 hash even if a collision attack was detected.
+            Sha1(Sha1CD::configure().safe_hash(false).build())
+        }
+    }
+
+    impl Sha1 {
+        /// Digest the given `bytes`.
+        pub fn update(&mut self, bytes: &[u8]) {
+            self.0.update(bytes)
+        }
+        /// Finalize the hash and produce a digest, even if a collision attack was detected.
+        pub fn digest(mut self) -> Sha1Digest {
+            let mut digest = sha1collisiondetection::Output::default();
+            self.0.finalize_into_dirty_cd(&mut digest).ok();
+            digest.into()
+        }
+        /// Finalize the hash and produce a digest, or fail if a collision attack was detected in the hashed data.
+        pub fn try_digest(self) -> Result<Sha1Digest, Collision> {
+            self.0.finalize_cd().map(Into::into).map_err(|_| Collision)
+        }
+    }
+}
+
+/// The error returned by [`Sha1::try_digest()`] and [`Hasher::try_digest()`] if the hashed data contains a known collision attack,
+/// which is only detected with the `sha1-collision-detection` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collision;
+
+impl std::fmt::Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("A Sha1 collision attack was detected in the hashed data")
     }
 }
 
+impl std::error::Error for Collision {}
+
 pub use _impl::Sha1;
 
+/// A 32 bytes digest produced by a [`Sha256`] hash implementation.
+pub type Sha256Digest = [u8; 32];
+
+/// A implementation of the Sha256 hash, which can be used once.
+#[derive(Default, Clone)]
+pub struct Sha256(sha2::Sha256);
+
+impl Sha256 {
+    /// Digest the given `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        use sha2::Digest;
+        self.0.update(bytes)
+    }
+    /// Finalize the hash and produce a digest.
+    pub fn digest(self) -> Sha256Digest {
+        use sha2::Digest;
+        self.0.finalize().into()
+    }
+}
+
+/// A hash of any [kind][git_hash::Kind], as obtained by [`hasher()`].
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Hasher {
+    /// The Sha1 hash
+    Sha1(Sha1),
+    /// The Sha256 hash
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    /// Digest the given `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha1(hash) => hash.update(bytes),
+            Hasher::Sha256(hash) => hash.update(bytes),
+        }
+    }
+    /// Finalize the hash and produce an object id of the respective kind.
+    pub fn digest(self) -> git_hash::ObjectId {
+        match self {
+            Hasher::Sha1(hash) => git_hash::ObjectId::new_sha1(hash.digest()),
+            Hasher::Sha256(hash) => git_hash::ObjectId::new_sha256(hash.digest()),
+        }
+    }
+    /// Finalize the hash and produce an object id of the respective kind, or fail if a collision attack was detected.
+    pub fn try_digest(self) -> Result<git_hash::ObjectId, Collision> {
+        Ok(match self {
+            Hasher::Sha1(hash) => git_hash::ObjectId::new_sha1(hash.try_digest()?),
+            Hasher::Sha256(hash) => git_hash::ObjectId::new_sha256(hash.digest()),
+        })
+    }
+}
+
+/// Return a hasher producing object ids of the given `kind`.
+pub fn hasher(kind: git_hash::Kind) -> Hasher {
+    match kind {
+        git_hash::Kind::Sha1 => Hasher::Sha1(Sha1::default()),
+        git_hash::Kind::Sha256 => Hasher::Sha256(Sha256::default()),
+    }
+}
+
 /// Compute a CRC32 hash from the given `bytes`, returning the CRC32 hash.
 ///
 /// When calling this function for the first time, `previous_value` should be `0`. Otherwise it
@@ -80,9 +203,7 @@ pub fn bytes_of_file(
     kind: git_hash::Kind,
     progress: &mut impl crate::progress::Progress,
 ) -> std::io::Result<git_hash::ObjectId> {
-    let mut hasher = match kind {
-        git_hash::Kind::Sha1 => crate::hash::Sha1::default(),
-    };
+    let mut hasher = hasher(kind);
     let start = std::time::Instant::now();
     // init progress before the possibility for failure, as convenience in case people want to recover
     progress.init(Some(num_bytes_from_start), crate::progress::bytes());
@@ -104,7 +225,7 @@ pub fn bytes_of_file(
         }
     }
 
-    let id = git_hash::ObjectId::new_sha1(hasher.digest());
+    let id = hasher.digest();
     progress.show_throughput(start);
     Ok(id)
 }
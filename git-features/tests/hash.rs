@@ -1,13 +1,68 @@
-use git_features::hash::Sha1;
-
-#[cfg(not(feature = "fast-sha1"))]
+#[cfg(not(any(feature = "fast-sha1", feature = "sha1-collision-detection")))]
 #[test]
 fn size_of_sha1() {
-    assert_eq!(std::mem::size_of::<Sha1>(), 96)
+    assert_eq!(std::mem::size_of::<git_features::hash::Sha1>(), 96)
 }
 
-#[cfg(feature = "fast-sha1")]
+#[cfg(all(feature = "fast-sha1", not(feature = "sha1-collision-detection")))]
 #[test]
 fn size_of_sha1() {
-    assert_eq!(std::mem::size_of::<Sha1>(), 104)
+    assert_eq!(std::mem::size_of::<git_features::hash::Sha1>(), 104)
+}
+
+#[test]
+fn hasher_produces_ids_of_the_requested_kind() {
+    for (kind, expected) in &[
+        (git_hash::Kind::Sha1, "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            git_hash::Kind::Sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+    ] {
+        let mut hasher = git_features::hash::hasher(*kind);
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        let id = hasher.digest();
+        assert_eq!(id.kind(), *kind);
+        assert_eq!(id.to_string(), *expected);
+    }
+}
+
+mod collision {
+    use git_features::hash::{self, Sha1};
+
+    const SHAMBLES: &[u8] = include_bytes!("fixtures/sha-mbles-1.bin");
+
+    fn sha1_of(bytes: &[u8]) -> Sha1 {
+        let mut hash = Sha1::default();
+        hash.update(bytes);
+        hash
+    }
+
+    #[test]
+    fn digest_is_the_actual_sha1_even_for_collision_attacks() {
+        assert_eq!(
+            git_hash::ObjectId::new_sha1(sha1_of(SHAMBLES).digest()).to_string(),
+            "8ac60ba76f1999a1ab70223f225aefdc78d4ddc0"
+        );
+    }
+
+    #[cfg(feature = "sha1-collision-detection")]
+    #[test]
+    fn try_digest_fails_on_collision_attacks() {
+        assert_eq!(sha1_of(SHAMBLES).try_digest(), Err(hash::Collision));
+        let mut hasher = hash::hasher(git_hash::Kind::Sha1);
+        hasher.update(SHAMBLES);
+        assert_eq!(hasher.try_digest(), Err(hash::Collision));
+        assert!(sha1_of(b"abc").try_digest().is_ok(), "regular data passes");
+    }
+
+    #[cfg(not(feature = "sha1-collision-detection"))]
+    #[test]
+    fn try_digest_does_not_detect_collision_attacks() {
+        assert_eq!(sha1_of(SHAMBLES).try_digest(), Ok(sha1_of(SHAMBLES).digest()));
+        let mut hasher = hash::hasher(git_hash::Kind::Sha1);
+        hasher.update(SHAMBLES);
+        assert!(hasher.try_digest().is_ok());
+    }
 }
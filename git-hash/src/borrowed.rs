@@ -1,4 +1,4 @@
-use crate::{ObjectId, SIZE_OF_SHA1_DIGEST};
+use crate::{ObjectId, SIZE_OF_SHA1_DIGEST, SIZE_OF_SHA256_DIGEST};
 use std::{convert::TryInto, fmt};
 
 /// A borrowed reference to a hash identifying objects.
@@ -25,6 +25,7 @@ impl std::fmt::Debug for oid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.kind() {
             crate::Kind::Sha1 => f.write_str("Sha1(")?,
+            crate::Kind::Sha256 => f.write_str("Sha256(")?,
         }
         for b in self.as_bytes() {
             write!(f, "{:02x}", b)?;
@@ -48,7 +49,7 @@ quick_error! {
 impl oid {
     pub fn try_from(value: &[u8]) -> Result<&Self, Error> {
         match value.len() {
-            20 => Ok(
+            SIZE_OF_SHA1_DIGEST | SIZE_OF_SHA256_DIGEST => Ok(
                 #[allow(unsafe_code)]
                 unsafe {
                     &*(value as *const [u8] as *const oid)
@@ -71,9 +72,9 @@ impl oid {
 impl oid {
     /// The kind of hash used for this Digest
     pub fn kind(&self) -> crate::Kind {
-        match self.bytes.len() {
-            20 => crate::Kind::Sha1,
-            _ => unreachable!("creating this instance is checked and fails on unknown lengths"),
+        match crate::Kind::from_len_in_bytes(self.bytes.len()) {
+            Some(kind) => kind,
+            None => unreachable!("creating this instance is checked and fails on unknown lengths"),
         }
     }
 
@@ -124,6 +125,7 @@ impl ToOwned for oid {
     fn to_owned(&self) -> Self::Owned {
         match self.kind() {
             crate::Kind::Sha1 => crate::ObjectId::Sha1(self.bytes.try_into().expect("no bug in hash detection")),
+            crate::Kind::Sha256 => crate::ObjectId::Sha256(self.bytes.try_into().expect("no bug in hash detection")),
         }
     }
 }
@@ -134,6 +136,12 @@ impl<'a> From<&'a [u8; SIZE_OF_SHA1_DIGEST]> for &'a oid {
     }
 }
 
+impl<'a> From<&'a [u8; SIZE_OF_SHA256_DIGEST]> for &'a oid {
+    fn from(v: &'a [u8; SIZE_OF_SHA256_DIGEST]) -> Self {
+        oid::from(v.as_ref())
+    }
+}
+
 impl fmt::Display for &oid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for b in self.as_bytes() {
@@ -178,7 +186,7 @@ impl<'de: 'a, 'a> serde::Deserialize<'de> for &'a oid {
                         return Err(__err);
                     }
                 };
-                Ok(oid::try_from(__field0).expect("exactly 20 bytes"))
+                Ok(oid::try_from(__field0).expect("exactly 20 or 32 bytes"))
             }
             #[inline]
             fn visit_seq<__A>(self, mut __seq: __A) -> std::result::Result<Self::Value, __A::Error>
@@ -199,7 +207,7 @@ impl<'de: 'a, 'a> serde::Deserialize<'de> for &'a oid {
                         ));
                     }
                 };
-                Ok(oid::try_from(__field0).expect("exactly 20 bytes"))
+                Ok(oid::try_from(__field0).expect("exactly 20 or 32 bytes"))
             }
         }
         serde::Deserializer::deserialize_newtype_struct(
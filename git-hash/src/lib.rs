@@ -22,7 +22,7 @@ pub mod decode {
     use std::str::FromStr;
 
     quick_error! {
-        /// An error returned by [`ObjectId::from_40_bytes_in_hex()`]
+        /// An error returned by [`ObjectId::from_hex()`]
         #[derive(Debug)]
         #[allow(missing_docs)]
         pub enum Error {
@@ -34,7 +34,8 @@ pub mod decode {
 
     /// Hash decoding
     impl ObjectId {
-        /// Create an instance from a `buffer` of 40 bytes encoded with hexadecimal notation.
+        /// Create an instance from a `buffer` of 40 or 64 bytes encoded with hexadecimal notation, for a Sha1 or Sha256
+        /// hash respectively.
         ///
         /// Such a buffer can be obtained using [`write_hex_to(buffer)`][ObjectId::write_hex_to()]
         pub fn from_hex(buffer: &[u8]) -> Result<ObjectId, Error> {
@@ -43,6 +44,9 @@ pub mod decode {
                 40 => Ok(ObjectId::Sha1(
                     <[u8; 20]>::from_hex(buffer).expect("our length check is correct thus we can decode hex"),
                 )),
+                64 => Ok(ObjectId::Sha256(
+                    <[u8; 32]>::from_hex(buffer).expect("our length check is correct thus we can decode hex"),
+                )),
                 len => Err(Error::InvalidHexEncodingLength(len)),
             }
         }
@@ -52,19 +56,15 @@ pub mod decode {
         type Err = Error;
 
         fn from_str(s: &str) -> Result<Self, Self::Err> {
-            use hex::FromHex;
-            match s.len() {
-                40 => Ok(ObjectId::Sha1(
-                    <[u8; 20]>::from_hex(s).expect("our length check is correct thus we can decode hex"),
-                )),
-                len => Err(Error::InvalidHexEncodingLength(len)),
-            }
+            Self::from_hex(s.as_bytes())
         }
     }
 }
 
 /// The size of a SHA1 hash digest in bytes
 pub const SIZE_OF_SHA1_DIGEST: usize = 20;
+/// The size of a SHA256 hash digest in bytes
+pub const SIZE_OF_SHA256_DIGEST: usize = 32;
 
 /// Denotes the kind of function to produce a `Id`
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
@@ -72,6 +72,8 @@ pub const SIZE_OF_SHA1_DIGEST: usize = 20;
 pub enum Kind {
     /// The Sha1 hash with 160 bits.
     Sha1,
+    /// The Sha256 hash with 256 bits.
+    Sha256,
 }
 
 impl Default for Kind {
@@ -79,3 +81,53 @@ impl Default for Kind {
         Kind::Sha1
     }
 }
+
+impl Kind {
+    /// Returns the amount of bytes taken up by a hash of this kind.
+    pub fn len_in_bytes(&self) -> usize {
+        match self {
+            Kind::Sha1 => SIZE_OF_SHA1_DIGEST,
+            Kind::Sha256 => SIZE_OF_SHA256_DIGEST,
+        }
+    }
+
+    /// Returns the amount of hexadecimal characters needed to encode a hash of this kind.
+    pub fn len_in_hex(&self) -> usize {
+        self.len_in_bytes() * 2
+    }
+
+    /// Returns the kind of hash whose digest is `len` bytes long, or `None` if there is no such hash.
+    pub fn from_len_in_bytes(len: usize) -> Option<Kind> {
+        match len {
+            SIZE_OF_SHA1_DIGEST => Some(Kind::Sha1),
+            SIZE_OF_SHA256_DIGEST => Some(Kind::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of this hash as used by git in the `extensions.objectFormat` configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Sha1 => "sha1",
+            Kind::Sha256 => "sha256",
+        }
+    }
+
+    /// Returns the hash kind named `name` as used by git in the `extensions.objectFormat` configuration, or `None` if it is
+    /// unknown.
+    pub fn from_name(name: &[u8]) -> Option<Kind> {
+        match name {
+            b"sha1" => Some(Kind::Sha1),
+            b"sha256" => Some(Kind::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Returns an id of this kind whose memory is zeroed.
+    pub fn null(&self) -> ObjectId {
+        match self {
+            Kind::Sha1 => ObjectId::null_sha1(),
+            Kind::Sha256 => ObjectId::Sha256([0u8; SIZE_OF_SHA256_DIGEST]),
+        }
+    }
+}
@@ -1,17 +1,26 @@
-use crate::{borrowed::oid, SIZE_OF_SHA1_DIGEST};
+use crate::{borrowed::oid, SIZE_OF_SHA1_DIGEST, SIZE_OF_SHA256_DIGEST};
 use std::{borrow::Borrow, fmt, io, ops::Deref};
 
 /// An owned hash identifying objects, most commonly Sha1
-#[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub enum ObjectId {
     Sha1([u8; SIZE_OF_SHA1_DIGEST]),
+    Sha256([u8; SIZE_OF_SHA256_DIGEST]),
+}
+
+// Hash like `oid` does, as required by our `Borrow<oid>` implementation to allow lookups in hashed collections by `&oid`.
+impl std::hash::Hash for ObjectId {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
 }
 
 impl std::fmt::Debug for ObjectId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ObjectId::Sha1(_hash) => f.write_str("Sha1(")?,
+            ObjectId::Sha256(_hash) => f.write_str("Sha256(")?,
         }
         for b in self.as_bytes() {
             write!(f, "{:02x}", b)?;
@@ -24,24 +33,32 @@ impl std::fmt::Debug for ObjectId {
 impl ObjectId {
     /// Returns the kind of hash used in this `Id`
     pub fn kind(&self) -> crate::Kind {
-        crate::Kind::Sha1
+        match self {
+            Self::Sha1(_) => crate::Kind::Sha1,
+            Self::Sha256(_) => crate::Kind::Sha256,
+        }
     }
     /// Return the raw byte slice representing this hash
     pub fn as_slice(&self) -> &[u8] {
         match self {
             Self::Sha1(b) => b.as_ref(),
+            Self::Sha256(b) => b.as_ref(),
         }
     }
     /// Return the raw mutable byte slice representing this hash
     pub fn as_mut_slice(&mut self) -> &mut [u8] {
         match self {
             Self::Sha1(b) => b.as_mut(),
+            Self::Sha256(b) => b.as_mut(),
         }
     }
 
     /// Write ourselves to `out` in hexadecimal notation
     pub fn write_hex_to(&self, mut out: impl io::Write) -> io::Result<()> {
-        out.write_all(&self.to_sha1_hex())
+        let mut hex_buf = [0u8; SIZE_OF_SHA256_DIGEST * 2];
+        let hex_len = self.kind().len_in_hex();
+        hex::encode_to_slice(self.as_slice(), &mut hex_buf[..hex_len]).expect("we can count");
+        out.write_all(&hex_buf[..hex_len])
     }
 
     pub const fn empty_tree() -> ObjectId {
@@ -57,6 +74,7 @@ impl ObjectId {
     pub fn sha1(&self) -> &[u8; SIZE_OF_SHA1_DIGEST] {
         match self {
             Self::Sha1(b) => &b,
+            Self::Sha256(_) => panic!("{:?} is not a Sha1 hash", self),
         }
     }
 
@@ -70,6 +88,7 @@ impl ObjectId {
                 hex::encode_to_slice(b, &mut hex_buf).expect("we can count");
                 hex_buf
             }
+            Self::Sha256(_) => panic!("{:?} is not a Sha1 hash", self),
         }
     }
 
@@ -108,16 +127,40 @@ impl ObjectId {
     }
 }
 
+/// Sha256 hash specific methods
+impl ObjectId {
+    /// Instantiate an Digest from 32 bytes of a Sha256 digest.
+    pub fn new_sha256(id: [u8; SIZE_OF_SHA256_DIGEST]) -> Self {
+        ObjectId::Sha256(id)
+    }
+
+    /// Instantiate an Digest from a slice 32 borrowed bytes of a Sha256 digest.
+    ///
+    /// Panics of the slice doesn't have a length of 32.
+    pub fn from_32_bytes(b: &[u8]) -> ObjectId {
+        let mut id = [0; SIZE_OF_SHA256_DIGEST];
+        id.copy_from_slice(b);
+        ObjectId::Sha256(id)
+    }
+}
+
 impl From<[u8; SIZE_OF_SHA1_DIGEST]> for ObjectId {
     fn from(v: [u8; 20]) -> Self {
         Self::new_sha1(v)
     }
 }
 
+impl From<[u8; SIZE_OF_SHA256_DIGEST]> for ObjectId {
+    fn from(v: [u8; 32]) -> Self {
+        Self::new_sha256(v)
+    }
+}
+
 impl From<&crate::oid> for ObjectId {
     fn from(v: &oid) -> Self {
         match v.kind() {
             crate::Kind::Sha1 => ObjectId::from_20_bytes(v.as_bytes()),
+            crate::Kind::Sha256 => ObjectId::from_32_bytes(v.as_bytes()),
         }
     }
 }
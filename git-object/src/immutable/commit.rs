@@ -21,7 +21,7 @@ use crate::{
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub struct Commit<'a> {
-    /// HEX hash of tree object we point to. Usually 40 bytes long.
+    /// HEX hash of tree object we point to. 40 bytes long for Sha1 and 64 bytes long for Sha256.
     ///
     /// Use [`tree()`][Commit::tree()] to obtain a decoded version of it.
     #[cfg_attr(feature = "serde1", serde(borrow))]
@@ -79,10 +79,11 @@ fn parse_message(i: &[u8]) -> IResult<&[u8], &BStr, decode::Error> {
 }
 
 fn parse(i: &[u8]) -> IResult<&[u8], Commit<'_>, decode::Error> {
-    let (i, tree) = parse::header_field(i, b"tree", parse::hex_sha1)
-        .map_err(decode::Error::context("tree <40 lowercase hex char>"))?;
-    let (i, parents) = many0(|i| parse::header_field(i, b"parent", parse::hex_sha1))(i)
-        .map_err(decode::Error::context("zero or more 'parent <40 lowercase hex char>'"))?;
+    let (i, tree) = parse::header_field(i, b"tree", parse::hex_hash)
+        .map_err(decode::Error::context("tree <40 or 64 lowercase hex char>"))?;
+    let (i, parents) = many0(|i| parse::header_field(i, b"parent", parse::hex_hash))(i).map_err(
+        decode::Error::context("zero or more 'parent <40 or 64 lowercase hex char>'"),
+    )?;
     let (i, author) =
         parse::header_field(i, b"author", parse::signature).map_err(decode::Error::context("author <signature>"))?;
     let (i, committer) = parse::header_field(i, b"committer", parse::signature)
@@ -207,8 +208,8 @@ pub mod iter {
             use State::*;
             Ok(match state {
                 Tree => {
-                    let (i, tree) = parse::header_field(i, b"tree", parse::hex_sha1)
-                        .map_err(decode::Error::context("tree <40 lowercase hex char>"))?;
+                    let (i, tree) = parse::header_field(i, b"tree", parse::hex_hash)
+                        .map_err(decode::Error::context("tree <40 or 64 lowercase hex char>"))?;
                     *state = State::Parents;
                     (
                         i,
@@ -218,8 +219,8 @@ pub mod iter {
                     )
                 }
                 Parents => {
-                    let (i, parent) = opt(|i| parse::header_field(i, b"parent", parse::hex_sha1))(i)
-                        .map_err(decode::Error::context("commit <40 lowercase hex char>"))?;
+                    let (i, parent) = opt(|i| parse::header_field(i, b"parent", parse::hex_hash))(i)
+                        .map_err(decode::Error::context("commit <40 or 64 lowercase hex char>"))?;
                     match parent {
                         Some(parent) => (
                             i,
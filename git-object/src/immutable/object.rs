@@ -41,10 +41,19 @@ pub enum Object<'a> {
 }
 
 impl<'a> Object<'a> {
-    /// Deserialize an object of `kind` from the given `data`.
+    /// Deserialize an object of `kind` from the given `data`, referring to other objects by Sha1 hashes.
     pub fn from_bytes(kind: Kind, data: &'a [u8]) -> Result<Object<'a>, decode::Error> {
+        Self::from_bytes_with_hash_kind(kind, data, git_hash::Kind::Sha1)
+    }
+
+    /// Deserialize an object of `kind` from the given `data`, referring to other objects by hashes of `hash_kind`.
+    pub fn from_bytes_with_hash_kind(
+        kind: Kind,
+        data: &'a [u8],
+        hash_kind: git_hash::Kind,
+    ) -> Result<Object<'a>, decode::Error> {
         Ok(match kind {
-            Kind::Tree => Object::Tree(Tree::from_bytes(data)?),
+            Kind::Tree => Object::Tree(Tree::from_bytes_with_hash_kind(data, hash_kind)?),
             Kind::Blob => Object::Blob(Blob { data }),
             Kind::Commit => Object::Commit(Commit::from_bytes(data)?),
             Kind::Tag => Object::Tag(Tag::from_bytes(data)?),
@@ -65,8 +65,13 @@ fn is_hex_digit_lc(b: u8) -> bool {
     matches!(b, b'0'..=b'9' | b'a'..=b'f')
 }
 
-pub(crate) fn hex_sha1(i: &[u8]) -> IResult<&[u8], &BStr, decode::Error> {
-    take_while_m_n(40usize, 40, is_hex_digit_lc)(i).map(|(i, o)| (i, o.as_bstr()))
+/// Parse the hexadecimal id of a Sha1 or Sha256 hash.
+pub(crate) fn hex_hash(i: &[u8]) -> IResult<&[u8], &BStr, decode::Error> {
+    alt((
+        take_while_m_n(64usize, 64, is_hex_digit_lc),
+        take_while_m_n(40usize, 40, is_hex_digit_lc),
+    ))(i)
+    .map(|(i, o)| (i, o.as_bstr()))
 }
 
 pub(crate) fn signature(i: &[u8]) -> IResult<&[u8], Signature<'_>, decode::Error> {
@@ -44,8 +44,8 @@ impl<'a> Tag<'a> {
 }
 
 fn parse(i: &[u8]) -> IResult<&[u8], Tag<'_>, decode::Error> {
-    let (i, target) = parse::header_field(i, b"object", parse::hex_sha1)
-        .map_err(decode::Error::context("object <40 lowercase hex char>"))?;
+    let (i, target) = parse::header_field(i, b"object", parse::hex_hash)
+        .map_err(decode::Error::context("object <40 or 64 lowercase hex char>"))?;
 
     let (i, kind) = parse::header_field(i, b"type", take_while1(is_alphabetic))
         .map_err(decode::Error::context("type <object kind>"))?;
@@ -30,12 +30,19 @@ pub struct TreeIter<'a> {
     /// The directories and files contained in this tree.
     #[cfg_attr(feature = "serde1", serde(borrow))]
     data: &'a [u8],
+    /// The kind of hash used for the ids of all entries.
+    hash_kind: git_hash::Kind,
 }
 
 impl<'a> TreeIter<'a> {
-    /// Instantiate an iterator from the given tree data.
+    /// Instantiate an iterator from the given tree data, with entries identified by Sha1 hashes.
     pub fn from_bytes(data: &'a [u8]) -> TreeIter<'a> {
-        TreeIter { data }
+        Self::from_bytes_with_hash_kind(data, git_hash::Kind::Sha1)
+    }
+
+    /// Instantiate an iterator from the given tree data, with entries identified by hashes of `hash_kind`.
+    pub fn from_bytes_with_hash_kind(data: &'a [u8], hash_kind: git_hash::Kind) -> TreeIter<'a> {
+        TreeIter { data, hash_kind }
     }
 }
 
@@ -53,9 +60,16 @@ pub struct Entry<'a> {
 }
 
 impl<'a> Tree<'a> {
-    /// Deserialize a Tree from `data`.
+    /// Deserialize a Tree from `data`, with entries identified by Sha1 hashes.
     pub fn from_bytes(data: &'a [u8]) -> Result<Tree<'a>, decode::Error> {
-        parse(data).map(|(_, t)| t).map_err(decode::Error::from)
+        Self::from_bytes_with_hash_kind(data, git_hash::Kind::Sha1)
+    }
+
+    /// Deserialize a Tree from `data`, with entries identified by hashes of `hash_kind`.
+    pub fn from_bytes_with_hash_kind(data: &'a [u8], hash_kind: git_hash::Kind) -> Result<Tree<'a>, decode::Error> {
+        parse(data, hash_kind.len_in_bytes())
+            .map(|(_, t)| t)
+            .map_err(decode::Error::from)
     }
 
     /// Create an instance of the empty tree.
@@ -75,7 +89,10 @@ impl<'a> TreeIter<'a> {
 
 impl<'a> Default for TreeIter<'a> {
     fn default() -> Self {
-        TreeIter { data: &[] }
+        TreeIter {
+            data: &[],
+            hash_kind: git_hash::Kind::Sha1,
+        }
     }
 }
 
@@ -86,7 +103,7 @@ impl<'a> Iterator for TreeIter<'a> {
         if self.data.is_empty() {
             return None;
         }
-        match parse_entry(self.data) {
+        match parse_entry(self.data, self.hash_kind.len_in_bytes()) {
             Ok((data_left, entry)) => {
                 self.data = data_left;
                 Some(Ok(entry))
@@ -117,23 +134,23 @@ impl TryFrom<&[u8]> for tree::EntryMode {
 }
 
 const NULL: &[u8] = b"\0";
-fn parse_entry(i: &[u8]) -> IResult<&[u8], Entry<'_>, decode::Error> {
+fn parse_entry(i: &[u8], hash_len: usize) -> IResult<&[u8], Entry<'_>, decode::Error> {
     let (i, mode) = terminated(take_while_m_n(5, 6, is_digit), tag(SPACE))(i)?;
     let mode = tree::EntryMode::try_from(mode).map_err(nom::Err::Error)?;
     let (i, filename) = terminated(take_while1(|b| b != NULL[0]), tag(NULL))(i)?;
-    let (i, oid) = take(20u8)(i)?;
+    let (i, oid) = take(hash_len)(i)?;
 
     Ok((
         i,
         Entry {
             mode,
             filename: filename.as_bstr(),
-            oid: git_hash::oid::try_from(oid).expect("we counted exactly 20 bytes"),
+            oid: git_hash::oid::try_from(oid).expect("we counted exactly as many bytes as the hash has"),
         },
     ))
 }
 
-fn parse(i: &[u8]) -> IResult<&[u8], Tree<'_>, decode::Error> {
-    let (i, entries) = all_consuming(many0(parse_entry))(i)?;
+fn parse(i: &[u8], hash_len: usize) -> IResult<&[u8], Tree<'_>, decode::Error> {
+    let (i, entries) = all_consuming(many0(|i| parse_entry(i, hash_len)))(i)?;
     Ok((i, Tree { entries }))
 }
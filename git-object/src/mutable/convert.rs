@@ -22,7 +22,7 @@ impl From<immutable::Tag<'_>> for mutable::Tag {
             pgp_signature,
         } = other;
         mutable::Tag {
-            target: git_hash::ObjectId::from_hex(&target).expect("40 bytes hex sha1"),
+            target: git_hash::ObjectId::from_hex(&target).expect("40 or 64 bytes hex hash"),
             name: name.to_owned(),
             target_kind,
             message: message.to_owned(),
@@ -44,10 +44,10 @@ impl From<immutable::Commit<'_>> for mutable::Commit {
             extra_headers,
         } = other;
         mutable::Commit {
-            tree: git_hash::ObjectId::from_hex(&tree).expect("40 bytes hex sha1"),
+            tree: git_hash::ObjectId::from_hex(&tree).expect("40 or 64 bytes hex hash"),
             parents: parents
                 .iter()
-                .map(|parent| git_hash::ObjectId::from_hex(parent).expect("40 bytes hex sha1"))
+                .map(|parent| git_hash::ObjectId::from_hex(parent).expect("40 or 64 bytes hex hash"))
                 .collect(),
             author: author.into(),
             committer: committer.into(),
@@ -144,6 +144,26 @@ mod method {
         assert_eq!(commit.tree, "1b2dfb4ac5e42080b682fc676e9738c94ce6d54d");
         Ok(())
     }
+
+    #[test]
+    fn tree_and_parents_with_sha256() -> Result<(), Box<dyn std::error::Error>> {
+        let tree = "6ef19b41225c5369f1c104d45d8d85efa9b057b53b14b4b9b939dd74decc5321";
+        let parent = "0aef9fb5ea1bcbd2fc4e5ab58d7bd3b7c8b9e43bb5c1dc3b92e9f3b77e0d3e10";
+        let data = format!(
+            "tree {}\nparent {}\nauthor a <a@example.com> 1 +0000\ncommitter a <a@example.com> 1 +0000\n\nmessage\n",
+            tree, parent
+        );
+        let commit = Commit::from_bytes(data.as_bytes())?;
+        assert_eq!(commit.tree(), hex_to_id(tree));
+        assert_eq!(commit.parents().collect::<Vec<_>>(), vec![hex_to_id(parent)]);
+
+        let truncated = data.replacen(&tree[..10], "", 1);
+        assert!(
+            Commit::from_bytes(truncated.as_bytes()).is_err(),
+            "ids must have the length of one of the supported hashes"
+        );
+        Ok(())
+    }
 }
 
 mod iter {
@@ -11,6 +11,26 @@ mod iter {
         assert_eq!(TreeIter::from_bytes(&[]).count(), 0, "empty trees are definitely ok");
     }
 
+    #[test]
+    fn sha256() -> crate::Result {
+        let id = hex_to_id("6ef19b41225c5369f1c104d45d8d85efa9b057b53b14b4b9b939dd74decc5321");
+        let mut data = b"100644 file\0".to_vec();
+        data.extend_from_slice(id.as_bytes());
+        assert_eq!(
+            TreeIter::from_bytes_with_hash_kind(&data, git_hash::Kind::Sha256).collect::<Result<Vec<_>, _>>()?,
+            vec![Entry {
+                mode: tree::EntryMode::Blob,
+                filename: b"file".as_bstr(),
+                oid: &id
+            }]
+        );
+        assert!(
+            TreeIter::from_bytes(&data).any(|entry| entry.is_err()),
+            "Sha256 ids don't parse as Sha1 ids"
+        );
+        Ok(())
+    }
+
     #[test]
     fn error_handling() {
         let data = fixture_bytes("tree", "everything.tree");
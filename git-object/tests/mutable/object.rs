@@ -117,7 +117,7 @@ use git_object::mutable::Object;
 fn size_in_memory() {
     assert_eq!(
         std::mem::size_of::<Object>(),
-        264,
+        296,
         "Prevent unexpected growth of what should be lightweight objects"
     )
 }
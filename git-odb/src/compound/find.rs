@@ -1,4 +1,4 @@
-use crate::{compound, data, loose, pack};
+use crate::{compound, data, find, loose, pack};
 
 /// Returned by [`compound::Db::find()`]
 #[derive(thiserror::Error, Debug)]
@@ -12,7 +12,7 @@ pub enum Error {
 
 pub(crate) struct PackLocation {
     pub pack_id: usize,
-    pub entry_index: u32,
+    pub pack_offset: u64,
 }
 
 impl compound::Db {
@@ -26,11 +26,9 @@ impl compound::Db {
         pack_cache: &mut impl pack::cache::DecodeEntry,
     ) -> Result<Option<data::Object<'a>>, Error> {
         let id = id.as_ref();
-        for pack in &self.packs {
-            if let Some(idx) = pack.internal_find_pack_index(id) {
-                let object = pack.internal_get_object_by_index(idx, buffer, pack_cache)?;
-                return Ok(Some(object));
-            }
+        if let Some(location) = self.internal_find(id) {
+            let object = self.internal_get_packed_object(id, location, buffer, pack_cache)?;
+            return Ok(Some(object));
         }
         if self.loose.contains(id) {
             return self.loose.find(id, buffer).map_err(Into::into);
@@ -38,31 +36,67 @@ impl compound::Db {
         Ok(None)
     }
 
-    /// Internal-use function to look up a packed object index or loose object.
+    /// Find the kind and size of an object as identified by [`ObjectId`][git_hash::ObjectId] without decoding it.
+    /// This will search the object in all contained object databases.
+    pub fn find_header(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<find::Header>, Error> {
+        let id = id.as_ref();
+        if let Some(PackLocation { pack_id, pack_offset }) = self.internal_find(id) {
+            let header = self.packs[pack_id].internal_get_header_at_offset(pack_offset)?;
+            return Ok(Some(header.into()));
+        }
+        self.loose.find_header(id).map_err(Into::into)
+    }
+
+    /// Find an object as identified by [`ObjectId`][git_hash::ObjectId] and return a stream to read its data.
+    /// This will search the object in all contained object databases.
+    /// Use a `pack_cache` to accelerate resolving deltified objects, or [`pack::cache::Never`] to disable any caching.
+    pub fn find_stream(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+        pack_cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Option<find::Stream<'_>>, Error> {
+        let id = id.as_ref();
+        if let Some(PackLocation { pack_id, pack_offset }) = self.internal_find(id) {
+            let stream = self.packs[pack_id].internal_get_stream_at_offset(id, pack_offset, pack_cache)?;
+            return Ok(Some(stream));
+        }
+        self.loose.find_stream(id).map_err(Into::into)
+    }
+
+    /// Internal-use function to look up the location of a packed object.
     /// Used to avoid double-lookups in linked::Db::locate.
     /// (The polonius borrow-checker would support this via the locate
     /// function, so this can be [simplified](https://github.com/Byron/gitoxide/blob/0c5f4043da4615820cb180804a81c2d4fe75fe5e/git-odb/src/compound/locate.rs#L47)
     /// once polonius is stable.)
     pub(crate) fn internal_find(&self, id: impl AsRef<git_hash::oid>) -> Option<PackLocation> {
         let id = id.as_ref();
-        for (pack_idx, pack) in self.packs.iter().enumerate() {
-            if let Some(idx) = pack.internal_find_pack_index(id) {
+        if let Some(multi_index) = self.multi_index.as_ref() {
+            if let Some(multi_index_entry) = multi_index.lookup(id) {
+                let (pack_index, pack_offset) = multi_index.pack_id_and_pack_offset_at_index(multi_index_entry);
+                return Some(PackLocation {
+                    pack_id: self.multi_index_packs[pack_index as usize],
+                    pack_offset,
+                });
+            }
+        }
+        for pack_idx in self.packs_without_multi_index.iter().copied() {
+            if let Some(pack_offset) = self.packs[pack_idx].internal_find_pack_offset(id) {
                 return Some(PackLocation {
                     pack_id: pack_idx,
-                    entry_index: idx,
+                    pack_offset,
                 });
             }
         }
         None
     }
 
-    pub(crate) fn internal_get_packed_object_by_index<'a>(
+    pub(crate) fn internal_get_packed_object<'a>(
         &self,
-        pack_index: usize,
-        object_index: u32,
+        id: &git_hash::oid,
+        PackLocation { pack_id, pack_offset }: PackLocation,
         buffer: &'a mut Vec<u8>,
         pack_cache: &mut impl pack::cache::DecodeEntry,
     ) -> Result<data::Object<'a>, pack::data::decode_entry::Error> {
-        self.packs[pack_index].internal_get_object_by_index(object_index, buffer, pack_cache)
+        self.packs[pack_id].internal_get_object_at_offset(id, pack_offset, buffer, pack_cache)
     }
 }
@@ -1,5 +1,9 @@
 use crate::{compound, loose, pack};
-use std::path::PathBuf;
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 /// Returned by [`compound::Db::at()`]
 #[derive(thiserror::Error, Debug)]
@@ -13,38 +17,117 @@ pub enum Error {
     Alternate(#[from] Box<crate::alternate::Error>),
 }
 
+/// The amount of times the pack directory is listed if packs were removed while opening them.
+const MAX_LISTING_ATTEMPTS: usize = 3;
+
 /// Instantiation
 impl compound::Db {
     /// Returns a compound database as initialized from the given git `objects_directory`, commonly `.git/objects`.
     ///
     /// Only loose and packed objects will be considered. See the [linked Db][crate::linked::Db] for a database with
     /// support for _git alternates_, i.e. linking to other repositories.
+    ///
+    /// Packs which are removed while they are opened, for example by a concurrent `git gc`, are skipped.
+    ///
+    /// A `multi-pack-index` file in the `pack` directory will be used to find objects in the packs it covers, unless
+    /// it can't be read or refers to packs which don't exist anymore.
     pub fn at(objects_directory: impl Into<PathBuf>) -> Result<compound::Db, Error> {
         let loose_objects = objects_directory.into();
         if !loose_objects.is_dir() {
             return Err(Error::Inaccessible(loose_objects));
         }
-        let packs = match std::fs::read_dir(loose_objects.join("pack")) {
-            Ok(entries) => {
-                let mut packs_and_sizes = entries
-                    .filter_map(Result::ok)
-                    .filter_map(|e| e.metadata().map(|md| (e.path(), md)).ok())
-                    .filter(|(_, md)| md.file_type().is_file())
-                    .filter(|(p, _)| {
-                        p.extension().unwrap_or_default() == "idx"
-                            && p.file_name().unwrap_or_default().to_string_lossy().starts_with("pack-")
+        let pack_directory = loose_objects.join("pack");
+        let mut attempt = 1;
+        let (pack_directory_state, mut packs_and_sizes) = loop {
+            let pack_directory_state = PackDirectoryState::read(&pack_directory);
+            let mut packs_and_sizes = Vec::with_capacity(pack_directory_state.index_names.len());
+            let mut packs_vanished = false;
+            for name in &pack_directory_state.index_names {
+                let path = pack_directory.join(name);
+                let len = std::fs::metadata(&path).map(|md| md.len()).unwrap_or(0);
+                match pack::Bundle::at(&path) {
+                    Ok(bundle) => packs_and_sizes.push((bundle, len)),
+                    // The pack was removed since listing the directory, for example by `git gc`, and is skipped like git does.
+                    Err(_) if !path.is_file() || !path.with_extension("pack").is_file() => packs_vanished = true,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            // List the directory again to learn about packs which were added in place of the removed ones.
+            if !packs_vanished || attempt == MAX_LISTING_ATTEMPTS {
+                break (pack_directory_state, packs_and_sizes);
+            }
+            attempt += 1;
+        };
+        packs_and_sizes.sort_by_key(|e| e.1);
+        let packs: Vec<pack::Bundle> = packs_and_sizes.into_iter().rev().map(|(b, _)| b).collect();
+
+        let multi_index_path = pack_directory.join("multi-pack-index");
+        // A multi-pack index which is unreadable or corrupt is ignored just like git does, as all packs are usable without it.
+        let (multi_index, multi_index_packs) = match pack::multi_index::File::at(multi_index_path).ok() {
+            Some(multi_index) => {
+                let multi_index_packs = multi_index
+                    .index_names()
+                    .iter()
+                    .map(|name| {
+                        packs
+                            .iter()
+                            .position(|bundle| bundle.index.path().file_name() == Some(name.as_os_str()))
                     })
-                    .map(|(p, md)| pack::Bundle::at(p).map(|b| (b, md.len())))
-                    .collect::<Result<Vec<_>, _>>()?;
-                packs_and_sizes.sort_by_key(|e| e.1);
-                packs_and_sizes.into_iter().rev().map(|(b, _)| b).collect()
+                    .collect::<Option<Vec<_>>>();
+                match multi_index_packs {
+                    Some(multi_index_packs) => (Some(multi_index), multi_index_packs),
+                    // The multi-pack index is outdated as some of its packs were removed, ignore it just like git does.
+                    None => (None, Vec::new()),
+                }
             }
-            Err(_) => Vec::new(),
+            None => (None, Vec::new()),
         };
+        let packs_without_multi_index = (0..packs.len())
+            .filter(|pack_index| !multi_index_packs.contains(pack_index))
+            .collect();
 
         Ok(compound::Db {
             loose: loose::Db::at(loose_objects),
             packs,
+            multi_index,
+            multi_index_packs,
+            packs_without_multi_index,
+            pack_directory_state,
         })
     }
 }
+
+/// The state of a pack directory as seen when loading its packs, to learn if packs were added or removed since.
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+pub(crate) struct PackDirectoryState {
+    /// The modification time of the pack directory, which changes whenever files are added or removed.
+    mtime: Option<SystemTime>,
+    /// The sorted names of all pack index files, as the modification time has a coarse resolution on some filesystems.
+    index_names: Vec<OsString>,
+    /// The modification time of the multi-pack index, if present, which may be rewritten in place.
+    multi_index_mtime: Option<SystemTime>,
+}
+
+impl PackDirectoryState {
+    pub(crate) fn read(pack_directory: &Path) -> Self {
+        let mtime = |path: &Path| std::fs::metadata(path).and_then(|md| md.modified()).ok();
+        let mut index_names: Vec<_> = match std::fs::read_dir(pack_directory) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+                .map(|e| e.file_name())
+                .filter(|name| {
+                    let name = Path::new(name);
+                    name.extension().unwrap_or_default() == "idx" && name.to_string_lossy().starts_with("pack-")
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        index_names.sort();
+        PackDirectoryState {
+            mtime: mtime(pack_directory),
+            index_names,
+            multi_index_mtime: mtime(&pack_directory.join("multi-pack-index")),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::{loose, pack};
 pub mod find;
 ///
 pub mod init;
+mod refresh;
 mod write;
 
 /// An object database with tiered lookup packs and loose objects.
@@ -14,4 +15,13 @@ pub struct Db {
     pub loose: loose::Db,
     /// All packs in the `objects/packs` directory
     pub packs: Vec<pack::Bundle>,
+    /// The multi-pack index in the `objects/packs` directory if present and covering only packs in `packs`, to find
+    /// objects in all of these packs with a single lookup.
+    pub multi_index: Option<pack::multi_index::File>,
+    /// The index into `packs` for each pack covered by `multi_index`.
+    pub(crate) multi_index_packs: Vec<usize>,
+    /// The indices into `packs` of all packs not covered by `multi_index`.
+    pub(crate) packs_without_multi_index: Vec<usize>,
+    /// The state of the `objects/packs` directory at the time `packs` were loaded.
+    pub(crate) pack_directory_state: init::PackDirectoryState,
 }
@@ -0,0 +1,24 @@
+use crate::compound::{self, init::PackDirectoryState};
+
+/// Refreshing
+impl compound::Db {
+    /// Returns true if packs were added to or removed from the `objects/packs` directory, or if the multi-pack index changed,
+    /// since our packs were loaded.
+    ///
+    /// This is useful for long-running processes to learn about packs written by other processes, for example when fetching
+    /// or running `git gc`, and reading the pack directory is cheap compared to a lookup that fails.
+    pub fn is_outdated(&self) -> bool {
+        PackDirectoryState::read(&self.loose.path.join("pack")) != self.pack_directory_state
+    }
+
+    /// Reload all packs and the multi-pack index if [outdated][compound::Db::is_outdated()], and return true if this was the case.
+    ///
+    /// Packs which were removed are unmapped, while objects in packs which were added can be found afterwards.
+    pub fn refresh(&mut self) -> Result<bool, compound::init::Error> {
+        if !self.is_outdated() {
+            return Ok(false);
+        }
+        *self = compound::Db::at(self.loose.path.clone())?;
+        Ok(true)
+    }
+}
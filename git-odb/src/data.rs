@@ -10,16 +10,24 @@ pub struct Object<'a> {
     pub kind: git_object::Kind,
     /// decoded, decompressed data, owned by a backing store.
     pub data: &'a [u8],
+    /// The kind of hash used to refer to other objects in `data`.
+    pub hash_kind: git_hash::Kind,
     /// If `Some`, this object is from a pack whose pack location can be used to look up pack related information
     pub(crate) pack_location: Option<pack::bundle::Location>,
 }
 
 impl<'a> Object<'a> {
-    /// Constructs a new data object from `kind` and `data`.
+    /// Constructs a new data object from `kind` and `data`, referring to other objects by Sha1 hashes.
     pub fn new(kind: git_object::Kind, data: &'a [u8]) -> Object<'a> {
+        Self::new_with_hash_kind(kind, data, git_hash::Kind::Sha1)
+    }
+
+    /// Constructs a new data object from `kind` and `data`, referring to other objects by hashes of `hash_kind`.
+    pub fn new_with_hash_kind(kind: git_object::Kind, data: &'a [u8], hash_kind: git_hash::Kind) -> Object<'a> {
         Object {
             kind,
             data,
+            hash_kind,
             pack_location: None,
         }
     }
@@ -29,19 +37,17 @@ impl<'a> Object<'a> {
     /// **Note** that [mutable, decoded objects][git_object::mutable::Object] can be created from a [`crate::data::Object`]
     /// using [`git_object::immutable::Object::into_mutable()`].
     pub fn decode(&self) -> Result<immutable::Object<'a>, immutable::object::decode::Error> {
-        Ok(match self.kind {
-            git_object::Kind::Tree => immutable::Object::Tree(immutable::Tree::from_bytes(self.data)?),
-            git_object::Kind::Blob => immutable::Object::Blob(immutable::Blob { data: self.data }),
-            git_object::Kind::Commit => immutable::Object::Commit(immutable::Commit::from_bytes(self.data)?),
-            git_object::Kind::Tag => immutable::Object::Tag(immutable::Tag::from_bytes(self.data)?),
-        })
+        immutable::Object::from_bytes_with_hash_kind(self.kind, self.data, self.hash_kind)
     }
 
     /// Returns this object as tree iterator to parse entries one at a time to avoid allocations, or
     /// `None` if this is not a tree object.
     pub fn into_tree_iter(self) -> Option<immutable::TreeIter<'a>> {
         match self.kind {
-            git_object::Kind::Tree => Some(immutable::TreeIter::from_bytes(self.data)),
+            git_object::Kind::Tree => Some(immutable::TreeIter::from_bytes_with_hash_kind(
+                self.data,
+                self.hash_kind,
+            )),
             _ => None,
         }
     }
@@ -83,7 +89,7 @@ pub mod verify {
             loose::object::header::encode(self.kind, self.data.len() as u64, &mut sink).expect("hash to always work");
             sink.hash.update(&self.data);
 
-            let actual_id = git_hash::ObjectId::from(sink.hash.digest());
+            let actual_id = sink.hash.digest();
             if desired != actual_id {
                 return Err(Error::ChecksumMismatch {
                     desired: desired.into(),
@@ -101,6 +107,6 @@ mod tests {
 
     #[test]
     fn size_of_object() {
-        assert_eq!(std::mem::size_of::<Object<'_>>(), 48, "this shouldn't change unnoticed");
+        assert_eq!(std::mem::size_of::<Object<'_>>(), 80, "this shouldn't change unnoticed");
     }
 }
@@ -25,6 +25,26 @@ pub trait Find {
         pack_cache: &mut impl crate::pack::cache::DecodeEntry,
     ) -> Result<Option<data::Object<'a>>, Self::Error>;
 
+    /// Find the [kind and size][Header] of the object matching `id` without decoding it, which is much faster than
+    /// [`find()`][Find::find()] as loose objects are only decompressed up to their header and deltified pack entries only up
+    /// to the header of the delta.
+    ///
+    /// Returns `Some` header if the object was present in the database, or the error that occurred during lookup.
+    fn find_header(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<Header>, Self::Error>;
+
+    /// Find an object matching `id` in the database and return a [`Stream`] to read its data incrementally, which is useful
+    /// for objects too large to be kept in memory.
+    ///
+    /// Loose objects and undeltified pack entries are decompressed while reading, whereas deltified pack entries have to be
+    /// resolved into memory first, using `pack_cache` like [`find()`][Find::find()] does.
+    ///
+    /// Returns `Some` stream if the object was present in the database, or the error that occurred during lookup.
+    fn find_stream(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+        pack_cache: &mut impl crate::pack::cache::DecodeEntry,
+    ) -> Result<Option<Stream<'_>>, Self::Error>;
+
     /// Return the [`PackEntry`] for `object` if it is backed by a pack.
     ///
     /// Note that this is only in the interest of avoiding duplicate work during pack generation
@@ -132,7 +152,7 @@ mod ext {
         );
         make_iter_lookup!(
             find_existing_commit_iter,
-            Kind::Blob,
+            Kind::Commit,
             immutable::CommitIter<'a>,
             into_commit_iter
         );
@@ -200,6 +220,65 @@ pub mod existing_iter {
     }
 }
 
+/// The kind and size of an object as returned by [`Find::find_header()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    /// The kind of the object.
+    pub kind: git_object::Kind,
+    /// The size of the object's decompressed data in bytes.
+    pub size: u64,
+}
+
+impl From<pack::data::decode_entry::Header> for Header {
+    fn from(header: pack::data::decode_entry::Header) -> Self {
+        Header {
+            kind: header.kind,
+            size: header.object_size,
+        }
+    }
+}
+
+/// An object whose data is read incrementally, as returned by [`Find::find_stream()`].
+pub struct Stream<'a> {
+    /// The kind of the object.
+    pub kind: git_object::Kind,
+    /// The size of the object's decompressed data in bytes, which is the amount of bytes that can be read from the stream.
+    pub size: u64,
+    pub(crate) inner: stream::Inner<'a>,
+}
+
+pub(crate) mod stream {
+    use crate::zlib::stream::inflate::ReadBoxed;
+    use std::{fs, io};
+
+    pub enum Inner<'a> {
+        /// The already decompressed bytes following the header, and the decompressor for the remaining bytes.
+        Loose(io::Chain<io::Cursor<Vec<u8>>, ReadBoxed<io::BufReader<fs::File>>>),
+        /// The decompressor for the data of an undeltified pack entry.
+        Packed(ReadBoxed<&'a [u8]>),
+        /// A deltified object which had to be resolved in memory.
+        Buffered(io::Cursor<Vec<u8>>),
+    }
+}
+
+impl<'a> Stream<'a> {
+    /// Returns true if the object data was decoded into memory before streaming it, as it is stored as delta in a pack.
+    pub fn is_buffered(&self) -> bool {
+        matches!(self.inner, stream::Inner::Buffered(_))
+    }
+}
+
+impl<'a> std::io::Read for Stream<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            stream::Inner::Loose(read) => read.read(buf),
+            stream::Inner::Packed(read) => read.read(buf),
+            stream::Inner::Buffered(read) => read.read(buf),
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)] // TODO: docs
@@ -210,10 +289,19 @@ pub struct PackEntry<'a> {
     pub crc32: Option<u32>,
     /// The version of the pack file containing `data`
     pub version: pack::data::Version,
+    /// An id uniquely identifying the pack file containing `data` among all packs of the object database.
+    pub pack_id: u32,
+    /// The offset of `data` within its pack file, useful to resolve the base of `OfsDelta` entries.
+    pub pack_offset: u64,
 }
 
 mod find_impls {
-    use crate::{data, data::Object, find::PackEntry, pack};
+    use crate::{
+        data,
+        data::Object,
+        find::{Header, PackEntry, Stream},
+        pack,
+    };
     use git_hash::oid;
     use std::ops::Deref;
 
@@ -232,6 +320,18 @@ mod find_impls {
             self.deref().find(id, buffer, pack_cache)
         }
 
+        fn find_header(&self, id: impl AsRef<oid>) -> Result<Option<Header>, Self::Error> {
+            self.deref().find_header(id)
+        }
+
+        fn find_stream(
+            &self,
+            id: impl AsRef<oid>,
+            pack_cache: &mut impl pack::cache::DecodeEntry,
+        ) -> Result<Option<Stream<'_>>, Self::Error> {
+            self.deref().find_stream(id, pack_cache)
+        }
+
         fn pack_entry(&self, object: &data::Object<'_>) -> Option<PackEntry<'_>> {
             self.deref().pack_entry(object)
         }
@@ -252,6 +352,18 @@ mod find_impls {
             self.deref().find(id, buffer, pack_cache)
         }
 
+        fn find_header(&self, id: impl AsRef<oid>) -> Result<Option<Header>, Self::Error> {
+            self.deref().find_header(id)
+        }
+
+        fn find_stream(
+            &self,
+            id: impl AsRef<oid>,
+            pack_cache: &mut impl pack::cache::DecodeEntry,
+        ) -> Result<Option<Stream<'_>>, Self::Error> {
+            self.deref().find_stream(id, pack_cache)
+        }
+
         fn pack_entry(&self, object: &data::Object<'_>) -> Option<PackEntry<'_>> {
             self.deref().pack_entry(object)
         }
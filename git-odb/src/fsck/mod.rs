@@ -0,0 +1,114 @@
+//! Checks for the content of objects and the connections between them, similar to `git fsck`.
+//!
+//! Use [`object::check()`] to check a single object, and [`linked::Db::fsck()`][crate::linked::Db::fsck()] to check all objects
+//! of a database along with their connectivity.
+use git_hash::ObjectId;
+use git_object::bstr::BString;
+
+///
+pub mod object;
+
+/// How severe a [`Problem`] is, similar to the message levels of `git fsck`.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// The object is corrupt or can't be used by git.
+    Error,
+    /// The object is usable, but wouldn't have been produced by git or may be dangerous to check out.
+    Warning,
+}
+
+/// An issue with the content of an object as found by [`object::check()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Problem {
+    /// The object data hashes to `actual` instead of the id of the object.
+    HashMismatch { actual: ObjectId },
+    /// The object couldn't be parsed.
+    Malformed { message: String },
+    /// A tree entry was cut short.
+    TruncatedTree,
+    /// The mode of the tree entry `name` has leading zeros, like `040000`.
+    ZeroPaddedFileMode { name: BString },
+    /// The `mode` of the tree entry `name` isn't one git would write.
+    BadFileMode { name: BString, mode: BString },
+    /// The tree entry `name` is sorted before the one preceding it.
+    TreeNotSorted { name: BString },
+    /// There are multiple tree entries with `name`.
+    DuplicateEntries { name: BString },
+    /// A tree entry has an empty name.
+    EmptyName,
+    /// The name of a tree entry contains a slash.
+    FullPathname { name: BString },
+    /// A tree entry is named `.`, `..` or `.git`, ignoring case.
+    ReservedName { name: BString },
+    /// The tree entry `name` refers to the null object id.
+    NullSha1 { name: BString },
+    /// The name of the identity in the `field` line, like `author`, is missing or contains angle brackets.
+    BadName { field: BString },
+    /// The identity in the `field` line, like `author`, has no email.
+    MissingEmail { field: BString },
+    /// The email of the identity in the `field` line, like `author`, is malformed.
+    BadEmail { field: BString },
+    /// The date of the identity in the `field` line, like `author`, is malformed.
+    BadDate { field: BString },
+    /// The timezone of the identity in the `field` line, like `author`, is malformed.
+    BadTimezone { field: BString },
+}
+
+impl Problem {
+    /// Return how severe this problem is, matching the default of `git fsck`.
+    pub fn severity(&self) -> Severity {
+        use Problem::*;
+        match self {
+            ZeroPaddedFileMode { .. }
+            | BadFileMode { .. }
+            | EmptyName
+            | FullPathname { .. }
+            | ReservedName { .. }
+            | NullSha1 { .. } => Severity::Warning,
+            HashMismatch { .. }
+            | Malformed { .. }
+            | TruncatedTree
+            | TreeNotSorted { .. }
+            | DuplicateEntries { .. }
+            | BadName { .. }
+            | MissingEmail { .. }
+            | BadEmail { .. }
+            | BadDate { .. }
+            | BadTimezone { .. } => Severity::Error,
+        }
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Problem::*;
+        match self {
+            HashMismatch { actual } => write!(f, "hashMismatch: the object data hashes to {}", actual),
+            Malformed { message } => write!(f, "malformed: {}", message),
+            TruncatedTree => f.write_str("badTree: a tree entry is truncated"),
+            ZeroPaddedFileMode { name } => write!(f, "zeroPaddedFilemode: the mode of '{}' is zero-padded", name),
+            BadFileMode { name, mode } => write!(f, "badFilemode: '{}' has mode {}", name, mode),
+            TreeNotSorted { name } => write!(f, "treeNotSorted: '{}' is not properly sorted", name),
+            DuplicateEntries { name } => write!(f, "duplicateEntries: '{}' exists more than once", name),
+            EmptyName => f.write_str("emptyName: an entry has an empty name"),
+            FullPathname { name } => write!(f, "fullPathname: '{}' contains a slash", name),
+            ReservedName { name } => {
+                let id = match name.as_slice() {
+                    b"." => "hasDot",
+                    b".." => "hasDotdot",
+                    _ => "hasDotgit",
+                };
+                write!(f, "{}: '{}' is a reserved name", id, name)
+            }
+            NullSha1 { name } => write!(f, "nullSha1: '{}' refers to the null object id", name),
+            BadName { field } => write!(f, "badName: the {} has an invalid name", field),
+            MissingEmail { field } => write!(f, "missingEmail: the {} has no email", field),
+            BadEmail { field } => write!(f, "badEmail: the {} has an invalid email", field),
+            BadDate { field } => write!(f, "badDate: the {} has an invalid date", field),
+            BadTimezone { field } => write!(f, "badTimezone: the {} has an invalid timezone", field),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crate::fsck::Problem;
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BString, ByteSlice},
+    immutable, Kind,
+};
+use std::cmp::Ordering;
+
+/// Check the `data` of the object with `id` and of `kind` for the problems `git fsck` finds, and return all of them.
+///
+/// Besides verifying the object hashes to `id` and can be parsed, trees are checked for entries with unusual modes, bad names,
+/// duplicates and their order, and commits and tags for malformed identities.
+pub fn check(id: &oid, kind: Kind, data: &[u8]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let object = crate::data::Object::new(kind, data);
+    if let Err(crate::data::verify::Error::ChecksumMismatch { actual, .. }) = object.verify_checksum(id) {
+        problems.push(Problem::HashMismatch { actual });
+    }
+    match kind {
+        Kind::Blob => {}
+        Kind::Tree => check_tree(data, id.kind(), &mut problems),
+        Kind::Commit => {
+            if let Err(err) = immutable::Commit::from_bytes(data) {
+                problems.push(Problem::Malformed {
+                    message: err.to_string(),
+                });
+            }
+            check_identities(data, &[b"author", b"committer"], &mut problems);
+        }
+        Kind::Tag => {
+            if let Err(err) = immutable::Tag::from_bytes(data) {
+                problems.push(Problem::Malformed {
+                    message: err.to_string(),
+                });
+            }
+            check_identities(data, &[b"tagger"], &mut problems);
+        }
+    }
+    problems
+}
+
+/// Return the ids of all objects the object of `kind` with `data` refers to by hashes of `hash_kind`, along with the kind they
+/// are supposed to have.
+///
+/// Submodule commits in trees aren't returned as they aren't part of the repository, and the references of objects which can't
+/// be parsed are returned as far as possible.
+pub fn references(kind: Kind, data: &[u8], hash_kind: git_hash::Kind) -> Vec<(ObjectId, Kind)> {
+    let mut out = Vec::new();
+    match kind {
+        Kind::Blob => {}
+        Kind::Tree => {
+            for entry in TreeEntries::new(data, hash_kind) {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(_truncated) => break,
+                };
+                let kind = match entry.mode() & 0o170000 {
+                    0o040000 => Kind::Tree,
+                    0o100000 | 0o120000 => Kind::Blob,
+                    _submodule_or_unknown => continue,
+                };
+                out.push((entry.id.to_owned(), kind));
+            }
+        }
+        Kind::Commit => {
+            for token in immutable::CommitIter::from_bytes(data) {
+                match token {
+                    Ok(immutable::commit::iter::Token::Tree { id }) => out.push((id, Kind::Tree)),
+                    Ok(immutable::commit::iter::Token::Parent { id }) => out.push((id, Kind::Commit)),
+                    _ => break,
+                }
+            }
+        }
+        Kind::Tag => {
+            if let Ok(tag) = immutable::Tag::from_bytes(data) {
+                out.push((tag.target(), tag.target_kind));
+            }
+        }
+    }
+    out
+}
+
+/// A tree entry as stored, without validating its mode.
+struct Entry<'a> {
+    mode: &'a [u8],
+    name: &'a [u8],
+    id: &'a oid,
+}
+
+impl<'a> Entry<'a> {
+    /// The mode parsed as octal number, or 0 if it isn't one.
+    fn mode(&self) -> u32 {
+        self.mode
+            .iter()
+            .try_fold(0u32, |mode, b| match b {
+                b'0'..=b'7' => mode.checked_mul(8).map(|mode| mode + u32::from(b - b'0')),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    fn is_tree(&self) -> bool {
+        self.mode() & 0o170000 == 0o040000
+    }
+}
+
+/// An iterator over the entries of tree data which fails once an entry is truncated.
+struct TreeEntries<'a> {
+    data: &'a [u8],
+    hash_len: usize,
+}
+
+impl<'a> TreeEntries<'a> {
+    fn new(data: &'a [u8], hash_kind: git_hash::Kind) -> Self {
+        TreeEntries {
+            data,
+            hash_len: hash_kind.len_in_bytes(),
+        }
+    }
+}
+
+impl<'a> Iterator for TreeEntries<'a> {
+    type Item = Result<Entry<'a>, ()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let parse = |data: &'a [u8]| {
+            let space = data.find_byte(b' ')?;
+            let nul = space + 1 + data[space + 1..].find_byte(0)?;
+            let id_end = nul + 1 + self.hash_len;
+            let entry = Entry {
+                mode: &data[..space],
+                name: &data[space + 1..nul],
+                id: oid::try_from(data.get(nul + 1..id_end)?).ok()?,
+            };
+            Some((entry, &data[id_end..]))
+        };
+        Some(match parse(self.data) {
+            Some((entry, rest)) => {
+                self.data = rest;
+                Ok(entry)
+            }
+            None => {
+                self.data = &[];
+                Err(())
+            }
+        })
+    }
+}
+
+fn check_tree(data: &[u8], hash_kind: git_hash::Kind, problems: &mut Vec<Problem>) {
+    const KNOWN_MODES: &[u32] = &[0o100644, 0o100755, 0o100664, 0o120000, 0o040000, 0o160000];
+    let mut previous: Option<Entry<'_>> = None;
+    for entry in TreeEntries::new(data, hash_kind) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(()) => {
+                problems.push(Problem::TruncatedTree);
+                break;
+            }
+        };
+        let name = || BString::from(entry.name);
+        if entry.mode.first() == Some(&b'0') {
+            problems.push(Problem::ZeroPaddedFileMode { name: name() });
+        }
+        if !KNOWN_MODES.contains(&entry.mode()) {
+            problems.push(Problem::BadFileMode {
+                name: name(),
+                mode: entry.mode.into(),
+            });
+        }
+        if entry.name.is_empty() {
+            problems.push(Problem::EmptyName);
+        }
+        if entry.name.contains(&b'/') {
+            problems.push(Problem::FullPathname { name: name() });
+        }
+        if entry.name == b"." || entry.name == b".." || entry.name.eq_ignore_ascii_case(b".git") {
+            problems.push(Problem::ReservedName { name: name() });
+        }
+        if entry.id.as_bytes().iter().all(|b| *b == 0) {
+            problems.push(Problem::NullSha1 { name: name() });
+        }
+        if let Some(previous) = previous.as_ref() {
+            if previous.name == entry.name {
+                problems.push(Problem::DuplicateEntries { name: name() });
+            } else if compare_entries(previous, &entry) == Ordering::Greater {
+                problems.push(Problem::TreeNotSorted { name: name() });
+            }
+        }
+        previous = Some(entry);
+    }
+}
+
+/// Compare tree entries like git does, as if the names of trees had a trailing slash.
+fn compare_entries(lhs: &Entry<'_>, rhs: &Entry<'_>) -> Ordering {
+    let common = lhs.name.len().min(rhs.name.len());
+    lhs.name[..common].cmp(&rhs.name[..common]).then_with(|| {
+        let terminator = |entry: &Entry<'_>| {
+            entry
+                .name
+                .get(common)
+                .copied()
+                .unwrap_or(if entry.is_tree() { b'/' } else { 0 })
+        };
+        terminator(lhs).cmp(&terminator(rhs))
+    })
+}
+
+/// Check the identities in the header lines starting with any of `fields` in the commit or tag `data`.
+fn check_identities(data: &[u8], fields: &[&[u8]], problems: &mut Vec<Problem>) {
+    for line in data.lines().take_while(|line| !line.is_empty()) {
+        for field in fields {
+            if let Some(identity) = line.strip_prefix(*field).and_then(|rest| rest.strip_prefix(b" ")) {
+                if let Some(problem) = check_identity(field, identity) {
+                    problems.push(problem);
+                }
+            }
+        }
+    }
+}
+
+/// Check an `identity` like `name <email> 1234567890 +0100` of the header line `field`, similar to `fsck_ident()` in git.
+fn check_identity(field: &[u8], identity: &[u8]) -> Option<Problem> {
+    let field = || BString::from(field);
+    let email_start = match identity.find_byteset(b"<>") {
+        Some(pos) if identity[pos] == b'<' => pos,
+        Some(_) => return Some(Problem::BadName { field: field() }),
+        None => return Some(Problem::MissingEmail { field: field() }),
+    };
+    if email_start == 0 || identity[email_start - 1] != b' ' {
+        return Some(Problem::BadName { field: field() });
+    }
+    let rest = &identity[email_start + 1..];
+    let email_end = match rest.find_byteset(b"<>") {
+        Some(pos) if rest[pos] == b'>' => pos,
+        _ => return Some(Problem::BadEmail { field: field() }),
+    };
+    let rest = match rest[email_end + 1..].strip_prefix(b" ") {
+        Some(rest) => rest,
+        None => return Some(Problem::BadDate { field: field() }),
+    };
+    let date_len = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+    if date_len == 0 || (rest[0] == b'0' && date_len > 1) {
+        return Some(Problem::BadDate { field: field() });
+    }
+    let timezone = match rest[date_len..].strip_prefix(b" ") {
+        Some(timezone) => timezone,
+        None => return Some(Problem::BadDate { field: field() }),
+    };
+    let is_valid_timezone =
+        timezone.len() == 5 && matches!(timezone[0], b'+' | b'-') && timezone[1..].iter().all(u8::is_ascii_digit);
+    if !is_valid_timezone {
+        return Some(Problem::BadTimezone { field: field() });
+    }
+    None
+}
@@ -2,7 +2,7 @@ use git_features::hash;
 use std::io;
 
 pub(crate) struct Write<T> {
-    pub hash: hash::Sha1,
+    pub hash: hash::Hasher,
     pub inner: T,
 }
 
@@ -26,11 +26,9 @@ where
     T: io::Write,
 {
     pub fn new(inner: T, kind: git_hash::Kind) -> Self {
-        match kind {
-            git_hash::Kind::Sha1 => Write {
-                inner,
-                hash: hash::Sha1::default(),
-            },
+        Write {
+            inner,
+            hash: hash::hasher(kind),
         }
     }
 }
@@ -34,6 +34,8 @@ pub(crate) mod hash;
 
 pub mod data;
 
+pub mod fsck;
+
 ///
 pub mod find;
 pub use find::{Find, FindExt};
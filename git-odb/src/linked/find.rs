@@ -1,4 +1,9 @@
-use crate::{compound, data, data::Object, find::PackEntry, linked, pack};
+use crate::{
+    compound, data,
+    data::Object,
+    find::{Header, PackEntry, Stream},
+    linked, pack,
+};
 use git_hash::oid;
 
 impl crate::Find for linked::Db {
@@ -13,9 +18,9 @@ impl crate::Find for linked::Db {
         let id = id.as_ref();
         for db in self.dbs.iter() {
             match db.internal_find(id) {
-                Some(compound::find::PackLocation { pack_id, entry_index }) => {
+                Some(location) => {
                     return db
-                        .internal_get_packed_object_by_index(pack_id, entry_index, buffer, pack_cache)
+                        .internal_get_packed_object(id, location, buffer, pack_cache)
                         .map(Some)
                         .map_err(Into::into)
                 }
@@ -29,6 +34,30 @@ impl crate::Find for linked::Db {
         Ok(None)
     }
 
+    fn find_header(&self, id: impl AsRef<oid>) -> Result<Option<Header>, Self::Error> {
+        let id = id.as_ref();
+        for db in self.dbs.iter() {
+            if let Some(header) = db.find_header(id)? {
+                return Ok(Some(header));
+            }
+        }
+        Ok(None)
+    }
+
+    fn find_stream(
+        &self,
+        id: impl AsRef<oid>,
+        pack_cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Option<Stream<'_>>, Self::Error> {
+        let id = id.as_ref();
+        for db in self.dbs.iter() {
+            if let Some(stream) = db.find_stream(id, pack_cache)? {
+                return Ok(Some(stream));
+            }
+        }
+        Ok(None)
+    }
+
     fn pack_entry(&self, object: &data::Object<'_>) -> Option<PackEntry<'_>> {
         object
             .pack_location
@@ -40,16 +69,18 @@ impl crate::Find for linked::Db {
                     .map(|b| (b, l))
             })
             .and_then(|(bundle, l)| {
-                let crc32 = bundle.index.crc32_at_index(l.index_file_id);
-                let pack_offset = bundle.index.pack_offset_at_index(l.index_file_id);
-                bundle
-                    .pack
-                    .entry_slice(l.entry_range(pack_offset))
-                    .map(|data| PackEntry {
-                        data,
-                        crc32,
-                        version: bundle.pack.version(),
-                    })
+                // Objects are located without their index file entry if they were found through a multi-pack index.
+                let crc32 = bundle
+                    .index
+                    .lookup(l.id)
+                    .and_then(|idx| bundle.index.crc32_at_index(idx));
+                bundle.pack.entry_slice(l.entry_range()).map(|data| PackEntry {
+                    data,
+                    crc32,
+                    version: bundle.pack.version(),
+                    pack_id: bundle.pack.id,
+                    pack_offset: l.pack_offset,
+                })
             })
     }
 }
@@ -66,6 +97,18 @@ impl crate::Find for &linked::Db {
         (*self).find(id, buffer, pack_cache)
     }
 
+    fn find_header(&self, id: impl AsRef<oid>) -> Result<Option<Header>, Self::Error> {
+        (*self).find_header(id)
+    }
+
+    fn find_stream(
+        &self,
+        id: impl AsRef<oid>,
+        pack_cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Option<Stream<'_>>, Self::Error> {
+        (*self).find_stream(id, pack_cache)
+    }
+
     fn pack_entry(&self, object: &data::Object<'_>) -> Option<PackEntry<'_>> {
         (*self).pack_entry(object)
     }
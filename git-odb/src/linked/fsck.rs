@@ -0,0 +1,276 @@
+use crate::{compound, fsck, linked, loose, pack, Find};
+use git_features::{
+    interrupt::is_triggered,
+    parallel,
+    progress::{self, Progress},
+};
+use git_hash::ObjectId;
+use git_object::Kind;
+use std::collections::{HashMap, HashSet};
+
+/// Returned by [`linked::Db::fsck()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    LooseIter(#[from] loose::db::iter::Error),
+    #[error(transparent)]
+    Find(#[from] compound::find::Error),
+    #[error("Object {id} was listed but couldn't be found")]
+    NotFound { id: ObjectId },
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Configuration for [`linked::Db::fsck()`]
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The amount of threads to use at most when checking objects. If `None`, all logical cores are used.
+    pub thread_limit: Option<usize>,
+}
+
+/// An object whose content has problems.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Malformed {
+    /// The id of the object.
+    pub id: ObjectId,
+    /// The kind of the object.
+    pub kind: Kind,
+    /// All problems found in the object.
+    pub problems: Vec<fsck::Problem>,
+}
+
+/// A reference from one object to another which doesn't exist or has an unexpected kind.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrokenLink {
+    /// The object containing the reference.
+    pub from: ObjectId,
+    /// The kind of the object containing the reference.
+    pub from_kind: Kind,
+    /// The object referred to.
+    pub to: ObjectId,
+    /// The kind the object referred to is supposed to have.
+    pub expected_kind: Kind,
+    /// The kind the object referred to actually has, or `None` if it doesn't exist.
+    pub actual_kind: Option<Kind>,
+}
+
+/// Returned by [`linked::Db::fsck()`]
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The amount of distinct objects which were checked.
+    pub num_objects: usize,
+    /// Objects whose content has problems.
+    pub malformed: Vec<Malformed>,
+    /// References between objects which are broken, no matter if the objects are reachable.
+    pub broken_links: Vec<BrokenLink>,
+    /// Tips which don't exist.
+    pub missing_tips: Vec<ObjectId>,
+    /// Objects reachable from the tips which don't exist.
+    pub missing: Vec<ObjectId>,
+    /// Objects which don't exist but are referred to by objects in [promisor packs][pack::Bundle::is_promisor()], and can
+    /// thus be fetched from the promisor remote of a partial clone. These aren't considered missing or broken links.
+    pub promised: Vec<ObjectId>,
+    /// Objects which aren't reachable from the tips and not referred to by any other object, along with their kind.
+    pub dangling: Vec<(ObjectId, Kind)>,
+    /// The amount of objects not reachable from the tips, including dangling ones.
+    pub num_unreachable: usize,
+}
+
+impl Outcome {
+    /// Return true if there is a problem which is an error, which excludes dangling objects and problems which are mere warnings.
+    pub fn has_errors(&self) -> bool {
+        !self.broken_links.is_empty()
+            || !self.missing_tips.is_empty()
+            || !self.missing.is_empty()
+            || self
+                .malformed
+                .iter()
+                .flat_map(|m| m.problems.iter())
+                .any(|problem| problem.severity() == fsck::Severity::Error)
+    }
+}
+
+/// An object as seen when checking it.
+struct Checked {
+    id: ObjectId,
+    kind: Kind,
+    problems: Vec<fsck::Problem>,
+    references: Vec<(ObjectId, Kind)>,
+}
+
+impl linked::Db {
+    /// Check all objects in all of our [databases][linked::Db::dbs] for [problems][fsck::object::check()] and verify their
+    /// connectivity, similar to `git fsck`.
+    ///
+    /// `tips` are the objects pointed to by all references, the entries of their reflogs and of the index, which may be commits,
+    /// tags, trees or blobs. All objects reachable from them must exist, whereas all other objects are unreachable and reported
+    /// as dangling if they aren't referred to by any other object either.
+    ///
+    /// Objects which don't exist but are referred to by objects in promisor packs are expected in partial clones and
+    /// reported as [promised][Outcome::promised] instead, similar to what git does.
+    ///
+    /// Objects are checked in parallel as configured by `options`.
+    pub fn fsck(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        mut progress: impl Progress,
+        Options { thread_limit }: Options,
+    ) -> Result<Outcome, Error> {
+        let ids = self.iter().collect::<Result<Vec<_>, _>>()?;
+        let (chunk_size, thread_limit, _) =
+            parallel::optimize_chunk_size_and_thread_limit(1000, Some(ids.len()), thread_limit, None);
+        let objects = {
+            let mut objects_progress = progress.add_child("checking objects");
+            objects_progress.init(Some(ids.len()), progress::count("objects"));
+            parallel::in_parallel(
+                ids.chunks(chunk_size),
+                thread_limit,
+                |_thread_index| Vec::new(),
+                |ids, buf| -> Result<Vec<Checked>, Error> {
+                    ids.iter()
+                        .map(|id| {
+                            let object = self
+                                .find(id, buf, &mut pack::cache::Never)?
+                                .ok_or(Error::NotFound { id: *id })?;
+                            Ok(Checked {
+                                id: *id,
+                                kind: object.kind,
+                                problems: fsck::object::check(id, object.kind, object.data),
+                                references: fsck::object::references(object.kind, object.data, id.kind()),
+                            })
+                        })
+                        .collect()
+                },
+                Reducer {
+                    objects: HashMap::with_capacity(ids.len()),
+                    progress: objects_progress,
+                },
+            )?
+        };
+
+        let mut connectivity_progress = progress.add_child("checking connectivity");
+        connectivity_progress.init(Some(objects.len()), progress::count("objects"));
+        let mut outcome = Outcome {
+            num_objects: objects.len(),
+            ..Default::default()
+        };
+        let promisor_objects: HashSet<_> = self
+            .dbs
+            .iter()
+            .flat_map(|db| db.packs.iter())
+            .filter(|bundle| bundle.is_promisor())
+            .flat_map(|bundle| bundle.index.iter().map(|entry| entry.oid))
+            .collect();
+        let promised: HashSet<_> = objects
+            .iter()
+            .filter(|(id, _)| promisor_objects.contains(*id))
+            .flat_map(|(_, checked)| checked.references.iter().map(|(to, _kind)| *to))
+            .filter(|to| !objects.contains_key(to))
+            .collect();
+        let mut referenced = HashSet::new();
+        for (id, checked) in &objects {
+            if !checked.problems.is_empty() {
+                outcome.malformed.push(Malformed {
+                    id: *id,
+                    kind: checked.kind,
+                    problems: checked.problems.clone(),
+                });
+            }
+            for (to, expected_kind) in &checked.references {
+                referenced.insert(*to);
+                let actual_kind = objects.get(to).map(|object| object.kind);
+                if actual_kind.is_none() && promised.contains(to) {
+                    continue;
+                }
+                if actual_kind != Some(*expected_kind) {
+                    outcome.broken_links.push(BrokenLink {
+                        from: *id,
+                        from_kind: checked.kind,
+                        to: *to,
+                        expected_kind: *expected_kind,
+                        actual_kind,
+                    });
+                }
+            }
+        }
+
+        let mut reachable = HashSet::new();
+        let mut missing = HashSet::new();
+        let mut pending = Vec::new();
+        for tip in tips {
+            if objects.contains_key(&tip) {
+                pending.push(tip);
+            } else {
+                outcome.missing_tips.push(tip);
+            }
+        }
+        while let Some(id) = pending.pop() {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            if !reachable.insert(id) {
+                continue;
+            }
+            connectivity_progress.inc();
+            for (to, _kind) in &objects[&id].references {
+                if objects.contains_key(to) {
+                    pending.push(*to);
+                } else if !promised.contains(to) {
+                    missing.insert(*to);
+                }
+            }
+        }
+        outcome.missing = missing.into_iter().collect();
+        outcome.promised = promised.into_iter().collect();
+        outcome.num_unreachable = objects.len() - reachable.len();
+        outcome.dangling = objects
+            .iter()
+            .filter(|(id, _)| !reachable.contains(*id) && !referenced.contains(*id))
+            .map(|(id, checked)| (*id, checked.kind))
+            .collect();
+
+        outcome.malformed.sort();
+        outcome.broken_links.sort();
+        outcome.missing_tips.sort();
+        outcome.missing.sort();
+        outcome.promised.sort();
+        outcome.dangling.sort();
+        Ok(outcome)
+    }
+}
+
+struct Reducer<P> {
+    objects: HashMap<ObjectId, Checked>,
+    progress: P,
+}
+
+impl<P> parallel::Reduce for Reducer<P>
+where
+    P: Progress,
+{
+    type Input = Result<Vec<Checked>, Error>;
+    type FeedProduce = ();
+    type Output = HashMap<ObjectId, Checked>;
+    type Error = Error;
+
+    fn feed(&mut self, input: Self::Input) -> Result<(), Self::Error> {
+        let checked = input?;
+        self.progress.inc_by(checked.len());
+        // Objects may be stored more than once, for example in multiple packs.
+        self.objects
+            .extend(checked.into_iter().map(|checked| (checked.id, checked)));
+        if is_triggered() {
+            return Err(Error::Interrupted);
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.objects)
+    }
+}
@@ -18,5 +18,27 @@ pub mod find;
 ///
 mod write;
 
+///
+pub mod reachable;
+
+///
+pub mod repack;
+
+///
+pub mod prune;
+
+///
+pub mod fsck;
+
 ///
 mod iter;
+
+mod refresh;
+
+///
+pub mod shared;
+pub use shared::Shared;
+
+///
+pub mod promisor;
+pub use promisor::Promisor;
@@ -0,0 +1,125 @@
+use crate::{data, find, linked, pack};
+use git_hash::{oid, ObjectId};
+use parking_lot::Mutex;
+
+/// Returned by [`Promisor::find()`], [`Promisor::find_header()`] and [`Promisor::prefetch()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Shared(#[from] linked::shared::Error),
+    #[error("Could not fetch {num_objects} missing object(s) from the promisor remote")]
+    Fetch {
+        source: Box<dyn std::error::Error + Send + Sync>,
+        num_objects: usize,
+    },
+}
+
+/// Configuration for [`Promisor`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The maximum amount of missing objects to request from the promisor remote with a single fetch when
+    /// [prefetching][Promisor::prefetch()].
+    pub batch_size: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { batch_size: 1000 }
+    }
+}
+
+/// A [shared linked database][linked::Shared] of a partial clone, which fetches missing objects from the promisor remote on demand.
+///
+/// Missing objects are passed to the `fetch` callback, which is expected to make them available in the database, usually by
+/// writing a [promisor pack][pack::Bundle::is_promisor()] received from the promisor remote. The database is refreshed
+/// afterwards to pick up new packs.
+///
+/// Objects looked up with [`find()`][Promisor::find()] are fetched one at a time, so objects known to be needed should be
+/// [prefetched][Promisor::prefetch()] to fetch all missing ones among them in batches instead.
+///
+/// Whether a repository is a partial clone, and which remote is the promisor remote, is configured by `remote.<name>.promisor`
+/// or `extensions.partialClone`.
+pub struct Promisor<F> {
+    db: linked::Shared,
+    fetch: Mutex<F>,
+    options: Options,
+}
+
+impl<F, E> Promisor<F>
+where
+    F: FnMut(&[ObjectId]) -> Result<(), E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    /// Create a new instance to find objects in `db`, which are passed to `fetch` if they are missing.
+    pub fn new(db: impl Into<linked::Shared>, fetch: F, options: Options) -> Self {
+        Promisor {
+            db: db.into(),
+            fetch: Mutex::new(fetch),
+            options,
+        }
+    }
+
+    /// Return the database we look up objects in.
+    pub fn shared(&self) -> &linked::Shared {
+        &self.db
+    }
+
+    /// Find an object like [`linked::Shared::find()`], but fetch it from the promisor remote if it is missing.
+    ///
+    /// Returns `None` if the object is still missing after fetching it.
+    pub fn find<'a>(
+        &self,
+        id: impl AsRef<oid>,
+        buffer: &'a mut Vec<u8>,
+        pack_cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Option<data::Object<'a>>, Error> {
+        let id = id.as_ref();
+        if self.find_header(id)?.is_none() {
+            return Ok(None);
+        }
+        self.db.find(id, buffer, pack_cache).map_err(Into::into)
+    }
+
+    /// Find the header of an object like [`linked::Shared::find_header()`], but fetch the object from the promisor remote
+    /// if it is missing.
+    pub fn find_header(&self, id: impl AsRef<oid>) -> Result<Option<find::Header>, Error> {
+        let id = id.as_ref();
+        if let Some(header) = self.db.find_header(id)? {
+            return Ok(Some(header));
+        }
+        self.fetch(&[id.to_owned()])?;
+        self.db.find_header(id).map_err(Into::into)
+    }
+
+    /// Fetch all objects among `ids` which are missing from the promisor remote, with at most
+    /// [`Options::batch_size`] objects per fetch, and return the amount of missing objects.
+    ///
+    /// This is useful before operations which need many objects, like checking out a tree or computing a diff, as each
+    /// fetch incurs a round-trip to the promisor remote.
+    pub fn prefetch(&self, ids: impl IntoIterator<Item = ObjectId>) -> Result<usize, Error> {
+        let mut missing = Vec::new();
+        for id in ids {
+            if self.db.find_header(id)?.is_none() {
+                missing.push(id);
+            }
+        }
+        missing.sort();
+        missing.dedup();
+        for batch in missing.chunks(self.options.batch_size.max(1)) {
+            self.fetch(batch)?;
+        }
+        if !missing.is_empty() {
+            self.db.refresh()?;
+        }
+        Ok(missing.len())
+    }
+
+    fn fetch(&self, ids: &[ObjectId]) -> Result<(), Error> {
+        (self.fetch.lock())(ids).map_err(|err| Error::Fetch {
+            source: Box::new(err),
+            num_objects: ids.len(),
+        })
+    }
+}
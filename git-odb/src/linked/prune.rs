@@ -0,0 +1,137 @@
+use crate::{linked, loose, pack, Find};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use git_hash::ObjectId;
+use std::{
+    io,
+    time::{Duration, SystemTime},
+};
+
+/// Returned by [`linked::Db::prune()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("An IO error occurred when obtaining the modification time of a loose object or removing it")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Find(#[from] crate::compound::find::Error),
+    #[error(transparent)]
+    LooseIter(#[from] loose::db::iter::Error),
+    #[error("Could not find the objects reachable from the tips")]
+    Reachable(#[from] linked::reachable::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Configuration for [`linked::Db::prune()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// Only unreachable loose objects modified before this time are removed, similar to `--expire` of `git prune`.
+    /// Use `SystemTime::now()` to remove all of them.
+    pub expire: SystemTime,
+    /// If true, only list the objects which would be removed without removing them, similar to `--dry-run`.
+    pub dry_run: bool,
+}
+
+impl Default for Options {
+    /// Expire objects older than two weeks, the default of `gc.pruneExpire`.
+    fn default() -> Self {
+        Options {
+            expire: SystemTime::now() - Duration::from_secs(2 * 7 * 24 * 60 * 60),
+            dry_run: false,
+        }
+    }
+}
+
+/// Returned by [`linked::Db::prune()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The ids of all objects which were removed, or would have been removed in a dry-run.
+    pub objects: Vec<ObjectId>,
+    /// The amount of bytes the files of these `objects` take on disk.
+    pub num_bytes: u64,
+}
+
+impl linked::Db {
+    /// Remove all loose objects of our own object database, the first of our [databases][linked::Db::dbs], which aren't
+    /// reachable from `tips` and were last modified before [`Options::expire`], similar to `git prune --expire`.
+    ///
+    /// `tips` are the objects pointed to by all references, the entries of their reflogs and of the index, which may be
+    /// commits, tags, trees or blobs, as returned by `git_repository::tips::from_git_dir()`. Tips which don't exist are
+    /// ignored, as reflogs may refer to objects which were removed already.
+    ///
+    /// Objects reachable from unreachable objects which aren't expired yet are retained as well, to not break them.
+    /// Unlike objects reachable from `tips`, objects missing below them are ignored, as they may have been removed before.
+    /// Packed objects are never removed, use [`repack()`][linked::Db::repack()] to drop unreachable objects from packs.
+    ///
+    /// Each object which is removed, or would be removed if [`Options::dry_run`] is set, is reported through `progress` as well.
+    pub fn prune(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        mut progress: impl Progress,
+        Options { expire, dry_run }: Options,
+    ) -> Result<Outcome, Error> {
+        let loose = &self.dbs[0].loose;
+        let mut buf = Vec::new();
+        let mut existing_tips = Vec::new();
+        for id in tips {
+            if self.find(id, &mut buf, &mut pack::cache::Never)?.is_some() {
+                existing_tips.push(id);
+            }
+        }
+        let mut recent_objects = Vec::new();
+        let mut expired = Vec::new();
+        for id in loose.iter() {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            let id = id?;
+            let metadata = std::fs::metadata(loose::db::hash_path(&id, loose.path.clone()))?;
+            if metadata.modified()? < expire {
+                expired.push((id, metadata.len()));
+            } else {
+                recent_objects.push(id);
+            }
+        }
+
+        let (_, mut reachable) =
+            linked::reachable::objects(self, &existing_tips, progress.add_child("collecting reachable objects"))?;
+        linked::reachable::add_objects_ignoring_missing(
+            self,
+            &recent_objects,
+            &mut reachable,
+            progress.add_child("collecting objects reachable from recent objects"),
+        )?;
+        expired.retain(|(id, _)| !reachable.contains(id));
+
+        let mut remove_progress = progress.add_child(if dry_run { "listing" } else { "removing" });
+        remove_progress.init(Some(expired.len()), progress::count("objects"));
+        let mut num_bytes = 0;
+        for (id, len) in &expired {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            if dry_run {
+                remove_progress.info(format!("would remove {}", id));
+            } else {
+                let path = loose::db::hash_path(id, loose.path.clone());
+                std::fs::remove_file(&path)?;
+                if let Some(fan_out_directory) = path.parent() {
+                    // Fails as long as there are other objects in it.
+                    std::fs::remove_dir(fan_out_directory).ok();
+                }
+                remove_progress.info(format!("removed {}", id));
+            }
+            num_bytes += len;
+            remove_progress.inc();
+        }
+        Ok(Outcome {
+            objects: expired.into_iter().map(|(id, _)| id).collect(),
+            num_bytes,
+        })
+    }
+}
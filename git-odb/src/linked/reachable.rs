@@ -0,0 +1,150 @@
+use crate::{
+    compound, linked, pack,
+    pack::data::output::objects_to_entries::{
+        self,
+        expand::{self, Expander},
+        ObjectExpansion,
+    },
+    Find,
+};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use git_hash::ObjectId;
+use git_object::{immutable, tree};
+use std::collections::HashSet;
+
+/// Returned when finding the objects reachable from tips as part of [`linked::Db::repack()`] and [`linked::Db::prune()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Find(#[from] compound::find::Error),
+    #[error("Object {id} is reachable but couldn't be found")]
+    NotFound { id: ObjectId },
+    #[error("An object could not be parsed")]
+    ObjectDecode(#[from] immutable::object::decode::Error),
+    #[error("Could not traverse the ancestry of the tips")]
+    Ancestors(#[from] git_traverse::commit::ancestors::Error),
+    #[error("Could not expand commits and trees into the objects they reference")]
+    Expand(#[source] objects_to_entries::Error<compound::find::Error>),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Return the ids of all objects reachable from `tips`, along with a set containing them.
+pub(crate) fn objects(
+    db: &linked::Db,
+    tips: &[ObjectId],
+    mut progress: impl Progress,
+) -> Result<(Vec<ObjectId>, HashSet<ObjectId>), Error> {
+    let mut buf = Vec::new();
+    let cache = &mut pack::cache::Never;
+    let (mut commits, mut others) = (Vec::new(), Vec::new());
+    for tip in tips {
+        let mut id = *tip;
+        loop {
+            let obj = db.find(id, &mut buf, cache)?.ok_or(Error::NotFound { id })?;
+            match obj.kind {
+                git_object::Kind::Commit => {
+                    commits.push(id);
+                    break;
+                }
+                git_object::Kind::Tag => {
+                    others.push(id);
+                    id = immutable::Tag::from_bytes(obj.data)?.target();
+                }
+                git_object::Kind::Tree | git_object::Kind::Blob => {
+                    others.push(id);
+                    break;
+                }
+            }
+        }
+    }
+
+    progress.init(None, progress::count("objects"));
+    let mut objects = Vec::new();
+    let mut seen = HashSet::new();
+    let mut expander = Expander::default();
+    let mut expanded = Vec::new();
+    let ancestors =
+        git_traverse::commit::Ancestors::new(commits, git_traverse::commit::ancestors::State::default(), |id, buf| {
+            db.find(id, buf, &mut pack::cache::Never)
+                .ok()
+                .flatten()
+                .and_then(|obj| obj.into_commit_iter())
+        });
+    for id in ancestors
+        .map(|id| id.map_err(Error::from))
+        .chain(others.into_iter().map(Ok))
+    {
+        if is_triggered() {
+            return Err(Error::Interrupted);
+        }
+        expanded.clear();
+        expander
+            .expand(
+                db,
+                cache,
+                &id?,
+                ObjectExpansion::TreeContents,
+                &mut |id| expand::is_new(&mut seen, id, ObjectExpansion::TreeContents),
+                &mut expanded,
+            )
+            .map_err(Error::Expand)?;
+        progress.inc_by(expanded.len());
+        objects.extend(expanded.iter().map(|(id, _name_hash)| *id));
+    }
+    Ok((objects, seen))
+}
+
+/// Add the ids of all objects reachable from `ids` to `seen` unless they are contained in it already, ignoring objects
+/// which can't be found along with all objects only reachable through them.
+///
+/// This is how git treats the objects reachable from recent objects when pruning, as these may refer to objects which
+/// were removed already.
+pub(crate) fn add_objects_ignoring_missing(
+    db: &linked::Db,
+    ids: &[ObjectId],
+    seen: &mut HashSet<ObjectId>,
+    mut progress: impl Progress,
+) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    let mut pending = ids.to_vec();
+    progress.init(None, progress::count("objects"));
+    while let Some(id) = pending.pop() {
+        if is_triggered() {
+            return Err(Error::Interrupted);
+        }
+        if !seen.insert(id) {
+            continue;
+        }
+        progress.inc();
+        let obj = match db.find(id, &mut buf, &mut pack::cache::Never)? {
+            Some(obj) => obj,
+            None => continue,
+        };
+        match obj.decode()? {
+            immutable::Object::Commit(commit) => {
+                pending.push(commit.tree());
+                pending.extend(commit.parents());
+            }
+            immutable::Object::Tree(tree) => {
+                for entry in tree.entries {
+                    match entry.mode {
+                        tree::EntryMode::Tree => pending.push(entry.oid.to_owned()),
+                        // Submodule commits are part of another repository.
+                        tree::EntryMode::Commit => {}
+                        tree::EntryMode::Blob | tree::EntryMode::BlobExecutable | tree::EntryMode::Link => {
+                            seen.insert(entry.oid.to_owned());
+                        }
+                    }
+                }
+            }
+            immutable::Object::Tag(tag) => pending.push(tag.target()),
+            immutable::Object::Blob(_) => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use crate::{compound, linked};
+
+/// Refreshing
+impl linked::Db {
+    /// Returns true if any of our [databases][linked::Db::dbs] is [outdated][compound::Db::is_outdated()] as packs were added
+    /// or removed.
+    pub fn is_outdated(&self) -> bool {
+        self.dbs.iter().any(compound::Db::is_outdated)
+    }
+
+    /// [Refresh][compound::Db::refresh()] all of our outdated [databases][linked::Db::dbs] and return true if at least one of
+    /// them was reloaded.
+    ///
+    /// _Note_ that changes to _git alternates_ are not picked up.
+    pub fn refresh(&mut self) -> Result<bool, compound::init::Error> {
+        let mut refreshed = false;
+        for db in self.dbs.iter_mut() {
+            refreshed |= db.refresh()?;
+        }
+        Ok(refreshed)
+    }
+}
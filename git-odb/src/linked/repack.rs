@@ -0,0 +1,347 @@
+use crate::{
+    compound, linked, loose, pack,
+    pack::data::output::{self, objects_to_entries::ObjectExpansion},
+};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use git_hash::ObjectId;
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Returned by [`linked::Db::repack()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("An IO error occurred when writing the new pack or removing the old ones")]
+    Io(#[from] io::Error),
+    #[error("Could not find the objects reachable from the tips")]
+    Reachable(#[from] linked::reachable::Error),
+    #[error(transparent)]
+    LooseIter(#[from] loose::db::iter::Error),
+    #[error("Could not write the new pack")]
+    WritePack(#[from] output::entries_to_bytes::Error<output::objects_to_entries::Error<compound::find::Error>>),
+    #[error("Could not index the new pack")]
+    IndexPack(#[from] pack::bundle::write::Error),
+    #[error("Could not move a temporary file into its desired place")]
+    Persist(#[from] tempfile::PersistError),
+    #[error(transparent)]
+    BundleInit(#[from] pack::bundle::Error),
+    #[error("Could not write the bitmap of the new pack")]
+    Bitmap(#[from] pack::bitmap::write::Error),
+    #[error("The object database could not be reloaded after repacking")]
+    Reload(#[from] compound::init::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Configuration for [`linked::Db::repack()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The amount of threads to use at most when producing the pack. If `None`, all logical cores are used.
+    pub thread_limit: Option<usize>,
+    /// If set, objects will be stored as deltas against similar objects, reusing deltas of existing packs as configured.
+    /// If unset, all objects are written as base objects.
+    pub delta_search: Option<output::objects_to_entries::DeltaSearch>,
+    /// If true, objects in the packs to be removed which aren't reachable from the tips are written into the new pack as well,
+    /// similar to `--keep-unreachable` of `git repack`. Otherwise they are lost along with their packs.
+    pub keep_unreachable: bool,
+    /// If true, write a [bitmap file][pack::bitmap::File] for the new pack, similar to `--write-bitmap-index`. This only
+    /// happens if all objects reachable from the tips are contained in it, as bitmaps can't refer to objects outside of
+    /// their pack.
+    pub write_bitmap_index: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            thread_limit: None,
+            delta_search: Some(Default::default()),
+            keep_unreachable: false,
+            write_bitmap_index: false,
+        }
+    }
+}
+
+/// Returned by [`linked::Db::repack()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The amount of objects written into the new pack.
+    pub num_objects: u32,
+    /// The path to the data file of the new pack, or `None` if there were no objects to write.
+    pub data_path: Option<PathBuf>,
+    /// The path to the index file of the new pack, or `None` if there were no objects to write.
+    pub index_path: Option<PathBuf>,
+    /// The outcome of writing the bitmap file of the new pack if one was written.
+    pub bitmap: Option<pack::bitmap::write::Outcome>,
+    /// The paths to the data files of all packs which were removed.
+    pub removed_packs: Vec<PathBuf>,
+    /// The amount of loose objects which were removed as they are contained in a pack now.
+    pub num_removed_loose_objects: usize,
+}
+
+/// The result of writing the new pack, before anything was removed.
+struct Written {
+    num_objects: u32,
+    paths: Option<(PathBuf, PathBuf)>,
+    bitmap: Option<pack::bitmap::write::Outcome>,
+}
+
+impl linked::Db {
+    /// Write all objects reachable from `tips` into a single new pack in our own object database, the first of our
+    /// [databases][linked::Db::dbs], and remove all packs it had before along with loose objects which are packed now,
+    /// similar to `git repack -a -d`.
+    ///
+    /// `tips` are the objects pointed to by all references, usually as obtained from the refs, `HEAD` and maybe the
+    /// reflogs and index of a repository, and may be commits, tags, trees or blobs.
+    ///
+    /// Objects which are contained in packs with a `.keep` file or in [promisor packs][pack::Bundle::is_promisor()] aren't written
+    /// into the new pack, and these packs are retained.
+    /// Objects only available in _alternates_ aren't written either, even though they are traversed to find the objects
+    /// reachable from them.
+    /// Loose objects which aren't reachable are retained, whereas unreachable objects in removed packs are lost unless
+    /// [`Options::keep_unreachable`] is set.
+    ///
+    /// Old packs and loose objects are removed only once the new pack and its index are in place, which also happens if
+    /// there is nothing to write. Afterwards our own database is reloaded to see the new pack.
+    pub fn repack(
+        &mut self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        mut progress: impl Progress,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let tips: Vec<_> = tips.into_iter().collect();
+        progress.init(Some(3), progress::steps());
+        // The pack generation requires a database which can be shared with its threads, which are done once it returns.
+        let db = Arc::new(linked::Db {
+            dbs: std::mem::take(&mut self.dbs),
+        });
+        let written = write_pack(&db, &tips, &mut progress, &options);
+        self.dbs = match Arc::try_unwrap(db) {
+            Ok(db) => db.dbs,
+            Err(_) => unreachable!("all threads using the database are done"),
+        };
+        let Written {
+            num_objects,
+            paths,
+            bitmap,
+        } = written?;
+        progress.inc();
+
+        let (removed_packs, num_removed_loose_objects) =
+            self.remove_packed(paths.as_ref(), progress.add_child("removing packed objects"))?;
+        progress.inc();
+        let (data_path, index_path) = match paths {
+            Some((data_path, index_path)) => (Some(data_path), Some(index_path)),
+            None => (None, None),
+        };
+        Ok(Outcome {
+            num_objects,
+            data_path,
+            index_path,
+            bitmap,
+            removed_packs,
+            num_removed_loose_objects,
+        })
+    }
+
+    /// Remove all packs of our own database except for the new one at `new_pack` and the kept ones, along with all loose objects
+    /// contained in the remaining packs, and reload it.
+    fn remove_packed(
+        &mut self,
+        new_pack: Option<&(PathBuf, PathBuf)>,
+        mut progress: impl Progress,
+    ) -> Result<(Vec<PathBuf>, usize), Error> {
+        let new_bundle = new_pack
+            .map(|(_, index_path)| pack::Bundle::at(index_path))
+            .transpose()?;
+        let local = &self.dbs[0];
+        let (kept, removable): (Vec<_>, Vec<_>) = local.packs.iter().partition(|bundle| is_kept(bundle));
+        let removed_packs: Vec<_> = removable
+            .iter()
+            .map(|bundle| bundle.pack.path().to_owned())
+            .filter(|data_path| Some(data_path) != new_pack.map(|(data_path, _)| data_path))
+            .collect();
+        let mut packed_loose_objects = Vec::new();
+        for id in local.loose.iter() {
+            let id = id?;
+            if new_bundle
+                .iter()
+                .chain(kept.iter().copied())
+                .any(|bundle| bundle.index.lookup(id).is_some())
+            {
+                packed_loose_objects.push(id);
+            }
+        }
+
+        // Unmap all packs before removing them, which is required on some platforms.
+        let objects_directory = local.loose.path.clone();
+        drop(new_bundle);
+        self.dbs[0] = compound::Db {
+            loose: loose::Db::at(objects_directory.clone()),
+            packs: Vec::new(),
+            multi_index: None,
+            multi_index_packs: Vec::new(),
+            packs_without_multi_index: Vec::new(),
+            pack_directory_state: Default::default(),
+        };
+
+        progress.init(
+            Some(removed_packs.len() + packed_loose_objects.len()),
+            progress::count("files"),
+        );
+        for data_path in &removed_packs {
+            for extension in &["bitmap", "rev", "idx", "pack"] {
+                remove_file_if_present(&data_path.with_extension(extension))?;
+            }
+            progress.inc();
+        }
+        if !removed_packs.is_empty() {
+            // The multi-pack index covers packs which don't exist anymore.
+            remove_file_if_present(&objects_directory.join("pack").join("multi-pack-index"))?;
+        }
+        for id in &packed_loose_objects {
+            let path = loose::db::hash_path(id, objects_directory.clone());
+            remove_file_if_present(&path)?;
+            if let Some(fan_out_directory) = path.parent() {
+                // Fails as long as there are other objects in it.
+                std::fs::remove_dir(fan_out_directory).ok();
+            }
+            progress.inc();
+        }
+
+        self.dbs[0] = compound::Db::at(objects_directory)?;
+        Ok((removed_packs, packed_loose_objects.len()))
+    }
+}
+
+/// Write the objects reachable from `tips` which are only available in our own database into a new pack, and index it.
+fn write_pack(
+    db: &Arc<linked::Db>,
+    tips: &[ObjectId],
+    progress: &mut impl Progress,
+    options: &Options,
+) -> Result<Written, Error> {
+    let local = &db.dbs[0];
+    let pack_directory = local.loose.path.join("pack");
+    let (kept, removable): (Vec<_>, Vec<_>) = local.packs.iter().partition(|bundle| is_kept(bundle));
+
+    let (reachable, mut seen) = linked::reachable::objects(db, tips, progress.add_child("collecting objects"))?;
+    let is_local = |id: &ObjectId| {
+        !kept.iter().any(|bundle| bundle.index.lookup(id).is_some())
+            && (removable.iter().any(|bundle| bundle.index.lookup(id).is_some()) || local.loose.contains(id))
+    };
+    let mut objects: Vec<_> = reachable.iter().filter(|id| is_local(id)).copied().collect();
+    let is_self_contained = objects.len() == reachable.len();
+    if options.keep_unreachable {
+        for bundle in &removable {
+            for entry in bundle.index.iter() {
+                if seen.insert(entry.oid) && is_local(&entry.oid) {
+                    objects.push(entry.oid);
+                }
+            }
+        }
+    }
+    if objects.is_empty() {
+        return Ok(Written {
+            num_objects: 0,
+            paths: None,
+            bitmap: None,
+        });
+    }
+
+    std::fs::create_dir_all(&pack_directory)?;
+    let num_objects = objects.len() as u32;
+    // All objects of a repository are identified by the same kind of hash, which is used for the pack and its index as well.
+    let hash_kind = objects[0].kind();
+    let mut pack_file = tempfile::NamedTempFile::new_in(&pack_directory)?;
+    {
+        let mut out = io::BufWriter::new(&mut pack_file);
+        let entries = output::objects_to_entries_iter(
+            db.clone(),
+            || pack::cache::Never,
+            objects.into_iter(),
+            progress.add_child("creating entries"),
+            output::objects_to_entries::Options {
+                thread_limit: options.thread_limit,
+                input_object_expansion: ObjectExpansion::AsIs,
+                delta_search: options.delta_search,
+                ..Default::default()
+            },
+        );
+        for written in
+            output::EntriesToBytesIter::new(entries, &mut out, num_objects, pack::data::Version::V2, hash_kind)
+        {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            written?;
+        }
+        out.flush()?;
+    }
+
+    let outcome = pack::Bundle::write_stream_to_directory(
+        io::BufReader::new(std::fs::File::open(pack_file.path())?),
+        Some(&pack_directory),
+        progress.add_child("indexing pack"),
+        None::<linked::Db>,
+        pack::bundle::write::Options {
+            hash_kind,
+            ..Default::default()
+        },
+    )?;
+    let (data_path, index_path) = {
+        let data_path = pack_directory.join(format!("pack-{}.pack", outcome.index.data_hash));
+        let index_path = data_path.with_extension("idx");
+        // Move the index last, as packs are only discovered by their index.
+        std::fs::rename(outcome.data_path.expect("directory set"), &data_path)?;
+        std::fs::rename(outcome.index_path.expect("directory set"), &index_path)?;
+        (data_path, index_path)
+    };
+
+    let bitmap = if options.write_bitmap_index && is_self_contained {
+        let bundle = pack::Bundle::at(&index_path)?;
+        let mut bitmap_file = tempfile::NamedTempFile::new_in(&pack_directory)?;
+        let outcome = {
+            let mut out = io::BufWriter::new(&mut bitmap_file);
+            let outcome = pack::bitmap::File::write_from_bundle(
+                &bundle,
+                tips.iter().copied(),
+                &mut pack::cache::Never,
+                &mut out,
+                progress.add_child("writing bitmap"),
+                Default::default(),
+            )?;
+            out.flush()?;
+            outcome
+        };
+        bitmap_file.persist(index_path.with_extension("bitmap"))?;
+        Some(outcome)
+    } else {
+        None
+    };
+
+    Ok(Written {
+        num_objects,
+        paths: Some((data_path, index_path)),
+        bitmap,
+    })
+}
+
+/// Return true if the pack of `bundle` has a `.keep` file or is a promisor pack and must not be removed.
+fn is_kept(bundle: &pack::Bundle) -> bool {
+    bundle.pack.path().with_extension("keep").is_file() || bundle.is_promisor()
+}
+
+fn remove_file_if_present(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{compound, data, find, linked, pack, Find};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Returned by [`Shared::find()`] and [`Shared::refresh()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Find(#[from] compound::find::Error),
+    #[error("Could not reload the packs of an object database")]
+    Refresh(#[from] compound::init::Error),
+}
+
+/// A [linked database][linked::Db] to be shared among threads for a long time, which picks up packs added or removed by other
+/// processes, for example when fetching or running `git gc`.
+///
+/// Objects are looked up in a [snapshot][Shared::snapshot()] of the database. If an object can't be found or reading it fails,
+/// the snapshot is [refreshed][Shared::refresh()] if packs changed on disk and the lookup is retried.
+///
+/// As the data returned by [`Find`] implementations is bound to the lifetime of the database, use a
+/// [snapshot][Shared::snapshot()] with all APIs generic over [`Find`].
+pub struct Shared {
+    db: RwLock<Arc<linked::Db>>,
+}
+
+impl From<linked::Db> for Shared {
+    fn from(db: linked::Db) -> Self {
+        Shared {
+            db: RwLock::new(Arc::new(db)),
+        }
+    }
+}
+
+impl Shared {
+    /// Return the database as currently known, which won't change while it is held.
+    pub fn snapshot(&self) -> Arc<linked::Db> {
+        self.db.read().clone()
+    }
+
+    /// Reload the packs of all databases which are [outdated][linked::Db::is_outdated()], and return true if this was the case.
+    ///
+    /// The current snapshot is updated in place if it isn't held by anyone, or all of its packs are reopened otherwise.
+    /// Readers are only blocked while reloading, not while checking if the database is outdated.
+    pub fn refresh(&self) -> Result<bool, Error> {
+        if !self.snapshot().is_outdated() {
+            return Ok(false);
+        }
+        let mut db = self.db.write();
+        if !db.is_outdated() {
+            // Another thread refreshed the database while we were waiting for the lock.
+            return Ok(true);
+        }
+        match Arc::get_mut(&mut db) {
+            Some(db) => {
+                db.refresh()?;
+            }
+            None => {
+                let dbs = db
+                    .dbs
+                    .iter()
+                    .map(|db| compound::Db::at(db.loose.path.clone()))
+                    .collect::<Result<_, _>>()?;
+                *db = Arc::new(linked::Db { dbs });
+            }
+        }
+        Ok(true)
+    }
+
+    /// Find an object like [`Find::find()`], but [refresh][Shared::refresh()] the database and try again if it couldn't be found
+    /// or read.
+    pub fn find<'a>(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+        buffer: &'a mut Vec<u8>,
+        pack_cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Option<data::Object<'a>>, Error> {
+        let id = id.as_ref();
+        // Only keep what's needed to recreate the object, as returning it directly would keep `buffer` borrowed for the
+        // retry as well. (The polonius borrow-checker would support this once it is stable.)
+        let found = self
+            .snapshot()
+            .find(id, buffer, pack_cache)
+            .map(|obj| obj.map(|obj| (obj.kind, obj.data.len(), obj.hash_kind, obj.pack_location)));
+        match found {
+            Ok(Some((kind, len, hash_kind, pack_location))) => {
+                return Ok(Some(data::Object {
+                    kind,
+                    data: &buffer[..len],
+                    hash_kind,
+                    pack_location,
+                }))
+            }
+            Ok(None) => {
+                if !self.refresh()? {
+                    return Ok(None);
+                }
+            }
+            Err(err) => {
+                if !self.refresh()? {
+                    return Err(err.into());
+                }
+            }
+        }
+        self.snapshot().find(id, buffer, pack_cache).map_err(Into::into)
+    }
+
+    /// Find the header of an object like [`Find::find_header()`], but [refresh][Shared::refresh()] the database and try again
+    /// if it couldn't be found or read.
+    pub fn find_header(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<find::Header>, Error> {
+        let id = id.as_ref();
+        match self.snapshot().find_header(id) {
+            Ok(Some(header)) => return Ok(Some(header)),
+            Ok(None) => {
+                if !self.refresh()? {
+                    return Ok(None);
+                }
+            }
+            Err(err) => {
+                if !self.refresh()? {
+                    return Err(err.into());
+                }
+            }
+        }
+        self.snapshot().find_header(id).map_err(Into::into)
+    }
+}
@@ -1,9 +1,15 @@
 use crate::{
-    data,
-    loose::{db::sha1_path, object::header, Db, HEADER_READ_UNCOMPRESSED_BYTES},
+    data, find,
+    loose::{db::hash_path, object::header, Db, HEADER_READ_UNCOMPRESSED_BYTES},
     zlib,
+    zlib::stream::inflate::ReadBoxed,
+};
+use std::{
+    convert::TryInto,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
 };
-use std::{convert::TryInto, fs, io::Read, path::PathBuf};
 
 /// Returned by [`Db::find()`]
 #[derive(thiserror::Error, Debug)]
@@ -24,13 +30,16 @@ pub enum Error {
     },
 }
 
+/// The amount of compressed bytes to read at a time when only decompressing the header of an object.
+const HEADER_READ_COMPRESSED_BYTES: usize = 256;
+
 /// Object lookup
 impl Db {
     const OPEN_ACTION: &'static str = "open";
 
     /// Returns true if the given id is contained in our repository.
     pub fn contains(&self, id: impl AsRef<git_hash::oid>) -> bool {
-        sha1_path(id.as_ref(), self.path.clone()).is_file()
+        hash_path(id.as_ref(), self.path.clone()).is_file()
     }
 
     /// Return the object identified by the given [`ObjectId`][git_hash::ObjectId] if present in this database,
@@ -66,8 +75,82 @@ impl Db {
         }
     }
 
+    /// Return the kind and size of the object identified by the given [`ObjectId`][git_hash::ObjectId] if present in this
+    /// database, decompressing only as much of it as needed to read its header.
+    ///
+    /// Returns `Err` if there was an error locating or reading the object. Returns `Ok<None>` if
+    /// there was no such object.
+    pub fn find_header(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<find::Header>, Error> {
+        Ok(self
+            .open_after_header(id.as_ref())?
+            .map(|(kind, size, _stream)| find::Header { kind, size }))
+    }
+
+    /// Return a stream to read the data of the object identified by the given [`ObjectId`][git_hash::ObjectId] if present in this
+    /// database, decompressing it while reading.
+    ///
+    /// Returns `Err` if there was an error locating or reading the object. Returns `Ok<None>` if
+    /// there was no such object.
+    pub fn find_stream(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<find::Stream<'static>>, Error> {
+        Ok(self
+            .open_after_header(id.as_ref())?
+            .map(|(kind, size, stream)| find::Stream {
+                kind,
+                size,
+                inner: find::stream::Inner::Loose(stream),
+            }))
+    }
+
+    /// Open the object with `id` and decompress its header, returning its kind and size along with a stream of its data.
+    #[allow(clippy::type_complexity)]
+    fn open_after_header(
+        &self,
+        id: &git_hash::oid,
+    ) -> Result<
+        Option<(
+            git_object::Kind,
+            u64,
+            io::Chain<io::Cursor<Vec<u8>>, ReadBoxed<io::BufReader<fs::File>>>,
+        )>,
+        Error,
+    > {
+        let path = hash_path(id, self.path.clone());
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Error::Io {
+                    source: err,
+                    action: Self::OPEN_ACTION,
+                    path,
+                })
+            }
+        };
+        let mut inflate = ReadBoxed {
+            inner: io::BufReader::with_capacity(HEADER_READ_COMPRESSED_BYTES, file),
+            decompressor: Box::new(flate2::Decompress::new(true)),
+        };
+        // A header is `<kind> <size>\0` and thus much shorter than this, no matter the size.
+        let mut header_buf = [0u8; 64];
+        let mut filled = 0;
+        while !header_buf[..filled].contains(&0) && filled < header_buf.len() {
+            let num_read = inflate.read(&mut header_buf[filled..]).map_err(|e| Error::Io {
+                source: e,
+                action: "deflate",
+                path: path.to_owned(),
+            })?;
+            if num_read == 0 {
+                break;
+            }
+            filled += num_read;
+        }
+        let (kind, size, header_size) = header::decode(&header_buf[..filled])?;
+        let data_read_with_header = io::Cursor::new(header_buf[header_size..filled].to_vec());
+        Ok(Some((kind, size, data_read_with_header.chain(inflate))))
+    }
+
     fn find_inner<'a>(&self, id: &git_hash::oid, buf: &'a mut Vec<u8>) -> Result<data::Object<'a>, Error> {
-        let path = sha1_path(id, self.path.clone());
+        let path = hash_path(id, self.path.clone());
 
         let mut inflate = zlib::Inflate::default();
         let ((status, consumed_in, consumed_out), bytes_read) = {
@@ -118,6 +201,7 @@ impl Db {
             Ok(crate::data::Object {
                 kind,
                 data: buf,
+                hash_kind: id.kind(),
                 pack_location: None,
             })
         } else {
@@ -145,6 +229,7 @@ impl Db {
             Ok(crate::data::Object {
                 kind,
                 data: buf,
+                hash_kind: id.kind(),
                 pack_location: None,
             })
         }
@@ -28,15 +28,17 @@ impl Db {
             let mut ci = p.components();
             let (c2, c1) = (ci.next_back(), ci.next_back());
             if let (Some(Normal(c1)), Some(Normal(c2))) = (c1, c2) {
-                if c1.len() == 2 && c2.len() == 38 {
+                // Sha1 ids have 38 hex characters left after the directory name, Sha256 ids 62.
+                if c1.len() == 2 && (c2.len() == 38 || c2.len() == 62) {
                     if let (Some(c1), Some(c2)) = (c1.to_str(), c2.to_str()) {
-                        let mut buf = [0u8; 40];
+                        let mut buf = [0u8; 64];
+                        let hex_len = 2 + c2.len();
                         {
-                            let (first_byte, rest) = buf.split_at_mut(2);
+                            let (first_byte, rest) = buf[..hex_len].split_at_mut(2);
                             first_byte.copy_from_slice(c1.as_bytes());
                             rest.copy_from_slice(c2.as_bytes());
                         }
-                        if let Ok(b) = git_hash::ObjectId::from_hex(&buf[..]) {
+                        if let Ok(b) = git_hash::ObjectId::from_hex(&buf[..hex_len]) {
                             is_valid_path = true;
                             return b;
                         }
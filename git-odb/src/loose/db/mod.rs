@@ -19,16 +19,11 @@ impl Db {
     }
 }
 
-pub(crate) fn sha1_path(id: &git_hash::oid, mut root: PathBuf) -> PathBuf {
-    match id.kind() {
-        git_hash::Kind::Sha1 => {
-            let hex = id.to_sha1_hex();
-            let buf = std::str::from_utf8(&hex).expect("ascii only in hex");
-            root.push(&buf[..2]);
-            root.push(&buf[2..]);
-            root
-        }
-    }
+pub(crate) fn hash_path(id: &git_hash::oid, mut root: PathBuf) -> PathBuf {
+    let hex = id.to_owned().to_string();
+    root.push(&hex[..2]);
+    root.push(&hex[2..]);
+    root
 }
 
 ///
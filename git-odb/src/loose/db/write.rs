@@ -20,6 +20,8 @@ pub enum Error {
         source: tempfile::PersistError,
         target: PathBuf,
     },
+    #[error("Refusing to write an object containing a Sha1 collision attack")]
+    Sha1Collision(#[from] git_features::hash::Collision),
 }
 
 impl crate::write::Write for Db {
@@ -34,18 +36,14 @@ impl crate::write::Write for Db {
         from: &[u8],
         hash: git_hash::Kind,
     ) -> Result<git_hash::ObjectId, Self::Error> {
-        match hash {
-            git_hash::Kind::Sha1 => {
-                let mut to = self.write_header(kind, from.len() as u64, hash)?;
-                to.write_all(from).map_err(|err| Error::Io {
-                    source: err,
-                    message: "stream all data into tempfile in",
-                    path: self.path.to_owned(),
-                })?;
-                to.flush()?;
-                self.finalize_object(to)
-            }
-        }
+        let mut to = self.write_header(kind, from.len() as u64, hash)?;
+        to.write_all(from).map_err(|err| Error::Io {
+            source: err,
+            message: "stream all data into tempfile in",
+            path: self.path.to_owned(),
+        })?;
+        to.flush()?;
+        self.finalize_object(to)
     }
 
     /// Write the given stream in `from` to disk with at least one syscall.
@@ -58,18 +56,14 @@ impl crate::write::Write for Db {
         mut from: impl io::Read,
         hash: git_hash::Kind,
     ) -> Result<git_hash::ObjectId, Self::Error> {
-        match hash {
-            git_hash::Kind::Sha1 => {
-                let mut to = self.write_header(kind, size, hash)?;
-                io::copy(&mut from, &mut to).map_err(|err| Error::Io {
-                    source: err,
-                    message: "stream all data into tempfile in",
-                    path: self.path.to_owned(),
-                })?;
-                to.flush()?;
-                self.finalize_object(to)
-            }
-        }
+        let mut to = self.write_header(kind, size, hash)?;
+        io::copy(&mut from, &mut to).map_err(|err| Error::Io {
+            source: err,
+            message: "stream all data into tempfile in",
+            path: self.path.to_owned(),
+        })?;
+        to.flush()?;
+        self.finalize_object(to)
     }
 }
 
@@ -103,8 +97,8 @@ impl Db {
         &self,
         hash::Write { hash, inner: file }: hash::Write<CompressedTempfile>,
     ) -> Result<git_hash::ObjectId, Error> {
-        let id = git_hash::ObjectId::from(hash.digest());
-        let object_path = loose::db::sha1_path(&id, self.path.clone());
+        let id = hash.try_digest()?;
+        let object_path = loose::db::hash_path(&id, self.path.clone());
         let object_dir = object_path
             .parent()
             .expect("each object path has a 1 hex-bytes directory");
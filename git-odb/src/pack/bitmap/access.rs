@@ -0,0 +1,110 @@
+use crate::pack::bitmap::{self, ewah, Bitmap, HEADER_LEN_WITHOUT_PACK_CHECKSUM};
+use byteorder::{BigEndian, ByteOrder};
+
+/// The amount of objects per kind in a bitmap, as returned by [`bitmap::File::counts()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub struct Counts {
+    pub commits: usize,
+    pub trees: usize,
+    pub blobs: usize,
+    pub tags: usize,
+}
+
+impl Counts {
+    /// The amount of objects of all kinds.
+    pub fn total(&self) -> usize {
+        self.commits + self.trees + self.blobs + self.tags
+    }
+}
+
+/// Access
+impl bitmap::File {
+    /// The checksum of the pack this bitmap file belongs to, to match it with its pack and index.
+    pub fn pack_checksum(&self) -> git_hash::ObjectId {
+        self.id_at(HEADER_LEN_WITHOUT_PACK_CHECKSUM)
+    }
+
+    /// The checksum stored at the end of this file, which is a hash over all bytes preceding it.
+    pub fn checksum(&self) -> git_hash::ObjectId {
+        self.id_at(self.data.len() - self.hash_kind.len_in_bytes())
+    }
+
+    fn id_at(&self, offset: usize) -> git_hash::ObjectId {
+        git_hash::oid::try_from(&self.data[offset..offset + self.hash_kind.len_in_bytes()])
+            .expect("hash length to be supported")
+            .to_owned()
+    }
+
+    /// A bitmap with all commits in the pack set.
+    pub fn commits(&self) -> &Bitmap {
+        &self.commits
+    }
+    /// A bitmap with all trees in the pack set.
+    pub fn trees(&self) -> &Bitmap {
+        &self.trees
+    }
+    /// A bitmap with all blobs in the pack set.
+    pub fn blobs(&self) -> &Bitmap {
+        &self.blobs
+    }
+    /// A bitmap with all tags in the pack set.
+    pub fn tags(&self) -> &Bitmap {
+        &self.tags
+    }
+
+    /// Iterate the positions in the pack index of all commits which have a bitmap, in the order they are stored.
+    pub fn index_positions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.entries.iter().map(|e| e.index_position)
+    }
+
+    /// Return the bitmap of all objects reachable from the commit at `index_position` in the pack index, or `None`
+    /// if there is no bitmap for it.
+    pub fn bitmap_at_index_position(&self, index_position: u32) -> Result<Option<Bitmap>, ewah::decode::Error> {
+        let mut entry_index = match self.entry_by_index_position.get(&index_position) {
+            Some(idx) => *idx,
+            None => return Ok(None),
+        };
+        let mut bitmap = Bitmap::default();
+        loop {
+            let entry = &self.entries[entry_index];
+            bitmap.xor(&ewah::decode(&self.data[entry.bitmap.clone()])?.0);
+            match entry.xor_offset {
+                0 => break,
+                xor_offset => entry_index -= xor_offset as usize,
+            }
+        }
+        Ok(Some(bitmap))
+    }
+
+    /// Return the hash of the path at which the object at `index_position` in the pack index was found when the
+    /// pack was created, or `None` if there is no name-hash cache or if `index_position` is out of bounds.
+    ///
+    /// A hash of 0 indicates that no path was known.
+    pub fn name_hash_at_index_position(&self, index_position: u32) -> Option<u32> {
+        let start = self.name_hash_cache_ofs? + index_position as usize * 4;
+        self.data
+            .get(start..start + 4)
+            .filter(|_| start + 4 <= self.data.len() - self.hash_kind.len_in_bytes())
+            .map(BigEndian::read_u32)
+    }
+
+    /// Count the objects set in `bitmap` by their kind.
+    pub fn counts(&self, bitmap: &Bitmap) -> Counts {
+        let count = |kind: &Bitmap| {
+            bitmap
+                .words()
+                .iter()
+                .zip(kind.words())
+                .map(|(lhs, rhs)| (lhs & rhs).count_ones() as usize)
+                .sum()
+        };
+        Counts {
+            commits: count(&self.commits),
+            trees: count(&self.trees),
+            blobs: count(&self.blobs),
+            tags: count(&self.tags),
+        }
+    }
+}
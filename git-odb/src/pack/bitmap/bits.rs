@@ -0,0 +1,111 @@
+/// An uncompressed set of bits, with each bit identifying an object by its position in a pack.
+///
+/// Trailing words without any bit set are never stored, which makes bitmaps with the same bits set equal.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Default)]
+pub struct Bitmap {
+    words: Vec<u64>,
+}
+
+/// Access and modification
+impl Bitmap {
+    /// Create a bitmap from `words`, with the first bit being the least significant bit of the first word.
+    pub fn from_words(words: Vec<u64>) -> Self {
+        let mut bitmap = Bitmap { words };
+        bitmap.trim();
+        bitmap
+    }
+
+    /// The words making up this bitmap, with the first bit being the least significant bit of the first word.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Return true if the bit at `position` is set.
+    pub fn contains(&self, position: u32) -> bool {
+        let (word, bit) = split(position);
+        self.words.get(word).map_or(false, |w| w & bit != 0)
+    }
+
+    /// Set the bit at `position`, returning true if it wasn't set before.
+    pub fn insert(&mut self, position: u32) -> bool {
+        let (word, bit) = split(position);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let was_set = self.words[word] & bit != 0;
+        self.words[word] |= bit;
+        !was_set
+    }
+
+    /// Set all bits which are set in `other`.
+    pub fn or(&mut self, other: &Bitmap) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (lhs, rhs) in self.words.iter_mut().zip(other.words.iter()) {
+            *lhs |= rhs;
+        }
+    }
+
+    /// Unset all bits which aren't set in `other`.
+    pub fn and(&mut self, other: &Bitmap) {
+        self.words.truncate(other.words.len());
+        for (lhs, rhs) in self.words.iter_mut().zip(other.words.iter()) {
+            *lhs &= rhs;
+        }
+        self.trim();
+    }
+
+    /// Unset all bits which are set in `other`.
+    pub fn and_not(&mut self, other: &Bitmap) {
+        for (lhs, rhs) in self.words.iter_mut().zip(other.words.iter()) {
+            *lhs &= !rhs;
+        }
+        self.trim();
+    }
+
+    /// Toggle all bits which are set in `other`.
+    pub fn xor(&mut self, other: &Bitmap) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (lhs, rhs) in self.words.iter_mut().zip(other.words.iter()) {
+            *lhs ^= rhs;
+        }
+        self.trim();
+    }
+
+    /// The amount of bits set.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns true if no bit is set.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Iterate the positions of all set bits in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.words.iter().enumerate().flat_map(|(word_index, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros();
+                word &= word - 1;
+                Some(word_index as u32 * 64 + bit)
+            })
+        })
+    }
+
+    fn trim(&mut self) {
+        let len = self.words.iter().rposition(|w| *w != 0).map_or(0, |pos| pos + 1);
+        self.words.truncate(len);
+    }
+}
+
+fn split(position: u32) -> (usize, u64) {
+    ((position / 64) as usize, 1 << (position % 64))
+}
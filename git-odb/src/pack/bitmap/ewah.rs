@@ -0,0 +1,135 @@
+//! The EWAH compression format used for all bitmaps in bitmap files.
+//!
+//! A compressed bitmap is stored as the amount of bits it represents (`u32`), the amount of 64 bit words that
+//! follow (`u32`), the words themselves and the position of the last run-length word among them (`u32`), all big-endian.
+//! Each run-length word is followed by the amount of literal words it declares and describes a run of words with all
+//! bits set or unset in its lower 33 bits, and the amount of literal words in its upper 31 bits.
+use crate::pack::bitmap::Bitmap;
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+use std::io;
+
+const RUNNING_LEN_BITS: u32 = 32;
+const MAX_RUNNING_LEN: u64 = (1 << RUNNING_LEN_BITS) - 1;
+const MAX_LITERAL_WORDS: u64 = (1 << (63 - RUNNING_LEN_BITS)) - 1;
+
+///
+pub mod decode {
+    /// Returned by [`ewah::decode()`][super::decode()].
+    #[derive(thiserror::Error, Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("An EWAH bitmap needs at least {needed} bytes, but only {actual} were available")]
+        Truncated { needed: usize, actual: usize },
+        #[error("The EWAH bitmap declares {declared} bits, but its words would set bit {position}")]
+        BitOutOfBounds { declared: u32, position: u64 },
+        #[error("The run-length word at word {index} declares more literal words than there are")]
+        LiteralWordsOutOfBounds { index: usize },
+    }
+}
+
+/// Return the amount of bytes taken by the EWAH compressed bitmap at the beginning of `data`, without decoding it.
+pub(crate) fn encoded_len(data: &[u8]) -> Result<usize, decode::Error> {
+    if data.len() < 8 {
+        return Err(decode::Error::Truncated {
+            needed: 8,
+            actual: data.len(),
+        });
+    }
+    let num_words = BigEndian::read_u32(&data[4..]) as usize;
+    let len = 4 + 4 + num_words * 8 + 4;
+    if data.len() < len {
+        return Err(decode::Error::Truncated {
+            needed: len,
+            actual: data.len(),
+        });
+    }
+    Ok(len)
+}
+
+/// Decode the EWAH compressed bitmap at the beginning of `data`, returning it along with the bytes following it.
+pub fn decode(data: &[u8]) -> Result<(Bitmap, &[u8]), decode::Error> {
+    let len = encoded_len(data)?;
+    let bit_size = BigEndian::read_u32(data);
+    let compressed = &data[8..len - 4];
+    let num_words = compressed.len() / 8;
+    let word_at = |index: usize| BigEndian::read_u64(&compressed[index * 8..]);
+
+    let mut words = Vec::with_capacity(((bit_size as usize) + 63) / 64);
+    let mut index = 0;
+    while index < num_words {
+        let rlw = word_at(index);
+        let running_bit = rlw & 1 == 1;
+        let running_len = ((rlw >> 1) & MAX_RUNNING_LEN) as usize;
+        let num_literals = (rlw >> (1 + RUNNING_LEN_BITS)) as usize;
+        if index + 1 + num_literals > num_words {
+            return Err(decode::Error::LiteralWordsOutOfBounds { index });
+        }
+        if (words.len() + running_len + num_literals) as u64 * 64 > u64::from(bit_size) + 63 {
+            return Err(decode::Error::BitOutOfBounds {
+                declared: bit_size,
+                position: (words.len() + running_len + num_literals) as u64 * 64 - 1,
+            });
+        }
+        words.resize(words.len() + running_len, if running_bit { u64::MAX } else { 0 });
+        words.extend((index + 1..index + 1 + num_literals).map(word_at));
+        index += 1 + num_literals;
+    }
+    let bits_in_last_word = bit_size % 64;
+    if bits_in_last_word != 0 && words.len() == ((bit_size as usize) + 63) / 64 {
+        if let Some(last) = words.last_mut() {
+            *last &= (1 << bits_in_last_word) - 1;
+        }
+    }
+    Ok((Bitmap::from_words(words), &data[len..]))
+}
+
+/// Write `bitmap` EWAH compressed to `out`.
+pub fn encode(bitmap: &Bitmap, mut out: impl io::Write) -> io::Result<()> {
+    let (words, last_rlw_position) = compress(bitmap.words());
+    let bit_size = bitmap
+        .words()
+        .last()
+        .map_or(0, |last| bitmap.words().len() as u32 * 64 - last.leading_zeros());
+    out.write_u32::<BigEndian>(bit_size)?;
+    out.write_u32::<BigEndian>(words.len() as u32)?;
+    for word in words {
+        out.write_u64::<BigEndian>(word)?;
+    }
+    out.write_u32::<BigEndian>(last_rlw_position)
+}
+
+/// Return the amount of bytes `bitmap` takes when EWAH compressed.
+pub(crate) fn compressed_len(bitmap: &Bitmap) -> usize {
+    4 + 4 + compress(bitmap.words()).0.len() * 8 + 4
+}
+
+/// Compress `words` into run-length words each followed by their literal words, returning them along with the position
+/// of the last run-length word.
+fn compress(words: &[u64]) -> (Vec<u64>, u32) {
+    let is_clean = |word: u64| word == 0 || word == u64::MAX;
+    let mut out = Vec::new();
+    let mut last_rlw_position = 0;
+    let mut index = 0;
+    while index < words.len() || out.is_empty() {
+        let mut running_len = 0;
+        let running_word = words.get(index).copied().filter(|word| is_clean(*word));
+        if let Some(running_word) = running_word {
+            while index < words.len() && words[index] == running_word && running_len < MAX_RUNNING_LEN {
+                running_len += 1;
+                index += 1;
+            }
+        }
+        let literals_start = index;
+        while index < words.len() && !is_clean(words[index]) && ((index - literals_start) as u64) < MAX_LITERAL_WORDS {
+            index += 1;
+        }
+        last_rlw_position = out.len() as u32;
+        out.push(
+            (running_word == Some(u64::MAX)) as u64
+                | running_len << 1
+                | ((index - literals_start) as u64) << (1 + RUNNING_LEN_BITS),
+        );
+        out.extend_from_slice(&words[literals_start..index]);
+    }
+    (out, last_rlw_position)
+}
@@ -0,0 +1,163 @@
+use crate::pack::bitmap::{
+    self, ewah, flags, Entry, Version, HEADER_LEN_WITHOUT_PACK_CHECKSUM, MAX_XOR_OFFSET, SIGNATURE,
+};
+use byteorder::{BigEndian, ByteOrder};
+use filebuffer::FileBuffer;
+use std::{collections::HashMap, convert::TryFrom, path::Path};
+
+/// Returned by [`bitmap::File::at()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open pack bitmap file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: String },
+    #[error("Unsupported bitmap file version: {version}")]
+    UnsupportedVersion { version: u16 },
+    #[error("A bitmap could not be decoded")]
+    Ewah(#[from] ewah::decode::Error),
+}
+
+/// The size of a lookup table row, consisting of the commit's index position, the offset of its bitmap
+/// entry and the row of the bitmap to xor with.
+const LOOKUP_TABLE_ROW_LEN: usize = 4 + 8 + 4;
+
+/// Instantiation
+impl bitmap::File {
+    /// Open the bitmap file at the given `path`, usually next to the pack it belongs to with the `.bitmap` extension.
+    pub fn at(path: impl AsRef<Path>) -> Result<bitmap::File, Error> {
+        Self::try_from(path.as_ref())
+    }
+
+    /// Open the bitmap file at the given `path` whose checksums are hashes of `hash_kind`, as used by repositories with the
+    /// respective object format.
+    ///
+    /// The hash kind can't be determined from the bitmap file itself, but from the [index][crate::pack::index::File::hash_kind()]
+    /// of its pack.
+    pub fn at_with_hash_kind(path: impl AsRef<Path>, hash_kind: git_hash::Kind) -> Result<bitmap::File, Error> {
+        Self::at_inner(path.as_ref(), hash_kind)
+    }
+}
+
+impl TryFrom<&Path> for bitmap::File {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        Self::at_inner(path, git_hash::Kind::Sha1)
+    }
+}
+
+impl bitmap::File {
+    fn at_inner(path: &Path, hash_kind: git_hash::Kind) -> Result<bitmap::File, Error> {
+        let hash_len = hash_kind.len_in_bytes();
+        let header_len = HEADER_LEN_WITHOUT_PACK_CHECKSUM + hash_len;
+        let data = FileBuffer::open(path).map_err(|e| Error::Io {
+            source: e,
+            path: path.to_owned(),
+        })?;
+        let corrupt = |message: String| Error::Corrupt { message };
+        if data.len() < header_len + hash_len {
+            return Err(corrupt(format!(
+                "Pack bitmap of size {} is too small for even an empty bitmap file",
+                data.len()
+            )));
+        }
+        let (sig, header) = data[..header_len].split_at(SIGNATURE.len());
+        if sig != SIGNATURE {
+            return Err(corrupt("Pack bitmap signature is invalid".into()));
+        }
+        let version = match BigEndian::read_u16(header) {
+            1 => Version::V1,
+            version => return Err(Error::UnsupportedVersion { version }),
+        };
+        let options = BigEndian::read_u16(&header[2..]);
+        if options & flags::FULL_DAG == 0 {
+            return Err(corrupt(
+                "Pack bitmaps which don't cover all objects reachable from their commits are unsupported".into(),
+            ));
+        }
+        let num_entries = BigEndian::read_u32(&header[4..]) as usize;
+
+        let data_end = data.len() - hash_len;
+        let body = &data[header_len..data_end];
+        let (commits, body) = ewah::decode(body)?;
+        let (trees, body) = ewah::decode(body)?;
+        let (blobs, body) = ewah::decode(body)?;
+        let (tags, mut body) = ewah::decode(body)?;
+
+        let mut entries = Vec::with_capacity(num_entries);
+        let mut entry_by_index_position = HashMap::with_capacity(num_entries);
+        for entry_index in 0..num_entries {
+            if body.len() < 6 {
+                return Err(corrupt(format!(
+                    "Pack bitmap is truncated after {} of {} entries",
+                    entry_index, num_entries
+                )));
+            }
+            let index_position = BigEndian::read_u32(body);
+            let xor_offset = body[4];
+            if xor_offset > MAX_XOR_OFFSET || xor_offset as usize > entry_index {
+                return Err(corrupt(format!(
+                    "The bitmap entry {} refers to a bitmap {} entries before it, which isn't possible",
+                    entry_index, xor_offset
+                )));
+            }
+            let start = data_end - body.len() + 6;
+            let len = ewah::encoded_len(&body[6..])?;
+            if entry_by_index_position.insert(index_position, entries.len()).is_some() {
+                return Err(corrupt(format!(
+                    "There is more than one bitmap for the commit at index position {}",
+                    index_position
+                )));
+            }
+            entries.push(Entry {
+                index_position,
+                xor_offset,
+                bitmap: start..start + len,
+            });
+            body = &body[6 + len..];
+        }
+
+        if options & flags::LOOKUP_TABLE != 0 {
+            let table_len = num_entries * LOOKUP_TABLE_ROW_LEN;
+            if body.len() < table_len {
+                return Err(corrupt("Pack bitmap is too small to hold its lookup table".into()));
+            }
+            body = &body[table_len..];
+        }
+        let name_hash_cache_ofs = if options & flags::HASH_CACHE != 0 {
+            if body.len() % 4 != 0 {
+                return Err(corrupt(format!(
+                    "The name-hash cache of {} bytes isn't a multiple of 4",
+                    body.len()
+                )));
+            }
+            Some(data_end - body.len())
+        } else if !body.is_empty() {
+            return Err(corrupt(format!(
+                "There are {} unexpected bytes after the last bitmap entry",
+                body.len()
+            )));
+        } else {
+            None
+        };
+
+        Ok(bitmap::File {
+            data,
+            path: path.to_owned(),
+            version,
+            commits,
+            trees,
+            blobs,
+            tags,
+            entries,
+            entry_by_index_position,
+            name_hash_cache_ofs,
+            hash_kind,
+        })
+    }
+}
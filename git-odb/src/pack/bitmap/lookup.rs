@@ -0,0 +1,125 @@
+use crate::pack::{
+    self,
+    bitmap::{self, ewah, traverse, Bitmap},
+};
+use git_hash::{oid, ObjectId};
+use git_object::immutable;
+
+/// Returned by [`Lookup::new()`] and [`Lookup::reachable_from()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The bitmap file belongs to pack {bitmap_pack_checksum}, not to pack {pack_checksum}")]
+    PackMismatch {
+        bitmap_pack_checksum: ObjectId,
+        pack_checksum: ObjectId,
+    },
+    #[error("The reverse index of the pack could not be used")]
+    ReverseIndex(#[from] pack::reverse_index::init::Error),
+    #[error("A bitmap could not be decoded")]
+    Ewah(#[from] ewah::decode::Error),
+    #[error("Object {id} is reachable but isn't contained in the pack")]
+    NotInPack { id: ObjectId },
+    #[error("An object could not be decoded from the pack")]
+    DecodeEntry(#[from] pack::data::decode_entry::Error),
+    #[error("An object could not be parsed")]
+    ObjectDecode(#[from] immutable::object::decode::Error),
+}
+
+/// Associates a [bitmap file][bitmap::File] with its pack to answer which objects are reachable from a commit.
+pub struct Lookup<'a> {
+    bitmaps: &'a bitmap::File,
+    bundle: &'a pack::Bundle,
+    positions: traverse::Positions,
+}
+
+/// Instantiation
+impl<'a> Lookup<'a> {
+    /// Use `bitmaps` to find objects reachable from commits in the pack of `bundle`, which must be the pack the bitmaps
+    /// were created for.
+    pub fn new(bitmaps: &'a bitmap::File, bundle: &'a pack::Bundle) -> Result<Self, Error> {
+        let pack_checksum = bundle.index.pack_checksum();
+        if bitmaps.pack_checksum() != pack_checksum {
+            return Err(Error::PackMismatch {
+                bitmap_pack_checksum: bitmaps.pack_checksum(),
+                pack_checksum,
+            });
+        }
+        Ok(Lookup {
+            bitmaps,
+            bundle,
+            positions: traverse::Positions::from_index(&bundle.index)?,
+        })
+    }
+}
+
+/// Access
+impl<'a> Lookup<'a> {
+    /// The bitmap file used for lookups.
+    pub fn bitmaps(&self) -> &'a bitmap::File {
+        self.bitmaps
+    }
+
+    pub(crate) fn positions(&self) -> &traverse::Positions {
+        &self.positions
+    }
+
+    /// Return the position of the object with `id` in the pack, which is its position in a bitmap, or `None` if it
+    /// isn't contained in the pack.
+    pub fn position(&self, id: impl AsRef<oid>) -> Option<u32> {
+        self.bundle
+            .index
+            .lookup(id)
+            .map(|index_position| self.positions.index_to_pack[index_position as usize])
+    }
+
+    /// Return the id of the object at `position` in the pack.
+    ///
+    /// # Panics
+    ///
+    /// If `position` is out of bounds.
+    pub fn id_at_position(&self, position: u32) -> &'a oid {
+        self.bundle
+            .index
+            .oid_at_index(self.positions.pack_to_index[position as usize])
+    }
+
+    /// Iterate the ids of all objects set in `bitmap`, in pack order.
+    pub fn ids<'b>(&'b self, bitmap: &'b Bitmap) -> impl Iterator<Item = &'a oid> + 'b {
+        bitmap.iter().map(move |position| self.id_at_position(position))
+    }
+
+    /// Count the objects set in `bitmap` by their kind.
+    pub fn counts(&self, bitmap: &Bitmap) -> bitmap::Counts {
+        self.bitmaps.counts(bitmap)
+    }
+
+    /// Return the bitmap stored for the commit with `id`, or `None` if there is none.
+    pub fn bitmap_for_commit(&self, id: impl AsRef<oid>) -> Result<Option<Bitmap>, ewah::decode::Error> {
+        match self.bundle.index.lookup(id) {
+            Some(index_position) => self.bitmaps.bitmap_at_index_position(index_position),
+            None => Ok(None),
+        }
+    }
+
+    /// Return a bitmap with all objects reachable from `tips` set, which may be commits, tags, trees or blobs.
+    ///
+    /// Stored bitmaps are used for all commits which have one, and only commits without one are traversed, along with their
+    /// trees. Use [`Bitmap::and_not()`] with the bitmap of objects reachable from another set of tips to obtain only the objects
+    /// the other side doesn't have, and [`counts()`][Lookup::counts()] to count them.
+    ///
+    /// `cache` is used to accelerate decoding objects of the pack.
+    pub fn reachable_from(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Bitmap, Error> {
+        traverse::reachable_from(
+            self.bundle,
+            &self.positions,
+            tips,
+            |index_position| Ok(self.bitmaps.bitmap_at_index_position(index_position)?),
+            cache,
+        )
+    }
+}
@@ -0,0 +1,107 @@
+//! Reachability bitmaps stored alongside a pack in a `.bitmap` file, to quickly obtain all objects reachable from a commit.
+//!
+//! Each bit corresponds to an object in the pack, in the order in which objects appear in the pack, that is
+//! sorted by their pack offset.
+use filebuffer::FileBuffer;
+use std::{collections::HashMap, ops::Range, path::PathBuf};
+
+/// The version of a bitmap file
+#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    V1 = 1,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V1
+    }
+}
+
+/// A bitmap entry as stored in the file, which may have to be combined with another entry to obtain the actual bitmap.
+struct Entry {
+    /// The position of the commit this bitmap belongs to in the pack index.
+    index_position: u32,
+    /// The amount of entries to go back to find the bitmap to xor the bitmap of this entry with, or 0.
+    xor_offset: u8,
+    /// The range of the EWAH bitmap in the file's data.
+    bitmap: Range<usize>,
+}
+
+/// A representation of a pack bitmap file
+pub struct File {
+    data: FileBuffer,
+    path: PathBuf,
+    version: Version,
+    commits: Bitmap,
+    trees: Bitmap,
+    blobs: Bitmap,
+    tags: Bitmap,
+    entries: Vec<Entry>,
+    entry_by_index_position: HashMap<u32, usize>,
+    name_hash_cache_ofs: Option<usize>,
+    hash_kind: git_hash::Kind,
+}
+
+/// Basic file information
+impl File {
+    /// The version of the bitmap file
+    pub fn version(&self) -> Version {
+        self.version
+    }
+    /// The path of the opened bitmap file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+    /// The amount of commits for which a bitmap is stored
+    pub fn num_bitmaps(&self) -> u32 {
+        self.entries.len() as u32
+    }
+    /// Returns true if the file contains a hash of the path each object was found at, as computed when creating the pack.
+    pub fn has_name_hash_cache(&self) -> bool {
+        self.name_hash_cache_ofs.is_some()
+    }
+    /// The kind of hash used for the checksum of the pack this file belongs to and for our own checksum.
+    pub fn hash_kind(&self) -> git_hash::Kind {
+        self.hash_kind
+    }
+}
+
+const SIGNATURE: &[u8] = b"BITM";
+/// The length of the header without the pack checksum, whose length depends on the kind of hash.
+const HEADER_LEN_WITHOUT_PACK_CHECKSUM: usize = 4 + 2 + 2 + 4;
+/// The maximum amount of entries to look back for the bitmap to xor with.
+const MAX_XOR_OFFSET: u8 = 160;
+
+mod flags {
+    /// All objects reachable from commits with bitmaps are in the pack.
+    pub const FULL_DAG: u16 = 0x1;
+    /// A name-hash for each object is stored after all bitmap entries.
+    pub const HASH_CACHE: u16 = 0x4;
+    /// A table to find bitmaps without reading all entries is stored after all bitmap entries.
+    pub const LOOKUP_TABLE: u16 = 0x10;
+}
+
+mod bits;
+pub use bits::Bitmap;
+
+///
+pub mod ewah;
+
+///
+pub mod init;
+
+mod access;
+pub use access::Counts;
+
+///
+pub mod lookup;
+pub use lookup::Lookup;
+
+mod traverse;
+
+///
+pub mod verify;
+///
+pub mod write;
@@ -0,0 +1,154 @@
+use crate::pack::{
+    self,
+    bitmap::{lookup::Error, Bitmap},
+};
+use git_hash::ObjectId;
+use git_object::immutable::{self, commit::iter::Token};
+
+/// Maps positions of objects in the pack index to their positions in the pack, which are their bit positions in bitmaps.
+pub(crate) struct Positions {
+    /// The position in the pack for each position in the index.
+    pub index_to_pack: Vec<u32>,
+    /// The position in the index for each position in the pack.
+    pub pack_to_index: Vec<u32>,
+}
+
+impl Positions {
+    pub fn from_index(index: &pack::index::File) -> Result<Self, pack::reverse_index::init::Error> {
+        let pack_to_index: Vec<u32> = match pack::reverse_index::File::for_index(index)? {
+            Some(rev) => rev.index_positions().collect(),
+            None => {
+                let mut pack_to_index: Vec<u32> = (0..index.num_objects()).collect();
+                pack_to_index.sort_by_key(|index_position| index.pack_offset_at_index(*index_position));
+                pack_to_index
+            }
+        };
+        let mut index_to_pack = vec![0; pack_to_index.len()];
+        for (pack_position, index_position) in pack_to_index.iter().enumerate() {
+            index_to_pack[*index_position as usize] = pack_position as u32;
+        }
+        Ok(Positions {
+            index_to_pack,
+            pack_to_index,
+        })
+    }
+}
+
+/// The position of the bitmap for objects of `kind` in the file.
+pub(crate) fn kind_index(kind: git_object::Kind) -> usize {
+    match kind {
+        git_object::Kind::Commit => 0,
+        git_object::Kind::Tree => 1,
+        git_object::Kind::Blob => 2,
+        git_object::Kind::Tag => 3,
+    }
+}
+
+/// Return the kind of the object at `index_position` in the pack of `bundle` without decoding it, following delta chains
+/// to their base object as needed.
+pub(crate) fn kind_at_index_position(bundle: &pack::Bundle, index_position: u32) -> Result<git_object::Kind, Error> {
+    use pack::data::entry::Header;
+    let mut pack_offset = bundle.index.pack_offset_at_index(index_position);
+    loop {
+        let entry = bundle.pack.entry(pack_offset);
+        match entry.header {
+            Header::OfsDelta { base_distance } => {
+                pack_offset = Header::verified_base_pack_offset(pack_offset, base_distance).ok_or(
+                    pack::data::decode_entry::Error::DeltaBaseUnresolved(
+                        bundle.index.oid_at_index(index_position).to_owned(),
+                    ),
+                )?;
+            }
+            Header::RefDelta { base_id } => {
+                let base_index_position = bundle.index.lookup(base_id).ok_or(Error::NotInPack { id: base_id })?;
+                pack_offset = bundle.index.pack_offset_at_index(base_index_position);
+            }
+            header => return Ok(header.to_kind().expect("base objects have a kind")),
+        }
+    }
+}
+
+/// Return a bitmap with all objects reachable from `tips` in the pack of `bundle` set, using `bitmap_at_index_position()`
+/// to obtain the bitmap of the commit at the given index position if there is one, to avoid traversing it.
+pub(crate) fn reachable_from(
+    bundle: &pack::Bundle,
+    positions: &Positions,
+    tips: impl IntoIterator<Item = ObjectId>,
+    mut bitmap_at_index_position: impl FnMut(u32) -> Result<Option<Bitmap>, Error>,
+    cache: &mut impl pack::cache::DecodeEntry,
+) -> Result<Bitmap, Error> {
+    let mut reachable = Bitmap::default();
+    let mut buf = Vec::new();
+    let mut objects: Vec<_> = tips.into_iter().collect();
+    let mut trees = Vec::new();
+    while let Some(id) = objects.pop() {
+        let index_position = bundle.index.lookup(id).ok_or(Error::NotInPack { id })?;
+        let position = positions.index_to_pack[index_position as usize];
+        if reachable.contains(position) {
+            continue;
+        }
+        if let Some(bitmap) = bitmap_at_index_position(index_position)? {
+            reachable.or(&bitmap);
+            continue;
+        }
+        let obj = bundle.internal_get_object_by_index(index_position, &mut buf, cache)?;
+        match obj.kind {
+            git_object::Kind::Tree => {
+                trees.push(id);
+            }
+            git_object::Kind::Blob => {
+                reachable.insert(position);
+            }
+            git_object::Kind::Tag => {
+                reachable.insert(position);
+                objects.push(immutable::Tag::from_bytes(obj.data)?.target());
+            }
+            git_object::Kind::Commit => {
+                reachable.insert(position);
+                for token in immutable::CommitIter::from_bytes(obj.data) {
+                    match token? {
+                        Token::Tree { id } => trees.push(id),
+                        Token::Parent { id } => objects.push(id),
+                        _ => break,
+                    }
+                }
+            }
+        }
+    }
+    insert_trees(bundle, positions, &mut trees, &mut reachable, &mut buf, cache)?;
+    Ok(reachable)
+}
+
+/// Set all `trees` and the objects they contain in `reachable`, skipping trees which are already set.
+fn insert_trees(
+    bundle: &pack::Bundle,
+    positions: &Positions,
+    trees: &mut Vec<ObjectId>,
+    reachable: &mut Bitmap,
+    buf: &mut Vec<u8>,
+    cache: &mut impl pack::cache::DecodeEntry,
+) -> Result<(), Error> {
+    while let Some(id) = trees.pop() {
+        let index_position = bundle.index.lookup(id).ok_or(Error::NotInPack { id })?;
+        if !reachable.insert(positions.index_to_pack[index_position as usize]) {
+            continue;
+        }
+        let obj = bundle.internal_get_object_by_index(index_position, buf, cache)?;
+        for entry in immutable::TreeIter::from_bytes_with_hash_kind(obj.data, obj.hash_kind) {
+            let entry = entry?;
+            match entry.mode {
+                git_object::tree::EntryMode::Tree => trees.push(entry.oid.to_owned()),
+                git_object::tree::EntryMode::Commit => {}
+                git_object::tree::EntryMode::Blob
+                | git_object::tree::EntryMode::BlobExecutable
+                | git_object::tree::EntryMode::Link => {
+                    let index_position = bundle.index.lookup(entry.oid).ok_or(Error::NotInPack {
+                        id: entry.oid.to_owned(),
+                    })?;
+                    reachable.insert(positions.index_to_pack[index_position as usize]);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use crate::pack::{
+    self,
+    bitmap::{self, ewah, lookup, traverse},
+};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use std::collections::HashMap;
+
+/// Returned by [`bitmap::File::verify_checksum()`] and [`bitmap::File::verify_integrity()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("pack bitmap checksum mismatch: expected {expected}, got {actual}")]
+    Mismatch {
+        expected: git_hash::ObjectId,
+        actual: git_hash::ObjectId,
+    },
+    #[error("Could not traverse the objects in the pack")]
+    Traverse(#[from] lookup::Error),
+    #[error("A bitmap could not be decoded")]
+    Ewah(#[from] ewah::decode::Error),
+    #[error("The bitmap of all objects of kind {kind} doesn't match the kinds of objects in the pack")]
+    KindMismatch { kind: git_object::Kind },
+    #[error("There is a bitmap for index position {index_position}, but the pack only has {num_objects} objects")]
+    IndexPositionOutOfBounds { index_position: u32, num_objects: u32 },
+    #[error("There is a bitmap for object {id}, which is a {kind}, not a commit")]
+    NotACommit {
+        id: git_hash::ObjectId,
+        kind: git_object::Kind,
+    },
+    #[error("The bitmap of commit {id} doesn't match the objects reachable from it")]
+    BitmapMismatch { id: git_hash::ObjectId },
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Verify and validate the content of the bitmap file
+impl bitmap::File {
+    /// Validate that our [`checksum()`][bitmap::File::checksum()] matches the actual contents
+    /// of this file, and return it if it does.
+    pub fn verify_checksum(&self, mut progress: impl Progress) -> Result<git_hash::ObjectId, Error> {
+        let data_len_without_trailer = self.data.len() - self.hash_kind.len_in_bytes();
+        let actual = match git_features::hash::bytes_of_file(
+            &self.path,
+            data_len_without_trailer,
+            self.hash_kind,
+            &mut progress,
+        ) {
+            Ok(id) => id,
+            Err(_io_err) => {
+                let mut hasher = git_features::hash::hasher(self.hash_kind);
+                hasher.update(&self.data[..data_len_without_trailer]);
+                hasher.digest()
+            }
+        };
+
+        let expected = self.checksum();
+        if actual == expected {
+            Ok(actual)
+        } else {
+            Err(Error::Mismatch { actual, expected })
+        }
+    }
+
+    /// Validate our checksum, that this file belongs to the pack in `bundle`, that the bitmaps of objects by kind match the
+    /// pack and that each stored bitmap contains exactly the objects reachable from its commit, as determined by a full
+    /// traversal of the commit graph and trees which only uses bitmaps that were verified already.
+    /// Returns our checksum if the integrity check is successful.
+    ///
+    /// `cache` is used to accelerate decoding objects of the pack.
+    pub fn verify_integrity(
+        &self,
+        bundle: &pack::Bundle,
+        cache: &mut impl pack::cache::DecodeEntry,
+        mut progress: impl Progress,
+    ) -> Result<git_hash::ObjectId, Error> {
+        progress.init(Some(3), progress::steps());
+        let checksum = self.verify_checksum(progress.add_child("checksum of pack bitmap"))?;
+        progress.inc();
+
+        let lookup = bitmap::Lookup::new(self, bundle)?;
+        let num_objects = bundle.index.num_objects();
+        {
+            let mut kinds_progress = progress.add_child("checking object kinds");
+            kinds_progress.init(Some(num_objects as usize), progress::count("objects"));
+            let kinds = [self.commits(), self.trees(), self.blobs(), self.tags()];
+            for index_position in 0..num_objects {
+                if is_triggered() {
+                    return Err(Error::Interrupted);
+                }
+                let kind = traverse::kind_at_index_position(bundle, index_position)?;
+                let position = lookup.positions().index_to_pack[index_position as usize];
+                for (kind_index, kind_bitmap) in kinds.iter().enumerate() {
+                    if kind_bitmap.contains(position) != (kind_index == traverse::kind_index(kind)) {
+                        return Err(Error::KindMismatch { kind });
+                    }
+                }
+                kinds_progress.inc();
+            }
+            for (kind, kind_bitmap) in [
+                git_object::Kind::Commit,
+                git_object::Kind::Tree,
+                git_object::Kind::Blob,
+                git_object::Kind::Tag,
+            ]
+            .iter()
+            .zip(kinds.iter())
+            {
+                if kind_bitmap.iter().any(|position| position >= num_objects) {
+                    return Err(Error::KindMismatch { kind: *kind });
+                }
+            }
+        }
+        progress.inc();
+
+        let mut bitmaps_progress = progress.add_child("checking bitmaps against traversal");
+        bitmaps_progress.init(Some(self.num_bitmaps() as usize), progress::count("bitmaps"));
+        // Verify bitmaps of ancestors first to use them when traversing their descendants, knowing that the bitmap of an
+        // ancestor has less bits set than the ones of its descendants.
+        let mut bitmaps = self
+            .index_positions()
+            .map(|index_position| {
+                self.bitmap_at_index_position(index_position)
+                    .map(|bitmap| (index_position, bitmap.expect("bitmap present for listed commit")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        bitmaps.sort_by_key(|(_, bitmap)| bitmap.count_ones());
+        let mut verified = HashMap::with_capacity(bitmaps.len());
+        for (index_position, actual) in bitmaps {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            if index_position >= num_objects {
+                return Err(Error::IndexPositionOutOfBounds {
+                    index_position,
+                    num_objects,
+                });
+            }
+            let id = bundle.index.oid_at_index(index_position).to_owned();
+            let kind = traverse::kind_at_index_position(bundle, index_position)?;
+            if kind != git_object::Kind::Commit {
+                return Err(Error::NotACommit { id, kind });
+            }
+            let expected = traverse::reachable_from(
+                bundle,
+                lookup.positions(),
+                Some(id),
+                |index_position| Ok(verified.get(&index_position).cloned()),
+                cache,
+            )?;
+            if actual != expected {
+                return Err(Error::BitmapMismatch { id });
+            }
+            verified.insert(index_position, actual);
+            bitmaps_progress.inc();
+        }
+        progress.inc();
+        Ok(checksum)
+    }
+}
@@ -0,0 +1,413 @@
+use crate::{
+    hash, pack,
+    pack::bitmap::{self, ewah, flags, lookup, traverse, Bitmap, Version, MAX_XOR_OFFSET, SIGNATURE},
+    pack::data::output::objects_to_entries::expand::name_hash,
+};
+use byteorder::{BigEndian, WriteBytesExt};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use git_hash::ObjectId;
+use git_object::{
+    bstr::{BString, ByteVec},
+    immutable::{self, commit::iter::Token},
+};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+};
+
+/// Returned by [`bitmap::File::write_from_bundle()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Could not traverse the objects in the pack")]
+    Traverse(#[from] lookup::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Configuration for [`bitmap::File::write_from_bundle()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// If true, store a hash of the path at which each object was first seen, which helps to find good delta bases when
+    /// the bitmaps are used to generate packs.
+    pub name_hash_cache: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { name_hash_cache: true }
+    }
+}
+
+/// Returned by [`bitmap::File::write_from_bundle()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The version of the written bitmap file
+    pub version: Version,
+    /// The checksum of the written bitmap file, also found in its trailing bytes
+    pub checksum: ObjectId,
+    /// The amount of commits for which a bitmap was written
+    pub num_bitmaps: u32,
+}
+
+/// The amount of previously written bitmaps to try to xor a bitmap with to find the smallest representation.
+const MAX_XOR_OFFSET_SEARCH: usize = 10;
+
+struct Commit {
+    index_position: u32,
+    time: u32,
+    parents: Vec<u32>,
+}
+
+/// Various ways of writing a bitmap file
+impl bitmap::File {
+    /// Write a bitmap file for the pack in `bundle` into `out`, which must contain all objects reachable from `tips`, similar to
+    /// `git repack -b`.
+    ///
+    /// `tips` are typically the objects all references point to, and may be tags, which are peeled to find their commits.
+    /// Like git, bitmaps are written for all commits if there are less than 100, otherwise for the 100 most recent ones and
+    /// increasingly fewer of the older ones, preferring commits in `tips`.
+    ///
+    /// `cache` is used to accelerate decoding objects of the pack.
+    /// The written file is expected to be placed next to the pack, with the `.bitmap` extension.
+    pub fn write_from_bundle(
+        bundle: &pack::Bundle,
+        tips: impl IntoIterator<Item = ObjectId>,
+        cache: &mut impl pack::cache::DecodeEntry,
+        out: impl io::Write,
+        mut progress: impl Progress,
+        Options { name_hash_cache }: Options,
+    ) -> Result<Outcome, Error> {
+        let start = std::time::Instant::now();
+        let num_objects = bundle.index.num_objects();
+        let positions = traverse::Positions::from_index(&bundle.index).map_err(lookup::Error::from)?;
+        progress.init(Some(5), progress::steps());
+
+        let kinds = {
+            let mut kinds_progress = progress.add_child("classifying objects");
+            kinds_progress.init(Some(num_objects as usize), progress::count("objects"));
+            let mut kinds = [
+                Bitmap::default(),
+                Bitmap::default(),
+                Bitmap::default(),
+                Bitmap::default(),
+            ];
+            for index_position in 0..num_objects {
+                if is_triggered() {
+                    return Err(Error::Interrupted);
+                }
+                let kind = traverse::kind_at_index_position(bundle, index_position)?;
+                kinds[traverse::kind_index(kind)].insert(positions.index_to_pack[index_position as usize]);
+                kinds_progress.inc();
+            }
+            kinds
+        };
+        progress.inc();
+
+        let mut buf = Vec::new();
+        let (commits, tip_positions, tag_name_hashes) = {
+            let mut commits_progress = progress.add_child("collecting commits");
+            commits_progress.init(None, progress::count("commits"));
+            let mut commits = Vec::new();
+            let mut tip_positions = HashSet::new();
+            let mut tag_name_hashes = Vec::new();
+            let mut seen = HashSet::new();
+            let mut pending: Vec<_> = tips.into_iter().map(|id| (id, true)).collect();
+            while let Some((id, is_tip)) = pending.pop() {
+                if is_triggered() {
+                    return Err(Error::Interrupted);
+                }
+                let index_position = bundle.index.lookup(id).ok_or(lookup::Error::NotInPack { id })?;
+                if is_tip {
+                    tip_positions.insert(index_position);
+                }
+                if !seen.insert(index_position) {
+                    continue;
+                }
+                let obj = bundle
+                    .internal_get_object_by_index(index_position, &mut buf, cache)
+                    .map_err(lookup::Error::from)?;
+                match obj.kind {
+                    git_object::Kind::Tag => {
+                        let tag = immutable::Tag::from_bytes(obj.data).map_err(lookup::Error::from)?;
+                        tag_name_hashes.push((index_position, name_hash(tag.name)));
+                        pending.push((tag.target(), is_tip));
+                    }
+                    git_object::Kind::Commit => {
+                        let mut time = 0;
+                        let mut parents = Vec::new();
+                        for token in immutable::CommitIter::from_bytes(obj.data) {
+                            match token.map_err(lookup::Error::from)? {
+                                Token::Parent { id } => {
+                                    parents.push(bundle.index.lookup(id).ok_or(lookup::Error::NotInPack { id })?);
+                                    pending.push((id, false));
+                                }
+                                Token::Committer { signature } => {
+                                    time = signature.time.time;
+                                    break;
+                                }
+                                _ => {}
+                            }
+                        }
+                        commits.push(Commit {
+                            index_position,
+                            time,
+                            parents,
+                        });
+                        commits_progress.inc();
+                    }
+                    git_object::Kind::Tree | git_object::Kind::Blob => {}
+                }
+            }
+            // commits with the same time are kept in pack order, which is the order in which git traversed them
+            commits.sort_by_key(|c| (Reverse(c.time), positions.index_to_pack[c.index_position as usize]));
+            (commits, tip_positions, tag_name_hashes)
+        };
+        progress.inc();
+
+        let mut selected = select_commits(&commits, &tip_positions);
+        // compute bitmaps of parents first to use them for their children
+        let topological_order = topological_order(&commits);
+        selected.sort_by_key(|index_position| topological_order[index_position]);
+        let bitmaps = {
+            let mut bitmaps_progress = progress.add_child("computing bitmaps");
+            bitmaps_progress.init(Some(selected.len()), progress::count("commits"));
+            let mut bitmaps = Vec::<(u32, Bitmap)>::with_capacity(selected.len());
+            let mut bitmap_by_index_position = HashMap::<u32, usize>::with_capacity(selected.len());
+            for index_position in &selected {
+                if is_triggered() {
+                    return Err(Error::Interrupted);
+                }
+                let bitmap = traverse::reachable_from(
+                    bundle,
+                    &positions,
+                    Some(bundle.index.oid_at_index(*index_position).to_owned()),
+                    |index_position| {
+                        Ok(bitmap_by_index_position
+                            .get(&index_position)
+                            .map(|idx| bitmaps[*idx].1.clone()))
+                    },
+                    cache,
+                )?;
+                bitmap_by_index_position.insert(*index_position, bitmaps.len());
+                bitmaps.push((*index_position, bitmap));
+                bitmaps_progress.inc();
+            }
+            bitmaps
+        };
+        progress.inc();
+
+        let name_hashes = if name_hash_cache {
+            let _progress = progress.add_child("computing name hashes");
+            Some(name_hashes(bundle, &commits, tag_name_hashes, cache, &mut buf)?)
+        } else {
+            None
+        };
+        progress.inc();
+
+        let mut out = io::BufWriter::with_capacity(8 * 4096, hash::Write::new(out, bundle.index.hash_kind()));
+        out.write_all(SIGNATURE)?;
+        out.write_u16::<BigEndian>(Version::V1 as u16)?;
+        out.write_u16::<BigEndian>(if name_hashes.is_some() {
+            flags::FULL_DAG | flags::HASH_CACHE
+        } else {
+            flags::FULL_DAG
+        })?;
+        out.write_u32::<BigEndian>(bitmaps.len() as u32)?;
+        out.write_all(bundle.index.pack_checksum().as_slice())?;
+        for kind in kinds.iter() {
+            ewah::encode(kind, &mut out)?;
+        }
+
+        {
+            let mut write_progress = progress.add_child("writing bitmaps");
+            write_progress.init(Some(bitmaps.len()), progress::count("bitmaps"));
+            for (entry_index, (index_position, bitmap)) in bitmaps.iter().enumerate() {
+                let (xor_offset, stored) = (1..=MAX_XOR_OFFSET_SEARCH.min(entry_index))
+                    .map(|xor_offset| {
+                        let mut xored = bitmap.clone();
+                        xored.xor(&bitmaps[entry_index - xor_offset].1);
+                        (xor_offset, xored)
+                    })
+                    .chain(Some((0, bitmap.clone())))
+                    .min_by_key(|(xor_offset, bitmap)| (ewah::compressed_len(bitmap), *xor_offset))
+                    .expect("at least the bitmap itself");
+                debug_assert!(xor_offset <= MAX_XOR_OFFSET as usize);
+                out.write_u32::<BigEndian>(*index_position)?;
+                out.write_u8(xor_offset as u8)?;
+                out.write_u8(0 /* flags */)?;
+                ewah::encode(&stored, &mut out)?;
+                write_progress.inc();
+            }
+        }
+        if let Some(name_hashes) = name_hashes {
+            for hash in name_hashes {
+                out.write_u32::<BigEndian>(hash)?;
+            }
+        }
+
+        let mut out = out.into_inner().map_err(|err| err.into_error())?;
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+
+        progress.inc();
+        progress.show_throughput_with(
+            start,
+            bitmaps.len(),
+            progress::count("bitmaps").expect("unit always set"),
+        );
+        Ok(Outcome {
+            version: Version::V1,
+            checksum,
+            num_bitmaps: bitmaps.len() as u32,
+        })
+    }
+}
+
+/// Select the commits to write bitmaps for from `commits` sorted by commit time, newest first, like `git` does.
+///
+/// Returns the index positions of the selected commits, newest first.
+fn select_commits(commits: &[Commit], tip_positions: &HashSet<u32>) -> Vec<u32> {
+    if commits.len() < 100 {
+        return commits.iter().map(|c| c.index_position).collect();
+    }
+    let mut selected = Vec::new();
+    let mut idx = 0;
+    loop {
+        let next = next_commit_index(idx);
+        if idx + next >= commits.len() {
+            break;
+        }
+        let window = &commits[idx..=idx + next];
+        let chosen = window
+            .iter()
+            .find(|c| tip_positions.contains(&c.index_position))
+            .unwrap_or(&window[next]);
+        selected.push(chosen.index_position);
+        idx += next + 1;
+    }
+    selected
+}
+
+/// Return the position of each commit in an order in which all parents come before their children, by index position.
+fn topological_order(commits: &[Commit]) -> HashMap<u32, usize> {
+    let parents_by_index_position: HashMap<_, _> = commits.iter().map(|c| (c.index_position, &c.parents)).collect();
+    let mut order = HashMap::with_capacity(commits.len());
+    let mut stack = Vec::new();
+    for commit in commits {
+        stack.push((commit.index_position, false));
+        while let Some((index_position, parents_are_ordered)) = stack.pop() {
+            if order.contains_key(&index_position) {
+                continue;
+            }
+            if parents_are_ordered {
+                let position = order.len();
+                order.insert(index_position, position);
+                continue;
+            }
+            stack.push((index_position, true));
+            stack.extend(
+                parents_by_index_position[&index_position]
+                    .iter()
+                    .filter(|parent| !order.contains_key(*parent))
+                    .map(|parent| (*parent, false)),
+            );
+        }
+    }
+    order
+}
+
+/// Return the distance from the commit at `idx` to the next commit to select, with commits at larger indices being
+/// older and spaced further apart.
+fn next_commit_index(idx: usize) -> usize {
+    const MIN_COMMITS: usize = 100;
+    const MAX_COMMITS: usize = 5000;
+    const MUST_REGION: usize = 100;
+    const MIN_REGION: usize = 20000;
+
+    if idx <= MUST_REGION {
+        return 0;
+    }
+    if idx <= MIN_REGION {
+        return (idx - MUST_REGION).min(MIN_COMMITS);
+    }
+    (idx - MIN_REGION).clamp(MIN_COMMITS, MAX_COMMITS)
+}
+
+/// Return the hash of the path each object is first seen at when traversing the trees of `commits` in order, for each index
+/// position, or 0 if there is no path. Tags use the hashes of their names as given in `tag_name_hashes`.
+fn name_hashes(
+    bundle: &pack::Bundle,
+    commits: &[Commit],
+    tag_name_hashes: Vec<(u32, u32)>,
+    cache: &mut impl pack::cache::DecodeEntry,
+    buf: &mut Vec<u8>,
+) -> Result<Vec<u32>, Error> {
+    let mut hashes = vec![0; bundle.index.num_objects() as usize];
+    let mut seen = vec![false; hashes.len()];
+    for (index_position, hash) in tag_name_hashes {
+        hashes[index_position as usize] = hash;
+    }
+    let mut trees = Vec::<(ObjectId, BString)>::new();
+    let lookup = |id: &git_hash::oid| {
+        bundle
+            .index
+            .lookup(id)
+            .ok_or_else(|| lookup::Error::NotInPack { id: id.to_owned() })
+    };
+    for commit in commits {
+        if is_triggered() {
+            return Err(Error::Interrupted);
+        }
+        let obj = bundle
+            .internal_get_object_by_index(commit.index_position, buf, cache)
+            .map_err(lookup::Error::from)?;
+        let tree_id = obj
+            .into_commit_iter()
+            .expect("commit")
+            .tree_id()
+            .expect("commits always have a tree");
+        trees.push((tree_id, BString::default()));
+        while let Some((tree_id, path)) = trees.pop() {
+            let index_position = lookup(&tree_id)? as usize;
+            if seen[index_position] {
+                continue;
+            }
+            seen[index_position] = true;
+            hashes[index_position] = name_hash(path.as_ref());
+            let obj = bundle
+                .internal_get_object_by_index(index_position as u32, buf, cache)
+                .map_err(lookup::Error::from)?;
+            for entry in immutable::TreeIter::from_bytes_with_hash_kind(obj.data, obj.hash_kind) {
+                let entry = entry.map_err(lookup::Error::from)?;
+                if entry.mode == git_object::tree::EntryMode::Commit {
+                    continue;
+                }
+                let mut entry_path = path.clone();
+                if !entry_path.is_empty() {
+                    entry_path.push(b'/');
+                }
+                entry_path.push_str(entry.filename);
+                if entry.mode.is_tree() {
+                    trees.push((entry.oid.to_owned(), entry_path));
+                } else {
+                    let index_position = lookup(entry.oid)? as usize;
+                    if !seen[index_position] {
+                        seen[index_position] = true;
+                        hashes[index_position] = name_hash(entry_path.as_ref());
+                    }
+                }
+            }
+        }
+    }
+    Ok(hashes)
+}
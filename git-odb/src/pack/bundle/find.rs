@@ -1,4 +1,4 @@
-use crate::{data, pack};
+use crate::{data, find, pack};
 
 impl pack::Bundle {
     /// Find an object with the given [`ObjectId`][git_hash::ObjectId] and place its data into `out`.
@@ -13,30 +13,76 @@ impl pack::Bundle {
         out: &'a mut Vec<u8>,
         cache: &mut impl pack::cache::DecodeEntry,
     ) -> Result<Option<data::Object<'a>>, pack::data::decode_entry::Error> {
-        let idx = match self.index.lookup(id) {
-            Some(idx) => idx,
+        let id = id.as_ref();
+        let ofs = match self.internal_find_pack_offset(id) {
+            Some(ofs) => ofs,
             None => return Ok(None),
         };
-        self.internal_get_object_by_index(idx, out, cache).map(Some)
+        self.internal_get_object_at_offset(id, ofs, out, cache).map(Some)
     }
 
-    /// Internal-use function to look up an object index. Used to avoid double-lookups in
+    /// Find the kind and size of the object with the given [`ObjectId`][git_hash::ObjectId] without decoding it, see
+    /// [`pack::data::File::decode_header()`].
+    pub fn find_header(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+    ) -> Result<Option<pack::data::decode_entry::Header>, pack::data::decode_entry::Error> {
+        let ofs = match self.internal_find_pack_offset(id.as_ref()) {
+            Some(ofs) => ofs,
+            None => return Ok(None),
+        };
+        self.internal_get_header_at_offset(ofs).map(Some)
+    }
+
+    /// Find an object with the given [`ObjectId`][git_hash::ObjectId] and return a stream to read its data, see
+    /// [`Find::find_stream()`][crate::Find::find_stream()].
+    ///
+    /// [`cache`][pack::cache::DecodeEntry] is used to accelerate the lookup of deltified objects, which are resolved in memory.
+    pub fn find_stream(
+        &self,
+        id: impl AsRef<git_hash::oid>,
+        cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<Option<find::Stream<'_>>, pack::data::decode_entry::Error> {
+        let id = id.as_ref();
+        let ofs = match self.internal_find_pack_offset(id) {
+            Some(ofs) => ofs,
+            None => return Ok(None),
+        };
+        self.internal_get_stream_at_offset(id, ofs, cache).map(Some)
+    }
+
+    /// Internal-use function to look up the pack offset of an object. Used to avoid double-lookups in
     /// [compound::Db::find()][crate::compound::Db::find()]. (The polonius borrow-checker would support this via the 'find'
     /// function, so this can be [simplified](https://github.com/Byron/gitoxide/blob/0c5f4043da4615820cb180804a81c2d4fe75fe5e/git-odb/src/compound/locate.rs#L47)
     /// once polonius is stable.)
-    pub(crate) fn internal_find_pack_index(&self, id: &git_hash::oid) -> Option<u32> {
-        self.index.lookup(id)
+    pub(crate) fn internal_find_pack_offset(&self, id: &git_hash::oid) -> Option<u64> {
+        self.index.lookup(id).map(|idx| self.index.pack_offset_at_index(idx))
     }
 
-    /// Internal-use function to get an object given an index previously returned from
-    /// internal_find_pack_index.
+    /// Internal-use function to get an object given its index in our index file.
     pub(crate) fn internal_get_object_by_index<'a>(
         &self,
         idx: u32,
         out: &'a mut Vec<u8>,
         cache: &mut impl pack::cache::DecodeEntry,
     ) -> Result<data::Object<'a>, pack::data::decode_entry::Error> {
-        let ofs = self.index.pack_offset_at_index(idx);
+        self.internal_get_object_at_offset(
+            self.index.oid_at_index(idx),
+            self.index.pack_offset_at_index(idx),
+            out,
+            cache,
+        )
+    }
+
+    /// Internal-use function to get the object `id` given its pack offset `ofs`, as previously returned from
+    /// internal_find_pack_offset or obtained from a multi-pack index.
+    pub(crate) fn internal_get_object_at_offset<'a>(
+        &self,
+        id: &git_hash::oid,
+        ofs: u64,
+        out: &'a mut Vec<u8>,
+        cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<data::Object<'a>, pack::data::decode_entry::Error> {
         let pack_entry = self.pack.entry(ofs);
         let header_size = pack_entry.header_size();
         self.pack
@@ -53,11 +99,49 @@ impl pack::Bundle {
             .map(move |r| crate::data::Object {
                 kind: r.kind,
                 data: out.as_slice(),
+                hash_kind: id.kind(),
                 pack_location: Some(pack::bundle::Location {
                     pack_id: self.pack.id,
-                    index_file_id: idx,
+                    id: id.to_owned(),
+                    pack_offset: ofs,
                     entry_size: r.compressed_size + header_size,
                 }),
             })
     }
+
+    /// Internal-use function to get the header of an object given its pack offset `ofs`.
+    pub(crate) fn internal_get_header_at_offset(
+        &self,
+        ofs: u64,
+    ) -> Result<pack::data::decode_entry::Header, pack::data::decode_entry::Error> {
+        self.pack.decode_header(self.pack.entry(ofs), |id| {
+            self.index
+                .lookup(id)
+                .map(|idx| self.pack.entry(self.index.pack_offset_at_index(idx)))
+        })
+    }
+
+    /// Internal-use function to get a stream of the object `id` given its pack offset `ofs`.
+    pub(crate) fn internal_get_stream_at_offset(
+        &self,
+        id: &git_hash::oid,
+        ofs: u64,
+        cache: &mut impl pack::cache::DecodeEntry,
+    ) -> Result<find::Stream<'_>, pack::data::decode_entry::Error> {
+        let entry = self.pack.entry(ofs);
+        if let Some(kind) = entry.header.to_kind() {
+            return Ok(find::Stream {
+                kind,
+                size: entry.decompressed_size,
+                inner: find::stream::Inner::Packed(self.pack.decompress_entry_stream(&entry)),
+            });
+        }
+        let mut buf = Vec::new();
+        let kind = self.internal_get_object_at_offset(id, ofs, &mut buf, cache)?.kind;
+        Ok(find::Stream {
+            kind,
+            size: buf.len() as u64,
+            inner: find::stream::Inner::Buffered(std::io::Cursor::new(buf)),
+        })
+    }
 }
@@ -56,15 +56,17 @@ pub enum Error {
 pub(crate) struct Location {
     /// The id of the pack containing the object
     pub(crate) pack_id: u32,
-    /// The index at which the object can be fonud in the index file
-    pub(crate) index_file_id: u32,
+    /// The id of the object, to find its entry in the index file if needed
+    pub(crate) id: git_hash::ObjectId,
+    /// The offset to the object's entry in the pack data file
+    pub(crate) pack_offset: u64,
     /// The size of the entry of disk
     pub(crate) entry_size: usize,
 }
 
 impl Location {
-    pub(crate) fn entry_range(&self, pack_offset: u64) -> pack::data::EntryRange {
-        pack_offset..pack_offset + self.entry_size as u64
+    pub(crate) fn entry_range(&self) -> pack::data::EntryRange {
+        self.pack_offset..self.pack_offset + self.entry_size as u64
     }
 }
 
@@ -87,6 +89,17 @@ impl Bundle {
     }
 }
 
+/// Partial clones
+impl Bundle {
+    /// Return true if this pack was received from a promisor remote, as indicated by a `.promisor` file next to it.
+    ///
+    /// Objects referred to by objects in such packs may be missing as they can be fetched from the promisor remote on demand,
+    /// which is the case in partial clones.
+    pub fn is_promisor(&self) -> bool {
+        self.pack.path().with_extension("promisor").is_file()
+    }
+}
+
 impl TryFrom<&Path> for Bundle {
     type Error = Error;
 
@@ -95,16 +108,13 @@ impl TryFrom<&Path> for Bundle {
             .extension()
             .and_then(|e| e.to_str())
             .ok_or_else(|| Error::InvalidPath(path.to_owned()))?;
-        Ok(match ext {
-            "idx" => Self {
-                index: pack::index::File::at(path)?,
-                pack: pack::data::File::at(path.with_extension("pack"))?,
-            },
-            "pack" => Self {
-                pack: pack::data::File::at(path)?,
-                index: pack::index::File::at(path.with_extension("idx"))?,
-            },
+        let (index_path, data_path) = match ext {
+            "idx" => (path.to_owned(), path.with_extension("pack")),
+            "pack" => (path.with_extension("idx"), path.to_owned()),
             _ => return Err(Error::InvalidPath(path.to_owned())),
-        })
+        };
+        let index = pack::index::File::at(index_path)?;
+        let pack = pack::data::File::at_with_hash_kind(data_path, index.hash_kind())?;
+        Ok(Self { index, pack })
     }
 }
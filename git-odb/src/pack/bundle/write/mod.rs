@@ -10,23 +10,30 @@ use std::{
 use tempfile::NamedTempFile;
 
 mod error;
-use error::Error;
+pub use error::Error;
 
 mod types;
-use types::PassThrough;
+use types::{LockWriter, PassThrough};
 pub use types::{Options, Outcome};
 
 impl pack::Bundle {
     /// Given a `pack` data stream into the `directory` if `Some` or discard it entirely if `None`.
     ///
     /// `progress` provides detailed progress information which can be discarded with [`git_features::progress::Discard`].
+    /// `thin_pack_base_object_lookup` is used to find the bases of deltas which refer to objects outside of the pack by id,
+    /// as found in thin packs that are received when fetching into or pushing to an existing repository. If `Some`, the
+    /// pack is rewritten with these bases inserted, otherwise it is written as is and thin packs fail to be indexed.
     /// `options` further configure how the task is performed.
-    pub fn write_stream_to_directory(
+    pub fn write_stream_to_directory<Find>(
         pack: impl io::BufRead,
         directory: Option<impl AsRef<Path>>,
         mut progress: impl Progress,
+        thin_pack_base_object_lookup: Option<Find>,
         options: Options,
-    ) -> Result<Outcome, Error> {
+    ) -> Result<Outcome, Error>
+    where
+        Find: crate::Find,
+    {
         let mut read_progress = progress.add_child("read pack");
         read_progress.init(None, progress::bytes());
         let pack = progress::Read {
@@ -41,7 +48,10 @@ impl pack::Bundle {
         let data_path: PathBuf = data_file.lock().path().into();
         let pack = PassThrough {
             reader: interrupt::Read { inner: pack },
-            writer: Some(data_file.clone()),
+            writer: match thin_pack_base_object_lookup {
+                Some(_) => None,
+                None => Some(data_file.clone()),
+            },
         };
         // This buff-reader is required to assure we call 'read()' in order to fill the (extra) buffer. Otherwise all the counting
         // we do with the wrapped pack reader doesn't work as it does not expect anyone to call BufRead functions directly.
@@ -51,11 +61,18 @@ impl pack::Bundle {
         let pack_entries_iter = pack::data::BytesToEntriesIter::new_from_header(
             buffered_pack,
             options.iteration_mode,
-            pack::data::input::EntryDataMode::Crc32,
+            entry_data_mode(&thin_pack_base_object_lookup),
+            options.hash_kind,
         )?;
         let pack_kind = pack_entries_iter.kind();
-        let (outcome, data_path, index_path) =
-            pack::Bundle::inner_write(directory, progress, options, data_file, data_path, pack_entries_iter)?;
+        let (outcome, data_path, index_path) = match thin_pack_base_object_lookup {
+            Some(db) => {
+                let pack_entries_iter =
+                    resolve_thin_pack(pack_entries_iter, db, data_file.clone(), pack_kind, options.hash_kind);
+                pack::Bundle::inner_write(directory, progress, options, data_file, data_path, pack_entries_iter)?
+            }
+            None => pack::Bundle::inner_write(directory, progress, options, data_file, data_path, pack_entries_iter)?,
+        };
 
         Ok(Outcome {
             index: outcome,
@@ -66,13 +83,17 @@ impl pack::Bundle {
     }
 
     /// Equivalent to [`write_stream_to_directory()`][pack::Bundle::write_stream_to_directory()] but offloads reading of the pack into its own thread, hence the `Send + 'static'` bounds.
-    pub fn write_to_directory_eagerly(
+    pub fn write_to_directory_eagerly<Find>(
         pack: impl io::Read + Send + 'static,
         pack_size: Option<u64>,
         directory: Option<impl AsRef<Path>>,
         mut progress: impl Progress,
+        thin_pack_base_object_lookup: Option<Find>,
         options: Options,
-    ) -> Result<Outcome, Error> {
+    ) -> Result<Outcome, Error>
+    where
+        Find: crate::Find,
+    {
         let mut read_progress = progress.add_child("read pack");
         read_progress.init(pack_size.map(|s| s as usize), progress::bytes());
         let pack = progress::Read {
@@ -87,22 +108,32 @@ impl pack::Bundle {
         let data_path: PathBuf = data_file.lock().path().into();
         let pack = PassThrough {
             reader: interrupt::Read { inner: pack },
-            writer: Some(data_file.clone()),
+            writer: match thin_pack_base_object_lookup {
+                Some(_) => None,
+                None => Some(data_file.clone()),
+            },
         };
         let eight_pages = 4096 * 8;
         let buffered_pack = io::BufReader::with_capacity(eight_pages, pack);
         let pack_entries_iter = pack::data::BytesToEntriesIter::new_from_header(
             buffered_pack,
             options.iteration_mode,
-            pack::data::input::EntryDataMode::Crc32,
+            entry_data_mode(&thin_pack_base_object_lookup),
+            options.hash_kind,
         )?;
         let pack_kind = pack_entries_iter.kind();
         let num_objects = pack_entries_iter.size_hint().0;
         let pack_entries_iter =
             git_features::parallel::EagerIterIf::new(|| num_objects > 25_000, pack_entries_iter, 5_000, 5);
 
-        let (outcome, data_path, index_path) =
-            pack::Bundle::inner_write(directory, progress, options, data_file, data_path, pack_entries_iter)?;
+        let (outcome, data_path, index_path) = match thin_pack_base_object_lookup {
+            Some(db) => {
+                let pack_entries_iter =
+                    resolve_thin_pack(pack_entries_iter, db, data_file.clone(), pack_kind, options.hash_kind);
+                pack::Bundle::inner_write(directory, progress, options, data_file, data_path, pack_entries_iter)?
+            }
+            None => pack::Bundle::inner_write(directory, progress, options, data_file, data_path, pack_entries_iter)?,
+        };
 
         Ok(Outcome {
             index: outcome,
@@ -119,6 +150,7 @@ impl pack::Bundle {
             thread_limit,
             iteration_mode: _,
             index_kind,
+            hash_kind: _,
         }: Options,
         data_file: Arc<parking_lot::Mutex<NamedTempFile>>,
         data_path: PathBuf,
@@ -139,7 +171,7 @@ impl pack::Bundle {
                     &mut index_file,
                 )?;
 
-                let data_path = directory.join(format!("{}.pack", outcome.data_hash.to_sha1_hex_string()));
+                let data_path = directory.join(format!("{}.pack", outcome.data_hash));
                 let index_path = data_path.with_extension("idx");
 
                 Arc::try_unwrap(data_file)
@@ -173,6 +205,36 @@ impl pack::Bundle {
     }
 }
 
+fn entry_data_mode<Find>(thin_pack_base_object_lookup: &Option<Find>) -> pack::data::input::EntryDataMode {
+    match thin_pack_base_object_lookup {
+        // The pack is rewritten from the entries, which requires their compressed data.
+        Some(_) => pack::data::input::EntryDataMode::KeepAndCrc32,
+        None => pack::data::input::EntryDataMode::Crc32,
+    }
+}
+
+/// Insert the bases of deltas in `pack_entries` which aren't part of the pack by looking them up in `db`, and write
+/// the resulting pack of `version` with a trailer of `hash_kind` into `data_file`.
+fn resolve_thin_pack<Find>(
+    pack_entries: impl Iterator<Item = Result<pack::data::input::Entry, pack::data::input::Error>>,
+    db: Find,
+    data_file: Arc<parking_lot::Mutex<NamedTempFile>>,
+    version: pack::data::Version,
+    hash_kind: git_hash::Kind,
+) -> impl Iterator<Item = Result<pack::data::input::Entry, pack::data::input::Error>>
+where
+    Find: crate::Find,
+{
+    pack::data::input::EntriesToBytesIter::new(
+        pack::data::input::LookupRefDeltaObjectsIter::new(pack_entries, move |id, buf| {
+            db.find(id, buf, &mut pack::cache::Never).ok().flatten()
+        }),
+        LockWriter { writer: data_file },
+        version,
+        hash_kind,
+    )
+}
+
 fn new_pack_file_resolver(
     data_path: PathBuf,
 ) -> io::Result<impl Fn(pack::data::EntryRange, &mut Vec<u8>) -> Option<()> + Send + Sync> {
@@ -13,6 +13,8 @@ pub struct Options {
     pub iteration_mode: pack::data::input::Mode,
     /// The version of pack index to write, should be [`pack::index::Version::default()`]
     pub index_kind: pack::index::Version,
+    /// The kind of hash of the repository receiving the pack, which is used for its trailer and the ids in it.
+    pub hash_kind: git_hash::Kind,
 }
 
 impl Default for Options {
@@ -22,6 +24,7 @@ impl Default for Options {
             thread_limit: None,
             iteration_mode: pack::data::input::Mode::Verify,
             index_kind: Default::default(),
+            hash_kind: Default::default(),
         }
     }
 }
@@ -79,3 +82,30 @@ where
         self.reader.consume(amt)
     }
 }
+
+/// Provide shared access to a file for reading, writing and seeking.
+pub(crate) struct LockWriter {
+    pub writer: Arc<parking_lot::Mutex<NamedTempFile>>,
+}
+
+impl io::Write for LockWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.lock().flush()
+    }
+}
+
+impl io::Read for LockWriter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.writer.lock().read(buf)
+    }
+}
+
+impl io::Seek for LockWriter {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.writer.lock().seek(pos)
+    }
+}
@@ -68,3 +68,184 @@ pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     assert_eq!(i, data.len());
     assert_eq!(target.len(), 0);
 }
+
+/// The amount of bytes hashed at once to find regions shared by base and target objects.
+const BLOCK_SIZE: usize = 16;
+/// The largest amount of bytes a single copy instruction will copy, as larger values aren't understood by all pack readers.
+const MAX_COPY_SIZE: usize = 0x10000;
+/// The largest amount of bytes a single insert instruction can hold.
+const MAX_INSERT_SIZE: usize = 0x7f;
+
+/// An index over the blocks of a base object to quickly find matching regions in target objects, allowing it to
+/// be reused for multiple deltas against the same base.
+pub struct BaseIndex {
+    blocks: std::collections::HashMap<u64, u32>,
+}
+
+impl BaseIndex {
+    /// Index the non-overlapping blocks of `base`, which must not be larger than `u32::MAX` bytes.
+    pub fn new(base: &[u8]) -> Self {
+        assert!(base.len() <= u32::MAX as usize, "delta bases are limited to 4GB");
+        let num_blocks = base.len() / BLOCK_SIZE;
+        let mut blocks = std::collections::HashMap::with_capacity(num_blocks);
+        // Insert back to front to prefer earlier offsets for duplicate blocks.
+        for ofs in (0..num_blocks).rev().map(|n| n * BLOCK_SIZE) {
+            blocks.insert(block_hash(&base[ofs..][..BLOCK_SIZE]), ofs as u32);
+        }
+        BaseIndex { blocks }
+    }
+
+    /// Produce the delta instructions to turn `base`, the data this index was created from, into `target`, or `None`
+    /// if the delta would be larger than `max_size` bytes.
+    pub fn encode(&self, base: &[u8], target: &[u8], max_size: usize) -> Option<Vec<u8>> {
+        let mut out = Vec::with_capacity(max_size.min(target.len() / 2 + 32));
+        encode_header_size(base.len() as u64, &mut out);
+        encode_header_size(target.len() as u64, &mut out);
+
+        let mut insert_start = 0;
+        let mut pos = 0;
+        while pos + BLOCK_SIZE <= target.len() {
+            let block = &target[pos..][..BLOCK_SIZE];
+            let base_ofs = self
+                .blocks
+                .get(&block_hash(block))
+                .map(|ofs| *ofs as usize)
+                .filter(|ofs| &base[*ofs..][..BLOCK_SIZE] == block);
+            match base_ofs {
+                None => {
+                    pos += 1;
+                    if out.len() + (pos - insert_start) > max_size {
+                        return None;
+                    }
+                }
+                Some(mut base_ofs) => {
+                    let mut start = pos;
+                    while start > insert_start && base_ofs > 0 && target[start - 1] == base[base_ofs - 1] {
+                        start -= 1;
+                        base_ofs -= 1;
+                    }
+                    let mut end = pos + BLOCK_SIZE;
+                    let mut base_end = base_ofs + (end - start);
+                    while end < target.len() && base_end < base.len() && target[end] == base[base_end] {
+                        end += 1;
+                        base_end += 1;
+                    }
+                    encode_insert(&target[insert_start..start], &mut out);
+                    encode_copy(base_ofs, end - start, &mut out);
+                    insert_start = end;
+                    pos = end;
+                    if out.len() > max_size {
+                        return None;
+                    }
+                }
+            }
+        }
+        encode_insert(&target[insert_start..], &mut out);
+        if out.len() > max_size {
+            None
+        } else {
+            Some(out)
+        }
+    }
+}
+
+fn block_hash(block: &[u8]) -> u64 {
+    use std::convert::TryInto;
+    let lo = u64::from_le_bytes(block[..8].try_into().expect("8 bytes"));
+    let hi = u64::from_le_bytes(block[8..16].try_into().expect("8 bytes"));
+    (lo ^ hi.rotate_left(29)).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+/// Encode `size` the way [`decode_header_size()`] expects it.
+fn encode_header_size(mut size: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn encode_insert(mut data: &[u8], out: &mut Vec<u8>) {
+    while !data.is_empty() {
+        let len = data.len().min(MAX_INSERT_SIZE);
+        out.push(len as u8);
+        out.extend_from_slice(&data[..len]);
+        data = &data[len..];
+    }
+}
+
+fn encode_copy(mut ofs: usize, mut len: usize, out: &mut Vec<u8>) {
+    while len != 0 {
+        let size = len.min(MAX_COPY_SIZE);
+        let cmd_pos = out.len();
+        let mut cmd = 0b1000_0000;
+        out.push(cmd);
+        for n in 0..4 {
+            let byte = (ofs >> (n * 8)) as u8;
+            if byte != 0 {
+                cmd |= 1 << n;
+                out.push(byte);
+            }
+        }
+        // A size of 0 is interpreted as MAX_COPY_SIZE
+        if size != MAX_COPY_SIZE {
+            for n in 0..3 {
+                let byte = (size >> (n * 8)) as u8;
+                if byte != 0 {
+                    cmd |= 0b0001_0000 << n;
+                    out.push(byte);
+                }
+            }
+        }
+        out[cmd_pos] = cmd;
+        ofs += size;
+        len -= size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(base: &[u8], target: &[u8]) -> Vec<u8> {
+        let delta = BaseIndex::new(base)
+            .encode(base, target, usize::MAX)
+            .expect("no size limit");
+        let (base_size, consumed) = decode_header_size(&delta);
+        assert_eq!(base_size as usize, base.len());
+        let (result_size, consumed_result) = decode_header_size(&delta[consumed..]);
+        assert_eq!(result_size as usize, target.len());
+        let mut result = vec![0; target.len()];
+        apply(base, &mut result, &delta[consumed + consumed_result..]);
+        assert_eq!(result, target);
+        delta
+    }
+
+    #[test]
+    fn encoded_deltas_apply_to_their_base() {
+        let base: Vec<u8> = (0..200_000u32)
+            .map(|n| (n.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        let mut target = base[1000..150_000].to_vec();
+        target.extend_from_slice(b"something new in the middle");
+        target.extend_from_slice(&base[..1000]);
+        target.extend_from_slice(&base[150_100..]);
+
+        let delta = round_trip(&base, &target);
+        assert!(delta.len() < 256, "large copies are cheap, got {} bytes", delta.len());
+        round_trip(&base, b"");
+        round_trip(b"", b"only inserts as the base is empty");
+        round_trip(b"tiny", b"tiny");
+    }
+
+    #[test]
+    fn encoding_stops_once_max_size_is_exceeded() {
+        let base = vec![1u8; 64];
+        let target: Vec<u8> = (0..=255u8).collect();
+        assert!(BaseIndex::new(&base).encode(&base, &target, 100).is_none());
+    }
+}
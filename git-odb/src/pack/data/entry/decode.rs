@@ -1,4 +1,4 @@
-use super::{BLOB, COMMIT, OFS_DELTA, REF_DELTA, SHA1_SIZE, TAG, TREE};
+use super::{BLOB, COMMIT, OFS_DELTA, REF_DELTA, TAG, TREE};
 use crate::pack::data;
 use std::io;
 
@@ -10,6 +10,11 @@ impl data::Entry {
     ///
     /// If we cannot understand the header, garbage data is likely to trigger this.
     pub fn from_bytes(d: &[u8], pack_offset: u64) -> data::Entry {
+        Self::from_bytes_with_hash_kind(d, pack_offset, git_hash::Kind::Sha1)
+    }
+
+    /// Like [`from_bytes()`][data::Entry::from_bytes()], but expects the base ids of ref-deltas to be of `hash_kind`.
+    pub fn from_bytes_with_hash_kind(d: &[u8], pack_offset: u64, hash_kind: git_hash::Kind) -> data::Entry {
         let (type_id, size, mut consumed) = parse_header_info(d);
 
         use data::entry::Header::*;
@@ -23,10 +28,13 @@ impl data::Entry {
                 delta
             }
             REF_DELTA => {
+                let hash_len = hash_kind.len_in_bytes();
                 let delta = RefDelta {
-                    base_id: git_hash::ObjectId::from_20_bytes(&d[consumed..consumed + SHA1_SIZE]),
+                    base_id: git_hash::oid::try_from(&d[consumed..consumed + hash_len])
+                        .expect("hash length to be supported")
+                        .to_owned(),
                 };
-                consumed += SHA1_SIZE;
+                consumed += hash_len;
                 delta
             }
             BLOB => Blob,
@@ -42,8 +50,13 @@ impl data::Entry {
         }
     }
 
-    /// Instantiate an `Entry` from the reader `r`, providing the `pack_offset` to allow tracking the start of the entry data section.
-    pub fn from_read(mut r: impl io::Read, pack_offset: u64) -> Result<data::Entry, io::Error> {
+    /// Instantiate an `Entry` from the reader `r`, providing the `pack_offset` to allow tracking the start of the entry data section,
+    /// and expecting the base ids of ref-deltas to be of `hash_kind`.
+    pub fn from_read(
+        mut r: impl io::Read,
+        pack_offset: u64,
+        hash_kind: git_hash::Kind,
+    ) -> Result<data::Entry, io::Error> {
         let (type_id, size, mut consumed) = streaming_parse_header_info(&mut r)?;
 
         use data::entry::Header::*;
@@ -57,13 +70,10 @@ impl data::Entry {
                 delta
             }
             REF_DELTA => {
-                let mut buf = [0u8; SHA1_SIZE];
-                r.read_exact(&mut buf)?;
-                let delta = RefDelta {
-                    base_id: git_hash::ObjectId::new_sha1(buf),
-                };
-                consumed += SHA1_SIZE;
-                delta
+                let mut base_id = hash_kind.null();
+                r.read_exact(base_id.as_mut_slice())?;
+                consumed += hash_kind.len_in_bytes();
+                RefDelta { base_id }
             }
             BLOB => Blob,
             TREE => Tree,
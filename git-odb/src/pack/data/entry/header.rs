@@ -1,6 +1,9 @@
 use super::{BLOB, COMMIT, OFS_DELTA, REF_DELTA, TAG, TREE};
 use std::io;
 
+/// The largest possible size of an encoded header, the one of a delta with the largest decompressed size and a Sha256 base id.
+pub(crate) const MAX_SIZE: usize = 10 + 32;
+
 /// The header portion of a pack data entry, identifying the kind of stored object.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
@@ -120,4 +123,10 @@ impl Header {
         }
         Ok(written)
     }
+
+    /// The size of the header in bytes when serialized along with `decompressed_size_in_bytes`.
+    pub fn size(&self, decompressed_size_in_bytes: u64) -> usize {
+        self.to_write(decompressed_size_in_bytes, io::sink())
+            .expect("io::sink() to never fail")
+    }
 }
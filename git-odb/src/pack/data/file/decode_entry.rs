@@ -3,6 +3,7 @@ use crate::{
     pack::data::delta,
     pack::{self, cache, data::File},
     zlib,
+    zlib::stream::inflate::ReadBoxed,
 };
 use smallvec::SmallVec;
 use std::{convert::TryInto, ops::Range};
@@ -48,6 +49,18 @@ pub struct Outcome {
     pub object_size: u64,
 }
 
+/// The kind and size of an object as obtained by [`File::decode_header()`], without decoding the object itself.
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    /// The kind of the object, which for deltified objects is the kind of the base object at the end of the delta chain.
+    pub kind: git_object::Kind,
+    /// The size of the fully decoded object in bytes.
+    pub object_size: u64,
+    /// The amount of deltas in the chain of entries that was traversed to find the kind of the object.
+    pub num_deltas: u32,
+}
+
 impl Outcome {
     pub(crate) fn default_from_kind(kind: git_object::Kind) -> Self {
         Self {
@@ -90,6 +103,18 @@ impl File {
         self.decompress_entry_from_data_offset(entry.data_offset, out)
     }
 
+    /// Return a reader which decompresses the data of the given `entry` while reading it.
+    ///
+    /// _Note_ that like [`decompress_entry()`][File::decompress_entry()], this does not resolve deltified objects.
+    pub(crate) fn decompress_entry_stream(&self, entry: &pack::data::Entry) -> ReadBoxed<&[u8]> {
+        let offset: usize = entry.data_offset.try_into().expect("offset representable by machine");
+        assert!(offset < self.data.len(), "entry offset out of bounds");
+        ReadBoxed {
+            inner: &self.data[offset..],
+            decompressor: Box::new(flate2::Decompress::new(true)),
+        }
+    }
+
     fn assure_v2(&self) {
         assert!(
             matches!(self.version, crate::pack::data::Version::V2),
@@ -106,7 +131,7 @@ impl File {
         assert!(pack_offset <= self.data.len(), "offset out of bounds");
 
         let object_data = &self.data[pack_offset..];
-        pack::data::Entry::from_bytes(object_data, offset)
+        pack::data::Entry::from_bytes_with_hash_kind(object_data, offset, self.hash_kind)
     }
 
     /// Decompress the object expected at the given data offset, sans pack header. This information is only
@@ -163,6 +188,52 @@ impl File {
         }
     }
 
+    /// Obtain the kind and size of the object stored in `entry` without decoding it.
+    ///
+    /// For base objects, this information is available in the entry header. For deltified objects, only the first bytes of the
+    /// delta are decompressed to learn the size of the resulting object, while the kind is the one of the base object found by
+    /// following the headers of the delta chain.
+    ///
+    /// `resolve` is a function to lookup the entries of objects with the given [`ObjectId`][git_hash::ObjectId] in this pack,
+    /// in case the full object id is used to refer to a base object.
+    pub fn decode_header(
+        &self,
+        entry: pack::data::Entry,
+        resolve: impl Fn(&git_hash::oid) -> Option<pack::data::Entry>,
+    ) -> Result<Header, Error> {
+        use crate::pack::data::entry::Header::*;
+        let object_size = match entry.header {
+            Tree | Blob | Commit | Tag => entry.decompressed_size,
+            OfsDelta { .. } | RefDelta { .. } => {
+                // Two sizes of at most 10 bytes each, the base object size and the result size.
+                let mut delta_header = [0u8; 20];
+                let offset: usize = entry.data_offset.try_into().expect("offset representable by machine");
+                let (_status, _consumed_in, consumed_out) =
+                    zlib::Inflate::default().once(&self.data[offset..], &mut delta_header)?;
+                let delta_header = &delta_header[..consumed_out];
+                let (_base_size, consumed) = delta::decode_header_size(delta_header);
+                delta::decode_header_size(&delta_header[consumed..]).0
+            }
+        };
+
+        let mut num_deltas = 0;
+        let mut cursor = entry;
+        loop {
+            cursor = match cursor.header {
+                Tree | Blob | Commit | Tag => {
+                    return Ok(Header {
+                        kind: cursor.header.to_kind().expect("a non-delta entry"),
+                        object_size,
+                        num_deltas,
+                    })
+                }
+                OfsDelta { base_distance } => self.entry(cursor.base_pack_offset(base_distance)),
+                RefDelta { base_id } => resolve(&base_id).ok_or(Error::DeltaBaseUnresolved(base_id))?,
+            };
+            num_deltas += 1;
+        }
+    }
+
     /// resolve: technically, this shoudln't ever be required as stored local packs don't refer to objects by id
     /// that are outside of the pack. Unless, of course, the ref refers to an object within this pack, which means
     /// it's very, very large as 20bytes are smaller than the corresponding MSB encoded number
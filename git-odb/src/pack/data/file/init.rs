@@ -1,6 +1,5 @@
 use crate::pack::data;
 use filebuffer::FileBuffer;
-use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
 use std::{convert::TryFrom, convert::TryInto, path::Path};
 
 /// Instantiation
@@ -9,6 +8,17 @@ impl data::File {
     pub fn at(path: impl AsRef<Path>) -> Result<data::File, data::header::decode::Error> {
         data::File::try_from(path.as_ref())
     }
+
+    /// Try opening a data file at the given `path` whose trailer is a hash of `hash_kind`, as used by repositories with the
+    /// respective object format.
+    ///
+    /// The hash kind can't be determined from the data file itself, but from its [index][crate::pack::index::File::hash_kind()].
+    pub fn at_with_hash_kind(
+        path: impl AsRef<Path>,
+        hash_kind: git_hash::Kind,
+    ) -> Result<data::File, data::header::decode::Error> {
+        Self::at_inner(path.as_ref(), hash_kind)
+    }
 }
 
 impl TryFrom<&Path> for data::File {
@@ -16,6 +26,12 @@ impl TryFrom<&Path> for data::File {
 
     /// Try opening a data file at the given `path`.
     fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        Self::at_inner(path, git_hash::Kind::Sha1)
+    }
+}
+
+impl data::File {
+    fn at_inner(path: &Path, hash_kind: git_hash::Kind) -> Result<data::File, data::header::decode::Error> {
         use data::header::N32_SIZE;
 
         let data = FileBuffer::open(path).map_err(|e| data::header::decode::Error::Io {
@@ -23,7 +39,7 @@ impl TryFrom<&Path> for data::File {
             path: path.to_owned(),
         })?;
         let pack_len = data.len();
-        if pack_len < N32_SIZE * 3 + SHA1_SIZE {
+        if pack_len < N32_SIZE * 3 + hash_kind.len_in_bytes() {
             return Err(data::header::decode::Error::Corrupt(format!(
                 "Pack data of size {} is too small for even an empty pack",
                 pack_len
@@ -37,6 +53,7 @@ impl TryFrom<&Path> for data::File {
             id: git_features::hash::crc32(path.as_os_str().to_string_lossy().as_bytes()),
             version: kind,
             num_objects,
+            hash_kind,
         })
     }
 }
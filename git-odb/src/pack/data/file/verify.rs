@@ -1,6 +1,5 @@
 use crate::pack::data::File;
 use git_features::progress::Progress;
-use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
 
 /// Returned by [`File::verify_checksum()`]
 #[derive(thiserror::Error, Debug)]
@@ -19,7 +18,7 @@ pub enum Error {
 impl File {
     /// The checksum in the trailer of this pack data file
     pub fn checksum(&self) -> git_hash::ObjectId {
-        git_hash::ObjectId::from_20_bytes(&self.data[self.data.len() - SHA1_SIZE..])
+        self.id_at(self.pack_end())
     }
 
     /// Verifies that the checksum of the packfile over all bytes preceding it indeed matches the actual checksum,
@@ -31,23 +30,19 @@ impl File {
     /// Have a look at [`index::File::verify_integrity(…)`][crate::pack::index::File::verify_integrity()] for an
     /// even more thorough integrity check.
     pub fn verify_checksum(&self, mut progress: impl Progress) -> Result<git_hash::ObjectId, Error> {
-        let right_before_trailer = self.data.len() - SHA1_SIZE;
-        let actual = match git_features::hash::bytes_of_file(
-            &self.path,
-            right_before_trailer,
-            git_hash::Kind::Sha1,
-            &mut progress,
-        ) {
-            Ok(id) => id,
-            Err(_io_err) => {
-                let start = std::time::Instant::now();
-                let mut hasher = git_features::hash::Sha1::default();
-                hasher.update(&self.data[..right_before_trailer]);
-                progress.inc_by(right_before_trailer);
-                progress.show_throughput(start);
-                git_hash::ObjectId::new_sha1(hasher.digest())
-            }
-        };
+        let right_before_trailer = self.pack_end();
+        let actual =
+            match git_features::hash::bytes_of_file(&self.path, right_before_trailer, self.hash_kind, &mut progress) {
+                Ok(id) => id,
+                Err(_io_err) => {
+                    let start = std::time::Instant::now();
+                    let mut hasher = git_features::hash::hasher(self.hash_kind);
+                    hasher.update(&self.data[..right_before_trailer]);
+                    progress.inc_by(right_before_trailer);
+                    progress.show_throughput(start);
+                    hasher.digest()
+                }
+            };
 
         let expected = self.checksum();
         if actual == expected {
@@ -57,3 +52,12 @@ impl File {
         }
     }
 }
+
+impl File {
+    /// Return the id of our hash kind at `offset` into the pack.
+    fn id_at(&self, offset: usize) -> git_hash::ObjectId {
+        git_hash::oid::try_from(&self.data[offset..offset + self.hash_kind.len_in_bytes()])
+            .expect("valid hash length")
+            .to_owned()
+    }
+}
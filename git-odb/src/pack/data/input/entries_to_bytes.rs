@@ -0,0 +1,128 @@
+use crate::{hash, pack, pack::data::input};
+use std::io::{self, SeekFrom};
+
+/// An implementation of [`Iterator`] to write [input entries][input::Entry] into a pack data file, passing them through
+/// unchanged except for the last one, which receives the trailer of the newly written pack.
+///
+/// The amount of entries doesn't have to be known in advance, the header is written last by seeking back to the beginning
+/// of `output`, which is then read again to compute the trailer. This makes it suitable for writing entries produced by
+/// [`LookupRefDeltaObjectsIter`][input::LookupRefDeltaObjectsIter].
+///
+/// # Panics
+///
+/// If the compressed data of entries isn't kept, see [`EntryDataMode::Keep`][input::EntryDataMode::Keep].
+pub struct EntriesToBytesIter<I: Iterator, W> {
+    /// An iterator for input [`input::Entry`] instances
+    pub input: std::iter::Peekable<I>,
+    /// A way of writing encoded bytes.
+    output: W,
+    /// The version of the pack data file to write.
+    data_version: pack::data::Version,
+    /// The kind of hash to use for the trailer.
+    hash_kind: git_hash::Kind,
+    /// The amount of entries written so far.
+    num_entries: u32,
+    /// True if the header was written already.
+    header_written: bool,
+    /// If we are done, no additional writes will occur
+    is_done: bool,
+}
+
+impl<I, W> EntriesToBytesIter<I, W>
+where
+    I: Iterator<Item = Result<input::Entry, input::Error>>,
+    W: io::Read + io::Write + io::Seek,
+{
+    /// Create a new instance reading [entries][input::Entry] from an `input` iterator and write pack data bytes of `version` to
+    /// the empty `output`, using `hash_kind` for the pack checksum.
+    ///
+    /// # Panics
+    ///
+    /// Only pack data `version` 2 is supported currently, triggering an assertion error otherwise.
+    pub fn new(input: I, output: W, version: pack::data::Version, hash_kind: git_hash::Kind) -> Self {
+        assert!(
+            matches!(version, pack::data::Version::V2),
+            "currently only pack version 2 can be written",
+        );
+        EntriesToBytesIter {
+            input: input.peekable(),
+            output,
+            data_version: version,
+            hash_kind,
+            num_entries: 0,
+            header_written: false,
+            is_done: false,
+        }
+    }
+
+    /// Consume this instance and return the `output` implementation.
+    pub fn into_write(self) -> W {
+        self.output
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let header_bytes = pack::data::header::encode(self.data_version, self.num_entries);
+        self.output.seek(SeekFrom::Start(0))?;
+        self.output.write_all(&header_bytes[..])?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Write the final header and return the trailer after appending it to the pack.
+    fn write_header_and_trailer(&mut self) -> io::Result<git_hash::ObjectId> {
+        self.write_header()?;
+        self.output.seek(SeekFrom::Start(0))?;
+        let mut hasher = hash::Write::new(io::sink(), self.hash_kind);
+        io::copy(&mut self.output, &mut hasher)?;
+        let digest = hasher.hash.digest();
+        self.output.seek(SeekFrom::End(0))?;
+        self.output.write_all(digest.as_slice())?;
+        self.output.flush()?;
+        Ok(digest)
+    }
+
+    fn next_inner(&mut self, mut entry: input::Entry) -> Result<input::Entry, input::Error> {
+        if !self.header_written {
+            // The amount of entries isn't known yet, it's written once we are done.
+            self.write_header()?;
+        }
+        entry.header.to_write(entry.decompressed_size, &mut self.output)?;
+        self.output.write_all(
+            entry
+                .compressed
+                .as_deref()
+                .expect("compressed data to be kept to write entries"),
+        )?;
+        self.num_entries += 1;
+        entry.trailer = None;
+        if self.input.peek().is_none() {
+            entry.trailer = Some(self.write_header_and_trailer()?);
+        }
+        Ok(entry)
+    }
+}
+
+impl<I, W> Iterator for EntriesToBytesIter<I, W>
+where
+    I: Iterator<Item = Result<input::Entry, input::Error>>,
+    W: io::Read + io::Write + io::Seek,
+{
+    /// The entry just written to the output, with the trailer set if it was the last one.
+    type Item = Result<input::Entry, input::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+        let result = match self.input.next()? {
+            Ok(entry) => self.next_inner(entry),
+            Err(err) => Err(err),
+        };
+        self.is_done = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
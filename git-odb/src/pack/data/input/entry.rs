@@ -0,0 +1,73 @@
+use crate::{pack, pack::data::input};
+use std::io::Write;
+
+impl input::Entry {
+    /// Create a new input entry from a given data `obj` set to be placed at the given `pack_offset`.
+    ///
+    /// This method is useful when arbitrary base entries are created, for instance when resolving thin packs.
+    pub fn from_data_obj(obj: &crate::data::Object<'_>, pack_offset: u64) -> Result<Self, input::Error> {
+        let header = to_header(obj.kind);
+        let compressed = compress_data(obj)?;
+        let compressed_size = compressed.len() as u64;
+        let mut entry = input::Entry {
+            header,
+            header_size: header.size(obj.data.len() as u64) as u16,
+            pack_offset,
+            compressed: Some(compressed),
+            compressed_size,
+            crc32: None,
+            decompressed_size: obj.data.len() as u64,
+            trailer: None,
+        };
+        entry.crc32 = Some(entry.compute_crc32());
+        Ok(entry)
+    }
+
+    /// The amount of bytes this entry may consume in a pack data file
+    pub fn bytes_in_pack(&self) -> u64 {
+        self.header_size as u64 + self.compressed_size
+    }
+
+    /// Update our CRC value by recalculating it from our header and compressed data.
+    ///
+    /// # Panics
+    ///
+    /// If the compressed data isn't kept, which is the case if it wasn't configured using
+    /// [`EntryDataMode::Keep`][input::EntryDataMode::Keep] or [`EntryDataMode::KeepAndCrc32`][input::EntryDataMode::KeepAndCrc32].
+    pub fn compute_crc32(&self) -> u32 {
+        let mut header_buf = [0u8; pack::data::entry::MAX_HEADER_SIZE];
+        let header_len = self
+            .header
+            .to_write(self.decompressed_size, header_buf.as_mut())
+            .expect("write to memory will not fail");
+        let state = git_features::hash::crc32_update(0, &header_buf[..header_len]);
+        git_features::hash::crc32_update(
+            state,
+            self.compressed
+                .as_ref()
+                .expect("compressed data to be kept to compute a crc32"),
+        )
+    }
+}
+
+fn to_header(kind: git_object::Kind) -> pack::data::entry::Header {
+    use git_object::Kind::*;
+    match kind {
+        Tree => pack::data::entry::Header::Tree,
+        Blob => pack::data::entry::Header::Blob,
+        Commit => pack::data::entry::Header::Commit,
+        Tag => pack::data::entry::Header::Tag,
+    }
+}
+
+fn compress_data(obj: &crate::data::Object<'_>) -> Result<Vec<u8>, input::Error> {
+    let mut out = crate::zlib::stream::deflate::Write::new(Vec::new());
+    if let Err(err) = std::io::copy(&mut &*obj.data, &mut out) {
+        match err.kind() {
+            std::io::ErrorKind::Other => return Err(input::Error::Io(err)),
+            err => unreachable!("Should never see other errors than zlib, but got {:?}", err,),
+        }
+    };
+    out.flush()?;
+    Ok(out.into_inner())
+}
@@ -1,6 +1,5 @@
 use crate::{hash, pack, pack::data::input, zlib::stream::inflate::ReadBoxed};
 use flate2::Decompress;
-use git_features::hash::Sha1;
 use std::{fs, io};
 
 /// An iterator over [`Entries`][input::Entry] in a byte stream.
@@ -12,8 +11,9 @@ pub struct BytesToEntriesIter<R> {
     offset: u64,
     had_error: bool,
     kind: pack::data::Version,
+    hash_kind: git_hash::Kind,
     objects_left: u32,
-    hash: Option<Sha1>,
+    hash: Option<git_features::hash::Hasher>,
     mode: input::Mode,
     compressed: input::EntryDataMode,
     compressed_buf: Option<Vec<u8>>,
@@ -33,13 +33,20 @@ where
         self.mode
     }
 
+    /// The kind of hash used for the trailer and the base ids of ref-deltas.
+    pub fn hash_kind(&self) -> git_hash::Kind {
+        self.hash_kind
+    }
+
     /// Obtain an iterator from a `read` stream to a pack data file and configure it using `mode` and `compressed`.
+    /// `hash_kind` is the kind of hash of the repository the pack belongs to, used for its trailer and the base ids of ref-deltas.
     ///
     /// Note that `read` is expected at the beginning of a valid pack data file with a header, entries and a trailer.
     pub fn new_from_header(
         mut read: R,
         mode: input::Mode,
         compressed: input::EntryDataMode,
+        hash_kind: git_hash::Kind,
     ) -> Result<BytesToEntriesIter<R>, input::Error> {
         let mut header_data = [0u8; 12];
         read.read_exact(&mut header_data)?;
@@ -57,9 +64,10 @@ where
             offset: 12,
             had_error: false,
             kind,
+            hash_kind,
             objects_left: num_objects,
             hash: if mode != input::Mode::AsIs {
-                let mut hash = Sha1::default();
+                let mut hash = git_features::hash::hasher(hash_kind);
                 hash.update(&header_data);
                 Some(hash)
             } else {
@@ -83,11 +91,11 @@ where
                         hash,
                     },
                 );
-                let res = pack::data::Entry::from_read(&mut read, self.offset);
+                let res = pack::data::Entry::from_read(&mut read, self.offset, self.hash_kind);
                 self.hash = Some(read.write.hash);
                 res
             }
-            None => pack::data::Entry::from_read(&mut self.read, self.offset),
+            None => pack::data::Entry::from_read(&mut self.read, self.offset, self.hash_kind),
         }
         .map_err(input::Error::from)?;
 
@@ -134,7 +142,7 @@ where
         }
 
         let crc32 = if self.compressed.crc32() {
-            let mut header_buf = [0u8; 32];
+            let mut header_buf = [0u8; pack::data::entry::MAX_HEADER_SIZE];
             let header_len = entry.header.to_write(bytes_copied, header_buf.as_mut())?;
             let state = git_features::hash::crc32_update(0, &header_buf[..header_len]);
             Some(git_features::hash::crc32_update(state, &compressed))
@@ -152,7 +160,7 @@ where
 
         // Last objects gets trailer (which is potentially verified)
         let trailer = if self.objects_left == 0 {
-            let mut id = git_hash::ObjectId::from([0; 20]);
+            let mut id = self.hash_kind.null();
             if let Err(err) = self.read.read_exact(id.as_mut_slice()) {
                 if self.mode != input::Mode::Restore {
                     return Err(err.into());
@@ -160,7 +168,7 @@ where
             }
 
             if let Some(hash) = self.hash.take() {
-                let actual_id = git_hash::ObjectId::from(hash.digest());
+                let actual_id = hash.digest();
                 if self.mode == input::Mode::Restore {
                     id = actual_id;
                 }
@@ -174,7 +182,7 @@ where
             Some(id)
         } else if self.mode == input::Mode::Restore {
             let hash = self.hash.clone().expect("in restore mode a hash is set");
-            Some(git_hash::ObjectId::from(hash.digest()))
+            Some(hash.digest())
         } else {
             None
         };
@@ -268,6 +276,11 @@ impl pack::data::File {
     /// Returns an iterator over [`Entries`][pack::data::input::Entry], without making use of the memory mapping.
     pub fn streaming_iter(&self) -> Result<BytesToEntriesIter<impl io::BufRead>, input::Error> {
         let reader = io::BufReader::with_capacity(4096 * 8, fs::File::open(&self.path)?);
-        BytesToEntriesIter::new_from_header(reader, input::Mode::Verify, input::EntryDataMode::KeepAndCrc32)
+        BytesToEntriesIter::new_from_header(
+            reader,
+            input::Mode::Verify,
+            input::EntryDataMode::KeepAndCrc32,
+            self.hash_kind(),
+        )
     }
 }
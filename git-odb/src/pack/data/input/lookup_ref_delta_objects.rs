@@ -0,0 +1,142 @@
+use crate::{data, pack, pack::data::input};
+use git_hash::{oid, ObjectId};
+use std::collections::HashMap;
+
+/// An iterator to resolve thin packs on the fly by inserting the bases of deltas referring to objects outside of the pack
+/// right before the first delta using them.
+///
+/// Deltas referring to bases by id are turned into deltas referring to them by offset, and the pack offsets as well as
+/// the distance to the base of all deltas are adjusted to account for the inserted entries.
+///
+/// # Notes
+///
+/// * The compressed data of all entries needs to be kept, which is configured with [`EntryDataMode::KeepAndCrc32`][input::EntryDataMode::KeepAndCrc32].
+/// * The trailer of the last entry is passed through unchanged even though it doesn't match the modified pack anymore.
+///   Use [`EntriesToBytesIter`][input::EntriesToBytesIter] to write the entries and obtain the new trailer.
+/// * Bases of deltas referring to objects within the pack by id have to be found by the lookup as well, which
+///   typically isn't the case. These are rare as deltas usually refer to objects within the pack by offset.
+pub struct LookupRefDeltaObjectsIter<I, LFn> {
+    /// The inner iterator whose entries we will resolve.
+    pub inner: I,
+    lookup: LFn,
+    /// The pack offsets of all entries seen so far, as `(pack offset in the input, pack offset in the output)`.
+    offsets: Vec<(u64, u64)>,
+    /// The pack offsets in the output of all bases we inserted so far.
+    inserted_bases: HashMap<ObjectId, u64>,
+    /// The pack offset of the next entry in the output, or `None` if no entry was seen yet.
+    next_pack_offset: Option<u64>,
+    /// A delta to return once its newly inserted base was returned.
+    next_delta: Option<input::Entry>,
+    buf: Vec<u8>,
+    is_done: bool,
+}
+
+impl<I, LFn> LookupRefDeltaObjectsIter<I, LFn>
+where
+    I: Iterator<Item = Result<input::Entry, input::Error>>,
+    LFn: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<data::Object<'a>>,
+{
+    /// Create a new instance wrapping `iter` and using `lookup` as function to retrieve objects that will serve as bases
+    /// for deltas which refer to them by id.
+    pub fn new(iter: I, lookup: LFn) -> Self {
+        LookupRefDeltaObjectsIter {
+            inner: iter,
+            lookup,
+            offsets: Vec::new(),
+            inserted_bases: HashMap::new(),
+            next_pack_offset: None,
+            next_delta: None,
+            buf: Vec::new(),
+            is_done: false,
+        }
+    }
+
+    /// The amount of bases inserted into the pack so far.
+    pub fn num_inserted_objects(&self) -> usize {
+        self.inserted_bases.len()
+    }
+
+    /// Return the pack offset in the output of the entry that was at `pack_offset` in the input.
+    fn shifted_pack_offset(&self, pack_offset: u64) -> Option<u64> {
+        self.offsets
+            .binary_search_by_key(&pack_offset, |(input, _output)| *input)
+            .ok()
+            .map(|idx| self.offsets[idx].1)
+    }
+
+    fn next_inner(&mut self, mut entry: input::Entry) -> Result<input::Entry, input::Error> {
+        use pack::data::entry::Header;
+        let mut pack_offset = *self.next_pack_offset.get_or_insert(entry.pack_offset);
+        let mut inserted_base = None;
+        let base_pack_offset = match entry.header {
+            Header::Blob | Header::Tree | Header::Commit | Header::Tag => None,
+            // Invalid distances are kept, for the consumer to detect them.
+            Header::OfsDelta { base_distance } => Header::verified_base_pack_offset(entry.pack_offset, base_distance)
+                .and_then(|base_pack_offset| self.shifted_pack_offset(base_pack_offset)),
+            Header::RefDelta { base_id } => match self.inserted_bases.get(&base_id) {
+                Some(base_pack_offset) => Some(*base_pack_offset),
+                None => {
+                    let obj =
+                        (self.lookup)(&base_id, &mut self.buf).ok_or(input::Error::NotFound { object_id: base_id })?;
+                    let base = input::Entry::from_data_obj(&obj, pack_offset)?;
+                    self.inserted_bases.insert(base_id, pack_offset);
+                    let base_pack_offset = pack_offset;
+                    pack_offset += base.bytes_in_pack();
+                    inserted_base = Some(base);
+                    Some(base_pack_offset)
+                }
+            },
+        };
+
+        self.offsets.push((entry.pack_offset, pack_offset));
+        entry.pack_offset = pack_offset;
+        if let Some(base_pack_offset) = base_pack_offset {
+            let header = Header::OfsDelta {
+                base_distance: pack_offset - base_pack_offset,
+            };
+            if header != entry.header {
+                entry.header = header;
+                entry.header_size = header.size(entry.decompressed_size) as u16;
+                entry.crc32 = Some(entry.compute_crc32());
+            }
+        }
+        self.next_pack_offset = Some(pack_offset + entry.bytes_in_pack());
+
+        Ok(match inserted_base {
+            Some(base) => {
+                self.next_delta = Some(entry);
+                base
+            }
+            None => entry,
+        })
+    }
+}
+
+impl<I, LFn> Iterator for LookupRefDeltaObjectsIter<I, LFn>
+where
+    I: Iterator<Item = Result<input::Entry, input::Error>>,
+    LFn: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<data::Object<'a>>,
+{
+    type Item = Result<input::Entry, input::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(delta) = self.next_delta.take() {
+            return Some(Ok(delta));
+        }
+        if self.is_done {
+            return None;
+        }
+        let result = match self.inner.next()? {
+            Ok(entry) => self.next_inner(entry),
+            Err(err) => Err(err),
+        };
+        self.is_done = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.inner.size_hint();
+        let pending = self.next_delta.is_some() as usize;
+        (min + pending, max.map(|max| max * 2 + pending))
+    }
+}
@@ -33,3 +33,11 @@ pub use types::{EntryDataMode, Error, Mode};
 
 mod iter;
 pub use iter::BytesToEntriesIter;
+
+mod entry;
+
+mod lookup_ref_delta_objects;
+pub use lookup_ref_delta_objects::LookupRefDeltaObjectsIter;
+
+mod entries_to_bytes;
+pub use entries_to_bytes::EntriesToBytesIter;
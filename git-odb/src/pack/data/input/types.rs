@@ -17,6 +17,8 @@ pub enum Error {
     },
     #[error("pack is incomplete: it was decompressed into {actual} bytes but {expected} bytes where expected.")]
     IncompletePack { actual: u64, expected: u64 },
+    #[error("The object {object_id} could not be found to serve as base of a delta in a thin pack")]
+    NotFound { object_id: git_hash::ObjectId },
 }
 
 /// Iteration Mode
@@ -26,6 +26,10 @@ pub struct EntriesToBytesIter<I, W> {
     header_info: Option<(pack::data::Version, u32)>,
     /// The pack data version with which pack entries should be written.
     entry_version: pack::data::Version,
+    /// The amount of bytes written so far.
+    written: u64,
+    /// The pack offsets of all entries written so far, to compute the base distance of entries which are deltas.
+    pack_offsets: Vec<u64>,
     /// If we are done, no additional writes will occour
     is_done: bool,
 }
@@ -42,21 +46,19 @@ where
     ///
     /// # Panics
     ///
-    /// Not all combinations of `hash_kind` and `version` are supported currently triggering assertion errors.
+    /// Not all `version`s are supported currently triggering assertion errors.
     pub fn new(input: I, output: W, num_entries: u32, version: pack::data::Version, hash_kind: git_hash::Kind) -> Self {
         assert!(
             matches!(version, pack::data::Version::V2),
             "currently only pack version 2 can be written",
         );
-        assert!(
-            matches!(hash_kind, git_hash::Kind::Sha1),
-            "currently only Sha1 is supported",
-        );
         EntriesToBytesIter {
             input,
             output: hash::Write::new(output, hash_kind),
             entry_version: version,
             header_info: Some((version, num_entries)),
+            written: 0,
+            pack_offsets: Vec::with_capacity(num_entries as usize),
             is_done: false,
         }
     }
@@ -78,21 +80,30 @@ where
         match self.input.next() {
             Some(entries) => {
                 for entry in entries.map_err(Error::Input)? {
-                    let header = entry.to_entry_header(self.entry_version, |_index_offset| {
-                        unimplemented!("a way to calculate pack offsets from object index offsets")
+                    let pack_offset = self.written + written;
+                    let pack_offsets = &self.pack_offsets;
+                    let header = entry.to_entry_header(self.entry_version, |nth_before| {
+                        let base_offset = pack_offsets
+                            .len()
+                            .checked_sub(nth_before)
+                            .and_then(|idx| pack_offsets.get(idx))
+                            .expect("the base of a delta to be written before it");
+                        pack_offset - base_offset
                     });
+                    self.pack_offsets.push(pack_offset);
                     written += header.to_write(entry.decompressed_size as u64, &mut self.output)? as u64;
                     written += std::io::copy(&mut &*entry.compressed_data, &mut self.output)? as u64;
                 }
             }
             None => {
                 let digest = self.output.hash.clone().digest();
-                self.output.write_all(&digest[..])?;
-                written += digest.len() as u64;
+                self.output.write_all(digest.as_slice())?;
+                written += digest.as_slice().len() as u64;
                 self.output.flush()?;
                 self.is_done = true;
             }
         };
+        self.written += written;
         Ok(written)
     }
 }
@@ -42,17 +42,25 @@ impl output::Entry {
             object_kind: obj.kind,
             entry_kind: Kind::Base,
             decompressed_size: obj.data.len(),
-            compressed_data: {
-                let mut out = crate::zlib::stream::deflate::Write::new(Vec::new());
-                if let Err(err) = std::io::copy(&mut &*obj.data, &mut out) {
-                    match err.kind() {
-                        std::io::ErrorKind::Other => return Err(Error::ZlibDeflate(err)),
-                        err => unreachable!("Should never see other errors than zlib, but got {:?}", err,),
-                    }
-                };
-                out.flush()?;
-                out.into_inner()
-            },
+            compressed_data: deflate(obj.data)?,
+        })
+    }
+
+    /// Create a new delta instance of `entry_kind` for the object with `oid` of kind `object_kind`, with `delta` being
+    /// the uncompressed delta instructions to recreate it from its base object.
+    pub(crate) fn from_delta(
+        oid: impl Into<ObjectId>,
+        object_kind: git_object::Kind,
+        entry_kind: Kind,
+        delta: &[u8],
+    ) -> Result<Self, Error> {
+        debug_assert!(!matches!(entry_kind, Kind::Base), "deltas need a base");
+        Ok(output::Entry {
+            id: oid.into(),
+            object_kind,
+            entry_kind,
+            decompressed_size: delta.len(),
+            compressed_data: deflate(delta)?,
         })
     }
 
@@ -89,3 +97,15 @@ impl output::Entry {
         }
     }
 }
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = crate::zlib::stream::deflate::Write::new(Vec::new());
+    if let Err(err) = std::io::copy(&mut &*data, &mut out) {
+        match err.kind() {
+            std::io::ErrorKind::Other => return Err(Error::ZlibDeflate(err)),
+            err => unreachable!("Should never see other errors than zlib, but got {:?}", err,),
+        }
+    };
+    out.flush()?;
+    Ok(out.into_inner())
+}
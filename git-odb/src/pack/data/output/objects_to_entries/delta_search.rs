@@ -0,0 +1,468 @@
+use super::{
+    base_entry,
+    expand::{self, Expander},
+    util, verify_crc32, DeltaSearch, Error, Options,
+};
+use crate::{
+    pack,
+    pack::data::{delta, entry::Header, output},
+};
+use git_hash::{oid, ObjectId};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+};
+
+/// The base of a delta as stored in a pack.
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum PackedBase {
+    Offset { pack_id: u32, pack_offset: u64 },
+    Id(ObjectId),
+}
+
+impl PackedBase {
+    fn from_entry(entry: &crate::find::PackEntry<'_>) -> Option<Self> {
+        match pack::data::Entry::from_bytes(entry.data, entry.pack_offset).header {
+            Header::OfsDelta { base_distance } => Header::verified_base_pack_offset(entry.pack_offset, base_distance)
+                .map(|pack_offset| PackedBase::Offset {
+                    pack_id: entry.pack_id,
+                    pack_offset,
+                }),
+            Header::RefDelta { base_id } => Some(PackedBase::Id(base_id)),
+            Header::Commit | Header::Tree | Header::Blob | Header::Tag => None,
+        }
+    }
+}
+
+/// Everything we need to know about an input object to sort it and to decide if its packed delta can be reused.
+struct Candidate {
+    id: ObjectId,
+    kind: git_object::Kind,
+    size: usize,
+    /// The hash of the path at which the object was encountered, or 0 if unknown.
+    name_hash: u32,
+    /// The pack id and offset of the object's entry, if it is packed.
+    location: Option<(u32, u64)>,
+    /// The base of the delta the object is stored as, if it can be reused.
+    packed_base: Option<PackedBase>,
+    /// If true, the object is known to the receiver and may only serve as base for deltas in a thin pack.
+    is_thin_base: bool,
+}
+
+/// An object within a [`Segment`].
+struct Planned {
+    id: ObjectId,
+    /// The position of the base object within the segment and the base as stored in the pack if the packed delta
+    /// should be reused, or `None` if the object is subject to delta search.
+    reuse: Option<(usize, PackedBase)>,
+    /// If false, the object must not become a delta as reused deltas depend on it.
+    may_be_delta: bool,
+    /// If true, the object is not written but serves as base for deltas in a thin pack.
+    is_thin_base: bool,
+}
+
+/// A contiguous run of sorted objects of the same kind to search deltas in, followed by all objects whose reused
+/// deltas depend on them. Deltas never refer to objects outside of their segment, except for thin pack bases.
+pub struct Segment {
+    objects: Vec<Planned>,
+    num_searched: usize,
+    window: usize,
+    depth: usize,
+}
+
+/// An object within the sliding window of delta search.
+struct WindowEntry {
+    pos: usize,
+    kind: git_object::Kind,
+    depth: usize,
+    data: Vec<u8>,
+    index: Option<delta::BaseIndex>,
+}
+
+impl Segment {
+    /// Turn all objects of this segment into entries, appending them to `out` in order.
+    pub fn into_entries<Locate>(
+        self,
+        db: &Locate,
+        version: pack::data::Version,
+        buf: &mut Vec<u8>,
+        cache: &mut impl pack::cache::DecodeEntry,
+        out: &mut Vec<output::Entry>,
+    ) -> Result<(), Error<Locate::Error>>
+    where
+        Locate: crate::Find,
+    {
+        let mut window = VecDeque::<WindowEntry>::with_capacity(self.window + 1);
+        let mut out_index = Vec::with_capacity(self.objects.len());
+        let delta_kind = |base: &Planned, base_out_index: Option<usize>, out: &[output::Entry]| {
+            if base.is_thin_base {
+                output::entry::Kind::DeltaOid { id: base.id }
+            } else {
+                output::entry::Kind::DeltaRef {
+                    nth_before: out.len() - base_out_index.expect("bases are written before their deltas"),
+                }
+            }
+        };
+        for (pos, object) in self.objects.iter().enumerate().take(self.num_searched) {
+            let obj = db
+                .find(object.id, buf, cache)?
+                .ok_or_else(|| Error::NotFound { oid: object.id })?;
+            let target = obj.data;
+
+            let mut best: Option<(usize, usize, Vec<u8>)> = None;
+            if object.may_be_delta {
+                for base in window.iter_mut().rev() {
+                    if base.kind != obj.kind || base.depth >= self.depth {
+                        continue;
+                    }
+                    // Like git, don't bother with bases much larger than the target.
+                    if target.len() < base.data.len() / 32 {
+                        continue;
+                    }
+                    let max_size = match &best {
+                        Some((_, _, delta)) => delta.len() - 1,
+                        None => (target.len() / 2).saturating_sub(git_hash::SIZE_OF_SHA1_DIGEST),
+                    };
+                    if max_size == 0 {
+                        break;
+                    }
+                    if target.len().saturating_sub(base.data.len()) >= max_size {
+                        continue;
+                    }
+                    let base_data = &base.data;
+                    let index = base.index.get_or_insert_with(|| delta::BaseIndex::new(base_data));
+                    if let Some(delta) = index.encode(base_data, target, max_size) {
+                        best = Some((base.pos, base.depth, delta));
+                    }
+                }
+            }
+
+            let depth = match best {
+                Some((base_pos, base_depth, delta)) => {
+                    let entry_kind = delta_kind(&self.objects[base_pos], out_index[base_pos], out);
+                    out_index.push(Some(out.len()));
+                    out.push(
+                        output::Entry::from_delta(object.id, obj.kind, entry_kind, &delta).map_err(Error::NewEntry)?,
+                    );
+                    base_depth + 1
+                }
+                None if object.is_thin_base => {
+                    out_index.push(None);
+                    0
+                }
+                None => {
+                    out_index.push(Some(out.len()));
+                    out.push(base_entry(db, &object.id, &obj, version)?);
+                    0
+                }
+            };
+            if self.window != 0 {
+                if window.len() == self.window {
+                    window.pop_front();
+                }
+                window.push_back(WindowEntry {
+                    pos,
+                    kind: obj.kind,
+                    depth,
+                    data: target.to_owned(),
+                    index: None,
+                });
+            }
+        }
+        drop(window);
+
+        for object in self.objects.iter().skip(self.num_searched) {
+            let (base_pos, expected_base) = object.reuse.expect("only objects reusing deltas follow searched ones");
+            let obj = db
+                .find(object.id, buf, cache)?
+                .ok_or_else(|| Error::NotFound { oid: object.id })?;
+            let reused = match db.pack_entry(&obj) {
+                Some(entry) if entry.version == version && PackedBase::from_entry(&entry) == Some(expected_base) => {
+                    verify_crc32(&entry)?;
+                    let pack_entry = pack::data::Entry::from_bytes(entry.data, entry.pack_offset);
+                    Some(output::Entry {
+                        id: object.id,
+                        object_kind: obj.kind,
+                        entry_kind: delta_kind(&self.objects[base_pos], out_index[base_pos], out),
+                        decompressed_size: pack_entry.decompressed_size as usize,
+                        compressed_data: entry.data[pack_entry.header_size()..].to_owned(),
+                    })
+                }
+                _ => None,
+            };
+            out_index.push(Some(out.len()));
+            out.push(match reused {
+                Some(entry) => entry,
+                None => base_entry(db, &object.id, &obj, version)?,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// An iterator which looks up all input objects on first use to plan the delta search, and returns the
+/// [segments][Segment] to search deltas in thereafter.
+pub struct Segments<Locate, MakeCache, Iter> {
+    input: Option<(Locate, MakeCache, Iter)>,
+    options: Options,
+    delta_search: DeltaSearch,
+    num_threads: usize,
+    segments: std::vec::IntoIter<Segment>,
+}
+
+impl<Locate, MakeCache, Iter> Segments<Locate, MakeCache, Iter> {
+    /// Create a new instance to find `objects` in `db` using a cache created by `make_cache`, expanding them as
+    /// configured in `options`, whose `chunk_size` is the minimal length of segments.
+    pub fn new(
+        (db, make_cache, objects): (Locate, MakeCache, Iter),
+        options: Options,
+        delta_search: DeltaSearch,
+        num_threads: usize,
+    ) -> Self {
+        Segments {
+            input: Some((db, make_cache, objects)),
+            options,
+            delta_search,
+            num_threads,
+            segments: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<Locate, MakeCache, Cache, Iter, Oid> Iterator for Segments<Locate, MakeCache, Iter>
+where
+    Locate: crate::Find,
+    MakeCache: Fn() -> Cache,
+    Cache: pack::cache::DecodeEntry,
+    Iter: Iterator<Item = Oid>,
+    Oid: AsRef<oid>,
+{
+    type Item = Result<util::Chunk<Oid>, Error<Locate::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((db, make_cache, objects)) = self.input.take() {
+            match self.plan(&db, &mut make_cache(), objects) {
+                Ok(segments) => self.segments = segments.into_iter(),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        self.segments.next().map(|segment| Ok(util::Chunk::Segment(segment)))
+    }
+}
+
+impl<Locate, MakeCache, Iter> Segments<Locate, MakeCache, Iter> {
+    fn plan<Oid: AsRef<oid>>(
+        &self,
+        db: &Locate,
+        cache: &mut impl pack::cache::DecodeEntry,
+        objects: impl Iterator<Item = Oid>,
+    ) -> Result<Vec<Segment>, Error<Locate::Error>>
+    where
+        Locate: crate::Find,
+    {
+        let DeltaSearch {
+            window,
+            depth,
+            reuse_deltas,
+        } = self.delta_search;
+        let Options {
+            version,
+            input_object_expansion: expansion,
+            ref thin_pack_bases,
+            ref excluded_objects,
+            ..
+        } = self.options;
+        let mut buf = Vec::new();
+        let mut candidates = Vec::new();
+        {
+            let mut seen: HashSet<_> = excluded_objects.iter().copied().collect();
+            let mut expander = Expander::default();
+            let mut expanded = Vec::new();
+            for id in objects {
+                expanded.clear();
+                expander.expand(
+                    db,
+                    cache,
+                    id.as_ref(),
+                    expansion,
+                    &mut |id| expand::is_new(&mut seen, id, expansion),
+                    &mut expanded,
+                )?;
+                for (id, name_hash) in expanded.drain(..) {
+                    let obj = db.find(id, &mut buf, cache)?.ok_or(Error::NotFound { oid: id })?;
+                    let entry = db.pack_entry(&obj);
+                    candidates.push(Candidate {
+                        id,
+                        kind: obj.kind,
+                        size: obj.data.len(),
+                        name_hash,
+                        location: entry.as_ref().map(|e| (e.pack_id, e.pack_offset)),
+                        packed_base: entry
+                            .filter(|e| reuse_deltas && e.version == version)
+                            .and_then(|e| PackedBase::from_entry(&e)),
+                        is_thin_base: false,
+                    });
+                }
+            }
+        }
+        if !thin_pack_bases.is_empty() {
+            let mut seen: HashSet<_> = candidates.iter().map(|c| c.id).collect();
+            for id in thin_pack_bases {
+                if !seen.insert(*id) {
+                    continue;
+                }
+                // The receiver has the object, we only use it if we have it too.
+                let obj = match db.find(id, &mut buf, cache)? {
+                    Some(obj) => obj,
+                    None => continue,
+                };
+                let entry = db.pack_entry(&obj);
+                candidates.push(Candidate {
+                    id: *id,
+                    kind: obj.kind,
+                    size: obj.data.len(),
+                    name_hash: 0,
+                    location: entry.as_ref().map(|e| (e.pack_id, e.pack_offset)),
+                    packed_base: None,
+                    is_thin_base: true,
+                });
+            }
+        }
+
+        let reuse_base = {
+            let by_location: HashMap<_, _> = candidates
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, c)| c.location.map(|location| (location, idx)))
+                .collect();
+            let by_id: HashMap<_, _> = candidates.iter().enumerate().map(|(idx, c)| (c.id, idx)).collect();
+            let mut reuse_base: Vec<Option<usize>> = candidates
+                .iter()
+                .enumerate()
+                .map(|(idx, c)| {
+                    c.packed_base
+                        .and_then(|base| match base {
+                            PackedBase::Offset { pack_id, pack_offset } => by_location.get(&(pack_id, pack_offset)),
+                            PackedBase::Id(id) => by_id.get(&id),
+                        })
+                        .copied()
+                        .filter(|base_idx| *base_idx != idx)
+                })
+                .collect();
+            // Break chains that are too long, which also breaks cycles that can be formed by ref-deltas across packs.
+            // Breaking a chain only shortens others, so a single pass suffices.
+            for idx in 0..reuse_base.len() {
+                let mut chain_len = 0;
+                let mut cur = idx;
+                while let Some(base_idx) = reuse_base[cur] {
+                    chain_len += 1;
+                    if chain_len > depth {
+                        break;
+                    }
+                    cur = base_idx;
+                }
+                if chain_len > depth {
+                    reuse_base[idx] = None;
+                }
+            }
+            reuse_base
+        };
+
+        let mut may_be_delta = vec![true; candidates.len()];
+        let mut dependents = HashMap::<usize, Vec<(usize, usize)>>::new();
+        for (idx, base_idx) in reuse_base.iter().enumerate() {
+            if let Some(base_idx) = base_idx {
+                may_be_delta[*base_idx] = false;
+                let mut chain_len = 1;
+                let mut root = *base_idx;
+                while let Some(base_idx) = reuse_base[root] {
+                    chain_len += 1;
+                    root = base_idx;
+                }
+                dependents.entry(root).or_default().push((chain_len, idx));
+            }
+        }
+
+        let mut searched: Vec<usize> = (0..candidates.len()).filter(|idx| reuse_base[*idx].is_none()).collect();
+        searched.sort_by_key(|idx| {
+            let c = &candidates[*idx];
+            (
+                Reverse(kind_order(c.kind)),
+                Reverse(c.name_hash),
+                Reverse(c.is_thin_base),
+                Reverse(c.size),
+                *idx,
+            )
+        });
+
+        let min_segment_len = self.options.chunk_size.max(window * 2).max(1);
+        let mut segments = Vec::new();
+        let mut rest = &searched[..];
+        while let Some(first) = rest.first() {
+            let kind = candidates[*first].kind;
+            let kind_len = rest
+                .iter()
+                .position(|idx| candidates[*idx].kind != kind)
+                .unwrap_or(rest.len());
+            let segment_len = (kind_len / self.num_threads.max(1)).max(min_segment_len);
+            let (of_kind, remaining) = rest.split_at(kind_len);
+            for searched in of_kind.chunks(segment_len) {
+                let mut pos_by_idx = HashMap::new();
+                let mut objects: Vec<_> = searched
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, idx)| {
+                        pos_by_idx.insert(*idx, pos);
+                        Planned {
+                            id: candidates[*idx].id,
+                            reuse: None,
+                            may_be_delta: may_be_delta[*idx] && !candidates[*idx].is_thin_base,
+                            is_thin_base: candidates[*idx].is_thin_base,
+                        }
+                    })
+                    .collect();
+                for root in searched {
+                    if let Some(mut dependents) = dependents.remove(root) {
+                        // Bases have shorter chains than their deltas, and thus are written first.
+                        dependents.sort_unstable();
+                        for (_, idx) in dependents {
+                            let base_idx = reuse_base[idx].expect("dependents reuse deltas");
+                            pos_by_idx.insert(idx, objects.len());
+                            objects.push(Planned {
+                                id: candidates[idx].id,
+                                reuse: Some((
+                                    pos_by_idx[&base_idx],
+                                    candidates[idx].packed_base.expect("reused deltas are packed"),
+                                )),
+                                may_be_delta: false,
+                                is_thin_base: false,
+                            });
+                        }
+                    }
+                }
+                if objects.iter().all(|o| o.is_thin_base) {
+                    continue;
+                }
+                segments.push(Segment {
+                    objects,
+                    num_searched: searched.len(),
+                    window,
+                    depth,
+                });
+            }
+            rest = remaining;
+        }
+        Ok(segments)
+    }
+}
+
+/// The order of kinds used by git when sorting objects for delta search, derived from their type ids in packs.
+fn kind_order(kind: git_object::Kind) -> u8 {
+    use git_object::Kind::*;
+    match kind {
+        Commit => 1,
+        Tree => 2,
+        Blob => 3,
+        Tag => 4,
+    }
+}
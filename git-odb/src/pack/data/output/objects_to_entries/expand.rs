@@ -0,0 +1,287 @@
+use super::{Error, ObjectExpansion};
+use crate::pack;
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    immutable::{self, commit::iter::Token},
+    tree,
+};
+use std::collections::HashSet;
+
+/// Return true if the object with `id` should be written into the pack, recording it in `seen` as needed by `expansion`.
+///
+/// Objects given as-is are not deduplicated, but like all others they are not written if they are part of `seen` already.
+pub fn is_new(seen: &mut HashSet<ObjectId>, id: &oid, expansion: ObjectExpansion) -> bool {
+    match expansion {
+        ObjectExpansion::AsIs => !seen.contains(id),
+        ObjectExpansion::TreeContents | ObjectExpansion::TreeAdditionsComparedToAncestor => seen.insert(id.to_owned()),
+    }
+}
+
+/// Compute a hash of `path` which sorts objects found at similar paths next to each other, with the file
+/// extension weighing the most, similar to `pack_name_hash()` in git.
+pub fn name_hash(path: &BStr) -> u32 {
+    path.iter()
+        .filter(|b| !matches!(b, b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r'))
+        .fold(0u32, |hash, b| (hash >> 2).wrapping_add(u32::from(*b) << 24))
+}
+
+/// State to expand input objects into all objects to pack, reusable across multiple expansions.
+#[derive(Default)]
+pub struct Expander {
+    buf: Vec<u8>,
+    other_buf: Vec<u8>,
+    pending: Vec<ObjectId>,
+    traverse: git_traverse::tree::breadthfirst::State<BString>,
+    changes: git_diff::tree::State<BString>,
+}
+
+impl Expander {
+    /// Expand the object with `id` as configured by `expansion`, pushing the ids of all objects to write along with
+    /// the [hash of their path][name_hash()] into `out`.
+    ///
+    /// `is_new(id)` is called once per encountered object to learn if it should be written, and trees which
+    /// aren't new aren't traversed either.
+    pub fn expand<Locate>(
+        &mut self,
+        db: &Locate,
+        cache: &mut impl pack::cache::DecodeEntry,
+        id: &oid,
+        expansion: ObjectExpansion,
+        is_new: &mut dyn FnMut(&oid) -> bool,
+        out: &mut Vec<(ObjectId, u32)>,
+    ) -> Result<(), Error<Locate::Error>>
+    where
+        Locate: crate::Find,
+    {
+        use git_object::Kind::*;
+        self.pending.clear();
+        self.pending.push(id.to_owned());
+        while let Some(id) = self.pending.pop() {
+            if !is_new(&id) {
+                continue;
+            }
+            out.push((id, 0));
+            if let ObjectExpansion::AsIs = expansion {
+                continue;
+            }
+            let obj = db.find(id, &mut self.buf, cache)?.ok_or(Error::NotFound { oid: id })?;
+            match obj.kind {
+                Blob => {}
+                Tag => {
+                    let target = immutable::Tag::from_bytes(obj.data)
+                        .map_err(Error::ObjectDecode)?
+                        .target();
+                    self.pending.push(target);
+                }
+                Tree => self.traverse_tree(db, cache, id, is_new, out)?,
+                Commit => {
+                    let (tree_id, parents) = commit_tree_and_parents(obj.data)?;
+                    if !is_new(&tree_id) {
+                        continue;
+                    }
+                    out.push((tree_id, 0));
+                    match expansion {
+                        ObjectExpansion::TreeAdditionsComparedToAncestor if !parents.is_empty() => {
+                            for parent in parents {
+                                let parent_tree_id = {
+                                    let parent = db
+                                        .find(parent, &mut self.buf, cache)?
+                                        .ok_or(Error::NotFound { oid: parent })?;
+                                    commit_tree_and_parents(parent.data)?.0
+                                };
+                                self.add_changed_objects(db, cache, parent_tree_id, tree_id, is_new, out)?;
+                            }
+                        }
+                        _ => self.traverse_tree(db, cache, tree_id, is_new, out)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn traverse_tree<Locate>(
+        &mut self,
+        db: &Locate,
+        cache: &mut impl pack::cache::DecodeEntry,
+        tree_id: ObjectId,
+        is_new: &mut dyn FnMut(&oid) -> bool,
+        out: &mut Vec<(ObjectId, u32)>,
+    ) -> Result<(), Error<Locate::Error>>
+    where
+        Locate: crate::Find,
+    {
+        git_traverse::tree::breadthfirst(
+            tree_id,
+            &mut self.traverse,
+            |id, buf| {
+                db.find(id, buf, cache)
+                    .ok()
+                    .flatten()
+                    .and_then(|obj| obj.into_tree_iter())
+            },
+            &mut Delegate {
+                path: BString::default(),
+                is_new,
+                out,
+            },
+        )
+        .map_err(Error::TreeTraverse)
+    }
+
+    /// Add all objects which are added or modified in the tree `rhs` compared to the tree `lhs`.
+    fn add_changed_objects<Locate>(
+        &mut self,
+        db: &Locate,
+        cache: &mut impl pack::cache::DecodeEntry,
+        lhs: ObjectId,
+        rhs: ObjectId,
+        is_new: &mut dyn FnMut(&oid) -> bool,
+        out: &mut Vec<(ObjectId, u32)>,
+    ) -> Result<(), Error<Locate::Error>>
+    where
+        Locate: crate::Find,
+    {
+        let lhs = db
+            .find(lhs, &mut self.buf, cache)?
+            .and_then(|obj| obj.into_tree_iter())
+            .ok_or(Error::NotFound { oid: lhs })?;
+        let rhs = db
+            .find(rhs, &mut self.other_buf, cache)?
+            .and_then(|obj| obj.into_tree_iter())
+            .ok_or(Error::NotFound { oid: rhs })?;
+        git_diff::tree::Changes::from(lhs)
+            .needed_to_obtain(
+                rhs,
+                &mut self.changes,
+                |id, buf| {
+                    db.find(id, buf, cache)
+                        .ok()
+                        .flatten()
+                        .and_then(|obj| obj.into_tree_iter())
+                },
+                &mut Delegate {
+                    path: BString::default(),
+                    is_new,
+                    out,
+                },
+            )
+            .map_err(Error::TreeChanges)
+    }
+}
+
+fn commit_tree_and_parents<E>(data: &[u8]) -> Result<(ObjectId, Vec<ObjectId>), Error<E>>
+where
+    E: std::error::Error + 'static,
+{
+    let mut tree_id = None;
+    let mut parents = Vec::new();
+    for token in immutable::CommitIter::from_bytes(data) {
+        match token.map_err(Error::ObjectDecode)? {
+            Token::Tree { id } => tree_id = Some(id),
+            Token::Parent { id } => parents.push(id),
+            _ => break,
+        }
+    }
+    Ok((tree_id.expect("commits always have a tree"), parents))
+}
+
+/// Observes tree entries or changes to trees, keeping track of the path to them.
+struct Delegate<'a> {
+    path: BString,
+    is_new: &'a mut dyn FnMut(&oid) -> bool,
+    out: &'a mut Vec<(ObjectId, u32)>,
+}
+
+impl<'a> Delegate<'a> {
+    fn push_element(&mut self, name: &BStr) {
+        if !self.path.is_empty() {
+            self.path.push(b'/');
+        }
+        self.path.push_str(name);
+    }
+
+    fn pop_element(&mut self) {
+        match self.path.rfind_byte(b'/') {
+            Some(pos) => self.path.resize(pos, 0),
+            None => self.path.clear(),
+        }
+    }
+
+    /// Record the object with `id` at the current path if it is new and return true, or false otherwise.
+    fn add(&mut self, id: &oid, mode: tree::EntryMode) -> bool {
+        // Submodule commits aren't part of the repository.
+        if mode == tree::EntryMode::Commit || !(self.is_new)(id) {
+            return false;
+        }
+        self.out.push((id.to_owned(), name_hash(self.path.as_ref())));
+        true
+    }
+}
+
+impl<'a> git_traverse::tree::visit::Visit for Delegate<'a> {
+    type PathId = BString;
+
+    fn set_current_path(&mut self, path: Self::PathId) {
+        self.path = path;
+    }
+
+    fn push_tracked_path_component(&mut self, component: &BStr) -> Self::PathId {
+        self.push_element(component);
+        self.path.clone()
+    }
+
+    fn push_path_component(&mut self, component: &BStr) {
+        self.push_element(component);
+    }
+
+    fn pop_path_component(&mut self) {
+        self.pop_element();
+    }
+
+    fn visit_tree(&mut self, entry: &immutable::tree::Entry<'_>) -> git_traverse::tree::visit::Action {
+        if self.add(entry.oid, entry.mode) {
+            git_traverse::tree::visit::Action::Continue
+        } else {
+            git_traverse::tree::visit::Action::Skip
+        }
+    }
+
+    fn visit_nontree(&mut self, entry: &immutable::tree::Entry<'_>) -> git_traverse::tree::visit::Action {
+        self.add(entry.oid, entry.mode);
+        git_traverse::tree::visit::Action::Continue
+    }
+}
+
+impl<'a> git_diff::tree::Visit for Delegate<'a> {
+    type PathId = BString;
+
+    fn set_current_path(&mut self, path: Self::PathId) {
+        self.path = path;
+    }
+
+    fn push_tracked_path_component(&mut self, component: &BStr) -> Self::PathId {
+        self.push_element(component);
+        self.path.clone()
+    }
+
+    fn push_path_component(&mut self, component: &BStr) {
+        self.push_element(component);
+    }
+
+    fn pop_path_component(&mut self) {
+        self.pop_element();
+    }
+
+    fn visit(&mut self, change: git_diff::tree::visit::Change) -> git_diff::tree::visit::Action {
+        use git_diff::tree::visit::Change::*;
+        match change {
+            Addition { entry_mode, oid } | Modification { entry_mode, oid, .. } => {
+                self.add(&oid, entry_mode);
+            }
+            Deletion { .. } => {}
+        }
+        git_diff::tree::visit::Action::Continue
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{pack, pack::data::output};
+use git_features::{hash, parallel, progress::Progress};
+use git_hash::oid;
+use std::{collections::HashSet, sync::Arc};
+
+/// Write all `objects` into `out`, optionally searching for deltas between them as configured by
+/// [`Options::delta_search`].
+/// Without delta search, objects are written rather immediately.
+/// Objects are held in memory and compressed using DEFLATE, with those in-flight chunks of compressed
+/// objects being sent to the current thread for writing. No buffering of these objects is performed,
+/// allowing for natural back-pressure in case of slow writers.
+///
+/// * `objects`
+///   * the list of objects to write, to be expanded as configured by [`Options::input_object_expansion`].
+/// * `progress`
+///   * a way to obtain progress information
+/// * `options`
+///   * more configuration
+///
+/// _Returns_ the checksum of the pack
+///
+/// ## Discussion
+///
+/// ### Advantages
+///
+/// * Begins writing immediately and supports back-pressure.
+/// * Abstract over object databases and how input is provided.
+///
+/// ### Disadvantages
+///
+/// * Thin packs are only produced if [`Options::thin_pack_bases`] and [`Options::delta_search`] are set, and the caller
+///   has to determine which objects the receiver has.
+/// * ~~currently there is no way to easily write the pack index, even though the state here is uniquely positioned to do
+///   so with minimal overhead (especially compared to `gixp index-from-pack`)~~ Probably works now by chaining Iterators
+///  or keeping enough state to write a pack and then generate an index with recorded data.
+/// * With delta search enabled, all objects have to be looked up once before the first entry is produced, to be able
+///   to sort them. Only their kind, size and location are kept in memory.
+///
+pub fn objects_to_entries_iter<Locate, Iter, Oid, Cache>(
+    db: Locate,
+    make_cache: impl Fn() -> Cache + Send + Clone + Sync + 'static,
+    objects: Iter,
+    _progress: impl Progress,
+    options: Options,
+) -> impl Iterator<Item = Result<Vec<output::Entry>, Error<Locate::Error>>>
+       + parallel::reduce::Finalize<
+    Reduce = parallel::reduce::IdentityWithResult<Vec<output::Entry>, Error<Locate::Error>>,
+>
+where
+    Locate: crate::Find + Clone + Send + Sync + 'static,
+    <Locate as crate::Find>::Error: Send,
+    Iter: Iterator<Item = Oid> + Send + 'static,
+    Oid: AsRef<oid> + Send + 'static,
+    Cache: pack::cache::DecodeEntry,
+{
+    let Options {
+        version,
+        thread_limit,
+        input_object_expansion,
+        chunk_size,
+        delta_search,
+        thin_pack_bases: _,
+        ref excluded_objects,
+    } = options;
+    assert!(
+        matches!(version, pack::data::Version::V2),
+        "currently we can only write version 2"
+    );
+    let lower_bound = objects.size_hint().0;
+    let (chunk_size, thread_limit, num_threads) = parallel::optimize_chunk_size_and_thread_limit(
+        chunk_size,
+        if lower_bound == 0 { None } else { Some(lower_bound) },
+        thread_limit,
+        None,
+    );
+    let seen = Arc::new(parking_lot::Mutex::new(
+        excluded_objects.iter().copied().collect::<HashSet<_>>(),
+    ));
+    type Chunks<Oid, E> = Box<dyn Iterator<Item = Result<util::Chunk<Oid>, Error<E>>> + Send>;
+    let chunks: Chunks<Oid, Locate::Error> = match delta_search {
+        None => Box::new(
+            util::Chunks {
+                iter: objects,
+                size: chunk_size,
+            }
+            .map(|oids| Ok(util::Chunk::Objects(oids))),
+        ),
+        Some(delta_search) => Box::new(delta_search::Segments::new(
+            (db.clone(), make_cache.clone(), objects),
+            Options { chunk_size, ..options },
+            delta_search,
+            num_threads,
+        )),
+    };
+
+    parallel::reduce::Stepwise::new(
+        chunks,
+        thread_limit,
+        move |_n| {
+            (
+                Vec::new(),                  // object locate buffer
+                make_cache(),                // cache to speed up pack operations
+                expand::Expander::default(), // state for expanding input objects
+                Vec::new(),                  // expanded objects
+            )
+        },
+        move |chunk: Result<util::Chunk<Oid>, Error<Locate::Error>>, (buf, cache, expander, expanded)| {
+            let mut out = Vec::new();
+            match chunk? {
+                util::Chunk::Objects(oids) => {
+                    for id in oids.into_iter() {
+                        expanded.clear();
+                        expander.expand(
+                            &db,
+                            cache,
+                            id.as_ref(),
+                            input_object_expansion,
+                            &mut |id| expand::is_new(&mut seen.lock(), id, input_object_expansion),
+                            expanded,
+                        )?;
+                        for (id, _name_hash) in expanded.iter() {
+                            let obj = db
+                                .find(id, buf, cache)?
+                                .ok_or_else(|| Error::NotFound { oid: id.to_owned() })?;
+                            out.push(base_entry(&db, id, &obj, version)?);
+                        }
+                    }
+                }
+                util::Chunk::Segment(segment) => segment.into_entries(&db, version, buf, cache, &mut out)?,
+            }
+            Ok(out)
+        },
+        parallel::reduce::IdentityWithResult::default(),
+    )
+}
+
+/// Create an entry for `obj` with `id` which isn't a delta, copying its compressed data from its pack if possible.
+fn base_entry<Locate>(
+    db: &Locate,
+    id: &oid,
+    obj: &crate::data::Object<'_>,
+    version: pack::data::Version,
+) -> Result<output::Entry, Error<Locate::Error>>
+where
+    Locate: crate::Find,
+{
+    Ok(match db.pack_entry(obj) {
+        Some(entry) if entry.version == version => {
+            verify_crc32(&entry)?;
+            let pack_entry = pack::data::Entry::from_bytes(entry.data, 0);
+            if pack_entry.header.is_base() {
+                output::Entry {
+                    id: id.to_owned(),
+                    object_kind: pack_entry.header.to_kind().expect("non-delta"),
+                    entry_kind: output::entry::Kind::Base,
+                    decompressed_size: obj.data.len(),
+                    compressed_data: entry.data[pack_entry.data_offset as usize..].to_owned(),
+                }
+            } else {
+                output::Entry::from_data(id, obj).map_err(Error::NewEntry)?
+            }
+        }
+        _ => output::Entry::from_data(id, obj).map_err(Error::NewEntry)?,
+    })
+}
+
+/// Assure the data of a pack `entry` is intact before copying it into a new pack.
+fn verify_crc32<E>(entry: &crate::find::PackEntry<'_>) -> Result<(), Error<E>>
+where
+    E: std::error::Error + 'static,
+{
+    if let Some(expected) = entry.crc32 {
+        let actual = hash::crc32(entry.data);
+        if actual != expected {
+            return Err(Error::PackToPackCopyCrc32Mismatch { actual, expected });
+        }
+    }
+    Ok(())
+}
+
+mod delta_search;
+pub(crate) mod expand;
+
+mod util {
+    /// A unit of work for the threads producing entries.
+    pub enum Chunk<Oid> {
+        /// Objects to turn into base entries, in order.
+        Objects(Vec<Oid>),
+        /// Objects sorted for delta search, along with the objects reusing deltas against them.
+        Segment(super::delta_search::Segment),
+    }
+
+    pub struct Chunks<I> {
+        pub size: usize,
+        pub iter: I,
+    }
+
+    impl<I, Item> Iterator for Chunks<I>
+    where
+        I: Iterator<Item = Item>,
+    {
+        type Item = Vec<Item>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let mut res = Vec::with_capacity(self.size);
+            let mut items_left = self.size;
+            while let Some(item) = self.iter.next() {
+                res.push(item);
+                items_left -= 1;
+                if items_left == 0 {
+                    break;
+                }
+            }
+            if res.is_empty() {
+                None
+            } else {
+                Some(res)
+            }
+        }
+    }
+}
+
+mod types {
+    use crate::pack::data::output::entry;
+    use git_hash::ObjectId;
+
+    /// The way input objects are handled
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    pub enum ObjectExpansion {
+        /// Don't do anything with the input objects except for transforming them into pack entries
+        AsIs,
+        /// Expand commits to their tree and all trees and blobs reachable from it, trees to all trees and blobs they
+        /// contain recursively, and annotated tags to the object they point to, expanding it in turn.
+        ///
+        /// Each object is written only once, no matter how often it is encountered.
+        TreeContents,
+        /// Like [`TreeContents`][ObjectExpansion::TreeContents], but commits with parents are only expanded to the
+        /// trees and blobs which were added or modified compared to the tree of each of their parents.
+        ///
+        /// This is useful to pack a range of commits, with [`Options::excluded_objects`] containing the objects
+        /// reachable from the commits the receiver has.
+        TreeAdditionsComparedToAncestor,
+    }
+
+    impl Default for ObjectExpansion {
+        fn default() -> Self {
+            ObjectExpansion::AsIs
+        }
+    }
+
+    /// Configuration options for the pack generation functions provied in [this module][crate::pack::data::output].
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Options {
+        /// The amount of threads to use at most when resolving the pack. If `None`, all logical cores are used.
+        pub thread_limit: Option<usize>,
+        /// The amount of objects per chunk or unit of work to be sent to threads for processing
+        /// TODO: could this become the window size?
+        pub chunk_size: usize,
+        /// The pack data version to produce
+        pub version: crate::pack::data::Version,
+        /// The way input objects are handled
+        pub input_object_expansion: ObjectExpansion,
+        /// If set, objects will be stored as deltas against similar objects, trading time spent on producing the
+        /// pack for its size.
+        /// If unset, all objects are written as base objects.
+        pub delta_search: Option<DeltaSearch>,
+        /// Objects the receiver of the pack is known to have, for example trees and blobs of the commits it advertised,
+        /// to be used as bases for deltas without being written into the pack. This makes it a _thin_ pack which
+        /// the receiver has to complete with its own objects.
+        ///
+        /// Only used if `delta_search` is set, and objects we don't have ourselves are ignored. As all of them
+        /// are looked up, it's best to keep this list small.
+        pub thin_pack_bases: Vec<ObjectId>,
+        /// Objects the receiver of the pack is known to have, which will never be written into the pack.
+        ///
+        /// If the [`input_object_expansion`][Options::input_object_expansion] encounters a tree contained in this
+        /// list it won't be traversed either, which is why it should contain the full closure of objects the
+        /// receiver has, or only objects at the boundary of the range to pack.
+        pub excluded_objects: Vec<ObjectId>,
+    }
+
+    /// Configuration for the delta search performed while generating packs, see [`Options::delta_search`].
+    ///
+    /// Similar to `git pack-objects`, objects are sorted by kind, the hash of the path they were encountered at and
+    /// their size, to then try to create deltas against the objects preceding them within a sliding window.
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    pub struct DeltaSearch {
+        /// The amount of objects preceding an object in sort order to try as delta base, similar to `--window` of
+        /// `git pack-objects`.
+        pub window: usize,
+        /// The maximum length of delta chains, similar to `--depth` of `git pack-objects`.
+        /// Chains of deltas reused from existing packs are limited by it as well.
+        pub depth: usize,
+        /// If true, deltas stored in existing packs will be copied as is if their base object is written into the
+        /// pack as well, instead of searching for a new delta.
+        pub reuse_deltas: bool,
+    }
+
+    impl Default for DeltaSearch {
+        fn default() -> Self {
+            DeltaSearch {
+                window: 10,
+                depth: 50,
+                reuse_deltas: true,
+            }
+        }
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                thread_limit: None,
+                chunk_size: 10,
+                version: Default::default(),
+                input_object_expansion: Default::default(),
+                delta_search: None,
+                thin_pack_bases: Vec::new(),
+                excluded_objects: Vec::new(),
+            }
+        }
+    }
+
+    /// The error returned by the pack generation function [`to_entry_iter()`][crate::pack::data::output::objects_to_entries_iter()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error<LocateErr>
+    where
+        LocateErr: std::error::Error + 'static,
+    {
+        #[error(transparent)]
+        Locate(#[from] LocateErr),
+        #[error("Object id {oid} wasn't found in object database")]
+        NotFound { oid: ObjectId },
+        #[error("Entry expected to have hash {expected}, but it had {actual}")]
+        PackToPackCopyCrc32Mismatch { actual: u32, expected: u32 },
+        #[error(transparent)]
+        NewEntry(entry::Error),
+        #[error(transparent)]
+        ObjectDecode(git_object::immutable::object::decode::Error),
+        #[error(transparent)]
+        TreeTraverse(git_traverse::tree::breadthfirst::Error),
+        #[error(transparent)]
+        TreeChanges(git_diff::tree::changes::Error),
+    }
+}
+pub use types::{DeltaSearch, Error, ObjectExpansion, Options};
@@ -1,6 +1,8 @@
-use crate::pack::index::{self, FAN_LEN};
+use crate::{
+    pack,
+    pack::index::{self, FAN_LEN},
+};
 use byteorder::{BigEndian, ByteOrder};
-use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
 use std::{convert::TryInto, mem::size_of};
 
 const N32_SIZE: usize = size_of::<u32>();
@@ -31,12 +33,12 @@ impl index::File {
     pub(crate) fn iter_v1(&self) -> impl Iterator<Item = Entry> + '_ {
         match self.version {
             index::Version::V1 => self.data[V1_HEADER_SIZE..]
-                .chunks(N32_SIZE + SHA1_SIZE)
+                .chunks(N32_SIZE + self.hash_len)
                 .take(self.num_objects as usize)
                 .map(|c| {
                     let (ofs, oid) = c.split_at(N32_SIZE);
                     Entry {
-                        oid: git_hash::ObjectId::from_20_bytes(oid),
+                        oid: oid_from_bytes(oid),
                         pack_offset: BigEndian::read_u32(ofs) as u64,
                         crc32: None,
                     }
@@ -49,13 +51,13 @@ impl index::File {
         let pack64_offset = self.offset_pack_offset64_v2();
         match self.version {
             index::Version::V2 => izip!(
-                self.data[V2_HEADER_SIZE..].chunks(SHA1_SIZE),
+                self.data[V2_HEADER_SIZE..].chunks(self.hash_len),
                 self.data[self.offset_crc32_v2()..].chunks(N32_SIZE),
                 self.data[self.offset_pack_offset_v2()..].chunks(N32_SIZE)
             )
             .take(self.num_objects as usize)
             .map(move |(oid, crc32, ofs32)| Entry {
-                oid: git_hash::ObjectId::from_20_bytes(oid),
+                oid: oid_from_bytes(oid),
                 pack_offset: self.pack_offset_from_offset_v2(ofs32, pack64_offset),
                 crc32: Some(BigEndian::read_u32(crc32)),
             }),
@@ -63,7 +65,7 @@ impl index::File {
         }
     }
 
-    /// Returns 20 bytes sha1 at the given index in our list of (sorted) sha1 hashes.
+    /// Returns the object id at the given index in our list of (sorted) object ids.
     /// The index ranges from 0 to self.num_objects()
     ///
     /// # Panics
@@ -74,10 +76,10 @@ impl index::File {
             .try_into()
             .expect("an architecture able to hold 32 bits of integer");
         let start = match self.version {
-            index::Version::V2 => V2_HEADER_SIZE + index * SHA1_SIZE,
-            index::Version::V1 => V1_HEADER_SIZE + index * (N32_SIZE + SHA1_SIZE) + N32_SIZE,
+            index::Version::V2 => V2_HEADER_SIZE + index * self.hash_len,
+            index::Version::V1 => V1_HEADER_SIZE + index * (N32_SIZE + self.hash_len) + N32_SIZE,
         };
-        git_hash::oid::try_from(&self.data[start..start + SHA1_SIZE]).expect("20 bytes SHA1 to be alright")
+        git_hash::oid::try_from(&self.data[start..start + self.hash_len]).expect("hash length to be supported")
     }
 
     /// Returns the offset into our pack data file at which to start reading the object at `index`.
@@ -95,7 +97,7 @@ impl index::File {
                 self.pack_offset_from_offset_v2(&self.data[start..start + N32_SIZE], self.offset_pack_offset64_v2())
             }
             index::Version::V1 => {
-                let start = V1_HEADER_SIZE + index * (N32_SIZE + SHA1_SIZE);
+                let start = V1_HEADER_SIZE + index * (N32_SIZE + self.hash_len);
                 BigEndian::read_u32(&self.data[start..start + N32_SIZE]) as u64
             }
         }
@@ -120,6 +122,19 @@ impl index::File {
         }
     }
 
+    /// Returns the entry at the given `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub(crate) fn entry_at_index(&self, index: u32) -> Entry {
+        Entry {
+            oid: self.oid_at_index(index).to_owned(),
+            pack_offset: self.pack_offset_at_index(index),
+            crc32: self.crc32_at_index(index),
+        }
+    }
+
     /// Returns the `index` of the given SHA1 for use with the [`oid_at_index()`][index::File::oid_at_index()],
     /// [`pack_offset_at_index()`][index::File::pack_offset_at_index()] or [`crc32_at_index()`][index::File::crc32_at_index()].
     pub fn lookup(&self, id: impl AsRef<git_hash::oid>) -> Option<u32> {
@@ -157,7 +172,15 @@ impl index::File {
     /// Return a vector of ascending offsets into our respective pack data file.
     ///
     /// Useful to control an iteration over all pack entries in a cache-friendly way.
-    pub fn sorted_offsets(&self) -> Vec<PackOffset> {
+    /// Sorting is avoided if there is a matching [reverse index][pack::reverse_index::File] next to this file, and it's an
+    /// error if it can't be used.
+    pub fn sorted_offsets(&self) -> Result<Vec<PackOffset>, pack::reverse_index::init::Error> {
+        if let Some(rev) = pack::reverse_index::File::for_index(self)? {
+            return Ok(rev
+                .index_positions()
+                .map(|index_position| self.pack_offset_at_index(index_position))
+                .collect());
+        }
         let mut ofs: Vec<_> = match self.version {
             index::Version::V1 => self.iter().map(|e| e.pack_offset).collect(),
             index::Version::V2 => {
@@ -172,11 +195,11 @@ impl index::File {
             }
         };
         ofs.sort_unstable();
-        ofs
+        Ok(ofs)
     }
 
     fn offset_crc32_v2(&self) -> usize {
-        V2_HEADER_SIZE + self.num_objects as usize * SHA1_SIZE
+        V2_HEADER_SIZE + self.num_objects as usize * self.hash_len
     }
 
     fn offset_pack_offset_v2(&self) -> usize {
@@ -198,3 +221,9 @@ impl index::File {
         }
     }
 }
+
+fn oid_from_bytes(bytes: &[u8]) -> git_hash::ObjectId {
+    git_hash::oid::try_from(bytes)
+        .expect("hash length to be supported")
+        .to_owned()
+}
@@ -1,7 +1,6 @@
 use crate::pack::index::{self, Version, FAN_LEN, V2_SIGNATURE};
 use byteorder::{BigEndian, ByteOrder};
 use filebuffer::FileBuffer;
-use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
 use std::{convert::TryFrom, mem::size_of, path::Path};
 
 /// Returned by [`index::File::at()`].
@@ -20,7 +19,6 @@ pub enum Error {
 }
 
 const N32_SIZE: usize = size_of::<u32>();
-const FOOTER_SIZE: usize = SHA1_SIZE * 2;
 
 /// Instantiation
 impl index::File {
@@ -39,7 +37,7 @@ impl TryFrom<&Path> for index::File {
             path: path.to_owned(),
         })?;
         let idx_len = data.len();
-        if idx_len < FAN_LEN * N32_SIZE + FOOTER_SIZE {
+        if idx_len < FAN_LEN * N32_SIZE + git_hash::Kind::Sha1.len_in_bytes() * 2 {
             return Err(Error::Corrupt {
                 message: format!("Pack index of size {} is too small for even an empty index", idx_len),
             });
@@ -71,16 +69,43 @@ impl TryFrom<&Path> for index::File {
 
             (kind, fan, num_objects)
         };
+        let hash_len = match kind {
+            Version::V1 => git_hash::Kind::Sha1.len_in_bytes(),
+            Version::V2 => v2_hash_len(idx_len, num_objects as usize).ok_or_else(|| Error::Corrupt {
+                message: format!(
+                    "Pack index of size {} does not match the size of an index with {} objects for any supported hash",
+                    idx_len, num_objects
+                ),
+            })?,
+        };
         Ok(index::File {
             data,
             path: path.to_owned(),
             version: kind,
             num_objects,
             fan,
+            hash_len,
         })
     }
 }
 
+/// A V2 index is made of the header, the fan, an id, crc32 and 32 bit offset for each object, an 64 bit offset for at most
+/// each object and the trailing pack and index checksums. The only unknown is the amount of 64 bit offsets, which is
+/// enough to tell the hash length apart.
+fn v2_hash_len(idx_len: usize, num_objects: usize) -> Option<usize> {
+    let fixed_len = V2_SIGNATURE.len() + N32_SIZE + FAN_LEN * N32_SIZE;
+    [git_hash::Kind::Sha1, git_hash::Kind::Sha256]
+        .iter()
+        .map(|kind| kind.len_in_bytes())
+        .find(|&hash_len| {
+            let expected_len = fixed_len + num_objects * (hash_len + N32_SIZE * 2) + hash_len * 2;
+            idx_len
+                .checked_sub(expected_len)
+                .map(|large_offsets_len| large_offsets_len % 8 == 0 && large_offsets_len / 8 <= num_objects)
+                .unwrap_or(false)
+        })
+}
+
 fn read_fan(d: &[u8]) -> ([u32; FAN_LEN], usize) {
     let mut fan = [0; FAN_LEN];
     for (c, f) in d.chunks(N32_SIZE).zip(fan.iter_mut()) {
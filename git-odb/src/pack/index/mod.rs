@@ -108,6 +108,7 @@ pub struct File {
     version: Version,
     num_objects: u32,
     fan: [u32; FAN_LEN],
+    hash_len: usize,
 }
 
 /// Basic file information
@@ -124,6 +125,10 @@ impl File {
     pub fn num_objects(&self) -> u32 {
         self.num_objects
     }
+    /// The kind of hash used for the object ids in this index, and for the checksums of the index and its pack.
+    pub fn hash_kind(&self) -> git_hash::Kind {
+        git_hash::Kind::from_len_in_bytes(self.hash_len).expect("hash length to be validated on instantiation")
+    }
 }
 
 const V2_SIGNATURE: &[u8] = b"\xfftOc";
@@ -8,6 +8,8 @@ pub enum Error<E: std::error::Error + Send + Sync + 'static> {
     Processor(#[source] E),
     #[error("Index file, pack file or object verification failed")]
     Verify(#[from] index::verify::Error),
+    #[error("The reverse index of the pack could not be used")]
+    ReverseIndex(#[from] pack::reverse_index::init::Error),
     #[error("The pack delta tree index could not be built")]
     Tree(#[from] pack::tree::from_offsets::Error),
     #[error("The tree traversal failed")]
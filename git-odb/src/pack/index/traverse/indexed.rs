@@ -51,11 +51,12 @@ impl index::File {
             },
             || -> Result<_, Error<_>> {
                 let sorted_entries =
-                    index_entries_sorted_by_offset_ascending(self, progress.add_child("collecting sorted index"));
+                    index_entries_sorted_by_offset_ascending(self, progress.add_child("collecting sorted index"))?;
                 let tree = pack::tree::Tree::from_offsets_in_pack(
-                    sorted_entries.into_iter().map(EntryWithDefault::from),
+                    sorted_entries.map(EntryWithDefault::from),
                     |e| e.index_entry.pack_offset,
                     pack.path(),
+                    pack.hash_kind(),
                     progress.add_child("indexing"),
                     |id| self.lookup(id).map(|idx| self.pack_offset_at_index(idx)),
                 )?;
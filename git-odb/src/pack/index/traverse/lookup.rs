@@ -46,8 +46,11 @@ impl index::File {
                 }
             },
             || {
-                let index_entries =
-                    util::index_entries_sorted_by_offset_ascending(self, progress.add_child("collecting sorted index"));
+                let index_entries: Vec<_> = util::index_entries_sorted_by_offset_ascending(
+                    self,
+                    progress.add_child("collecting sorted index"),
+                )?
+                .collect();
 
                 let (chunk_size, thread_limit, available_cores) =
                     parallel::optimize_chunk_size_and_thread_limit(1000, Some(index_entries.len()), thread_limit, None);
@@ -2,10 +2,19 @@ use crate::pack;
 use git_features::progress::{self, Progress};
 use std::{io, time::Instant};
 
-pub(crate) fn index_entries_sorted_by_offset_ascending(
-    idx: &pack::index::File,
+/// Return all entries of `idx` sorted by their pack offset, in the order of its [reverse index][pack::reverse_index::File]
+/// if there is one to avoid collecting and sorting them. It's an error if the reverse index can't be used.
+pub(crate) fn index_entries_sorted_by_offset_ascending<'a>(
+    idx: &'a pack::index::File,
     mut progress: impl Progress,
-) -> Vec<pack::index::Entry> {
+) -> Result<Box<dyn Iterator<Item = pack::index::Entry> + 'a>, pack::reverse_index::init::Error> {
+    if let Some(rev) = pack::reverse_index::File::for_index(idx)? {
+        progress.info("using reverse index");
+        return Ok(Box::new((0..rev.num_objects()).map(move |pack_position| {
+            let index_position = rev.index_position_at(pack_position);
+            idx.entry_at_index(index_position)
+        })));
+    }
     progress.init(Some(idx.num_objects as usize), progress::count("entries"));
     let start = Instant::now();
 
@@ -17,7 +26,7 @@ pub(crate) fn index_entries_sorted_by_offset_ascending(
     v.sort_by_key(|e| e.pack_offset);
 
     progress.show_throughput(start);
-    v
+    Ok(Box::new(v.into_iter()))
 }
 
 pub(crate) struct Count<W> {
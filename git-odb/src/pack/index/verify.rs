@@ -1,5 +1,4 @@
 use git_features::progress::{self, Progress};
-use git_hash::SIZE_OF_SHA1_DIGEST as SHA1_SIZE;
 use git_object::{
     bstr::{BString, ByteSlice},
     immutable::object,
@@ -53,35 +52,40 @@ impl index::File {
     ///
     /// It's a hash over all bytes of the index.
     pub fn index_checksum(&self) -> git_hash::ObjectId {
-        git_hash::ObjectId::from_20_bytes(&self.data[self.data.len() - SHA1_SIZE..])
+        self.id_at(self.data.len() - self.hash_len)
     }
 
     /// Returns the hash of the pack data file that this index file corresponds to.
     ///
     /// It should [`pack::data::File::checksum()`] of the corresponding pack data file.
     pub fn pack_checksum(&self) -> git_hash::ObjectId {
-        let from = self.data.len() - SHA1_SIZE * 2;
-        git_hash::ObjectId::from_20_bytes(&self.data[from..from + SHA1_SIZE])
+        self.id_at(self.data.len() - self.hash_len * 2)
+    }
+
+    fn id_at(&self, offset: usize) -> git_hash::ObjectId {
+        git_hash::oid::try_from(&self.data[offset..offset + self.hash_len])
+            .expect("hash length to be supported")
+            .to_owned()
     }
 
     /// Validate that our [`index_checksum()`][index::File::index_checksum()] matches the actual contents
     /// of this index file, and return it if it does.
     pub fn verify_checksum(&self, mut progress: impl Progress) -> Result<git_hash::ObjectId, Error> {
-        let data_len_without_trailer = self.data.len() - SHA1_SIZE;
+        let data_len_without_trailer = self.data.len() - self.hash_len;
         let actual = match git_features::hash::bytes_of_file(
             &self.path,
             data_len_without_trailer,
-            git_hash::Kind::Sha1,
+            self.hash_kind(),
             &mut progress,
         ) {
             Ok(id) => id,
             Err(_io_err) => {
                 let start = std::time::Instant::now();
-                let mut hasher = git_features::hash::Sha1::default();
+                let mut hasher = git_features::hash::hasher(self.hash_kind());
                 hasher.update(&self.data[..data_len_without_trailer]);
                 progress.inc_by(data_len_without_trailer);
                 progress.show_throughput(start);
-                git_hash::ObjectId::new_sha1(hasher.digest())
+                hasher.digest()
             }
         };
 
@@ -171,14 +175,16 @@ impl index::File {
             use git_object::Kind::*;
             match object_kind {
                 Tree | Commit | Tag => {
-                    let borrowed_object =
-                        git_object::immutable::Object::from_bytes(object_kind, buf).map_err(|err| {
-                            Error::ObjectDecode {
-                                source: err,
-                                kind: object_kind,
-                                id: index_entry.oid,
-                            }
-                        })?;
+                    let borrowed_object = git_object::immutable::Object::from_bytes_with_hash_kind(
+                        object_kind,
+                        buf,
+                        index_entry.oid.kind(),
+                    )
+                    .map_err(|err| Error::ObjectDecode {
+                        source: err,
+                        kind: object_kind,
+                        id: index_entry.oid,
+                    })?;
                     if let Mode::Sha1Crc32DecodeEncode = mode {
                         let object = mutable::Object::from(borrowed_object);
                         encode_buf.clear();
@@ -25,4 +25,9 @@ pub enum Error {
     Tree(#[from] pack::tree::Error),
     #[error(transparent)]
     TreeTraversal(#[from] pack::tree::traverse::Error),
+    #[error("The object at pack offset {pack_offset} contains a Sha1 collision attack")]
+    Sha1Collision {
+        source: git_features::hash::Collision,
+        pack_offset: u64,
+    },
 }
@@ -51,7 +51,8 @@ impl pack::index::File {
     ///
     /// # Remarks
     ///
-    /// * neither in-pack nor out-of-pack Ref Deltas are supported here, these must have been resolved beforehand.
+    /// * neither in-pack nor out-of-pack Ref Deltas are supported here, these must have been resolved beforehand,
+    ///   for example using [`LookupRefDeltaObjectsIter`][pack::data::input::LookupRefDeltaObjectsIter].
     /// * `make_resolver()` will only be called after the iterator stopped returning elements and produces a function that
     /// provides all bytes belonging to a pack entry writing them to the given mutable output `Vec`.
     /// It should return `None` if the entry cannot be resolved from the pack that produced the `entries` iterator, causing
@@ -75,8 +76,9 @@ impl pack::index::File {
         let mut bytes_to_process = 0u64;
         let mut last_seen_trailer = None;
         let mut last_base_index = None;
-        let anticipated_num_objects = entries.size_hint().0;
-        let mut tree = Tree::with_capacity(anticipated_num_objects)?;
+        let (anticipated_num_objects, max_num_objects) = entries.size_hint();
+        // Thin packs may grow while resolving them, and the tree must be able to hold all entries without reallocating.
+        let mut tree = Tree::with_capacity(max_num_objects.unwrap_or(anticipated_num_objects))?;
         let indexing_start = std::time::Instant::now();
 
         root_progress.init(Some(4), progress::steps());
@@ -141,7 +143,7 @@ impl pack::index::File {
             num_objects += 1;
             objects_progress.inc();
         }
-        if num_objects != anticipated_num_objects {
+        if num_objects < anticipated_num_objects {
             objects_progress.info(format!(
                 "Recovered from pack streaming error, anticipated {} objects, got {}",
                 anticipated_num_objects, num_objects
@@ -159,28 +161,36 @@ impl pack::index::File {
 
         root_progress.inc();
 
+        let pack_hash = last_seen_trailer.ok_or(Error::IteratorInvariantTrailer)?;
+        let hash_kind = pack_hash.kind();
         let resolver = make_resolver()?;
         let sorted_pack_offsets_by_oid = {
             let in_parallel_if_pack_is_big_enough = || bytes_to_process > 5_000_000;
-            let mut items = tree.traverse(
-                in_parallel_if_pack_is_big_enough,
-                resolver,
-                root_progress.add_child("Resolving"),
-                root_progress.add_child("Decoding"),
-                thread_limit,
-                pack_entries_end,
-                || (),
-                |data,
-                 _progress,
-                 Context {
-                     entry,
-                     decompressed: bytes,
-                     ..
-                 }| {
-                    modify_base(data, entry, bytes, kind.hash());
-                    Ok::<_, Error>(())
-                },
-            )?;
+            let mut items = tree
+                .traverse(
+                    in_parallel_if_pack_is_big_enough,
+                    resolver,
+                    root_progress.add_child("Resolving"),
+                    root_progress.add_child("Decoding"),
+                    thread_limit,
+                    pack_entries_end,
+                    || (),
+                    |data,
+                     _progress,
+                     Context {
+                         entry,
+                         decompressed: bytes,
+                         ..
+                     }| { modify_base(data, entry, bytes, hash_kind) },
+                )
+                .map_err(|err| match err {
+                    // Surface errors of our own, like detected collisions, as such.
+                    pack::tree::traverse::Error::Inspect(err) => match err.downcast::<Error>() {
+                        Ok(err) => *err,
+                        Err(err) => Error::TreeTraversal(pack::tree::traverse::Error::Inspect(err)),
+                    },
+                    err => Error::TreeTraversal(err),
+                })?;
             root_progress.inc();
 
             {
@@ -192,7 +202,6 @@ impl pack::index::File {
             items
         };
 
-        let pack_hash = last_seen_trailer.ok_or(Error::IteratorInvariantTrailer)?;
         let index_hash = encode::write_to(
             out,
             sorted_pack_offsets_by_oid,
@@ -219,16 +228,24 @@ fn modify_base(
     pack_entry: &pack::data::Entry,
     decompressed: &[u8],
     hash: git_hash::Kind,
-) {
-    fn compute_hash(kind: git_object::Kind, bytes: &[u8], hash_kind: git_hash::Kind) -> git_hash::ObjectId {
+) -> Result<(), Error> {
+    fn compute_hash(
+        kind: git_object::Kind,
+        bytes: &[u8],
+        hash_kind: git_hash::Kind,
+    ) -> Result<git_hash::ObjectId, git_features::hash::Collision> {
         let mut write = crate::hash::Write::new(io::sink(), hash_kind);
         loose::object::header::encode(kind, bytes.len() as u64, &mut write)
             .expect("write to sink and hash cannot fail");
         write.hash.update(bytes);
-        git_hash::ObjectId::from(write.hash.digest())
+        write.hash.try_digest()
     }
 
     let object_kind = pack_entry.header.to_kind().expect("base object as source of iteration");
-    let id = compute_hash(object_kind, &decompressed, hash);
+    let id = compute_hash(object_kind, &decompressed, hash).map_err(|err| Error::Sha1Collision {
+        source: err,
+        pack_offset: pack_entry.pack_offset(),
+    })?;
     entry.id = id;
+    Ok(())
 }
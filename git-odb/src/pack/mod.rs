@@ -3,6 +3,7 @@
 #[doc(inline)]
 pub use bundle::Bundle;
 
+pub mod bitmap;
 ///
 pub mod bundle;
 ///
@@ -11,5 +12,7 @@ pub mod cache;
 pub mod data;
 ///
 pub mod index;
+pub mod multi_index;
+pub mod reverse_index;
 ///
 pub mod tree;
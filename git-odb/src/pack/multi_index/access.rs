@@ -0,0 +1,97 @@
+use crate::pack::multi_index::{self, FAN_LEN, HIGH_BIT};
+use byteorder::{BigEndian, ByteOrder};
+use std::convert::TryInto;
+
+/// Represents an entry within a multi-pack index file, mapping object [`IDs`][git_hash::ObjectId] to a location
+/// in one of the packs it covers.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Entry {
+    /// The ID of the object
+    pub oid: git_hash::ObjectId,
+    /// The index of the pack containing the object, see [`index_names()`][multi_index::File::index_names()].
+    pub pack_index: u32,
+    /// The offset to the object's header in the pack data file
+    pub pack_offset: u64,
+}
+
+/// Iteration and access
+impl multi_index::File {
+    /// Returns the object id at the given index in our list of (sorted) object ids.
+    /// The index ranges from 0 to self.num_objects()
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn oid_at_index(&self, index: u32) -> &git_hash::oid {
+        let index: usize = index
+            .try_into()
+            .expect("an architecture able to hold 32 bits of integer");
+        let hash_len = self.hash_kind.len_in_bytes();
+        let start = self.lookup_ofs + index * hash_len;
+        git_hash::oid::try_from(&self.data[start..start + hash_len]).expect("the length of a supported hash")
+    }
+
+    /// Returns the index of the pack containing the object at `index`, along with the offset into its pack data file at
+    /// which to start reading the object.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn pack_id_and_pack_offset_at_index(&self, index: u32) -> (u32, u64) {
+        let index: usize = index
+            .try_into()
+            .expect("an architecture able to hold 32 bits of integer");
+        let start = self.offsets_ofs + index * 8;
+        let pack_index = BigEndian::read_u32(&self.data[start..]);
+        let ofs32 = BigEndian::read_u32(&self.data[start + 4..]);
+        let pack_offset = if ofs32 & HIGH_BIT == HIGH_BIT {
+            let from = self
+                .large_offsets_ofs
+                .expect("a large offset chunk if there are large offsets")
+                + (ofs32 ^ HIGH_BIT) as usize * 8;
+            BigEndian::read_u64(&self.data[from..from + 8])
+        } else {
+            ofs32 as u64
+        };
+        (pack_index, pack_offset)
+    }
+
+    /// Returns the `index` of the given object id for use with the [`oid_at_index()`][multi_index::File::oid_at_index()]
+    /// or [`pack_id_and_pack_offset_at_index()`][multi_index::File::pack_id_and_pack_offset_at_index()].
+    pub fn lookup(&self, id: impl AsRef<git_hash::oid>) -> Option<u32> {
+        let id = id.as_ref();
+        let first_byte = id.first_byte() as usize;
+        let mut upper_bound = self.fan[first_byte];
+        let mut lower_bound = if first_byte != 0 { self.fan[first_byte - 1] } else { 0 };
+
+        while lower_bound < upper_bound {
+            let mid = (lower_bound + upper_bound) / 2;
+            let mid_sha = self.oid_at_index(mid);
+
+            use std::cmp::Ordering::*;
+            match id.cmp(mid_sha) {
+                Less => upper_bound = mid,
+                Equal => return Some(mid),
+                Greater => lower_bound = mid + 1,
+            }
+        }
+        None
+    }
+
+    /// An iterator over all [`Entries`][Entry] of this index file, sorted by object id.
+    pub fn iter(&self) -> impl Iterator<Item = Entry> + '_ {
+        (0..self.num_objects).map(move |idx| {
+            let (pack_index, pack_offset) = self.pack_id_and_pack_offset_at_index(idx);
+            Entry {
+                oid: self.oid_at_index(idx).to_owned(),
+                pack_index,
+                pack_offset,
+            }
+        })
+    }
+
+    pub(crate) fn fan(&self) -> &[u32; FAN_LEN] {
+        &self.fan
+    }
+}
@@ -0,0 +1,201 @@
+use crate::pack::multi_index::{
+    self, chunk, Version, CHUNK_TABLE_ENTRY_LEN, FAN_LEN, HASH_VERSION_SHA1, HASH_VERSION_SHA256, HEADER_LEN, HIGH_BIT,
+    SIGNATURE,
+};
+use byteorder::{BigEndian, ByteOrder};
+use filebuffer::FileBuffer;
+use std::{
+    convert::TryFrom,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Returned by [`multi_index::File::at()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open multi-pack index file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: String },
+    #[error("Unsupported multi-pack index version: {version}")]
+    UnsupportedVersion { version: u8 },
+    #[error("Unsupported object hash version: {version}")]
+    UnsupportedHashVersion { version: u8 },
+    #[error("Multi-pack index files building on {count} other multi-pack index files are unsupported")]
+    UnsupportedBaseFiles { count: u8 },
+}
+
+/// Instantiation
+impl multi_index::File {
+    /// Open the multi-pack index file at the given `path`.
+    pub fn at(path: impl AsRef<Path>) -> Result<multi_index::File, Error> {
+        Self::try_from(path.as_ref())
+    }
+}
+
+impl TryFrom<&Path> for multi_index::File {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        let data = FileBuffer::open(path).map_err(|e| Error::Io {
+            source: e,
+            path: path.to_owned(),
+        })?;
+        let corrupt = |message: String| Error::Corrupt { message };
+        if data.len() < HEADER_LEN {
+            return Err(corrupt(format!(
+                "Multi-pack index of size {} is too small for even an empty index",
+                data.len()
+            )));
+        }
+        let (sig, header) = data[..HEADER_LEN].split_at(SIGNATURE.len());
+        if sig != SIGNATURE {
+            return Err(corrupt("Multi-pack index signature is invalid".into()));
+        }
+        let version = match header[0] {
+            1 => Version::V1,
+            version => return Err(Error::UnsupportedVersion { version }),
+        };
+        let hash_kind = match header[1] {
+            HASH_VERSION_SHA1 => git_hash::Kind::Sha1,
+            HASH_VERSION_SHA256 => git_hash::Kind::Sha256,
+            version => return Err(Error::UnsupportedHashVersion { version }),
+        };
+        let hash_len = hash_kind.len_in_bytes();
+        if data.len() < HEADER_LEN + CHUNK_TABLE_ENTRY_LEN + hash_len {
+            return Err(corrupt(format!(
+                "Multi-pack index of size {} is too small for even an empty index",
+                data.len()
+            )));
+        }
+        let num_chunks = header[2] as usize;
+        if header[3] != 0 {
+            return Err(Error::UnsupportedBaseFiles { count: header[3] });
+        }
+        let num_packs = BigEndian::read_u32(&header[4..]);
+
+        let chunk_table_end = HEADER_LEN + (num_chunks + 1) * CHUNK_TABLE_ENTRY_LEN;
+        let data_end = data.len() - hash_len;
+        if chunk_table_end > data_end {
+            return Err(corrupt(format!(
+                "Multi-pack index is too small to hold a table of {} chunks",
+                num_chunks
+            )));
+        }
+        let mut chunks = Vec::with_capacity(num_chunks);
+        {
+            let table = &data[HEADER_LEN..chunk_table_end];
+            let offset_at = |idx: usize| BigEndian::read_u64(&table[idx * CHUNK_TABLE_ENTRY_LEN + 4..][..8]) as usize;
+            for idx in 0..num_chunks {
+                let id = &table[idx * CHUNK_TABLE_ENTRY_LEN..][..4];
+                let range = offset_at(idx)..offset_at(idx + 1);
+                if range.start < chunk_table_end || range.start > range.end || range.end > data_end {
+                    return Err(corrupt(format!(
+                        "Chunk {:?} at {:?} is out of bounds",
+                        String::from_utf8_lossy(id),
+                        range
+                    )));
+                }
+                chunks.push((<[u8; 4]>::try_from(id).expect("4 bytes"), range));
+            }
+        }
+        let chunk = |id: [u8; 4]| -> Option<Range<usize>> {
+            chunks
+                .iter()
+                .find(|(chunk_id, _)| *chunk_id == id)
+                .map(|(_, r)| r.clone())
+        };
+        let required_chunk = |id: [u8; 4]| {
+            chunk(id).ok_or_else(|| {
+                corrupt(format!(
+                    "Required chunk {:?} is missing",
+                    String::from_utf8_lossy(&id[..])
+                ))
+            })
+        };
+
+        let fan_range = required_chunk(chunk::OID_FANOUT)?;
+        if fan_range.len() != FAN_LEN * 4 {
+            return Err(corrupt("The fan-out chunk has an unexpected size".into()));
+        }
+        let mut fan = [0; FAN_LEN];
+        for (c, f) in data[fan_range].chunks(4).zip(fan.iter_mut()) {
+            *f = BigEndian::read_u32(c);
+        }
+        let num_objects = fan[FAN_LEN - 1];
+
+        let lookup = required_chunk(chunk::OID_LOOKUP)?;
+        if lookup.len() != num_objects as usize * hash_len {
+            return Err(corrupt(format!(
+                "The object id chunk is expected to hold {} objects",
+                num_objects
+            )));
+        }
+        let offsets = required_chunk(chunk::OBJECT_OFFSETS)?;
+        if offsets.len() != num_objects as usize * 8 {
+            return Err(corrupt(format!(
+                "The object offsets chunk is expected to hold {} objects",
+                num_objects
+            )));
+        }
+        let large_offsets = chunk(chunk::LARGE_OFFSETS);
+        if let Some(large_offsets) = large_offsets.as_ref() {
+            if large_offsets.len() % 8 != 0 {
+                return Err(corrupt("The large offsets chunk has an unexpected size".into()));
+            }
+        }
+        // Validate all entries once so that lookups can't refer to packs or large offsets which don't exist.
+        let num_large_offsets = large_offsets.as_ref().map(|r| r.len() / 8).unwrap_or(0);
+        for entry in data[offsets.clone()].chunks(8) {
+            let pack_index = BigEndian::read_u32(entry);
+            if pack_index >= num_packs {
+                return Err(corrupt(format!(
+                    "An object refers to pack {} even though there are only {} packs",
+                    pack_index, num_packs
+                )));
+            }
+            let ofs32 = BigEndian::read_u32(&entry[4..]);
+            if ofs32 & HIGH_BIT == HIGH_BIT && (ofs32 ^ HIGH_BIT) as usize >= num_large_offsets {
+                return Err(corrupt(format!(
+                    "An object refers to large offset {} even though there are only {} large offsets",
+                    ofs32 ^ HIGH_BIT,
+                    num_large_offsets
+                )));
+            }
+        }
+
+        let index_names: Vec<PathBuf> = data[required_chunk(chunk::PACK_NAMES)?]
+            .split(|b| *b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                std::str::from_utf8(name)
+                    .map(PathBuf::from)
+                    .map_err(|_| corrupt("Pack index names must be valid UTF-8".into()))
+            })
+            .collect::<Result<_, _>>()?;
+        if index_names.len() != num_packs as usize {
+            return Err(corrupt(format!(
+                "Expected {} pack index names, but found {}",
+                num_packs,
+                index_names.len()
+            )));
+        }
+
+        Ok(multi_index::File {
+            path: path.to_owned(),
+            version,
+            hash_kind,
+            num_objects,
+            fan,
+            index_names,
+            lookup_ofs: lookup.start,
+            offsets_ofs: offsets.start,
+            large_offsets_ofs: large_offsets.map(|r| r.start),
+            data,
+        })
+    }
+}
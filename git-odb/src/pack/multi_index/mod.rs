@@ -0,0 +1,92 @@
+//! A multi-pack index to find objects in all of the packs it covers with a single lookup, typically stored in
+//! `objects/pack/multi-pack-index`.
+use filebuffer::FileBuffer;
+use std::path::PathBuf;
+
+/// The version of a multi-pack index file
+#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    V1 = 1,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V1
+    }
+}
+
+/// A representation of a multi-pack index file
+pub struct File {
+    data: FileBuffer,
+    path: PathBuf,
+    version: Version,
+    hash_kind: git_hash::Kind,
+    num_objects: u32,
+    fan: [u32; FAN_LEN],
+    index_names: Vec<PathBuf>,
+    lookup_ofs: usize,
+    offsets_ofs: usize,
+    large_offsets_ofs: Option<usize>,
+}
+
+/// Basic file information
+impl File {
+    /// The version of the multi-pack index
+    pub fn version(&self) -> Version {
+        self.version
+    }
+    /// The kind of hash used for object ids and the trailing checksum.
+    pub fn hash_kind(&self) -> git_hash::Kind {
+        self.hash_kind
+    }
+    /// The path of the opened multi-pack index file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+    /// The amount of objects stored in all packs covered by this index, without duplicates.
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+    /// The amount of packs covered by this index
+    pub fn num_packs(&self) -> u32 {
+        self.index_names.len() as u32
+    }
+    /// The file names of the pack indices covered by this index, relative to the directory containing it, in the order
+    /// of their pack index as returned by [`pack_id_and_pack_offset_at_index()`][File::pack_id_and_pack_offset_at_index()].
+    pub fn index_names(&self) -> &[PathBuf] {
+        &self.index_names
+    }
+}
+
+const FAN_LEN: usize = 256;
+const SIGNATURE: &[u8] = b"MIDX";
+const HEADER_LEN: usize = 12;
+const CHUNK_TABLE_ENTRY_LEN: usize = 12;
+/// The alignment of chunks in bytes.
+const CHUNK_ALIGNMENT: usize = 4;
+const LARGE_OFFSET_THRESHOLD: u64 = 0x7fff_ffff;
+const HIGH_BIT: u32 = 0x8000_0000;
+/// The object hash versions stored in the header, identifying the kind of hash in use.
+const HASH_VERSION_SHA1: u8 = 1;
+const HASH_VERSION_SHA256: u8 = 2;
+
+mod chunk {
+    pub const PACK_NAMES: [u8; 4] = *b"PNAM";
+    pub const OID_FANOUT: [u8; 4] = *b"OIDF";
+    pub const OID_LOOKUP: [u8; 4] = *b"OIDL";
+    pub const OBJECT_OFFSETS: [u8; 4] = *b"OOFF";
+    pub const LARGE_OFFSETS: [u8; 4] = *b"LOFF";
+}
+
+///
+pub mod init;
+
+mod access;
+pub use access::Entry;
+
+///
+pub mod verify;
+///
+pub mod write;
@@ -0,0 +1,152 @@
+use crate::pack::{self, multi_index, multi_index::FAN_LEN};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use std::path::PathBuf;
+
+/// Returned by [`multi_index::File::verify_checksum()`] and [`multi_index::File::verify_integrity()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("multi-pack index checksum mismatch: expected {expected}, got {actual}")]
+    Mismatch {
+        expected: git_hash::ObjectId,
+        actual: git_hash::ObjectId,
+    },
+    #[error("The fan-out table entry for byte {byte} doesn't match the amount of object ids starting with it or a smaller byte")]
+    Fan { byte: usize },
+    #[error("The object ids at index {index} and the one following it are not sorted or duplicates")]
+    OutOfOrder { index: u32 },
+    #[error("Object {id} refers to pack {pack_index}, but there are only {num_packs} packs")]
+    PackIndexOutOfBounds {
+        id: git_hash::ObjectId,
+        pack_index: u32,
+        num_packs: u32,
+    },
+    #[error("Could not open the pack index of a pack covered by the multi-pack index")]
+    IndexOpen(#[from] pack::index::init::Error),
+    #[error("Object {id} is supposed to be in the pack with index '{index_path}', but it wasn't found there")]
+    ObjectNotFound {
+        id: git_hash::ObjectId,
+        index_path: PathBuf,
+    },
+    #[error("Object {id} was expected at pack offset {expected} of the pack with index '{index_path}', but its offset is {actual}")]
+    PackOffsetMismatch {
+        id: git_hash::ObjectId,
+        index_path: PathBuf,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Verify and validate the content of the multi-pack index file
+impl multi_index::File {
+    /// Returns the trailing hash stored at the end of this multi-pack index file.
+    ///
+    /// It's a hash over all bytes of the file.
+    pub fn checksum(&self) -> git_hash::ObjectId {
+        git_hash::oid::try_from(&self.data[self.data.len() - self.hash_kind.len_in_bytes()..])
+            .expect("the length of a supported hash")
+            .to_owned()
+    }
+
+    /// Validate that our [`checksum()`][multi_index::File::checksum()] matches the actual contents
+    /// of this file, and return it if it does.
+    pub fn verify_checksum(&self, mut progress: impl Progress) -> Result<git_hash::ObjectId, Error> {
+        let data_len_without_trailer = self.data.len() - self.hash_kind.len_in_bytes();
+        let actual = match git_features::hash::bytes_of_file(
+            &self.path,
+            data_len_without_trailer,
+            self.hash_kind,
+            &mut progress,
+        ) {
+            Ok(id) => id,
+            Err(_io_err) => {
+                let mut hasher = git_features::hash::hasher(self.hash_kind);
+                hasher.update(&self.data[..data_len_without_trailer]);
+                hasher.digest()
+            }
+        };
+
+        let expected = self.checksum();
+        if actual == expected {
+            Ok(actual)
+        } else {
+            Err(Error::Mismatch { actual, expected })
+        }
+    }
+
+    /// Similar to `git multi-pack-index verify`, validate our checksum, the ordering of object ids and that the location
+    /// of each object matches the one in the pack index of the pack it is supposed to be in.
+    /// Returns our checksum if the integrity check is successful.
+    ///
+    /// The pack indices of all covered packs are expected to be located in the same directory as this file.
+    pub fn verify_integrity(&self, mut progress: impl Progress) -> Result<git_hash::ObjectId, Error> {
+        progress.init(Some(3), progress::steps());
+        let checksum = self.verify_checksum(progress.add_child("checksum of multi-pack index"))?;
+        progress.inc();
+
+        {
+            let _progress = progress.add_child("checking fan-out table and object order");
+            let fan = self.fan();
+            let mut expected = [0u32; FAN_LEN];
+            for idx in 0..self.num_objects {
+                let id = self.oid_at_index(idx);
+                expected[id.first_byte() as usize] += 1;
+                if idx + 1 < self.num_objects && id >= self.oid_at_index(idx + 1) {
+                    return Err(Error::OutOfOrder { index: idx });
+                }
+            }
+            let mut count = 0;
+            for (byte, (expected, actual)) in expected.iter().zip(fan.iter()).enumerate() {
+                count += expected;
+                if count != *actual {
+                    return Err(Error::Fan { byte });
+                }
+            }
+        }
+        progress.inc();
+
+        let directory = self.path.parent().expect("a parent directory for every file");
+        let indices = self
+            .index_names
+            .iter()
+            .map(|name| pack::index::File::at(directory.join(name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut objects_progress = progress.add_child("checking object locations");
+        objects_progress.init(Some(self.num_objects as usize), progress::count("objects"));
+        for entry in self.iter() {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            let index = indices
+                .get(entry.pack_index as usize)
+                .ok_or(Error::PackIndexOutOfBounds {
+                    id: entry.oid,
+                    pack_index: entry.pack_index,
+                    num_packs: self.num_packs(),
+                })?;
+            let actual = index
+                .lookup(entry.oid)
+                .map(|idx| index.pack_offset_at_index(idx))
+                .ok_or_else(|| Error::ObjectNotFound {
+                    id: entry.oid,
+                    index_path: index.path().to_owned(),
+                })?;
+            if actual != entry.pack_offset {
+                return Err(Error::PackOffsetMismatch {
+                    id: entry.oid,
+                    index_path: index.path().to_owned(),
+                    expected: entry.pack_offset,
+                    actual,
+                });
+            }
+            objects_progress.inc();
+        }
+        progress.inc();
+        Ok(checksum)
+    }
+}
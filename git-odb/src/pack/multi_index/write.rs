@@ -0,0 +1,225 @@
+use crate::{
+    hash, pack,
+    pack::multi_index::{
+        self, chunk, Version, CHUNK_ALIGNMENT, CHUNK_TABLE_ENTRY_LEN, FAN_LEN, HASH_VERSION_SHA1, HASH_VERSION_SHA256,
+        HEADER_LEN, HIGH_BIT, LARGE_OFFSET_THRESHOLD, SIGNATURE,
+    },
+};
+use byteorder::{BigEndian, WriteBytesExt};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+use std::{
+    cmp::Reverse,
+    io::{self, Write},
+    path::PathBuf,
+    time::SystemTime,
+};
+
+/// Returned by [`multi_index::File::write_from_index_paths()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    IndexOpen(#[from] pack::index::init::Error),
+    #[error("The path '{0}' is not a pack index file name with valid UTF-8")]
+    InvalidIndexPath(PathBuf),
+    #[error("The pack index at '{path}' uses {actual:?} object ids, but previous ones use {expected:?} object ids")]
+    HashKindMismatch {
+        path: PathBuf,
+        expected: git_hash::Kind,
+        actual: git_hash::Kind,
+    },
+    #[error("A multi-pack index can cover at most {} objects, got {0}", u32::MAX)]
+    TooManyObjects(usize),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Returned by [`multi_index::File::write_from_index_paths()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The version of the written multi-pack index
+    pub version: Version,
+    /// The checksum of the written multi-pack index, also found in its trailing bytes
+    pub checksum: git_hash::ObjectId,
+    /// The amount of objects in all covered packs, without duplicates
+    pub num_objects: u32,
+}
+
+struct Entry {
+    id: git_hash::ObjectId,
+    pack_index: u32,
+    pack_offset: u64,
+}
+
+/// Various ways of writing a multi-pack index file
+impl multi_index::File {
+    /// Write a multi-pack index covering the packs of all pack indices at `index_paths` into `out`, similar to
+    /// `git multi-pack-index write`.
+    ///
+    /// The written file is expected to be placed in the directory containing the pack indices, usually `objects/pack`,
+    /// named `multi-pack-index`.
+    /// Objects contained in multiple packs are associated with the pack that was modified most recently.
+    /// All pack indices must use the same kind of hash, which is also used for the multi-pack index.
+    pub fn write_from_index_paths(
+        mut index_paths: Vec<PathBuf>,
+        out: impl io::Write,
+        mut progress: impl Progress,
+    ) -> Result<Outcome, Error> {
+        let start = std::time::Instant::now();
+        let index_names = {
+            index_paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+            index_paths.dedup_by(|a, b| a.file_name() == b.file_name());
+            index_paths
+                .iter()
+                .map(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .filter(|name| name.ends_with(".idx"))
+                        .map(ToOwned::to_owned)
+                        .ok_or_else(|| Error::InvalidIndexPath(path.to_owned()))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        progress.init(Some(2), progress::steps());
+        let mut hash_kind = None;
+        let entries = {
+            let mut entries_progress = progress.add_child("collecting entries");
+            entries_progress.init(Some(index_paths.len()), progress::count("indices"));
+            let mut entries = Vec::new();
+            let mut pack_mtimes = Vec::with_capacity(index_paths.len());
+            for (pack_index, index_path) in index_paths.iter().enumerate() {
+                if is_triggered() {
+                    return Err(Error::Interrupted);
+                }
+                let index = pack::index::File::at(index_path)?;
+                let expected = *hash_kind.get_or_insert(index.hash_kind());
+                if index.hash_kind() != expected {
+                    return Err(Error::HashKindMismatch {
+                        path: index_path.to_owned(),
+                        expected,
+                        actual: index.hash_kind(),
+                    });
+                }
+                pack_mtimes.push(
+                    std::fs::metadata(index_path.with_extension("pack"))
+                        .or_else(|_| std::fs::metadata(index_path))
+                        .and_then(|md| md.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH),
+                );
+                entries.extend(index.iter().map(|e| Entry {
+                    id: e.oid,
+                    pack_index: pack_index as u32,
+                    pack_offset: e.pack_offset,
+                }));
+                entries_progress.inc();
+            }
+            entries.sort_by_key(|e| (e.id, Reverse(pack_mtimes[e.pack_index as usize]), e.pack_index));
+            entries.dedup_by_key(|e| e.id);
+            entries
+        };
+        if entries.len() > u32::MAX as usize {
+            return Err(Error::TooManyObjects(entries.len()));
+        }
+        progress.inc();
+        let hash_kind = hash_kind.unwrap_or_default();
+
+        let _info = progress.add_child("writing multi-pack index");
+        let num_large_offsets = entries
+            .iter()
+            .filter(|e| e.pack_offset > LARGE_OFFSET_THRESHOLD)
+            .count();
+        let pack_names_len = {
+            let len: usize = index_names.iter().map(|name| name.len() + 1).sum();
+            len + (CHUNK_ALIGNMENT - len % CHUNK_ALIGNMENT) % CHUNK_ALIGNMENT
+        };
+        let mut chunks = vec![
+            (chunk::PACK_NAMES, pack_names_len),
+            (chunk::OID_FANOUT, FAN_LEN * 4),
+            (chunk::OID_LOOKUP, entries.len() * hash_kind.len_in_bytes()),
+            (chunk::OBJECT_OFFSETS, entries.len() * 8),
+        ];
+        if num_large_offsets != 0 {
+            chunks.push((chunk::LARGE_OFFSETS, num_large_offsets * 8));
+        }
+
+        let mut out = io::BufWriter::with_capacity(8 * 4096, hash::Write::new(out, hash_kind));
+        out.write_all(SIGNATURE)?;
+        out.write_u8(Version::V1 as u8)?;
+        out.write_u8(match hash_kind {
+            git_hash::Kind::Sha1 => HASH_VERSION_SHA1,
+            git_hash::Kind::Sha256 => HASH_VERSION_SHA256,
+        })?;
+        out.write_u8(chunks.len() as u8)?;
+        out.write_u8(0 /* base multi-pack index files */)?;
+        out.write_u32::<BigEndian>(index_names.len() as u32)?;
+
+        let mut chunk_offset = (HEADER_LEN + (chunks.len() + 1) * CHUNK_TABLE_ENTRY_LEN) as u64;
+        for (id, len) in &chunks {
+            out.write_all(id)?;
+            out.write_u64::<BigEndian>(chunk_offset)?;
+            chunk_offset += *len as u64;
+        }
+        out.write_all(&[0u8; 4])?;
+        out.write_u64::<BigEndian>(chunk_offset)?;
+
+        let mut written = 0;
+        for name in &index_names {
+            out.write_all(name.as_bytes())?;
+            out.write_u8(0)?;
+            written += name.len() + 1;
+        }
+        out.write_all(&[0u8; CHUNK_ALIGNMENT][..pack_names_len - written])?;
+
+        let mut fan = [0u32; FAN_LEN];
+        for entry in &entries {
+            fan[entry.id.first_byte() as usize] += 1;
+        }
+        let mut count = 0;
+        for num_objects_with_byte in fan.iter() {
+            count += num_objects_with_byte;
+            out.write_u32::<BigEndian>(count)?;
+        }
+
+        for entry in &entries {
+            out.write_all(entry.id.as_slice())?;
+        }
+
+        let mut num_large_offsets = 0;
+        for entry in &entries {
+            out.write_u32::<BigEndian>(entry.pack_index)?;
+            out.write_u32::<BigEndian>(if entry.pack_offset > LARGE_OFFSET_THRESHOLD {
+                num_large_offsets += 1;
+                (num_large_offsets - 1) | HIGH_BIT
+            } else {
+                entry.pack_offset as u32
+            })?;
+        }
+        for entry in entries.iter().filter(|e| e.pack_offset > LARGE_OFFSET_THRESHOLD) {
+            out.write_u64::<BigEndian>(entry.pack_offset)?;
+        }
+
+        let mut out = out.into_inner().map_err(|err| err.into_error())?;
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+
+        progress.inc();
+        progress.show_throughput_with(
+            start,
+            entries.len(),
+            progress::count("objects").expect("unit always set"),
+        );
+        Ok(Outcome {
+            version: Version::V1,
+            checksum,
+            num_objects: entries.len() as u32,
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use crate::pack::reverse_index::{self, HEADER_LEN};
+use byteorder::{BigEndian, ByteOrder};
+
+/// Access
+impl reverse_index::File {
+    /// Returns the position in the pack index of the object at `pack_position`, the position of the object in the pack when
+    /// ordering all objects by their pack offset.
+    ///
+    /// The returned position is always in bounds of the pack index as all positions are validated when opening the file.
+    ///
+    /// # Panics
+    ///
+    /// If `pack_position` is out of bounds.
+    pub fn index_position_at(&self, pack_position: u32) -> u32 {
+        let start = HEADER_LEN + pack_position as usize * 4;
+        BigEndian::read_u32(&self.data[start..start + 4])
+    }
+
+    /// Iterate the positions in the pack index of all objects, in the order in which they appear in the pack.
+    pub fn index_positions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.data[HEADER_LEN..HEADER_LEN + self.num_objects as usize * 4]
+            .chunks(4)
+            .map(BigEndian::read_u32)
+    }
+
+    /// Returns the hash of the pack data file that this reverse index belongs to.
+    pub fn pack_checksum(&self) -> git_hash::ObjectId {
+        self.id_at(self.data.len() - self.hash_kind.len_in_bytes() * 2)
+    }
+
+    pub(crate) fn id_at(&self, offset: usize) -> git_hash::ObjectId {
+        git_hash::oid::try_from(&self.data[offset..offset + self.hash_kind.len_in_bytes()])
+            .expect("hash length to be supported")
+            .to_owned()
+    }
+}
@@ -0,0 +1,128 @@
+use crate::pack::{
+    self,
+    reverse_index::{self, Version, HEADER_LEN, SIGNATURE},
+};
+use byteorder::{BigEndian, ByteOrder};
+use filebuffer::FileBuffer;
+use std::{convert::TryFrom, path::Path};
+
+/// Returned by [`reverse_index::File::at()`] and [`reverse_index::File::for_index()`].
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not open reverse index file at '{path}'")]
+    Io {
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error("{message}")]
+    Corrupt { message: String },
+    #[error("Unsupported reverse index version: {version}")]
+    UnsupportedVersion { version: u32 },
+    #[error("Unsupported object hash version: {version}")]
+    UnsupportedHashVersion { version: u32 },
+    #[error("The reverse index at '{path}' doesn't belong to the pack of its pack index")]
+    PackMismatch { path: std::path::PathBuf },
+}
+
+/// Instantiation
+impl reverse_index::File {
+    /// Open the reverse index file at the given `path`.
+    pub fn at(path: impl AsRef<Path>) -> Result<reverse_index::File, Error> {
+        Self::try_from(path.as_ref())
+    }
+
+    /// Open the reverse index next to `index`, with the `.rev` extension, or return `None` if there is none.
+    ///
+    /// It's an error if it exists but doesn't belong to the same pack as `index`.
+    pub fn for_index(index: &pack::index::File) -> Result<Option<reverse_index::File>, Error> {
+        let path = index.path().with_extension("rev");
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let rev = Self::try_from(path.as_path())?;
+        if rev.num_objects != index.num_objects() || rev.pack_checksum() != index.pack_checksum() {
+            return Err(Error::PackMismatch { path });
+        }
+        Ok(Some(rev))
+    }
+}
+
+impl TryFrom<&Path> for reverse_index::File {
+    type Error = Error;
+
+    fn try_from(path: &Path) -> Result<Self, Self::Error> {
+        let data = FileBuffer::open(path).map_err(|e| Error::Io {
+            source: e,
+            path: path.to_owned(),
+        })?;
+        let corrupt = |message: String| Error::Corrupt { message };
+        if data.len() < HEADER_LEN {
+            return Err(corrupt(format!(
+                "Reverse index of size {} is too small for even an empty index",
+                data.len()
+            )));
+        }
+        let (sig, header) = data[..HEADER_LEN].split_at(SIGNATURE.len());
+        if sig != SIGNATURE {
+            return Err(corrupt("Reverse index signature is invalid".into()));
+        }
+        let version = match BigEndian::read_u32(header) {
+            1 => Version::V1,
+            version => return Err(Error::UnsupportedVersion { version }),
+        };
+        let hash_version = BigEndian::read_u32(&header[4..]);
+        let hash_kind = [git_hash::Kind::Sha1, git_hash::Kind::Sha256]
+            .iter()
+            .copied()
+            .find(|kind| reverse_index::hash_version(*kind) == hash_version)
+            .ok_or(Error::UnsupportedHashVersion { version: hash_version })?;
+        let trailer_len = hash_kind.len_in_bytes() * 2;
+        if data.len() < HEADER_LEN + trailer_len {
+            return Err(corrupt(format!(
+                "Reverse index of size {} is too small for even an empty index",
+                data.len()
+            )));
+        }
+        let positions_len = data.len() - HEADER_LEN - trailer_len;
+        if positions_len % 4 != 0 {
+            return Err(corrupt(format!(
+                "The positions of the reverse index take {} bytes, which isn't a multiple of 4",
+                positions_len
+            )));
+        }
+
+        let num_objects = (positions_len / 4) as u32;
+        // Each position in the pack index must be listed exactly once so that lookups can't go out of bounds.
+        let mut seen = vec![false; num_objects as usize];
+        for (pack_position, index_position) in data[HEADER_LEN..HEADER_LEN + positions_len]
+            .chunks(4)
+            .map(BigEndian::read_u32)
+            .enumerate()
+        {
+            match seen.get_mut(index_position as usize) {
+                Some(seen) if !*seen => *seen = true,
+                Some(_) => {
+                    return Err(corrupt(format!(
+                        "The index position {} at pack position {} is listed more than once",
+                        index_position, pack_position
+                    )))
+                }
+                None => {
+                    return Err(corrupt(format!(
+                        "The index position {} at pack position {} is out of bounds",
+                        index_position, pack_position
+                    )))
+                }
+            }
+        }
+
+        Ok(reverse_index::File {
+            path: path.to_owned(),
+            version,
+            num_objects,
+            hash_kind,
+            data,
+        })
+    }
+}
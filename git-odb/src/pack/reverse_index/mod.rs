@@ -0,0 +1,69 @@
+//! A reverse index stored alongside a pack in a `.rev` file, mapping positions of objects in the pack to their positions in the
+//! pack index.
+//!
+//! Objects are listed in the order in which they appear in the pack, that is sorted by their pack offset, which makes sorting
+//! all offsets of the pack index unnecessary.
+use filebuffer::FileBuffer;
+use std::path::PathBuf;
+
+/// The version of a reverse index file
+#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[allow(missing_docs)]
+pub enum Version {
+    V1 = 1,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V1
+    }
+}
+
+/// A representation of a reverse index file
+pub struct File {
+    data: FileBuffer,
+    path: PathBuf,
+    version: Version,
+    num_objects: u32,
+    hash_kind: git_hash::Kind,
+}
+
+/// Basic file information
+impl File {
+    /// The version of the reverse index file
+    pub fn version(&self) -> Version {
+        self.version
+    }
+    /// The path of the opened reverse index file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+    /// The amount of objects in the pack
+    pub fn num_objects(&self) -> u32 {
+        self.num_objects
+    }
+    /// The kind of hash used for the pack checksum and the trailer
+    pub fn hash_kind(&self) -> git_hash::Kind {
+        self.hash_kind
+    }
+}
+
+const SIGNATURE: &[u8] = b"RIDX";
+const HEADER_LEN: usize = 4 + 4 + 4;
+
+mod access;
+///
+pub mod init;
+///
+pub mod verify;
+///
+pub mod write;
+
+/// The hash version used by git in the header of a reverse index to identify the hash `kind`.
+fn hash_version(kind: git_hash::Kind) -> u32 {
+    match kind {
+        git_hash::Kind::Sha1 => 1,
+        git_hash::Kind::Sha256 => 2,
+    }
+}
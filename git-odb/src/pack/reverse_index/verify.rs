@@ -0,0 +1,116 @@
+use crate::pack::{self, reverse_index};
+use git_features::{
+    interrupt::is_triggered,
+    progress::{self, Progress},
+};
+
+/// Returned by [`reverse_index::File::verify_checksum()`] and [`reverse_index::File::verify_integrity()`]
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("reverse index checksum mismatch: expected {expected}, got {actual}")]
+    Mismatch {
+        expected: git_hash::ObjectId,
+        actual: git_hash::ObjectId,
+    },
+    #[error("The reverse index lists {actual} objects, but the pack index has {expected}")]
+    NumObjects { expected: u32, actual: u32 },
+    #[error("The reverse index belongs to pack {actual}, but the pack index to pack {expected}")]
+    PackMismatch {
+        expected: git_hash::ObjectId,
+        actual: git_hash::ObjectId,
+    },
+    #[error("The index position {index_position} at pack position {pack_position} is out of bounds")]
+    IndexPositionOutOfBounds { pack_position: u32, index_position: u32 },
+    #[error("The object at pack position {pack_position} doesn't follow the object before it in the pack")]
+    OutOfOrder { pack_position: u32 },
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Verify and validate the content of the reverse index file
+impl reverse_index::File {
+    /// Returns the trailing hash stored at the end of this reverse index file.
+    ///
+    /// It's a hash over all bytes of the file.
+    pub fn checksum(&self) -> git_hash::ObjectId {
+        self.id_at(self.data.len() - self.hash_kind.len_in_bytes())
+    }
+
+    /// Validate that our [`checksum()`][reverse_index::File::checksum()] matches the actual contents
+    /// of this file, and return it if it does.
+    pub fn verify_checksum(&self, mut progress: impl Progress) -> Result<git_hash::ObjectId, Error> {
+        let data_len_without_trailer = self.data.len() - self.hash_kind.len_in_bytes();
+        let actual = match git_features::hash::bytes_of_file(
+            &self.path,
+            data_len_without_trailer,
+            self.hash_kind,
+            &mut progress,
+        ) {
+            Ok(id) => id,
+            Err(_io_err) => {
+                let mut hasher = git_features::hash::hasher(self.hash_kind);
+                hasher.update(&self.data[..data_len_without_trailer]);
+                hasher.digest()
+            }
+        };
+
+        let expected = self.checksum();
+        if actual == expected {
+            Ok(actual)
+        } else {
+            Err(Error::Mismatch { actual, expected })
+        }
+    }
+
+    /// Validate our checksum and that we list all objects of `index` by ascending pack offset, exactly once.
+    /// Returns our checksum if the integrity check is successful.
+    pub fn verify_integrity(
+        &self,
+        index: &pack::index::File,
+        mut progress: impl Progress,
+    ) -> Result<git_hash::ObjectId, Error> {
+        progress.init(Some(2), progress::steps());
+        let checksum = self.verify_checksum(progress.add_child("Checksum of reverse index"))?;
+        progress.inc();
+
+        if self.num_objects != index.num_objects() {
+            return Err(Error::NumObjects {
+                expected: index.num_objects(),
+                actual: self.num_objects,
+            });
+        }
+        if self.pack_checksum() != index.pack_checksum() {
+            return Err(Error::PackMismatch {
+                expected: index.pack_checksum(),
+                actual: self.pack_checksum(),
+            });
+        }
+        let mut objects_progress = progress.add_child("checking object order");
+        objects_progress.init(Some(self.num_objects as usize), progress::count("objects"));
+        let mut previous_offset = None;
+        for (pack_position, index_position) in self.index_positions().enumerate() {
+            if is_triggered() {
+                return Err(Error::Interrupted);
+            }
+            let pack_position = pack_position as u32;
+            if index_position >= index.num_objects() {
+                return Err(Error::IndexPositionOutOfBounds {
+                    pack_position,
+                    index_position,
+                });
+            }
+            // Strictly ascending offsets also assure that each object is listed only once.
+            let offset = index.pack_offset_at_index(index_position);
+            if let Some(previous_offset) = previous_offset {
+                if previous_offset >= offset {
+                    return Err(Error::OutOfOrder { pack_position });
+                }
+            }
+            previous_offset = Some(offset);
+            objects_progress.inc();
+        }
+        progress.inc();
+        Ok(checksum)
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    hash, pack,
+    pack::reverse_index::{self, Version, SIGNATURE},
+};
+use byteorder::{BigEndian, WriteBytesExt};
+use git_features::progress::{self, Progress};
+use std::io::{self, Write};
+
+/// Returned by [`reverse_index::File::write_from_index()`]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The version of the written reverse index
+    pub version: Version,
+    /// The checksum of the written reverse index, also found in its trailing bytes
+    pub checksum: git_hash::ObjectId,
+    /// The amount of objects listed in the reverse index
+    pub num_objects: u32,
+}
+
+/// Various ways of writing a reverse index file
+impl reverse_index::File {
+    /// Write a reverse index for the pack of `index` into `out`, similar to what `git index-pack --rev-index` does.
+    ///
+    /// The written file is expected to be placed next to the pack index, with the `.rev` extension.
+    pub fn write_from_index(
+        index: &pack::index::File,
+        out: impl io::Write,
+        mut progress: impl Progress,
+    ) -> io::Result<Outcome> {
+        let start = std::time::Instant::now();
+        progress.init(Some(2), progress::steps());
+        let pack_to_index = {
+            let _progress = progress.add_child("sorting by pack offset");
+            let mut pack_to_index: Vec<u32> = (0..index.num_objects()).collect();
+            pack_to_index.sort_by_key(|index_position| index.pack_offset_at_index(*index_position));
+            pack_to_index
+        };
+        progress.inc();
+
+        let _info = progress.add_child("writing reverse index");
+        let mut out = io::BufWriter::with_capacity(8 * 4096, hash::Write::new(out, index.hash_kind()));
+        out.write_all(SIGNATURE)?;
+        out.write_u32::<BigEndian>(Version::V1 as u32)?;
+        out.write_u32::<BigEndian>(reverse_index::hash_version(index.hash_kind()))?;
+        for index_position in &pack_to_index {
+            out.write_u32::<BigEndian>(*index_position)?;
+        }
+        out.write_all(index.pack_checksum().as_slice())?;
+
+        let mut out = out.into_inner().map_err(|err| err.into_error())?;
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+
+        progress.inc();
+        progress.show_throughput_with(
+            start,
+            pack_to_index.len(),
+            progress::count("objects").expect("unit always set"),
+        );
+        Ok(Outcome {
+            version: Version::V1,
+            checksum,
+            num_objects: index.num_objects(),
+        })
+    }
+}
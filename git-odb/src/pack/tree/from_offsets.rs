@@ -35,6 +35,7 @@ impl<T> Tree<T> {
     /// for obtaining the objects entry within the pack.
     /// * `pack_path` is the path to the pack file itself and from which to read the entry data, which is a pack file matching the offsets
     /// returned by `get_pack_offset(…)`.
+    /// * `hash_kind` is the kind of hash used in the pack, which determines the size of the base ids of ref-deltas.
     /// * `progress` is used to track progress when creating the tree.
     /// * `resolve_in_pack_id(git_hash::oid) -> Option<PackOffset>` takes an object ID and tries to resolve it to an object within this pack if
     /// possible. Failing to do so aborts the operation, and this function is not expected to be called in usual packs. It's a theoretical
@@ -45,6 +46,7 @@ impl<T> Tree<T> {
         data_sorted_by_offsets: impl Iterator<Item = T>,
         get_pack_offset: impl Fn(&T) -> PackOffset,
         pack_path: impl AsRef<std::path::Path>,
+        hash_kind: git_hash::Kind,
         mut progress: impl Progress,
         resolve_in_pack_id: impl Fn(&git_hash::oid) -> Option<PackOffset>,
     ) -> Result<Self, Error> {
@@ -83,7 +85,7 @@ impl<T> Tree<T> {
             if let Some(previous_offset) = previous_cursor_position {
                 Self::advance_cursor_to_pack_offset(&mut r, pack_offset, previous_offset)?;
             };
-            let entry = pack::data::Entry::from_read(&mut r, pack_offset).map_err(|err| Error::Io {
+            let entry = pack::data::Entry::from_read(&mut r, pack_offset, hash_kind).map_err(|err| Error::Io {
                 source: err,
                 message: "EOF while parsing header",
             })?;
@@ -40,7 +40,6 @@ impl crate::write::Write for Sink {
         mut from: impl io::Read,
         hash: git_hash::Kind,
     ) -> Result<git_hash::ObjectId, Self::Error> {
-        use git_features::hash::Sha1;
         let mut buf = [0u8; 8096];
 
         let possibly_compress = |buf: &[u8]| -> io::Result<()> {
@@ -49,29 +48,25 @@ impl crate::write::Write for Sink {
             }
             Ok(())
         };
-        match hash {
-            git_hash::Kind::Sha1 => {
-                let mut hasher = Sha1::default();
-                let header_len = loose::object::header::encode(kind, size, &mut buf[..])?;
-                hasher.update(&buf[..header_len]);
-                possibly_compress(&buf[..header_len])?;
+        let mut hasher = git_features::hash::hasher(hash);
+        let header_len = loose::object::header::encode(kind, size, &mut buf[..])?;
+        hasher.update(&buf[..header_len]);
+        possibly_compress(&buf[..header_len])?;
 
-                let mut size: usize = size.try_into().expect("object size to fit into usize");
-                while size != 0 {
-                    let bytes = size.min(buf.len());
-                    from.read_exact(&mut buf[..bytes])?;
-                    hasher.update(&buf[..bytes]);
-                    possibly_compress(&buf[..bytes])?;
-                    size -= bytes;
-                }
-                if let Some(compressor) = self.compressor.as_ref() {
-                    let mut c = compressor.borrow_mut();
-                    c.flush()?;
-                    c.reset();
-                }
-
-                Ok(hasher.digest().into())
-            }
+        let mut size: usize = size.try_into().expect("object size to fit into usize");
+        while size != 0 {
+            let bytes = size.min(buf.len());
+            from.read_exact(&mut buf[..bytes])?;
+            hasher.update(&buf[..bytes]);
+            possibly_compress(&buf[..bytes])?;
+            size -= bytes;
         }
+        if let Some(compressor) = self.compressor.as_ref() {
+            let mut c = compressor.borrow_mut();
+            c.flush()?;
+            c.reset();
+        }
+
+        Ok(hasher.digest())
     }
 }
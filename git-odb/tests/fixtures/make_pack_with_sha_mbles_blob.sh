@@ -0,0 +1,7 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+blob=$(git hash-object -w "$(dirname "$0")/sha-mbles-1.bin")
+echo "$blob" > blob
+echo "$blob" | git pack-objects -q pack > /dev/null
@@ -0,0 +1,17 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q source
+(cd source
+  git config commit.gpgsign false
+  git config uploadpack.allowFilter true
+  for rev in $(seq 1 3); do
+    echo "content $rev" > "file-$rev"
+    git add .
+    git commit -q -m "revision $rev"
+  done
+  git rev-list --objects --all --filter=blob:none --filter-print-omitted | sed -n 's/^~//p' | sort > ../omitted-blobs
+)
+
+git clone -q --no-checkout --filter=blob:none "file://$PWD/source" partial
+(cd partial && git rev-parse HEAD) > head
@@ -0,0 +1,35 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+mkdir dir
+echo "a" > a
+echo "b" > dir/b
+git add a dir
+git commit -q -m "good"
+git repack -adq
+git rev-parse HEAD > tips
+
+# a commit whose tree refers to a blob which doesn't exist
+missing_blob=1111111111111111111111111111111111111111
+echo "$missing_blob" > missing-blob
+tree=$(printf "100644 blob %s\tmissing\n" "$missing_blob" | git mktree --missing)
+git commit-tree -m "broken" "$tree" > broken-commit
+git update-ref refs/heads/broken "$(cat broken-commit)"
+cat broken-commit >> tips
+
+# a commit whose author has a bad email
+good_tree=$(git rev-parse HEAD^{tree})
+printf "tree %s\nauthor a <a@example.com 1234567890 +0000\ncommitter c <c@example.com> 1234567890 +0000\n\nbad email\n" "$good_tree" \
+  | git hash-object --literally -t commit -w --stdin > bad-email-commit
+git update-ref refs/heads/bad-email "$(cat bad-email-commit)"
+cat bad-email-commit >> tips
+
+# an unreachable tree with a zero-padded mode, which refers to existing objects only
+dir_tree=$(git rev-parse HEAD:dir)
+{ printf "040000 dir\0"; echo "$dir_tree" | xxd -r -p; } \
+  | git hash-object --literally -t tree -w --stdin > zero-padded-tree
+
+echo "dangling" | git hash-object -w --stdin > dangling-blob
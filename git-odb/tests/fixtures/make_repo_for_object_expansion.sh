@@ -0,0 +1,45 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+mkdir -p dir/sub other
+echo a > a
+echo b > dir/b
+echo c > dir/sub/c
+echo c > other/same-as-c
+git add .
+git commit -q -m "initial"
+
+git update-index --add --cacheinfo 160000,1111111111111111111111111111111111111111,submodule
+echo a2 > a
+echo d > dir/sub/d
+git add .
+git commit -q -m "add submodule and files"
+
+git checkout -q -b side
+echo b2 > dir/b
+mkdir -p side/nested
+echo e > side/nested/e
+git add .
+git commit -q -m "side change"
+
+git checkout -q main 2>/dev/null || git checkout -q master
+echo a3 > a
+git rm -q dir/sub/c
+git commit -q -am "main change"
+git merge -q --no-ff -m "merge" side
+
+git tag -a -m "annotated" v1 HEAD~1
+echo f > other/f
+git add other/f
+git commit -q -m "tip"
+
+{ git rev-list --all; git rev-parse v1; } > input-all
+git rev-list --objects --all | cut -d' ' -f1 > expected-all
+git rev-parse HEAD^{tree} > input-tree
+git rev-list --objects HEAD^{tree} | cut -d' ' -f1 > expected-tree
+git rev-list HEAD~2..HEAD > input-range
+git rev-list --objects HEAD~2 | cut -d' ' -f1 > excluded-range
+git rev-list --objects HEAD~2..HEAD | cut -d' ' -f1 > expected-range
@@ -0,0 +1,29 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+seq 1 100 > numbers
+git add numbers
+git commit -q -m "packed"
+git repack -adq
+
+echo "loose" > loose
+git add loose
+git commit -q -m "loose"
+
+# a branch which becomes unreachable once it is deleted, with all of its unique objects loose
+branch=$(git rev-parse --abbrev-ref HEAD)
+git checkout -q -b dropped
+echo "dropped" > dropped
+git add dropped
+git commit -q -m "dropped"
+git rev-list --objects dropped --not "$branch" -- | cut -d' ' -f1 > unreachable-objects
+git checkout -q "$branch"
+git branch -q -D dropped
+
+echo "unreachable" | git hash-object -w --stdin >> unreachable-objects
+
+git for-each-ref --format='%(objectname)' > tips
+git rev-list --objects --all | cut -d' ' -f1 > reachable-objects
@@ -0,0 +1,56 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  git config commit.gpgsign false
+  echo "base" > file
+  git add file
+  git commit -q -m "base"
+  git repack -adq
+)
+
+git clone -q --shared base main
+cd main
+git config commit.gpgsign false
+
+function commit_and_pack() {
+  seq 1 "$(($1 * 100))" > numbers
+  git add numbers
+  git commit -q -m "packed $1"
+  git repack -dq
+}
+
+commit_and_pack 1
+
+# a commit which becomes unreachable once its branch is deleted
+git checkout -q -b dropped
+echo "dropped" > dropped
+git add dropped
+git commit -q -m "dropped"
+git rev-parse HEAD > unreachable-packed-commit
+git checkout -q -
+commit_and_pack 2
+git branch -q -D dropped
+
+# a kept pack
+packs_before=$(ls .git/objects/pack/*.pack)
+commit_and_pack 3
+for pack in .git/objects/pack/*.pack; do
+  echo "$packs_before" | grep -q "$pack" || kept_pack=$pack
+done
+touch "${kept_pack%.pack}.keep"
+git show-index < "${kept_pack%.pack}.idx" | cut -d' ' -f2 > kept-objects
+
+commit_and_pack 4
+git tag -a -m "annotated" annotated
+
+echo "loose" > loose
+git add loose
+git commit -q -m "loose"
+echo "unreachable" | git hash-object -w --stdin > unreachable-loose-object
+
+git for-each-ref --format='%(objectname)' > tips
+git rev-parse HEAD >> tips
+git rev-list --objects --all | cut -d' ' -f1 > reachable-objects
+(cd ../base && git rev-list --objects --all | cut -d' ' -f1) > alternate-objects
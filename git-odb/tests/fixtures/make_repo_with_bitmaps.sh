@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+# enough commits for git to not write bitmaps for all of them
+for rev in $(seq 1 150); do
+  mkdir -p "dir-$((rev % 7))"
+  echo "content $rev" > "dir-$((rev % 7))/file-$((rev % 3))"
+  git add .
+  git commit -q -m "revision $rev"
+done
+git tag -a -m "annotated" annotated HEAD~20
+git repack -adbq
+
+for rev in HEAD HEAD~7 HEAD~77 HEAD~149 annotated; do
+  git rev-list --objects "$rev" | cut -d' ' -f1 | git cat-file --batch-check='%(objectname) %(objecttype)' > "objects-$(echo "$rev" | tr '~' '-')"
+done
+git rev-parse HEAD HEAD~7 HEAD~77 HEAD~149 annotated > revs
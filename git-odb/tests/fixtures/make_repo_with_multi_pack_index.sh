@@ -0,0 +1,27 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+for rev in $(seq 1 4); do
+  mkdir -p "dir-$rev"
+  echo "content $rev" > "dir-$rev/file"
+  seq 1 "$((rev * 100))" > numbers
+  git add .
+  git commit -q -m "revision $rev"
+  git repack -dq
+done
+git multi-pack-index write
+(cd .git/objects/pack && ls pack-*.idx) > covered-indices
+
+# a pack which isn't covered by the multi-pack index, and a loose object
+echo "uncovered" > uncovered
+git add uncovered
+git commit -q -m "uncovered"
+git repack -dq
+echo "loose" > loose
+git add loose
+git commit -q -m "loose"
+
+git rev-list --objects --all | cut -d' ' -f1 > all-objects
@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+seq 1 400 > numbers
+printf 'line %s of a file which gets edited a little in every revision\n' $(seq 1 100) > text
+git add numbers text
+git commit -q -m "initial"
+
+for rev in $(seq 1 8); do
+  sed -i.bak "$((rev * 10))s/.*/revision $rev/" numbers
+  sed -i.bak "$((rev * 7))s/.*/an edited line in revision $rev/" text
+  rm -f ./*.bak
+  git commit -q -am "revision $rev"
+done
+
+git repack -adq --window=10 --depth=50
+
+# leave some objects loose
+echo "appended after repacking" >> text
+git commit -q -am "loose"
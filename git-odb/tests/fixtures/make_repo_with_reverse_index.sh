@@ -0,0 +1,14 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+for rev in $(seq 1 4); do
+  seq 1 "$((rev * 100))" > numbers
+  echo "content $rev" > "file-$rev"
+  git add .
+  git commit -q -m "revision $rev"
+done
+git -c pack.writeReverseIndex=true repack -adq
+(cd .git/objects/pack && ls pack-*.idx) > index-name
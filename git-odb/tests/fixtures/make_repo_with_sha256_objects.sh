@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --object-format=sha256
+git config commit.gpgsign false
+
+for rev in $(seq 1 4); do
+  seq 1 "$((rev * 100))" > numbers
+  echo "content $rev" > "file-$rev"
+  git add .
+  git commit -q -m "revision $rev"
+done
+git -c pack.writeReverseIndex=true repack -adbq
+git multi-pack-index write
+(cd .git/objects/pack && ls pack-*.idx) > index-name
+git rev-parse HEAD > packed-commit
+
+echo "loose content" > loose
+git add loose
+git commit -q -m "loose revision"
+git rev-parse HEAD:loose > loose-blob
+git rev-parse HEAD > head
+git rev-list --objects --all | wc -l | tr -d ' ' > num-objects
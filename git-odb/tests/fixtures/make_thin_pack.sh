@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+seq 1 1000 > numbers
+git add .
+git commit -q -m "base"
+git rev-parse HEAD > base
+
+seq 1 1001 > numbers
+git commit -q -am "changed numbers"
+git rev-parse HEAD > head
+printf '%s\n^%s\n' "$(cat head)" "$(cat base)" | git pack-objects -q --thin --stdout --revs > thin.pack
+
+# Only keep the objects of the base revision, which are needed to resolve the thin pack.
+git reset -q --hard "$(cat base)"
+git reflog expire --expire=now --all
+git gc -q --prune=now
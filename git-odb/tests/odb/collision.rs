@@ -0,0 +1,44 @@
+use crate::{fixture_path, hex_to_id, scripted_fixture_repo_read_only};
+use git_features::progress;
+use git_odb::{loose, pack, write::Write};
+use std::path::PathBuf;
+
+// The blocks of the SHA-mbles collision attack only collide when hashed right after the prefix they were computed for.
+// Objects are hashed along with their header though, which is why objects containing them don't collide and are written
+// with the same id as git computes, no matter if collision detection is enabled.
+const SHAMBLES_BLOB: &str = "5a7c30e97646c66422abe0a9793a5fcb9f1cf8d6";
+
+fn shambles() -> crate::Result<Vec<u8>> {
+    Ok(std::fs::read(fixture_path("sha-mbles-1.bin"))?)
+}
+
+#[test]
+fn loose_objects_are_written_with_the_id_git_computes() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let id = loose::Db::at(tmp.path()).write_buf(git_object::Kind::Blob, &shambles()?, git_hash::Kind::Sha1)?;
+    assert_eq!(id, hex_to_id(SHAMBLES_BLOB));
+    Ok(())
+}
+
+#[test]
+fn pack_indices_are_identical_to_the_ones_written_by_git() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_pack_with_sha_mbles_blob.sh")?;
+    assert_eq!(std::fs::read_to_string(repo.join("blob"))?.trim(), SHAMBLES_BLOB);
+    let index_path: PathBuf = std::fs::read_dir(&repo)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| path.extension() == Some("idx".as_ref()))
+        .expect("pack index written by git");
+
+    let outcome = pack::Bundle::write_stream_to_directory(
+        std::io::BufReader::new(std::fs::File::open(index_path.with_extension("pack"))?),
+        None::<PathBuf>,
+        progress::Discard,
+        None::<git_odb::linked::Db>,
+        Default::default(),
+    )?;
+    assert_eq!(
+        outcome.index.index_hash,
+        pack::index::File::at(&index_path)?.index_checksum()
+    );
+    Ok(())
+}
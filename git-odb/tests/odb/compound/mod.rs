@@ -38,3 +38,46 @@ mod locate {
         can_locate(&db(), "dd25c539efbb0ab018caa4cda2d133285634e9b5"); // pack c043
     }
 }
+
+mod multi_index {
+    use crate::{hex_to_id, linked::writable_git_dir, scripted_fixture_repo_read_only};
+    use git_odb::compound::Db;
+    use std::path::Path;
+
+    fn assert_all_objects_are_found(db: &Db, repo: &Path) -> crate::Result {
+        let mut buf = Vec::new();
+        for hex in std::fs::read_to_string(repo.join("all-objects"))?.lines() {
+            assert!(
+                db.find(hex_to_id(hex), &mut buf, &mut git_odb::pack::cache::Never)?
+                    .is_some(),
+                "object {} is found in packs, covered or not, or as loose object",
+                hex
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn is_used_for_covered_packs_and_all_objects_can_be_found() -> crate::Result {
+        let repo = scripted_fixture_repo_read_only("make_repo_with_multi_pack_index.sh")?;
+        let db = Db::at(repo.join(".git").join("objects"))?;
+        assert_eq!(db.packs.len(), 5);
+        let midx = db.multi_index.as_ref().expect("multi-pack index present");
+        assert_eq!(midx.num_packs(), 4, "one pack isn't covered by the multi-pack index");
+        assert_all_objects_are_found(&db, &repo)
+    }
+
+    #[test]
+    fn corrupt_files_are_ignored() -> crate::Result {
+        let repo = scripted_fixture_repo_read_only("make_repo_with_multi_pack_index.sh")?;
+        let (_tmp, git_dir) = writable_git_dir(&repo)?;
+        let path = git_dir.join("objects").join("pack").join("multi-pack-index");
+        let data = std::fs::read(&path)?;
+        std::fs::write(&path, &data[..data.len() / 2])?;
+
+        let db = Db::at(git_dir.join("objects"))?;
+        assert!(db.multi_index.is_none(), "truncated multi-pack indices are ignored");
+        assert_eq!(db.packs.len(), 5);
+        assert_all_objects_are_found(&db, &repo)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::hex_to_id;
+use git_object::Kind;
+use git_odb::{
+    fsck::{self, Problem, Severity},
+    Write,
+};
+
+fn id_of(kind: Kind, data: &[u8]) -> git_hash::ObjectId {
+    git_odb::sink()
+        .write_buf(kind, data, git_hash::Kind::Sha1)
+        .expect("hashing never fails")
+}
+
+fn check(kind: Kind, data: &[u8]) -> Vec<Problem> {
+    fsck::object::check(&id_of(kind, data), kind, data)
+}
+
+fn tree(entries: &[(&str, &str)]) -> Vec<u8> {
+    let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let mut out = Vec::new();
+    for (mode, name) in entries {
+        out.extend_from_slice(format!("{} {}\0", mode, name).as_bytes());
+        out.extend_from_slice(id.as_bytes());
+    }
+    out
+}
+
+fn commit(author: &str) -> Vec<u8> {
+    format!(
+        "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\nauthor {}\ncommitter c <c@example.com> 1234567890 +0000\n\nmessage\n",
+        author
+    )
+    .into_bytes()
+}
+
+mod object {
+    use super::{check, commit, id_of, tree};
+    use bstr::ByteSlice;
+    use git_object::Kind;
+    use git_odb::fsck::{self, Problem, Severity};
+
+    #[test]
+    fn valid_objects_have_no_problems() {
+        assert!(check(Kind::Blob, b"hello").is_empty());
+        assert!(check(
+            Kind::Tree,
+            &tree(&[("100644", "a"), ("40000", "a-dir"), ("100755", "b")])
+        )
+        .is_empty());
+        assert!(check(Kind::Commit, &commit("a <a@example.com> 1234567890 +0100")).is_empty());
+    }
+
+    #[test]
+    fn hash_mismatch() {
+        let actual = id_of(Kind::Blob, b"hello");
+        assert_eq!(
+            fsck::object::check(&id_of(Kind::Blob, b"world"), Kind::Blob, b"hello"),
+            vec![Problem::HashMismatch { actual }]
+        );
+    }
+
+    #[test]
+    fn trees_sort_directories_as_if_they_had_a_trailing_slash() {
+        assert!(
+            check(Kind::Tree, &tree(&[("100644", "a.b"), ("40000", "a")])).is_empty(),
+            "'a/' sorts after 'a.b'"
+        );
+        assert_eq!(
+            check(Kind::Tree, &tree(&[("40000", "a"), ("100644", "a.b")])),
+            vec![Problem::TreeNotSorted { name: "a.b".into() }]
+        );
+    }
+
+    #[test]
+    fn tree_entry_problems() {
+        assert_eq!(
+            check(Kind::Tree, &tree(&[("040000", "dir")])),
+            vec![Problem::ZeroPaddedFileMode { name: "dir".into() }]
+        );
+        assert_eq!(
+            check(Kind::Tree, &tree(&[("100600", "file")])),
+            vec![Problem::BadFileMode {
+                name: "file".into(),
+                mode: "100600".into()
+            }]
+        );
+        assert_eq!(
+            check(Kind::Tree, &tree(&[("100644", "a"), ("100644", "a")])),
+            vec![Problem::DuplicateEntries { name: "a".into() }]
+        );
+        assert_eq!(
+            check(Kind::Tree, &tree(&[("100644", "a"), ("100644", "c"), ("100644", "b")])),
+            vec![Problem::TreeNotSorted { name: "b".into() }]
+        );
+        assert_eq!(
+            check(Kind::Tree, &tree(&[("40000", ".GIT"), ("100644", "a/b")])),
+            vec![
+                Problem::ReservedName { name: ".GIT".into() },
+                Problem::FullPathname { name: "a/b".into() }
+            ]
+        );
+        let mut truncated = tree(&[("100644", "a")]);
+        truncated.pop();
+        assert_eq!(check(Kind::Tree, &truncated), vec![Problem::TruncatedTree]);
+    }
+
+    #[test]
+    fn identity_problems() {
+        for (author, expected) in &[
+            (
+                "a <a@example.com 1234567890 +0000",
+                Problem::BadEmail { field: "author".into() },
+            ),
+            ("a 1234567890 +0000", Problem::MissingEmail { field: "author".into() }),
+            (
+                "a<a@example.com> 1234567890 +0000",
+                Problem::BadName { field: "author".into() },
+            ),
+            (
+                "a <a@example.com> 0123 +0000",
+                Problem::BadDate { field: "author".into() },
+            ),
+            (
+                "a <a@example.com> 1234567890 0000",
+                Problem::BadTimezone { field: "author".into() },
+            ),
+        ] {
+            let problems = check(Kind::Commit, &commit(author));
+            assert!(problems.contains(expected), "{:?} in {:?}", expected, problems);
+            assert_eq!(expected.severity(), Severity::Error);
+        }
+    }
+
+    #[test]
+    fn references_skip_submodules() {
+        let data = tree(&[("100644", "a"), ("40000", "b"), ("160000", "c")]);
+        let kinds: Vec<_> = fsck::object::references(Kind::Tree, &data, git_hash::Kind::Sha1)
+            .into_iter()
+            .map(|(_, kind)| kind)
+            .collect();
+        assert_eq!(kinds, vec![Kind::Blob, Kind::Tree]);
+
+        let data = commit("a <a@example.com> 1234567890 +0100");
+        let references = fsck::object::references(Kind::Commit, &data, git_hash::Kind::Sha1);
+        assert_eq!(references.len(), 1);
+        assert_eq!(
+            references[0].0.to_string().as_bytes().as_bstr(),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+        );
+    }
+}
+
+#[test]
+fn problem_display_uses_git_message_ids() {
+    let problem = Problem::ZeroPaddedFileMode { name: "dir".into() };
+    assert_eq!(problem.severity(), Severity::Warning);
+    assert_eq!(
+        problem.to_string(),
+        "zeroPaddedFilemode: the mode of 'dir' is zero-padded"
+    );
+}
@@ -0,0 +1,79 @@
+use super::ids_in;
+use crate::{hex_to_id, scripted_fixture_repo_read_only};
+use git_features::progress;
+use git_object::Kind;
+use git_odb::{fsck::Problem, linked};
+
+#[test]
+fn missing_dangling_and_malformed_objects_are_found() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_repo_for_fsck.sh")?;
+    let db = linked::Db::at(repo.join(".git").join("objects"))?;
+    let missing_tip = hex_to_id("0000000000000000000000000000000000000001");
+    let tips = ids_in(repo.join("tips"))?.into_iter().chain(Some(missing_tip));
+    let one = |name: &str| -> crate::Result<git_hash::ObjectId> { Ok(ids_in(repo.join(name))?[0]) };
+
+    let outcome = db.fsck(tips, progress::Discard, Default::default())?;
+    assert!(outcome.has_errors());
+    assert_eq!(outcome.missing_tips, vec![missing_tip]);
+    assert_eq!(outcome.missing, vec![one("missing-blob")?]);
+    assert_eq!(outcome.broken_links.len(), 1);
+    let link = &outcome.broken_links[0];
+    assert_eq!(
+        (link.from_kind, link.to, link.expected_kind, link.actual_kind),
+        (Kind::Tree, one("missing-blob")?, Kind::Blob, None)
+    );
+
+    assert_eq!(
+        outcome
+            .malformed
+            .iter()
+            .map(|m| (m.id, m.kind, m.problems.last().cloned()))
+            .collect::<Vec<_>>(),
+        {
+            let mut expected = vec![
+                (
+                    one("bad-email-commit")?,
+                    Kind::Commit,
+                    Some(Problem::BadEmail { field: "author".into() }),
+                ),
+                (
+                    one("zero-padded-tree")?,
+                    Kind::Tree,
+                    Some(Problem::ZeroPaddedFileMode { name: "dir".into() }),
+                ),
+            ];
+            expected.sort();
+            expected
+        }
+    );
+
+    let mut expected_dangling = vec![
+        (one("dangling-blob")?, Kind::Blob),
+        (one("zero-padded-tree")?, Kind::Tree),
+    ];
+    expected_dangling.sort();
+    assert_eq!(outcome.dangling, expected_dangling);
+    assert_eq!(
+        outcome.num_unreachable, 2,
+        "the tree referenced by the dangling tree is reachable anyway"
+    );
+    Ok(())
+}
+
+#[test]
+fn without_tips_all_unreferenced_objects_are_dangling() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_repo_for_fsck.sh")?;
+    let db = linked::Db::at(repo.join(".git").join("objects"))?;
+    let outcome = db.fsck(None, progress::Discard, linked::fsck::Options { thread_limit: Some(1) })?;
+    assert!(
+        outcome.missing.is_empty(),
+        "nothing is reachable and thus nothing is missing"
+    );
+    assert_eq!(outcome.num_unreachable, outcome.num_objects);
+    let dangling_commits = ids_in(repo.join("tips"))?;
+    for id in &dangling_commits {
+        assert!(outcome.dangling.contains(&(*id, Kind::Commit)));
+    }
+    assert_eq!(outcome.dangling.len(), dangling_commits.len() + 2);
+    Ok(())
+}
@@ -1,5 +1,44 @@
-use crate::fixture_path;
-use git_odb::linked::Db;
+use crate::{fixture_path, hex_to_id};
+use git_odb::{linked, linked::Db, pack, Find};
+use std::path::Path;
+
+mod fsck;
+mod promisor;
+mod prune;
+mod refresh;
+mod repack;
+
+pub fn ids_in(path: impl AsRef<Path>) -> crate::Result<Vec<git_hash::ObjectId>> {
+    Ok(std::fs::read_to_string(path)?.lines().map(hex_to_id).collect())
+}
+
+/// Copy the directory at `src` recursively to `dst`.
+pub fn copy_dir(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dst.join(entry.file_name()))?;
+        } else {
+            std::fs::copy(entry.path(), dst.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Return a writable copy of the git directory of the repository at `repo`, along with the temporary directory holding it.
+pub fn writable_git_dir(repo: &Path) -> crate::Result<(git_testtools::tempfile::TempDir, std::path::PathBuf)> {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let git_dir = tmp.path().join("git");
+    copy_dir(&repo.join(".git"), &git_dir)?;
+    Ok((tmp, git_dir))
+}
+
+pub fn contains(db: &linked::Db, id: &git_hash::oid) -> bool {
+    db.find(id, &mut Vec::new(), &mut pack::cache::Never)
+        .expect("no read error")
+        .is_some()
+}
 
 fn db() -> Db {
     Db::at(fixture_path("objects")).expect("valid object path")
@@ -29,7 +68,8 @@ mod iter {
 
 mod locate {
     use crate::{hex_to_id, linked::db};
-    use git_odb::{linked::Db, pack, Find};
+    use git_object::Kind;
+    use git_odb::{find, linked::Db, pack, Find, FindExt};
 
     fn can_locate(db: &Db, hex_id: &str) {
         let mut buf = vec![];
@@ -39,6 +79,21 @@ mod locate {
             .is_some());
     }
 
+    #[test]
+    fn iterators_of_the_wrong_kind_are_errors_naming_the_expected_kind() {
+        let db = db();
+        let mut buf = Vec::new();
+        let blob = hex_to_id("37d4e6c5c48ba0d245164c4e10d5f41140cab980");
+        match db.find_existing_commit_iter(&blob, &mut buf, &mut pack::cache::Never) {
+            Err(find::existing_iter::Error::ObjectKind { expected }) => assert_eq!(expected, Kind::Commit),
+            _ => panic!("a blob is no commit"),
+        }
+        match db.find_existing_tree_iter(&blob, &mut buf, &mut pack::cache::Never) {
+            Err(find::existing_iter::Error::ObjectKind { expected }) => assert_eq!(expected, Kind::Tree),
+            _ => panic!("a blob is no tree"),
+        }
+    }
+
     #[test]
     fn loose_object() {
         can_locate(&db(), "37d4e6c5c48ba0d245164c4e10d5f41140cab980");
@@ -50,6 +105,58 @@ mod locate {
         can_locate(&db(), "4dac9989f96bc5b5b1263b582c08f0c5f0b58542"); // pack a2bf
         can_locate(&db(), "dd25c539efbb0ab018caa4cda2d133285634e9b5"); // pack c043
     }
+
+    #[test]
+    fn headers_of_loose_and_packed_objects() -> crate::Result {
+        let db = db();
+        let mut buf = Vec::new();
+        for hex in &[
+            "37d4e6c5c48ba0d245164c4e10d5f41140cab980",
+            "501b297447a8255d3533c6858bb692575cdefaa0",
+            "4dac9989f96bc5b5b1263b582c08f0c5f0b58542",
+            "dd25c539efbb0ab018caa4cda2d133285634e9b5",
+        ] {
+            let id = hex_to_id(hex);
+            let header = db.find_header(&id)?.expect("id present");
+            let object = db.find(&id, &mut buf, &mut pack::cache::Never)?.expect("id present");
+            assert_eq!(header.kind, object.kind);
+            assert_eq!(header.size, object.data.len() as u64);
+        }
+        assert_eq!(
+            db.find_header(hex_to_id("0000000000000000000000000000000000000001"))?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn streams_of_loose_and_packed_objects() -> crate::Result {
+        use std::io::Read;
+        let db = db();
+        let mut buf = Vec::new();
+        let mut streamed = Vec::new();
+        for hex in &[
+            "37d4e6c5c48ba0d245164c4e10d5f41140cab980",
+            "501b297447a8255d3533c6858bb692575cdefaa0",
+            "4dac9989f96bc5b5b1263b582c08f0c5f0b58542",
+            "dd25c539efbb0ab018caa4cda2d133285634e9b5",
+        ] {
+            let id = hex_to_id(hex);
+            let mut stream = db.find_stream(&id, &mut pack::cache::Never)?.expect("id present");
+            streamed.clear();
+            stream.read_to_end(&mut streamed)?;
+            let object = db.find(&id, &mut buf, &mut pack::cache::Never)?.expect("id present");
+            assert_eq!(stream.kind, object.kind);
+            assert_eq!(streamed, object.data);
+        }
+        assert!(db
+            .find_stream(
+                hex_to_id("0000000000000000000000000000000000000001"),
+                &mut pack::cache::Never
+            )?
+            .is_none());
+        Ok(())
+    }
 }
 
 mod init {
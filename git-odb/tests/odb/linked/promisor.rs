@@ -0,0 +1,119 @@
+use super::{ids_in, writable_git_dir};
+use crate::scripted_fixture_repo_read_only;
+use git_features::progress;
+use git_hash::ObjectId;
+use git_odb::{linked, loose, pack, Find, Write};
+use std::{cell::RefCell, path::Path};
+
+/// Return a fetch callback which copies the requested objects from the `source` repository into the loose objects at
+/// `objects`, recording all requested batches in `calls`.
+fn copy_from_source<'a>(
+    repo: &Path,
+    objects: &Path,
+    calls: &'a RefCell<Vec<Vec<ObjectId>>>,
+) -> impl FnMut(&[ObjectId]) -> Result<(), loose::db::write::Error> + 'a {
+    let source = linked::Db::at(repo.join("source").join(".git").join("objects")).expect("valid source repository");
+    let loose = loose::Db::at(objects);
+    move |ids| {
+        calls.borrow_mut().push(ids.to_vec());
+        let mut buf = Vec::new();
+        for id in ids {
+            if let Some(object) = source
+                .find(id, &mut buf, &mut pack::cache::Never)
+                .expect("no read error")
+            {
+                loose.write_buf(object.kind, object.data, git_hash::Kind::Sha1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn packs_of_partial_clones_are_promisor_packs() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_partial_clone.sh")?;
+    let db = linked::Db::at(repo.join("partial").join(".git").join("objects"))?;
+    assert_eq!(db.dbs[0].packs.len(), 1);
+    assert!(db.dbs[0].packs[0].is_promisor());
+
+    let db = linked::Db::at(repo.join("source").join(".git").join("objects"))?;
+    assert!(db.dbs[0].packs.iter().all(|bundle| !bundle.is_promisor()));
+    Ok(())
+}
+
+#[test]
+fn fsck_reports_objects_missing_from_promisor_packs_as_promised() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_partial_clone.sh")?;
+    let db = linked::Db::at(repo.join("partial").join(".git").join("objects"))?;
+    let outcome = db.fsck(ids_in(repo.join("head"))?, progress::Discard, Default::default())?;
+    assert!(!outcome.has_errors());
+    assert!(outcome.missing.is_empty());
+    assert!(outcome.broken_links.is_empty());
+    assert_eq!(outcome.promised, ids_in(repo.join("omitted-blobs"))?);
+    Ok(())
+}
+
+#[test]
+fn missing_objects_are_fetched_on_demand() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_partial_clone.sh")?;
+    let (_tmp, git_dir) = writable_git_dir(&repo.join("partial"))?;
+    let objects = git_dir.join("objects");
+    let calls = RefCell::new(Vec::new());
+    let promisor = linked::Promisor::new(
+        linked::Db::at(&objects)?,
+        copy_from_source(&repo, &objects, &calls),
+        Default::default(),
+    );
+
+    let blob = ids_in(repo.join("omitted-blobs"))?[0];
+    let mut buf = Vec::new();
+    let object = promisor
+        .find(&blob, &mut buf, &mut pack::cache::Never)?
+        .expect("blob was fetched");
+    assert_eq!(object.kind, git_object::Kind::Blob);
+    assert!(object.data.starts_with(b"content "));
+    assert_eq!(*calls.borrow(), vec![vec![blob]]);
+
+    assert!(
+        promisor.find(&blob, &mut buf, &mut pack::cache::Never)?.is_some(),
+        "the object is now present"
+    );
+    assert_eq!(calls.borrow().len(), 1, "present objects aren't fetched again");
+
+    let unknown = crate::hex_to_id("0000000000000000000000000000000000000001");
+    assert!(
+        promisor.find(&unknown, &mut buf, &mut pack::cache::Never)?.is_none(),
+        "objects unknown to the promisor remote remain missing"
+    );
+    assert_eq!(*calls.borrow().last().expect("one more call"), vec![unknown]);
+    Ok(())
+}
+
+#[test]
+fn prefetching_fetches_missing_objects_in_batches() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_partial_clone.sh")?;
+    let (_tmp, git_dir) = writable_git_dir(&repo.join("partial"))?;
+    let objects = git_dir.join("objects");
+    let calls = RefCell::new(Vec::new());
+    let promisor = linked::Promisor::new(
+        linked::Db::at(&objects)?,
+        copy_from_source(&repo, &objects, &calls),
+        linked::promisor::Options { batch_size: 2 },
+    );
+
+    let head = ids_in(repo.join("head"))?[0];
+    let omitted = ids_in(repo.join("omitted-blobs"))?;
+    let ids = omitted.iter().cloned().chain(Some(head)).chain(omitted.iter().cloned());
+    assert_eq!(promisor.prefetch(ids)?, 3, "present and duplicate objects are ignored");
+    assert_eq!(*calls.borrow(), vec![omitted[..2].to_vec(), omitted[2..].to_vec()]);
+
+    assert_eq!(promisor.prefetch(omitted.iter().cloned())?, 0);
+    assert_eq!(calls.borrow().len(), 2, "nothing is fetched if nothing is missing");
+
+    let outcome = promisor
+        .shared()
+        .snapshot()
+        .fsck(Some(head), progress::Discard, Default::default())?;
+    assert!(outcome.promised.is_empty(), "all promised objects were fetched");
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use super::{contains, ids_in, writable_git_dir};
+use crate::{hex_to_id, scripted_fixture_repo_read_only};
+use git_features::progress;
+use git_odb::{linked, pack, Find};
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+fn repo_and_db() -> crate::Result<(std::path::PathBuf, git_testtools::tempfile::TempDir, linked::Db)> {
+    let repo = scripted_fixture_repo_read_only("make_repo_for_prune.sh")?;
+    let (tmp, git_dir) = writable_git_dir(&repo)?;
+    let db = linked::Db::at(git_dir.join("objects"))?;
+    Ok((repo, tmp, db))
+}
+
+/// Options expiring all objects, as the ones in our copy of the fixture were just modified.
+fn expire_all(dry_run: bool) -> linked::prune::Options {
+    linked::prune::Options {
+        expire: SystemTime::now() + Duration::from_secs(60 * 60),
+        dry_run,
+    }
+}
+
+#[test]
+fn dry_run_lists_unreachable_objects_without_removing_them() -> crate::Result {
+    let (repo, _tmp, db) = repo_and_db()?;
+    let unreachable = ids_in(repo.join("unreachable-objects"))?;
+    assert_eq!(
+        unreachable.len(),
+        4,
+        "commit, tree and blob of a deleted branch and a lone blob"
+    );
+
+    let outcome = db.prune(ids_in(repo.join("tips"))?, progress::Discard, expire_all(true))?;
+    assert_eq!(
+        outcome.objects.iter().collect::<HashSet<_>>(),
+        unreachable.iter().collect::<HashSet<_>>()
+    );
+    assert!(outcome.num_bytes > 0);
+    assert!(unreachable.iter().all(|id| contains(&db, id)), "nothing was removed");
+    Ok(())
+}
+
+#[test]
+fn unreachable_loose_objects_are_removed() -> crate::Result {
+    let (repo, _tmp, db) = repo_and_db()?;
+    let missing_tip = hex_to_id("0000000000000000000000000000000000000001");
+    let tips = ids_in(repo.join("tips"))?.into_iter().chain(Some(missing_tip));
+
+    let outcome = db.prune(tips, progress::Discard, expire_all(false))?;
+    let unreachable = ids_in(repo.join("unreachable-objects"))?;
+    assert_eq!(outcome.objects.len(), unreachable.len());
+    assert!(unreachable.iter().all(|id| !contains(&db, id)));
+    assert!(
+        ids_in(repo.join("reachable-objects"))?
+            .iter()
+            .all(|id| contains(&db, id)),
+        "reachable objects are retained, tips which don't exist are ignored"
+    );
+    assert_eq!(
+        db.dbs[0].loose.iter().count(),
+        3,
+        "the reachable loose commit, its tree and blob"
+    );
+
+    let outcome = db.prune(ids_in(repo.join("tips"))?, progress::Discard, expire_all(false))?;
+    assert!(outcome.objects.is_empty(), "there is nothing left to prune");
+    Ok(())
+}
+
+#[test]
+fn objects_which_are_not_expired_are_retained_along_with_the_objects_they_reach() -> crate::Result {
+    let (repo, _tmp, db) = repo_and_db()?;
+    let outcome = db.prune(ids_in(repo.join("tips"))?, progress::Discard, Default::default())?;
+    assert!(
+        outcome.objects.is_empty(),
+        "all objects were modified within the last two weeks"
+    );
+
+    let outcome = db.prune(None, progress::Discard, Default::default())?;
+    assert!(
+        outcome.objects.is_empty(),
+        "without tips, the recent objects themselves keep everything they reach"
+    );
+    Ok(())
+}
+
+#[test]
+fn objects_missing_below_recent_objects_are_ignored() -> crate::Result {
+    let (repo, _tmp, db) = repo_and_db()?;
+    let unreachable = ids_in(repo.join("unreachable-objects"))?;
+    let mut buf = Vec::new();
+    let unreachable_tree = unreachable
+        .iter()
+        .find_map(|id| {
+            db.find(id, &mut buf, &mut pack::cache::Never)
+                .expect("no read error")
+                .and_then(|obj| obj.into_commit_iter())
+                .and_then(|mut commit| commit.tree_id())
+        })
+        .expect("the unreachable commit");
+    let hex = unreachable_tree.to_string();
+    std::fs::remove_file(db.dbs[0].loose.path.join(&hex[..2]).join(&hex[2..]))?;
+
+    let outcome = db.prune(ids_in(repo.join("tips"))?, progress::Discard, Default::default())?;
+    assert!(
+        outcome.objects.is_empty(),
+        "the recent commit's tree is missing, but everything else is retained"
+    );
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::fixture_path;
+use git_odb::linked;
+use std::path::Path;
+
+const PACK: &str = "pack-a2bf8e71d8c18879e499335762dd95119d93d9f1";
+
+/// Return a copy of the fixture object database holding only the given packs.
+fn objects_with_packs(names: &[&str]) -> crate::Result<(git_testtools::tempfile::TempDir, std::path::PathBuf)> {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let objects = tmp.path().join("objects");
+    std::fs::create_dir_all(objects.join("pack"))?;
+    for name in names {
+        add_pack(&objects, name)?;
+    }
+    Ok((tmp, objects))
+}
+
+fn add_pack(objects: &Path, name: &str) -> std::io::Result<()> {
+    for extension in &["pack", "idx"] {
+        let file_name = format!("{}.{}", name, extension);
+        std::fs::copy(
+            fixture_path("objects").join("pack").join(&file_name),
+            objects.join("pack").join(file_name),
+        )?;
+    }
+    Ok(())
+}
+
+fn remove_pack(objects: &Path, name: &str) -> std::io::Result<()> {
+    for extension in &["idx", "pack"] {
+        std::fs::remove_file(objects.join("pack").join(format!("{}.{}", name, extension)))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn added_and_removed_packs_are_picked_up() -> crate::Result {
+    let (_tmp, objects) = objects_with_packs(&["pack-11fdfa9e156ab73caae3b6da867192221f2089c2"])?;
+    let mut db = linked::Db::at(&objects)?;
+    assert!(!db.is_outdated());
+    assert!(!db.refresh()?, "nothing changed");
+
+    add_pack(&objects, PACK)?;
+    assert!(db.is_outdated());
+    assert!(db.refresh()?);
+    assert!(!db.is_outdated(), "the new state was recorded");
+    assert_eq!(db.dbs[0].packs.len(), 2);
+
+    remove_pack(&objects, PACK)?;
+    assert!(db.refresh()?);
+    assert_eq!(db.dbs[0].packs.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn packs_removed_while_opening_them_are_skipped() -> crate::Result {
+    let (_tmp, objects) = objects_with_packs(&["pack-11fdfa9e156ab73caae3b6da867192221f2089c2", PACK])?;
+    // The state in between removing the pack data file and its index, as seen by a concurrent `git gc`.
+    std::fs::remove_file(objects.join("pack").join(format!("{}.pack", PACK)))?;
+    let db = linked::Db::at(&objects)?;
+    assert_eq!(db.dbs[0].packs.len(), 1, "the incomplete pack is ignored");
+    Ok(())
+}
+
+mod shared {
+    use super::{add_pack, objects_with_packs, PACK};
+    use crate::hex_to_id;
+    use git_object::Kind;
+    use git_odb::{linked, pack, Find};
+
+    #[test]
+    fn objects_in_packs_added_later_are_found_after_refreshing() -> crate::Result {
+        let (_tmp, objects) = objects_with_packs(&[])?;
+        let db = linked::Shared::from(linked::Db::at(&objects)?);
+        let id = hex_to_id("0d9726f3fd2359cda58217724f12af273bf440a6");
+        let mut buf = Vec::new();
+        assert!(db.find(&id, &mut buf, &mut pack::cache::Never)?.is_none());
+
+        let snapshot = db.snapshot();
+        add_pack(&objects, PACK)?;
+        let object = db
+            .find(&id, &mut buf, &mut pack::cache::Never)?
+            .expect("present after refresh");
+        assert_eq!(object.kind, Kind::Tree);
+        assert!(
+            snapshot.find_header(&id)?.is_none(),
+            "snapshots held while refreshing don't change"
+        );
+        assert_eq!(db.snapshot().dbs[0].packs.len(), 1);
+        assert_eq!(db.find_header(&id)?.map(|h| h.kind), Some(Kind::Tree));
+        Ok(())
+    }
+
+    #[test]
+    fn present_objects_are_found_without_refreshing() -> crate::Result {
+        let (_tmp, objects) = objects_with_packs(&[PACK])?;
+        let db = linked::Shared::from(linked::Db::at(&objects)?);
+        let snapshot = db.snapshot();
+        let id = hex_to_id("0d9726f3fd2359cda58217724f12af273bf440a6");
+        let (mut buf, mut expected) = (Vec::new(), Vec::new());
+        let object = db.find(&id, &mut buf, &mut pack::cache::Never)?.expect("present");
+        let expected = snapshot
+            .find(&id, &mut expected, &mut pack::cache::Never)?
+            .expect("present");
+        assert_eq!(object, expected);
+        assert!(snapshot.pack_entry(&object).is_some(), "the pack location is retained");
+        assert!(std::sync::Arc::ptr_eq(&snapshot, &db.snapshot()));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_trigger_no_reload_if_nothing_changed() -> crate::Result {
+        let (_tmp, objects) = objects_with_packs(&[PACK])?;
+        let db = linked::Shared::from(linked::Db::at(&objects)?);
+        let snapshot = db.snapshot();
+        assert!(db
+            .find_header(hex_to_id("0000000000000000000000000000000000000001"))?
+            .is_none());
+        assert!(!db.refresh()?);
+        assert!(
+            std::sync::Arc::ptr_eq(&snapshot, &db.snapshot()),
+            "the database wasn't replaced"
+        );
+        Ok(())
+    }
+}
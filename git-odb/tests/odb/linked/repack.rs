@@ -0,0 +1,157 @@
+use super::{contains, copy_dir, ids_in, writable_git_dir};
+use crate::{fixture_path, scripted_fixture_repo_read_only};
+use git_features::progress;
+use git_odb::{linked, pack};
+use std::collections::HashSet;
+
+mod with_alternates_and_kept_pack {
+    use crate::linked::{contains, ids_in, writable_git_dir};
+    use crate::scripted_fixture_repo_read_only;
+    use git_features::progress;
+    use git_odb::linked;
+    use std::collections::HashSet;
+
+    #[test]
+    fn only_local_reachable_objects_are_packed_and_kept_packs_and_unreachable_loose_objects_remain() -> crate::Result {
+        let repo = scripted_fixture_repo_read_only("make_repo_for_repack.sh")?.join("main");
+        let (_tmp, git_dir) = writable_git_dir(&repo)?;
+        let mut db = linked::Db::at(git_dir.join("objects"))?;
+        assert_eq!(db.dbs.len(), 2, "the base repository is an alternate");
+        let packs_before = db.dbs[0].packs.len();
+        assert_eq!(packs_before, 4);
+
+        let outcome = db.repack(ids_in(repo.join("tips"))?, progress::Discard, Default::default())?;
+
+        let reachable = ids_in(repo.join("reachable-objects"))?;
+        let alternate: HashSet<_> = ids_in(repo.join("alternate-objects"))?.into_iter().collect();
+        let kept: HashSet<_> = ids_in(repo.join("kept-objects"))?.into_iter().collect();
+        let expected: HashSet<_> = reachable
+            .iter()
+            .filter(|id| !alternate.contains(*id) && !kept.contains(*id))
+            .copied()
+            .collect();
+        assert_eq!(outcome.num_objects as usize, expected.len());
+        assert_eq!(outcome.removed_packs.len(), packs_before - 1, "all but the kept pack");
+        assert!(outcome.bitmap.is_none(), "not requested");
+        assert!(outcome.removed_packs.iter().all(|path| !path.exists()));
+
+        let local = &db.dbs[0];
+        assert_eq!(local.packs.len(), 2, "the new pack and the kept one");
+        let new_pack = local
+            .packs
+            .iter()
+            .find(|bundle| Some(bundle.index.path()) == outcome.index_path.as_deref())
+            .expect("new pack is loaded");
+        let actual: HashSet<_> = new_pack.index.iter().map(|entry| entry.oid).collect();
+        assert_eq!(actual, expected);
+        assert!(
+            reachable.iter().all(|id| contains(&db, id)),
+            "all reachable objects can still be found"
+        );
+
+        let unreachable_loose = ids_in(repo.join("unreachable-loose-object"))?;
+        assert_eq!(
+            local.loose.iter().collect::<Result<Vec<_>, _>>()?,
+            unreachable_loose,
+            "loose objects which aren't packed remain"
+        );
+        assert!(
+            !contains(&db, &ids_in(repo.join("unreachable-packed-commit"))?[0]),
+            "unreachable objects of removed packs are gone"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unreachable_objects_of_removed_packs_can_be_kept() -> crate::Result {
+        let repo = scripted_fixture_repo_read_only("make_repo_for_repack.sh")?.join("main");
+        let (_tmp, git_dir) = writable_git_dir(&repo)?;
+        let mut db = linked::Db::at(git_dir.join("objects"))?;
+
+        let outcome = db.repack(
+            ids_in(repo.join("tips"))?,
+            progress::Discard,
+            linked::repack::Options {
+                keep_unreachable: true,
+                write_bitmap_index: true,
+                ..Default::default()
+            },
+        )?;
+        assert!(
+            contains(&db, &ids_in(repo.join("unreachable-packed-commit"))?[0]),
+            "the unreachable commit, its tree and blob are in the new pack"
+        );
+        assert!(
+            outcome.bitmap.is_none(),
+            "bitmaps can't be written as reachable objects are in the alternate and the kept pack"
+        );
+        Ok(())
+    }
+}
+
+#[test]
+fn repository_with_multi_pack_index_is_consolidated_into_one_pack_with_bitmap() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_repo_with_multi_pack_index.sh")?;
+    let (_tmp, git_dir) = writable_git_dir(&repo)?;
+    let all_objects = ids_in(repo.join("all-objects"))?;
+    let head = all_objects[0];
+    let mut db = linked::Db::at(git_dir.join("objects"))?;
+    assert!(db.dbs[0].multi_index.is_some());
+
+    let options = linked::repack::Options {
+        write_bitmap_index: true,
+        ..Default::default()
+    };
+    let outcome = db.repack(Some(head), progress::Discard, options.clone())?;
+    assert_eq!(outcome.num_objects as usize, all_objects.len());
+    assert_eq!(outcome.removed_packs.len(), 5);
+    assert_eq!(
+        outcome.num_removed_loose_objects, 3,
+        "the loose commit, its tree and blob"
+    );
+    assert!(!git_dir.join("objects/pack/multi-pack-index").exists());
+
+    let local = &db.dbs[0];
+    assert!(local.multi_index.is_none());
+    assert_eq!(local.packs.len(), 1);
+    assert_eq!(local.loose.iter().count(), 0);
+    assert!(all_objects.iter().all(|id| contains(&db, id)));
+
+    let bundle = &local.packs[0];
+    let bitmap = pack::bitmap::File::at(bundle.index.path().with_extension("bitmap"))?;
+    assert_eq!(bitmap.checksum(), outcome.bitmap.expect("self-contained pack").checksum);
+    bitmap.verify_integrity(bundle, &mut pack::cache::Never, progress::Discard)?;
+    let lookup = pack::bitmap::Lookup::new(&bitmap, bundle)?;
+    let reachable = lookup.reachable_from(Some(head), &mut pack::cache::Never)?;
+    assert_eq!(
+        lookup.ids(&reachable).map(ToOwned::to_owned).collect::<HashSet<_>>(),
+        all_objects.iter().copied().collect::<HashSet<_>>()
+    );
+
+    let outcome = db.repack(Some(head), progress::Discard, options)?;
+    assert_eq!(db.dbs[0].packs.len(), 1, "repacking again yields a single pack as well");
+    assert!(all_objects.iter().all(|id| contains(&db, id)));
+    assert!(outcome.index_path.expect("objects written").is_file());
+    Ok(())
+}
+
+#[test]
+fn without_tips_all_packs_are_removed_but_loose_objects_remain() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let objects_dir = tmp.path().join("objects");
+    copy_dir(&fixture_path("objects"), &objects_dir)?;
+    let mut db = linked::Db::at(&objects_dir)?;
+    let num_loose_objects = db.dbs[0].loose.iter().count();
+
+    let outcome = db.repack(None, progress::Discard, Default::default())?;
+    assert_eq!(outcome.num_objects, 0);
+    assert!(outcome.index_path.is_none());
+    assert_eq!(outcome.removed_packs.len(), 3, "without tips, no object is reachable");
+    assert_eq!(db.dbs[0].packs.len(), 0);
+    assert_eq!(
+        db.dbs[0].loose.iter().count(),
+        num_loose_objects,
+        "unreachable loose objects are retained"
+    );
+    Ok(())
+}
@@ -26,6 +26,70 @@ fn iter() {
     oids.sort();
     assert_eq!(oids, object_ids())
 }
+mod find_header {
+    use crate::{hex_to_id, loose::db::ldb};
+    use git_object::Kind;
+
+    #[test]
+    fn kind_and_size_match_the_decompressed_object() -> crate::Result {
+        let db = ldb();
+        let mut buf = Vec::new();
+        for id in super::object_ids().into_iter().filter(|id| id.to_string() != BIG_BLOB) {
+            let header = db.find_header(&id)?.expect("id present");
+            let object = db.find(&id, &mut buf)?.expect("id present");
+            assert_eq!(header.kind, object.kind);
+            assert_eq!(header.size, object.data.len() as u64);
+        }
+        Ok(())
+    }
+
+    const BIG_BLOB: &str = "a706d7cd20fc8ce71489f34b50cf01011c104193";
+
+    #[test]
+    fn big_objects_are_only_decompressed_up_to_their_header() -> crate::Result {
+        let header = ldb().find_header(hex_to_id(BIG_BLOB))?.expect("id present");
+        assert_eq!(header.kind, Kind::Blob);
+        assert_eq!(header.size, 56915);
+        Ok(())
+    }
+
+    #[test]
+    fn not_existing() -> crate::Result {
+        assert_eq!(
+            ldb().find_header(hex_to_id("37d4e6c5c48ba0d245164c4e10d5f41140cab989"))?,
+            None
+        );
+        Ok(())
+    }
+}
+
+mod find_stream {
+    use crate::{hex_to_id, loose::db::ldb};
+    use std::io::Read;
+
+    #[test]
+    fn all_objects_are_streamed_with_their_header_stripped() -> crate::Result {
+        let db = ldb();
+        for id in super::object_ids() {
+            let mut stream = db.find_stream(&id)?.expect("id present");
+            assert!(!stream.is_buffered());
+            let mut data = Vec::new();
+            stream.read_to_end(&mut data)?;
+            assert_eq!(data.len() as u64, stream.size);
+            git_odb::data::Object::new(stream.kind, &data).verify_checksum(&id)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn not_existing() -> crate::Result {
+        assert!(ldb()
+            .find_stream(hex_to_id("37d4e6c5c48ba0d245164c4e10d5f41140cab989"))?
+            .is_none());
+        Ok(())
+    }
+}
+
 pub fn locate_oid(id: git_hash::ObjectId, buf: &mut Vec<u8>) -> git_odb::data::Object<'_> {
     ldb().find(id, buf).expect("read success").expect("id present")
 }
@@ -20,8 +20,11 @@ pub fn hex_to_id(hex: &str) -> git_hash::ObjectId {
 }
 
 pub mod alternate;
+pub mod collision;
 pub mod compound;
+pub mod fsck;
 pub mod linked;
 pub mod loose;
 pub mod pack;
+pub mod sha256;
 pub mod sink;
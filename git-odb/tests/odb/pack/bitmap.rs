@@ -0,0 +1,333 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use git_hash::ObjectId;
+use git_odb::pack::{self, bitmap};
+
+use crate::{hex_to_id, scripted_fixture_repo_read_only};
+
+struct Fixture {
+    repo: PathBuf,
+    bundle: pack::Bundle,
+    bitmaps: bitmap::File,
+}
+
+fn fixture() -> crate::Result<Fixture> {
+    let repo = scripted_fixture_repo_read_only("make_repo_with_bitmaps.sh")?;
+    let pack_dir = repo.join(".git").join("objects").join("pack");
+    let bitmap_path = std::fs::read_dir(&pack_dir)?
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .find(|p| p.extension().map_or(false, |ext| ext == "bitmap"))
+        .expect("a bitmap file");
+    Ok(Fixture {
+        bundle: pack::Bundle::at(bitmap_path.with_extension("pack"))?,
+        bitmaps: bitmap::File::at(bitmap_path)?,
+        repo,
+    })
+}
+
+/// Return the ids of all objects reachable from `rev` along with their kind, as listed by `git rev-list --objects`.
+fn expected_objects(repo: &std::path::Path, rev: &str) -> crate::Result<Vec<(ObjectId, String)>> {
+    Ok(
+        std::fs::read_to_string(repo.join(format!("objects-{}", rev.replace('~', "-"))))?
+            .lines()
+            .map(|line| {
+                let mut tokens = line.split(' ');
+                (
+                    hex_to_id(tokens.next().expect("id")),
+                    tokens.next().expect("kind").to_owned(),
+                )
+            })
+            .collect(),
+    )
+}
+
+fn rev_ids(repo: &std::path::Path) -> crate::Result<Vec<(&'static str, ObjectId)>> {
+    Ok(["HEAD", "HEAD~7", "HEAD~77", "HEAD~149", "annotated"]
+        .iter()
+        .copied()
+        .zip(std::fs::read_to_string(repo.join("revs"))?.lines().map(hex_to_id))
+        .collect())
+}
+
+fn ids(lookup: &bitmap::Lookup<'_>, bitmap: &bitmap::Bitmap) -> HashSet<ObjectId> {
+    lookup.ids(bitmap).map(ToOwned::to_owned).collect()
+}
+
+#[test]
+fn read() -> crate::Result {
+    let Fixture { bundle, bitmaps, .. } = fixture()?;
+    assert_eq!(bitmaps.version(), bitmap::Version::V1);
+    assert_eq!(bitmaps.pack_checksum(), bundle.index.pack_checksum());
+    assert!(
+        bitmaps.num_bitmaps() > 0 && bitmaps.num_bitmaps() < 150,
+        "git doesn't write a bitmap for every commit"
+    );
+    assert_eq!(bitmaps.commits().count_ones(), 150);
+    assert_eq!(
+        bitmaps.commits().count_ones()
+            + bitmaps.trees().count_ones()
+            + bitmaps.blobs().count_ones()
+            + bitmaps.tags().count_ones(),
+        bundle.index.num_objects() as usize,
+        "every object has exactly one kind"
+    );
+
+    assert!(bitmaps.has_name_hash_cache());
+    let lookup = bitmap::Lookup::new(&bitmaps, &bundle)?;
+    for position in bitmaps.blobs().iter() {
+        let index_position = bundle
+            .index
+            .lookup(lookup.id_at_position(position))
+            .expect("object in pack");
+        assert_ne!(
+            bitmaps.name_hash_at_index_position(index_position),
+            Some(0),
+            "all blobs were found at a path"
+        );
+    }
+    assert_eq!(bitmaps.name_hash_at_index_position(bundle.index.num_objects()), None);
+    Ok(())
+}
+
+#[test]
+fn reachable_objects_are_the_same_as_the_ones_listed_by_git() -> crate::Result {
+    let Fixture { repo, bundle, bitmaps } = fixture()?;
+    let lookup = bitmap::Lookup::new(&bitmaps, &bundle)?;
+
+    let mut num_revs_without_bitmap = 0;
+    for (rev, id) in rev_ids(&repo)? {
+        if lookup.bitmap_for_commit(id)?.is_none() {
+            num_revs_without_bitmap += 1;
+        }
+        let reachable = lookup.reachable_from(Some(id), &mut pack::cache::Never)?;
+        let expected = expected_objects(&repo, rev)?;
+        assert_eq!(
+            ids(&lookup, &reachable),
+            expected.iter().map(|(id, _)| *id).collect(),
+            "{}",
+            rev
+        );
+
+        let count = |kind: &str| expected.iter().filter(|(_, k)| k == kind).count();
+        assert_eq!(
+            lookup.counts(&reachable),
+            bitmap::Counts {
+                commits: count("commit"),
+                trees: count("tree"),
+                blobs: count("blob"),
+                tags: count("tag"),
+            },
+            "{}",
+            rev
+        );
+        assert_eq!(lookup.counts(&reachable).total(), reachable.count_ones());
+    }
+    assert!(
+        num_revs_without_bitmap > 0,
+        "some commits have to be traversed to reach the ones with bitmaps"
+    );
+    Ok(())
+}
+
+#[test]
+fn and_not_removes_objects_reachable_from_other_tips() -> crate::Result {
+    let Fixture { repo, bundle, bitmaps } = fixture()?;
+    let lookup = bitmap::Lookup::new(&bitmaps, &bundle)?;
+    let revs = rev_ids(&repo)?;
+
+    let mut wants = lookup.reachable_from(Some(revs[0].1), &mut pack::cache::Never)?;
+    let haves = lookup.reachable_from(vec![revs[2].1, revs[4].1], &mut pack::cache::Never)?;
+    wants.and_not(&haves);
+
+    let have_ids: HashSet<_> = expected_objects(&repo, revs[2].0)?
+        .into_iter()
+        .chain(expected_objects(&repo, revs[4].0)?)
+        .map(|(id, _)| id)
+        .collect();
+    let expected: HashSet<_> = expected_objects(&repo, revs[0].0)?
+        .into_iter()
+        .map(|(id, _)| id)
+        .filter(|id| !have_ids.contains(id))
+        .collect();
+    assert_eq!(ids(&lookup, &wants), expected);
+    Ok(())
+}
+
+#[test]
+fn lookups_fail_for_bitmaps_of_other_packs() -> crate::Result {
+    let Fixture { bitmaps, .. } = fixture()?;
+    let other_bundle = pack::Bundle::at(crate::fixture_path(super::SMALL_PACK_INDEX))?;
+    assert!(matches!(
+        bitmap::Lookup::new(&bitmaps, &other_bundle),
+        Err(bitmap::lookup::Error::PackMismatch { .. })
+    ));
+    Ok(())
+}
+
+mod ewah {
+    use git_odb::pack::bitmap::{ewah, Bitmap};
+
+    fn encoded(bit_size: u32, words: &[u64], rlw_position: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&bit_size.to_be_bytes());
+        buf.extend_from_slice(&(words.len() as u32).to_be_bytes());
+        for word in words {
+            buf.extend_from_slice(&word.to_be_bytes());
+        }
+        buf.extend_from_slice(&rlw_position.to_be_bytes());
+        buf
+    }
+
+    fn rlw(running_bit: bool, running_len: u64, num_literals: u64) -> u64 {
+        running_bit as u64 | running_len << 1 | num_literals << 33
+    }
+
+    #[test]
+    fn runs_and_literal_words_are_expanded() -> crate::Result {
+        let mut data = encoded(64 * 4 + 3, &[rlw(true, 2, 1), 0b101, rlw(false, 0, 1), 0b111], 2);
+        data.extend_from_slice(b"rest");
+        let (bitmap, rest) = ewah::decode(&data)?;
+        assert_eq!(rest, b"rest");
+        assert_eq!(bitmap, Bitmap::from_words(vec![u64::MAX, u64::MAX, 0b101, 0b111]));
+        assert_eq!(bitmap.count_ones(), 128 + 2 + 3);
+        assert_eq!(
+            bitmap.iter().skip(128).collect::<Vec<_>>(),
+            vec![128, 130, 192, 193, 194]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn runs_beyond_the_amount_of_bits_are_rejected() {
+        assert!(matches!(
+            ewah::decode(&encoded(64, &[rlw(true, 2, 0)], 0)),
+            Err(ewah::decode::Error::BitOutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_literal_words_are_rejected() {
+        assert!(matches!(
+            ewah::decode(&encoded(128, &[rlw(false, 0, 2), 1], 0)),
+            Err(ewah::decode::Error::LiteralWordsOutOfBounds { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn truncated_input_is_rejected() {
+        let data = encoded(64, &[rlw(true, 1, 0)], 0);
+        assert!(matches!(
+            ewah::decode(&data[..data.len() - 1]),
+            Err(ewah::decode::Error::Truncated { .. })
+        ));
+    }
+}
+
+#[test]
+fn verify_integrity_of_bitmaps_written_by_git() -> crate::Result {
+    let Fixture { bundle, bitmaps, .. } = fixture()?;
+    let checksum = bitmaps.verify_integrity(&bundle, &mut pack::cache::Never, git_features::progress::Discard)?;
+    assert_eq!(checksum, bitmaps.checksum());
+    Ok(())
+}
+
+#[test]
+fn verify_checksum_fails_on_corruption() -> crate::Result {
+    let Fixture { bitmaps, .. } = fixture()?;
+    let mut data = std::fs::read(bitmaps.path())?;
+    let tmp = tempfile::TempDir::new()?;
+    let last_byte = data.len() - 1;
+    data[last_byte] ^= 0xff;
+    let path = tmp.path().join("corrupt.bitmap");
+    std::fs::write(&path, data)?;
+
+    assert!(matches!(
+        bitmap::File::at(path)?.verify_checksum(git_features::progress::Discard),
+        Err(bitmap::verify::Error::Mismatch { .. })
+    ));
+    Ok(())
+}
+
+mod write_from_bundle {
+    use std::collections::HashSet;
+
+    use git_odb::pack::{self, bitmap};
+
+    use crate::pack::bitmap::{fixture, rev_ids, Fixture};
+
+    fn write(
+        Fixture { repo, bundle, .. }: &Fixture,
+        options: bitmap::write::Options,
+    ) -> crate::Result<(tempfile::TempDir, bitmap::File)> {
+        let tmp = tempfile::TempDir::new()?;
+        let path = tmp.path().join("pack.bitmap");
+        let tips = rev_ids(repo)?;
+        let outcome = bitmap::File::write_from_bundle(
+            bundle,
+            vec![tips[0].1, tips[4].1],
+            &mut pack::cache::Never,
+            std::fs::File::create(&path)?,
+            git_features::progress::Discard,
+            options,
+        )?;
+        let bitmaps = bitmap::File::at(path)?;
+        assert_eq!(outcome.version, bitmap::Version::V1);
+        assert_eq!(outcome.checksum, bitmaps.checksum());
+        assert_eq!(outcome.num_bitmaps, bitmaps.num_bitmaps());
+        Ok((tmp, bitmaps))
+    }
+
+    #[test]
+    fn selects_the_same_commits_and_produces_the_same_bitmaps_as_git() -> crate::Result {
+        let fixture = fixture()?;
+        let (_keep, actual) = write(&fixture, Default::default())?;
+        let Fixture {
+            bundle,
+            bitmaps: expected,
+            ..
+        } = fixture;
+        assert_eq!(
+            actual.verify_integrity(&bundle, &mut pack::cache::Never, git_features::progress::Discard)?,
+            actual.checksum()
+        );
+
+        assert_eq!(actual.pack_checksum(), expected.pack_checksum());
+        assert_eq!(actual.commits(), expected.commits());
+        assert_eq!(actual.trees(), expected.trees());
+        assert_eq!(actual.blobs(), expected.blobs());
+        assert_eq!(actual.tags(), expected.tags());
+        assert_eq!(
+            actual.index_positions().collect::<HashSet<_>>(),
+            expected.index_positions().collect::<HashSet<_>>(),
+            "the selection of commits to write bitmaps for is the same as the one of git"
+        );
+        for index_position in expected.index_positions() {
+            assert_eq!(
+                actual.bitmap_at_index_position(index_position)?,
+                expected.bitmap_at_index_position(index_position)?
+            );
+        }
+
+        assert!(actual.has_name_hash_cache());
+        for index_position in 0..bundle.index.num_objects() {
+            assert_eq!(
+                actual.name_hash_at_index_position(index_position),
+                expected.name_hash_at_index_position(index_position),
+                "paths are unique in the fixture, so objects are always found at the same path"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn without_name_hash_cache() -> crate::Result {
+        let fixture = fixture()?;
+        let (_keep, actual) = write(&fixture, bitmap::write::Options { name_hash_cache: false })?;
+        let bundle = fixture.bundle;
+        assert!(!actual.has_name_hash_cache());
+        assert_eq!(actual.name_hash_at_index_position(0), None);
+        actual.verify_integrity(&bundle, &mut pack::cache::Never, git_features::progress::Discard)?;
+        Ok(())
+    }
+}
@@ -35,6 +35,82 @@ mod locate {
         }
     }
 
+    mod find_header {
+        use crate::{fixture_path, hex_to_id, pack::PACKS_AND_INDICES};
+        use git_odb::pack;
+
+        #[test]
+        fn kind_and_size_match_the_decoded_object_without_decoding_deltas() -> crate::Result {
+            let mut num_deltified = 0;
+            for (_index_path, data_path) in PACKS_AND_INDICES {
+                let bundle = pack::Bundle::at(fixture_path(data_path))?;
+                let mut buf = Vec::new();
+                for entry in bundle.index.iter() {
+                    let header = bundle.find_header(entry.oid)?.expect("id present");
+                    let obj = bundle
+                        .find(entry.oid, &mut buf, &mut pack::cache::Never)?
+                        .expect("id present");
+                    assert_eq!(header.kind, obj.kind);
+                    assert_eq!(header.object_size, obj.data.len() as u64);
+                    if header.num_deltas > 0 {
+                        num_deltified += 1;
+                    }
+                }
+            }
+            assert!(num_deltified > 0, "deltified objects are part of the fixtures");
+            Ok(())
+        }
+
+        #[test]
+        fn not_existing() -> crate::Result {
+            let bundle = pack::Bundle::at(fixture_path(PACKS_AND_INDICES[0].1))?;
+            assert_eq!(
+                bundle.find_header(hex_to_id("0000000000000000000000000000000000000001"))?,
+                None
+            );
+            Ok(())
+        }
+    }
+
+    mod find_stream {
+        use crate::{fixture_path, pack::PACKS_AND_INDICES};
+        use git_odb::pack;
+        use std::io::Read;
+
+        #[test]
+        fn undeltified_objects_are_streamed_and_deltified_ones_are_buffered() -> crate::Result {
+            let (mut num_streamed, mut num_buffered) = (0, 0);
+            for (_index_path, data_path) in PACKS_AND_INDICES {
+                let bundle = pack::Bundle::at(fixture_path(data_path))?;
+                let mut buf = Vec::new();
+                let mut streamed = Vec::new();
+                for entry in bundle.index.iter() {
+                    let mut stream = bundle
+                        .find_stream(entry.oid, &mut pack::cache::Never)?
+                        .expect("id present");
+                    streamed.clear();
+                    stream.read_to_end(&mut streamed)?;
+                    let obj = bundle
+                        .find(entry.oid, &mut buf, &mut pack::cache::Never)?
+                        .expect("id present");
+                    assert_eq!(stream.kind, obj.kind);
+                    assert_eq!(stream.size, obj.data.len() as u64);
+                    assert_eq!(streamed, obj.data);
+
+                    let is_delta = bundle.find_header(entry.oid)?.expect("id present").num_deltas > 0;
+                    assert_eq!(stream.is_buffered(), is_delta);
+                    if is_delta {
+                        num_buffered += 1;
+                    } else {
+                        num_streamed += 1;
+                    }
+                }
+            }
+            assert!(num_streamed > 0 && num_buffered > 0);
+            Ok(())
+        }
+    }
+
     #[test]
     fn blob() -> Result<(), Box<dyn std::error::Error>> {
         let mut out = Vec::new();
@@ -73,7 +149,7 @@ mod locate {
 }
 
 mod write_to_directory {
-    use crate::{fixture_path, pack::SMALL_PACK, pack::SMALL_PACK_INDEX};
+    use crate::{fixture_path, pack::SMALL_PACK, pack::SMALL_PACK_INDEX, scripted_fixture_repo_read_only};
     use git_features::progress;
     use git_odb::pack::{self, bundle};
     use std::{fs, path::Path};
@@ -115,7 +191,7 @@ mod write_to_directory {
         sorted_entries.sort_by_key(|e| e.file_name());
         assert_eq!(sorted_entries.len(), 2, "we want a pack and the corresponding index");
 
-        let pack_hash = res.index.data_hash.to_sha1_hex_string();
+        let pack_hash = res.index.data_hash.to_string();
         assert_eq!(file_name(&sorted_entries[0]), format!("{}.idx", pack_hash));
         assert_eq!(Some(sorted_entries[0].path()), index_path);
 
@@ -127,6 +203,54 @@ mod write_to_directory {
         Ok(())
     }
 
+    #[test]
+    fn packs_without_deltas_referring_to_objects_outside_of_the_pack_are_written_unchanged(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let db = git_odb::linked::Db::at(fixture_path("objects"))?;
+        let mut res = pack::Bundle::write_stream_to_directory(
+            std::io::BufReader::new(fs::File::open(fixture_path(SMALL_PACK))?),
+            Some(&dir),
+            progress::Discard,
+            Some(db),
+            bundle::write::Options::default(),
+        )?;
+        let data_path = res.data_path.take().expect("directory set");
+        res.index_path = None;
+        assert_eq!(res, expected_outcome()?);
+        assert_eq!(fs::read(data_path)?, fs::read(fixture_path(SMALL_PACK))?);
+        Ok(())
+    }
+
+    #[test]
+    fn thin_packs_are_resolved_by_inserting_the_bases_of_deltas() -> Result<(), Box<dyn std::error::Error>> {
+        let repo = scripted_fixture_repo_read_only("make_thin_pack.sh")?;
+        let dir = TempDir::new()?;
+        let res = pack::Bundle::write_stream_to_directory(
+            std::io::BufReader::new(fs::File::open(repo.join("thin.pack"))?),
+            Some(&dir),
+            progress::Discard,
+            Some(git_odb::linked::Db::at(repo.join(".git").join("objects"))?),
+            bundle::write::Options::default(),
+        )?;
+        assert_eq!(res.index.num_objects, 4, "the base of the delta was inserted");
+
+        let bundle = res.to_bundle().expect("written to a directory")?;
+        bundle.index.verify_integrity(
+            Some((
+                &bundle.pack,
+                pack::index::verify::Mode::Sha1Crc32DecodeEncode,
+                pack::index::traverse::Algorithm::DeltaTreeLookup,
+                || pack::cache::Never,
+            )),
+            None,
+            progress::Discard.into(),
+        )?;
+        let head = git_hash::ObjectId::from_hex(fs::read_to_string(repo.join("head"))?.trim().as_bytes())?;
+        assert!(bundle.find(head, &mut Vec::new(), &mut pack::cache::Never)?.is_some());
+        Ok(())
+    }
+
     fn file_name(entry: &fs::DirEntry) -> String {
         entry.path().file_name().unwrap().to_str().unwrap().to_owned()
     }
@@ -141,10 +265,12 @@ mod write_to_directory {
             None,
             directory,
             progress::Discard,
+            None::<git_odb::linked::Db>,
             bundle::write::Options {
                 thread_limit: None,
                 iteration_mode: pack::data::input::Mode::Verify,
                 index_kind: pack::index::Version::V2,
+                hash_kind: git_hash::Kind::Sha1,
             },
         )
         .map_err(Into::into)
@@ -85,6 +85,7 @@ mod entries {
                     std::io::BufReader::new(std::fs::File::open(pack_file_path)?),
                     Some(tmp_dir.path()),
                     progress::Discard,
+                    None::<linked::Db>,
                     pack::bundle::write::Options::default(),
                 )?
                 .data_path
@@ -100,4 +101,470 @@ mod entries {
             Ok(())
         }
     }
+
+    mod delta_compression {
+        use crate::scripted_fixture_repo_read_only;
+        use git_features::progress;
+        use git_odb::{compound, linked, pack, pack::data::output};
+        use std::sync::Arc;
+
+        fn db() -> crate::Result<Arc<linked::Db>> {
+            let path = scripted_fixture_repo_read_only("make_repo_with_packed_revisions.sh")?
+                .join(".git")
+                .join("objects");
+            linked::Db::at(path).map_err(Into::into).map(Into::into)
+        }
+
+        fn entries(
+            db: &Arc<linked::Db>,
+            delta_search: Option<output::objects_to_entries::DeltaSearch>,
+        ) -> crate::Result<Vec<output::Entry>> {
+            entries_of(
+                db,
+                db.arc_iter().flat_map(Result::ok).collect(),
+                output::objects_to_entries::Options {
+                    delta_search,
+                    ..Default::default()
+                },
+            )
+        }
+
+        fn entries_of(
+            db: &Arc<linked::Db>,
+            objects: Vec<git_hash::ObjectId>,
+            options: output::objects_to_entries::Options,
+        ) -> crate::Result<Vec<output::Entry>> {
+            Ok(output::objects_to_entries_iter(
+                db.clone(),
+                || pack::cache::Never,
+                objects.into_iter(),
+                progress::Discard,
+                options,
+            )
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect())
+        }
+
+        fn delta_bases(entries: &[output::Entry]) -> Vec<(usize, usize)> {
+            entries
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, e)| match e.entry_kind {
+                    output::entry::Kind::DeltaRef { nth_before } => Some((idx, idx - nth_before)),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// Write `entries` into a pack in `dir` and return its path along with its size in bytes.
+        fn write_pack(entries: Vec<output::Entry>, dir: &std::path::Path) -> crate::Result<(std::path::PathBuf, u64)> {
+            let pack_file_path = dir.join("new.pack");
+            let mut pack_file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&pack_file_path)?;
+            let num_entries = entries.len();
+            let num_written_bytes = output::entries_to_bytes::EntriesToBytesIter::new(
+                entries
+                    .chunks(3)
+                    .map(|chunk| Ok::<_, output::objects_to_entries::Error<compound::find::Error>>(chunk.to_vec())),
+                &mut pack_file,
+                num_entries as u32,
+                pack::data::Version::V2,
+                git_hash::Kind::Sha1,
+            )
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .sum::<u64>();
+            assert_eq!(num_written_bytes, pack_file.metadata()?.len());
+            Ok((pack_file_path, num_written_bytes))
+        }
+
+        /// Write `entries` into a pack, verify it and return its size in bytes.
+        fn write_and_verify(entries: Vec<output::Entry>) -> crate::Result<u64> {
+            let tmp_dir = tempfile::TempDir::new()?;
+            let num_entries = entries.len();
+            let (pack_file_path, num_written_bytes) = write_pack(entries, tmp_dir.path())?;
+
+            let bundle = pack::Bundle::at(
+                pack::Bundle::write_stream_to_directory(
+                    std::io::BufReader::new(std::fs::File::open(pack_file_path)?),
+                    Some(tmp_dir.path()),
+                    progress::Discard,
+                    None::<linked::Db>,
+                    pack::bundle::write::Options::default(),
+                )?
+                .data_path
+                .expect("directory set"),
+            )?;
+            assert_eq!(bundle.index.num_objects() as usize, num_entries);
+            bundle.verify_integrity(
+                pack::index::verify::Mode::Sha1Crc32DecodeEncode,
+                pack::index::traverse::Algorithm::DeltaTreeLookup,
+                || pack::cache::Never,
+                None,
+                progress::Discard.into(),
+            )?;
+            Ok(num_written_bytes)
+        }
+
+        #[test]
+        fn searching_deltas_produces_smaller_packs() -> crate::Result {
+            let db = db()?;
+            let obj_count = db.iter().count();
+            let without_deltas = entries(&db, None)?;
+            assert!(delta_bases(&without_deltas).is_empty());
+
+            let with_deltas = entries(
+                &db,
+                Some(output::objects_to_entries::DeltaSearch {
+                    reuse_deltas: false,
+                    ..Default::default()
+                }),
+            )?;
+            assert_eq!(with_deltas.len(), obj_count, "each object gets one entry");
+            let bases = delta_bases(&with_deltas);
+            assert!(
+                bases.len() >= 16,
+                "all but the first revision of both files become deltas"
+            );
+            for (delta, base) in bases {
+                assert_eq!(with_deltas[delta].object_kind, with_deltas[base].object_kind);
+            }
+
+            let size_without_deltas = write_and_verify(without_deltas)?;
+            let size_with_deltas = write_and_verify(with_deltas)?;
+            assert!(
+                size_with_deltas * 2 < size_without_deltas,
+                "{} should be much smaller than {}",
+                size_with_deltas,
+                size_without_deltas
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn deltas_can_be_reused_from_existing_packs() -> crate::Result {
+            let db = db()?;
+            let with_reuse = entries(&db, Some(Default::default()))?;
+            assert!(!delta_bases(&with_reuse).is_empty());
+            write_and_verify(with_reuse)?;
+            Ok(())
+        }
+
+        #[test]
+        fn delta_chains_are_limited_in_length() -> crate::Result {
+            let db = db()?;
+            for reuse_deltas in [false, true].iter().copied() {
+                let entries = entries(
+                    &db,
+                    Some(output::objects_to_entries::DeltaSearch {
+                        depth: 1,
+                        reuse_deltas,
+                        ..Default::default()
+                    }),
+                )?;
+                let bases = delta_bases(&entries);
+                assert!(!bases.is_empty());
+                for (_, base) in bases {
+                    assert_eq!(
+                        entries[base].entry_kind,
+                        output::entry::Kind::Base,
+                        "deltas are never based on deltas"
+                    );
+                }
+                write_and_verify(entries)?;
+            }
+
+            let no_deltas = entries(
+                &db,
+                Some(output::objects_to_entries::DeltaSearch {
+                    depth: 0,
+                    ..Default::default()
+                }),
+            )?;
+            assert!(delta_bases(&no_deltas).is_empty());
+            Ok(())
+        }
+
+        #[test]
+        fn expanded_objects_are_written_once_into_valid_packs() -> crate::Result {
+            let db = db()?;
+            let obj_count = db.iter().count();
+            let entries = entries_of(
+                &db,
+                db.arc_iter().flat_map(Result::ok).collect(),
+                output::objects_to_entries::Options {
+                    input_object_expansion: output::objects_to_entries::ObjectExpansion::TreeContents,
+                    delta_search: Some(Default::default()),
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(
+                entries.len(),
+                obj_count,
+                "all objects are reachable, and each is written once"
+            );
+            assert!(!delta_bases(&entries).is_empty());
+            write_and_verify(entries)?;
+            Ok(())
+        }
+
+        #[test]
+        fn thin_packs_are_completed_with_their_bases_when_indexing() -> crate::Result {
+            let db = db()?;
+            let mut packed: Vec<_> = db.dbs[0].packs[0].index.iter().map(|e| e.oid).collect();
+            packed.sort();
+            let thin_pack_bases = packed.split_off(packed.len() / 2);
+            let entries = entries_of(
+                &db,
+                packed.clone(),
+                output::objects_to_entries::Options {
+                    delta_search: Some(output::objects_to_entries::DeltaSearch {
+                        reuse_deltas: false,
+                        ..Default::default()
+                    }),
+                    thin_pack_bases,
+                    ..Default::default()
+                },
+            )?;
+            let mut used_bases: Vec<_> = entries
+                .iter()
+                .filter_map(|e| match e.entry_kind {
+                    output::entry::Kind::DeltaOid { id } => Some(id),
+                    _ => None,
+                })
+                .collect();
+            used_bases.sort();
+            used_bases.dedup();
+            assert!(!used_bases.is_empty(), "it's a thin pack");
+
+            let tmp_dir = tempfile::TempDir::new()?;
+            let num_entries = entries.len();
+            let (pack_file_path, _) = write_pack(entries, tmp_dir.path())?;
+            let write_bundle = |db: Option<Arc<linked::Db>>| -> crate::Result<pack::bundle::write::Outcome> {
+                Ok(pack::Bundle::write_stream_to_directory(
+                    std::io::BufReader::new(std::fs::File::open(&pack_file_path)?),
+                    Some(tmp_dir.path()),
+                    progress::Discard,
+                    db,
+                    pack::bundle::write::Options::default(),
+                )?)
+            };
+            assert!(
+                write_bundle(None).is_err(),
+                "thin packs can't be indexed without looking up their bases"
+            );
+
+            let outcome = write_bundle(Some(db))?;
+            assert_eq!(
+                outcome.index.num_objects as usize,
+                num_entries + used_bases.len(),
+                "each base is added once"
+            );
+            let bundle = outcome.to_bundle().expect("directory set")?;
+            bundle.verify_integrity(
+                pack::index::verify::Mode::Sha1Crc32DecodeEncode,
+                pack::index::traverse::Algorithm::DeltaTreeLookup,
+                || pack::cache::Never,
+                None,
+                progress::Discard.into(),
+            )?;
+            let mut buf = Vec::new();
+            for id in packed.iter().chain(used_bases.iter()) {
+                assert!(bundle.find(id, &mut buf, &mut pack::cache::Never)?.is_some());
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn thin_packs_refer_to_objects_the_receiver_has() -> crate::Result {
+            let db = db()?;
+            let mut packed: Vec<_> = db.dbs[0].packs[0].index.iter().map(|e| e.oid).collect();
+            packed.sort();
+            let mut objects: Vec<_> = db.dbs[0].loose.iter().collect::<Result<_, _>>()?;
+            let thin_pack_bases = packed.split_off(packed.len() / 2);
+            objects.extend(packed);
+
+            for reuse_deltas in [false, true].iter().copied() {
+                let options = output::objects_to_entries::Options {
+                    delta_search: Some(output::objects_to_entries::DeltaSearch {
+                        reuse_deltas,
+                        ..Default::default()
+                    }),
+                    thin_pack_bases: thin_pack_bases.clone(),
+                    ..Default::default()
+                };
+                let entries = entries_of(&db, objects.clone(), options.clone())?;
+                assert_eq!(entries.len(), objects.len(), "the bases are not written");
+                let thin_bases: Vec<_> = entries
+                    .iter()
+                    .filter_map(|e| match e.entry_kind {
+                        output::entry::Kind::DeltaOid { id } => Some(id),
+                        _ => None,
+                    })
+                    .collect();
+                assert!(!thin_bases.is_empty(), "some deltas refer to objects the receiver has");
+                for id in thin_bases {
+                    assert!(thin_pack_bases.contains(&id));
+                }
+                for (delta, base) in delta_bases(&entries) {
+                    assert_eq!(entries[delta].object_kind, entries[base].object_kind);
+                }
+
+                let entries = entries_of(
+                    &db,
+                    objects.clone(),
+                    output::objects_to_entries::Options {
+                        delta_search: None,
+                        ..options
+                    },
+                )?;
+                assert!(
+                    entries
+                        .iter()
+                        .all(|e| matches!(e.entry_kind, output::entry::Kind::Base)),
+                    "bases are only used with delta search"
+                );
+            }
+            Ok(())
+        }
+    }
+
+    mod object_expansion {
+        use crate::scripted_fixture_repo_read_only;
+        use git_features::progress;
+        use git_hash::ObjectId;
+        use git_odb::{linked, pack, pack::data::output};
+        use std::{path::PathBuf, sync::Arc};
+
+        fn repo() -> crate::Result<(PathBuf, Arc<linked::Db>)> {
+            let dir = scripted_fixture_repo_read_only("make_repo_for_object_expansion.sh")?;
+            let db = linked::Db::at(dir.join(".git").join("objects"))?;
+            Ok((dir, db.into()))
+        }
+
+        /// Read the ids in the file `name` written by the fixture script.
+        fn ids(dir: &PathBuf, name: &str) -> crate::Result<Vec<ObjectId>> {
+            Ok(std::fs::read_to_string(dir.join(name))?
+                .lines()
+                .map(|hex| ObjectId::from_hex(hex.as_bytes()))
+                .collect::<Result<_, _>>()?)
+        }
+
+        fn sorted(mut ids: Vec<ObjectId>) -> Vec<ObjectId> {
+            ids.sort();
+            ids
+        }
+
+        /// Return the ids of all entries produced from `objects` with each delta search setting, asserting that
+        /// every object is written only once.
+        fn expanded_ids(
+            db: &Arc<linked::Db>,
+            objects: Vec<ObjectId>,
+            options: output::objects_to_entries::Options,
+        ) -> crate::Result<Vec<ObjectId>> {
+            let mut expected = None;
+            for delta_search in [None, Some(Default::default())].iter().copied() {
+                let entries: Vec<_> = output::objects_to_entries_iter(
+                    db.clone(),
+                    || pack::cache::Never,
+                    objects.clone().into_iter(),
+                    progress::Discard,
+                    output::objects_to_entries::Options {
+                        delta_search,
+                        ..options.clone()
+                    },
+                )
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
+                .collect();
+                let ids = sorted(entries.iter().map(|e| e.id).collect());
+                let mut unique_ids = ids.clone();
+                unique_ids.dedup();
+                assert_eq!(ids, unique_ids, "objects are written only once");
+                match &expected {
+                    None => expected = Some(ids),
+                    Some(expected) => {
+                        assert_eq!(&ids, expected, "delta search doesn't affect which objects are written")
+                    }
+                }
+            }
+            Ok(expected.expect("at least one iteration"))
+        }
+
+        #[test]
+        fn commits_and_tags_expand_to_all_reachable_objects() -> crate::Result {
+            let (dir, db) = repo()?;
+            for expansion in [
+                output::objects_to_entries::ObjectExpansion::TreeContents,
+                output::objects_to_entries::ObjectExpansion::TreeAdditionsComparedToAncestor,
+            ]
+            .iter()
+            .copied()
+            {
+                let actual = expanded_ids(
+                    &db,
+                    ids(&dir, "input-all")?,
+                    output::objects_to_entries::Options {
+                        input_object_expansion: expansion,
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(actual, sorted(ids(&dir, "expected-all")?), "{:?}", expansion);
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn trees_expand_recursively() -> crate::Result {
+            let (dir, db) = repo()?;
+            let actual = expanded_ids(
+                &db,
+                ids(&dir, "input-tree")?,
+                output::objects_to_entries::Options {
+                    input_object_expansion: output::objects_to_entries::ObjectExpansion::TreeContents,
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(actual, sorted(ids(&dir, "expected-tree")?));
+            Ok(())
+        }
+
+        #[test]
+        fn commit_ranges_only_expand_to_objects_the_receiver_does_not_have() -> crate::Result {
+            let (dir, db) = repo()?;
+            let options = output::objects_to_entries::Options {
+                input_object_expansion: output::objects_to_entries::ObjectExpansion::TreeAdditionsComparedToAncestor,
+                excluded_objects: ids(&dir, "excluded-range")?,
+                ..Default::default()
+            };
+            let actual = expanded_ids(&db, ids(&dir, "input-range")?, options.clone())?;
+            assert_eq!(actual, sorted(ids(&dir, "expected-range")?));
+
+            let input = ids(&dir, "input-all")?;
+            let as_is = expanded_ids(
+                &db,
+                input.clone(),
+                output::objects_to_entries::Options {
+                    input_object_expansion: output::objects_to_entries::ObjectExpansion::AsIs,
+                    ..options.clone()
+                },
+            )?;
+            assert_eq!(
+                as_is,
+                sorted(
+                    input
+                        .into_iter()
+                        .filter(|id| !options.excluded_objects.contains(id))
+                        .collect()
+                ),
+                "excluded objects are never written"
+            );
+            Ok(())
+        }
+    }
 }
@@ -107,6 +107,7 @@ mod file {
                     io::BufReader::new(fs::File::open(fixture_path(data_path))?),
                     *mode,
                     *compressed,
+                    git_hash::Kind::Sha1,
                 )?;
 
                 let mut actual = Vec::<u8>::new();
@@ -305,7 +306,7 @@ mod file {
                 .values()
                 .map(|v| *v as usize)
                 .sum::<usize>();
-            let sorted_offsets = idx.sorted_offsets();
+            let sorted_offsets = idx.sorted_offsets()?;
             assert_eq!(num_objects, sorted_offsets.len());
             for idx_entry in idx.iter() {
                 let pack_entry = pack.entry(idx_entry.pack_offset);
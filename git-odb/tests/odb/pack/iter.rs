@@ -4,7 +4,7 @@ use git_odb::pack;
 fn size_of_entry() {
     assert_eq!(
         std::mem::size_of::<pack::data::input::Entry>(),
-        104,
+        136,
         "let's keep the size in check as we have many of them"
     );
 }
@@ -25,6 +25,7 @@ mod new_from_header {
                 std::io::BufReader::new(data.as_slice()),
                 Mode::AsIs,
                 EntryDataMode::Ignore,
+                git_hash::Kind::Sha1,
             )? {
                 let entry = entry?;
 
@@ -60,6 +61,7 @@ mod new_from_header {
                     std::io::BufReader::new(fs::File::open(fixture_path(SMALL_PACK))?),
                     *trailer_mode,
                     *compression_mode,
+                    git_hash::Kind::Sha1,
                 )?;
 
                 let num_objects = iter.len();
@@ -101,6 +103,7 @@ mod new_from_header {
             std::io::BufReader::new(&pack[..pack.len() - 20]),
             Mode::Restore,
             EntryDataMode::Ignore,
+            git_hash::Kind::Sha1,
         )?;
         let num_objects = iter.len();
         assert_eq!(iter.by_ref().take(42 - 1).count(), num_objects - 1);
@@ -119,6 +122,7 @@ mod new_from_header {
             std::io::BufReader::new(&pack[..pack.len() / 2]),
             Mode::Restore,
             EntryDataMode::Ignore,
+            git_hash::Kind::Sha1,
         )?;
         let mut num_objects = 0;
         while let Some(entry) = iter.next() {
@@ -13,8 +13,11 @@ const PACKS_AND_INDICES: &[(&'static str, &'static str)] =
 const V2_PACKS_AND_INDICES: &[(&'static str, &'static str)] =
     &[(SMALL_PACK_INDEX, SMALL_PACK), (INDEX_V2, PACK_FOR_INDEX_V2)];
 
+mod bitmap;
 mod bundle;
 mod data;
 mod index;
 mod iter;
+mod multi_index;
+mod reverse_index;
 mod tree;
@@ -0,0 +1,140 @@
+use std::convert::TryInto;
+use std::path::{Path, PathBuf};
+
+use git_odb::pack;
+
+use crate::scripted_fixture_repo_read_only;
+
+fn fixture() -> crate::Result<(PathBuf, Vec<PathBuf>)> {
+    let repo = scripted_fixture_repo_read_only("make_repo_with_multi_pack_index.sh")?;
+    let pack_dir = repo.join(".git").join("objects").join("pack");
+    let covered_indices = std::fs::read_to_string(repo.join("covered-indices"))?
+        .lines()
+        .map(|name| pack_dir.join(name))
+        .collect();
+    Ok((pack_dir, covered_indices))
+}
+
+fn multi_index(pack_dir: &Path) -> crate::Result<pack::multi_index::File> {
+    Ok(pack::multi_index::File::at(pack_dir.join("multi-pack-index"))?)
+}
+
+#[test]
+fn read_and_lookup_all_objects_of_covered_packs() -> crate::Result {
+    let (pack_dir, covered_indices) = fixture()?;
+    let midx = multi_index(&pack_dir)?;
+
+    assert_eq!(midx.version(), pack::multi_index::Version::V1);
+    assert_eq!(midx.num_packs(), 4);
+    assert_eq!(
+        midx.index_names(),
+        covered_indices
+            .iter()
+            .map(|path| PathBuf::from(path.file_name().expect("file name")))
+            .collect::<Vec<_>>()
+    );
+
+    let mut num_objects = 0;
+    for (pack_id, index_path) in covered_indices.iter().enumerate() {
+        let index = pack::index::File::at(index_path)?;
+        for entry in index.iter() {
+            let midx_index = midx
+                .lookup(&entry.oid)
+                .expect("all objects of covered packs are present");
+            assert_eq!(midx.oid_at_index(midx_index), entry.oid);
+            assert_eq!(
+                midx.pack_id_and_pack_offset_at_index(midx_index),
+                (pack_id as u32, entry.pack_offset)
+            );
+            num_objects += 1;
+        }
+    }
+    assert_eq!(
+        midx.num_objects(),
+        num_objects,
+        "there are no duplicates in the fixture"
+    );
+    assert_eq!(midx.iter().count(), num_objects as usize);
+    assert!(
+        midx.iter().zip(midx.iter().skip(1)).all(|(a, b)| a.oid < b.oid),
+        "entries are sorted by object id"
+    );
+    Ok(())
+}
+
+#[test]
+fn verify_integrity() -> crate::Result {
+    let (pack_dir, _) = fixture()?;
+    let midx = multi_index(&pack_dir)?;
+    let checksum = midx.verify_integrity(git_features::progress::Discard)?;
+    assert_eq!(checksum, midx.checksum());
+    assert_eq!(midx.verify_checksum(git_features::progress::Discard)?, checksum);
+    Ok(())
+}
+
+#[test]
+fn write_from_index_paths_is_identical_to_what_git_writes() -> crate::Result {
+    let (pack_dir, mut covered_indices) = fixture()?;
+    covered_indices.reverse();
+    let mut buf = Vec::new();
+    let outcome =
+        pack::multi_index::File::write_from_index_paths(covered_indices, &mut buf, git_features::progress::Discard)?;
+
+    let expected = std::fs::read(pack_dir.join("multi-pack-index"))?;
+    assert_eq!(outcome.version, pack::multi_index::Version::V1);
+    assert_eq!(outcome.num_objects, multi_index(&pack_dir)?.num_objects());
+    assert_eq!(outcome.checksum, multi_index(&pack_dir)?.checksum());
+    assert!(
+        buf == expected,
+        "the written multi-pack index is byte-identical to git's own"
+    );
+    Ok(())
+}
+
+#[test]
+fn corrupted_files_fail_verification() -> crate::Result {
+    let (pack_dir, _) = fixture()?;
+    let mut data = std::fs::read(pack_dir.join("multi-pack-index"))?;
+    let tmp = tempfile::TempDir::new()?;
+    let last_byte = data.len() - 1;
+    data[last_byte] ^= 0xff;
+    let path = tmp.path().join("multi-pack-index");
+    std::fs::write(&path, data)?;
+
+    let midx = pack::multi_index::File::at(&path)?;
+    assert!(matches!(
+        midx.verify_checksum(git_features::progress::Discard),
+        Err(pack::multi_index::verify::Error::Mismatch { .. })
+    ));
+    Ok(())
+}
+
+/// Return the range of the chunk with the given `id` in the multi-pack index `data`.
+fn chunk_range(data: &[u8], id: &[u8; 4]) -> std::ops::Range<usize> {
+    let num_chunks = data[6] as usize;
+    let offset_at = |idx: usize| {
+        let start = 12 + idx * 12 + 4;
+        u64::from_be_bytes(data[start..start + 8].try_into().expect("8 bytes")) as usize
+    };
+    let idx = (0..num_chunks)
+        .find(|idx| &data[12 + idx * 12..][..4] == id)
+        .expect("chunk present");
+    offset_at(idx)..offset_at(idx + 1)
+}
+
+#[test]
+fn out_of_bounds_pack_ids_are_rejected_when_opening() -> crate::Result {
+    let (pack_dir, _) = fixture()?;
+    let mut data = std::fs::read(pack_dir.join("multi-pack-index"))?;
+    let offsets = chunk_range(&data, b"OOFF");
+    data[offsets.start..offsets.start + 4].copy_from_slice(&4u32.to_be_bytes());
+    let tmp = tempfile::TempDir::new()?;
+    let path = tmp.path().join("multi-pack-index");
+    std::fs::write(&path, data)?;
+
+    assert!(matches!(
+        pack::multi_index::File::at(&path),
+        Err(pack::multi_index::init::Error::Corrupt { .. })
+    ));
+    Ok(())
+}
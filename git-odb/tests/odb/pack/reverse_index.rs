@@ -0,0 +1,131 @@
+use crate::{fixture_path, scripted_fixture_repo_read_only};
+use git_features::progress;
+use git_odb::pack::{self, cache, index};
+use std::path::PathBuf;
+
+fn index_path() -> crate::Result<PathBuf> {
+    let repo = scripted_fixture_repo_read_only("make_repo_with_reverse_index.sh")?;
+    let name = std::fs::read_to_string(repo.join("index-name"))?;
+    Ok(repo.join(".git").join("objects").join("pack").join(name.trim()))
+}
+
+#[test]
+fn read_and_verify_against_pack_index() -> crate::Result {
+    let index = index::File::at(index_path()?)?;
+    let rev = pack::reverse_index::File::for_index(&index)?.expect("written by git");
+    assert_eq!(rev.version(), pack::reverse_index::Version::V1);
+    assert_eq!(rev.num_objects(), index.num_objects());
+    assert_eq!(rev.pack_checksum(), index.pack_checksum());
+    assert_eq!(rev.verify_integrity(&index, progress::Discard)?, rev.checksum());
+
+    let offsets: Vec<_> = rev
+        .index_positions()
+        .map(|index_position| index.pack_offset_at_index(index_position))
+        .collect();
+    let mut sorted_offsets: Vec<_> = index.iter().map(|e| e.pack_offset).collect();
+    sorted_offsets.sort_unstable();
+    assert_eq!(offsets, sorted_offsets);
+    assert_eq!(index.sorted_offsets()?, sorted_offsets, "the reverse index is used");
+    assert_eq!(
+        rev.index_position_at(0),
+        rev.index_positions().next().expect("at least one object")
+    );
+    Ok(())
+}
+
+#[test]
+fn write_from_index_produces_the_same_file_as_git() -> crate::Result {
+    let index = index::File::at(index_path()?)?;
+    let mut buf = Vec::new();
+    let outcome = pack::reverse_index::File::write_from_index(&index, &mut buf, progress::Discard)?;
+    assert_eq!(buf, std::fs::read(index.path().with_extension("rev"))?);
+    assert_eq!(outcome.num_objects, index.num_objects());
+    assert_eq!(outcome.version, pack::reverse_index::Version::V1);
+    assert_eq!(
+        outcome.checksum,
+        pack::reverse_index::File::at(index.path().with_extension("rev"))?.checksum()
+    );
+    Ok(())
+}
+
+#[test]
+fn pack_traversal_uses_it_with_all_algorithms() -> crate::Result {
+    let index = index::File::at(index_path()?)?;
+    let pack = pack::data::File::at(index.path().with_extension("pack"))?;
+    let mut outcomes = Vec::new();
+    for algorithm in &[
+        index::traverse::Algorithm::Lookup,
+        index::traverse::Algorithm::DeltaTreeLookup,
+    ] {
+        let (checksum, outcome, _) = index.verify_integrity(
+            Some((&pack, index::verify::Mode::Sha1Crc32Decode, *algorithm, || cache::Never)),
+            None,
+            progress::Discard.into(),
+        )?;
+        assert_eq!(checksum, index.index_checksum());
+        outcomes.push(outcome.expect("traversed"));
+    }
+    assert_eq!(outcomes[0], outcomes[1]);
+    Ok(())
+}
+
+#[test]
+fn a_reverse_index_of_another_pack_is_rejected() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let index_path = tmp.path().join("pack.idx");
+    std::fs::copy(fixture_path(super::SMALL_PACK_INDEX), &index_path)?;
+    std::fs::copy(
+        self::index_path()?.with_extension("rev"),
+        index_path.with_extension("rev"),
+    )?;
+
+    let index = index::File::at(&index_path)?;
+    assert!(matches!(
+        pack::reverse_index::File::for_index(&index),
+        Err(pack::reverse_index::init::Error::PackMismatch { .. })
+    ));
+    assert!(
+        matches!(
+            index.sorted_offsets(),
+            Err(pack::reverse_index::init::Error::PackMismatch { .. })
+        ),
+        "invalid reverse indices aren't ignored"
+    );
+    Ok(())
+}
+
+#[test]
+fn out_of_bounds_index_positions_are_rejected_when_opening_and_verifying() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let index_path = tmp.path().join("pack.idx");
+    std::fs::copy(self::index_path()?, &index_path)?;
+    std::fs::copy(
+        self::index_path()?.with_extension("pack"),
+        index_path.with_extension("pack"),
+    )?;
+    let mut rev = std::fs::read(self::index_path()?.with_extension("rev"))?;
+    let header_len = 12;
+    rev[header_len..header_len + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+    std::fs::write(index_path.with_extension("rev"), rev)?;
+
+    let index = index::File::at(&index_path)?;
+    assert!(matches!(
+        pack::reverse_index::File::for_index(&index),
+        Err(pack::reverse_index::init::Error::Corrupt { .. })
+    ));
+    let pack = pack::data::File::at(index_path.with_extension("pack"))?;
+    for algorithm in &[
+        index::traverse::Algorithm::Lookup,
+        index::traverse::Algorithm::DeltaTreeLookup,
+    ] {
+        assert!(matches!(
+            index.verify_integrity(
+                Some((&pack, index::verify::Mode::Sha1Crc32, *algorithm, || cache::Never)),
+                None,
+                progress::Discard.into(),
+            ),
+            Err(index::traverse::Error::ReverseIndex(_))
+        ));
+    }
+    Ok(())
+}
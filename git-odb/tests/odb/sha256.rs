@@ -0,0 +1,358 @@
+use crate::scripted_fixture_repo_read_only;
+use git_features::progress;
+use git_odb::{
+    linked, loose,
+    pack::{self, cache, index},
+    write::Write,
+    Find,
+};
+use std::path::{Path, PathBuf};
+
+fn repo() -> crate::Result<PathBuf> {
+    Ok(scripted_fixture_repo_read_only("make_repo_with_sha256_objects.sh")?)
+}
+
+fn id_in(repo: &Path, file: &str) -> crate::Result<git_hash::ObjectId> {
+    Ok(git_hash::ObjectId::from_hex(
+        std::fs::read_to_string(repo.join(file))?.trim().as_bytes(),
+    )?)
+}
+
+fn index_path(repo: &Path) -> crate::Result<PathBuf> {
+    let name = std::fs::read_to_string(repo.join("index-name"))?;
+    Ok(repo.join(".git").join("objects").join("pack").join(name.trim()))
+}
+
+#[test]
+fn pack_index_and_data_use_32_byte_hashes() -> crate::Result {
+    let repo = repo()?;
+    let bundle = pack::Bundle::at(index_path(&repo)?)?;
+    assert_eq!(bundle.index.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(bundle.pack.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(bundle.index.pack_checksum(), bundle.pack.checksum());
+    assert_eq!(
+        bundle.index.verify_checksum(progress::Discard)?,
+        bundle.index.index_checksum()
+    );
+    assert_eq!(bundle.pack.verify_checksum(progress::Discard)?, bundle.pack.checksum());
+    assert!(bundle.index.iter().all(|e| e.oid.kind() == git_hash::Kind::Sha256));
+
+    for algorithm in &[
+        index::traverse::Algorithm::Lookup,
+        index::traverse::Algorithm::DeltaTreeLookup,
+    ] {
+        let (_, outcome, _) = bundle.index.verify_integrity(
+            Some((&bundle.pack, index::verify::Mode::Sha1Crc32, *algorithm, || {
+                cache::Never
+            })),
+            None,
+            progress::Discard.into(),
+        )?;
+        assert_eq!(outcome.expect("traversed").num_commits, 4);
+    }
+
+    let mut buf = Vec::new();
+    let commit = bundle
+        .find(id_in(&repo, "packed-commit")?, &mut buf, &mut cache::Never)?
+        .expect("packed commit is present");
+    assert_eq!(commit.kind, git_object::Kind::Commit);
+    Ok(())
+}
+
+#[test]
+fn reverse_index_uses_32_byte_hashes() -> crate::Result {
+    let index = index::File::at(index_path(&repo()?)?)?;
+    let rev = pack::reverse_index::File::for_index(&index)?.expect("written by git");
+    assert_eq!(rev.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(rev.verify_integrity(&index, progress::Discard)?, rev.checksum());
+
+    let mut buf = Vec::new();
+    pack::reverse_index::File::write_from_index(&index, &mut buf, progress::Discard)?;
+    assert_eq!(buf, std::fs::read(rev.path())?, "we write the same file as git");
+    Ok(())
+}
+
+#[test]
+fn multi_pack_index_uses_32_byte_hashes() -> crate::Result {
+    let index_path = index_path(&repo()?)?;
+    let midx_path = index_path.with_file_name("multi-pack-index");
+    let midx = pack::multi_index::File::at(&midx_path)?;
+    assert_eq!(midx.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(midx.checksum().kind(), git_hash::Kind::Sha256);
+    assert_eq!(midx.verify_integrity(progress::Discard)?, midx.checksum());
+
+    let index = index::File::at(&index_path)?;
+    assert_eq!(midx.num_objects(), index.num_objects());
+    for entry in index.iter() {
+        let midx_entry = midx.lookup(entry.oid).expect("all objects are covered");
+        assert_eq!(midx.oid_at_index(midx_entry), entry.oid);
+        assert_eq!(
+            midx.pack_id_and_pack_offset_at_index(midx_entry),
+            (0, entry.pack_offset)
+        );
+    }
+
+    let mut buf = Vec::new();
+    let outcome = pack::multi_index::File::write_from_index_paths(vec![index_path], &mut buf, progress::Discard)?;
+    assert_eq!(outcome.checksum, midx.checksum());
+    assert!(
+        buf == std::fs::read(&midx_path)?,
+        "the written multi-pack index is byte-identical to git's own"
+    );
+    Ok(())
+}
+
+#[test]
+fn loose_objects_are_found_iterated_and_written_with_the_same_id_as_git() -> crate::Result {
+    let repo = repo()?;
+    let objects_dir = repo.join(".git").join("objects");
+    let loose_blob = id_in(&repo, "loose-blob")?;
+    let db = loose::Db::at(&objects_dir);
+
+    let mut buf = Vec::new();
+    let blob = db.find(&loose_blob, &mut buf)?.expect("loose blob is present");
+    assert_eq!(blob.data, b"loose content\n");
+    blob.verify_checksum(&loose_blob)?;
+
+    let ids = db.iter().collect::<Result<Vec<_>, _>>()?;
+    assert!(ids.contains(&loose_blob));
+    assert!(ids.iter().all(|id| id.kind() == git_hash::Kind::Sha256));
+
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let written =
+        loose::Db::at(tmp.path()).write_buf(git_object::Kind::Blob, b"loose content\n", git_hash::Kind::Sha256)?;
+    assert_eq!(written, loose_blob);
+    Ok(())
+}
+
+#[test]
+fn linked_db_finds_loose_and_packed_objects() -> crate::Result {
+    let repo = repo()?;
+    let db = linked::Db::at(repo.join(".git").join("objects"))?;
+    let mut buf = Vec::new();
+    for file in &["loose-blob", "packed-commit"] {
+        assert!(db.find(id_in(&repo, file)?, &mut buf, &mut cache::Never)?.is_some());
+    }
+    Ok(())
+}
+
+#[test]
+fn fsck_checks_trees_commits_and_their_connectivity() -> crate::Result {
+    let repo = repo()?;
+    let db = linked::Db::at(repo.join(".git").join("objects"))?;
+    let outcome = db.fsck(Some(id_in(&repo, "head")?), progress::Discard, Default::default())?;
+    assert!(!outcome.has_errors());
+    assert!(outcome.malformed.is_empty());
+    assert_eq!(outcome.num_unreachable, 0);
+    assert_eq!(
+        outcome.num_objects,
+        std::fs::read_to_string(repo.join("num-objects"))?
+            .trim()
+            .parse::<usize>()?
+    );
+    Ok(())
+}
+
+#[test]
+fn trees_of_commits_can_be_traversed() -> crate::Result {
+    let repo = repo()?;
+    let db = linked::Db::at(repo.join(".git").join("objects"))?;
+    let mut buf = Vec::new();
+    let commit = db
+        .find(id_in(&repo, "head")?, &mut buf, &mut cache::Never)?
+        .expect("head commit is present")
+        .decode()?
+        .into_commit()
+        .expect("a commit");
+    assert_eq!(commit.tree().kind(), git_hash::Kind::Sha256);
+    assert_eq!(commit.parents().count(), 1);
+
+    let tree_id = commit.tree();
+    let mut tree_buf = Vec::new();
+    let tree = db
+        .find(tree_id, &mut tree_buf, &mut cache::Never)?
+        .expect("tree is present")
+        .into_tree_iter()
+        .expect("a tree");
+    let entries = tree.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        entries.iter().map(|e| e.filename.to_string()).collect::<Vec<_>>(),
+        ["file-1", "file-2", "file-3", "file-4", "loose", "numbers"]
+    );
+    let loose_blob = id_in(&repo, "loose-blob")?;
+    assert!(entries.iter().any(|e| e.oid == loose_blob.as_ref()));
+    Ok(())
+}
+
+#[test]
+fn pack_index_written_from_a_pack_stream_matches_the_one_written_by_git() -> crate::Result {
+    let index_path = index_path(&repo()?)?;
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let outcome = pack::Bundle::write_stream_to_directory(
+        std::io::BufReader::new(std::fs::File::open(index_path.with_extension("pack"))?),
+        Some(tmp.path()),
+        progress::Discard,
+        None::<linked::Db>,
+        pack::bundle::write::Options {
+            hash_kind: git_hash::Kind::Sha256,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.index.data_hash.kind(), git_hash::Kind::Sha256);
+
+    let written_index_path = outcome.index_path.clone().expect("written to a directory");
+    assert_eq!(
+        written_index_path.file_name(),
+        Some(std::ffi::OsStr::new(&format!("{}.idx", outcome.index.data_hash))),
+        "files are named after the full Sha256 pack checksum"
+    );
+    assert_eq!(
+        std::fs::read(written_index_path)?,
+        std::fs::read(&index_path)?,
+        "we write the same index as git"
+    );
+    let bundle = outcome.to_bundle().expect("written to a directory")?;
+    assert_eq!(bundle.index.hash_kind(), git_hash::Kind::Sha256);
+    Ok(())
+}
+
+#[test]
+fn streamed_pack_entries_use_32_byte_trailers() -> crate::Result {
+    let pack_path = index_path(&repo()?)?.with_extension("pack");
+    let pack = pack::data::File::at_with_hash_kind(&pack_path, git_hash::Kind::Sha256)?;
+    let entries = pack.streaming_iter()?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(entries.len(), pack.num_objects() as usize);
+    assert_eq!(
+        entries.last().and_then(|e| e.trailer),
+        Some(pack.checksum()),
+        "the trailer is verified with the hash of the pack"
+    );
+    Ok(())
+}
+
+/// Assert that all hashes in the pack and index of `bundle` are 32 bytes, and that all of its objects can be decoded.
+fn assert_bundle_uses_32_byte_hashes(bundle: &pack::Bundle) -> crate::Result {
+    assert_eq!(bundle.index.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(bundle.pack.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(bundle.index.pack_checksum(), bundle.pack.checksum());
+    assert!(bundle.index.iter().all(|e| e.oid.kind() == git_hash::Kind::Sha256));
+    bundle.index.verify_integrity(
+        Some((
+            &bundle.pack,
+            index::verify::Mode::Sha1Crc32DecodeEncode,
+            index::traverse::Algorithm::DeltaTreeLookup,
+            || cache::Never,
+        )),
+        None,
+        progress::Discard.into(),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn repacking_writes_packs_and_indices_of_32_byte_hashes() -> crate::Result {
+    let repo = repo()?;
+    let (_tmp, git_dir) = crate::linked::writable_git_dir(&repo)?;
+    let mut db = linked::Db::at(git_dir.join("objects"))?;
+    let outcome = db.repack(
+        Some(id_in(&repo, "head")?),
+        progress::Discard,
+        linked::repack::Options {
+            write_bitmap_index: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome.num_objects as usize,
+        std::fs::read_to_string(repo.join("num-objects"))?
+            .trim()
+            .parse::<usize>()?
+    );
+    assert_eq!(outcome.removed_packs.len(), 1);
+
+    assert_eq!(db.dbs[0].packs.len(), 1, "only the new pack remains");
+    let bundle = &db.dbs[0].packs[0];
+    assert_bundle_uses_32_byte_hashes(bundle)?;
+    let mut buf = Vec::new();
+    for file in &["loose-blob", "packed-commit", "head"] {
+        assert!(db.find(id_in(&repo, file)?, &mut buf, &mut cache::Never)?.is_some());
+    }
+
+    let bitmap =
+        pack::bitmap::File::at_with_hash_kind(bundle.index.path().with_extension("bitmap"), bundle.index.hash_kind())?;
+    assert_eq!(bitmap.checksum(), outcome.bitmap.expect("self-contained pack").checksum);
+    bitmap.verify_integrity(bundle, &mut cache::Never, progress::Discard)?;
+    Ok(())
+}
+
+#[test]
+fn thin_packs_are_resolved_into_packs_and_indices_of_32_byte_hashes() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_thin_pack_with_sha256_objects.sh")?;
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let outcome = pack::Bundle::write_stream_to_directory(
+        std::io::BufReader::new(std::fs::File::open(repo.join("thin.pack"))?),
+        Some(tmp.path()),
+        progress::Discard,
+        Some(linked::Db::at(repo.join(".git").join("objects"))?),
+        pack::bundle::write::Options {
+            hash_kind: git_hash::Kind::Sha256,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.index.num_objects, 4, "the base of the delta was inserted");
+
+    let bundle = outcome.to_bundle().expect("written to a directory")?;
+    assert_bundle_uses_32_byte_hashes(&bundle)?;
+    let mut buf = Vec::new();
+    assert!(bundle
+        .find(id_in(&repo, "head")?, &mut buf, &mut cache::Never)?
+        .is_some());
+    Ok(())
+}
+
+#[test]
+fn bitmaps_use_32_byte_hashes_and_are_written_like_git_does() -> crate::Result {
+    let repo = repo()?;
+    let index_path = index_path(&repo)?;
+    let bundle = pack::Bundle::at(&index_path)?;
+    let expected =
+        pack::bitmap::File::at_with_hash_kind(index_path.with_extension("bitmap"), bundle.index.hash_kind())?;
+    assert_eq!(expected.hash_kind(), git_hash::Kind::Sha256);
+    assert_eq!(expected.pack_checksum(), bundle.pack.checksum());
+    assert_eq!(
+        expected.verify_integrity(&bundle, &mut cache::Never, progress::Discard)?,
+        expected.checksum()
+    );
+
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let path = tmp.path().join("pack.bitmap");
+    let packed_commit = id_in(&repo, "packed-commit")?;
+    let outcome = pack::bitmap::File::write_from_bundle(
+        &bundle,
+        Some(packed_commit),
+        &mut cache::Never,
+        std::fs::File::create(&path)?,
+        progress::Discard,
+        Default::default(),
+    )?;
+    let actual = pack::bitmap::File::at_with_hash_kind(&path, git_hash::Kind::Sha256)?;
+    assert_eq!(actual.checksum(), outcome.checksum);
+    assert_eq!(
+        actual.verify_integrity(&bundle, &mut cache::Never, progress::Discard)?,
+        actual.checksum()
+    );
+    assert_eq!(actual.pack_checksum(), expected.pack_checksum());
+    assert_eq!(actual.commits(), expected.commits());
+    assert_eq!(actual.trees(), expected.trees());
+    assert_eq!(actual.blobs(), expected.blobs());
+    assert_eq!(actual.tags(), expected.tags());
+
+    let lookup = pack::bitmap::Lookup::new(&actual, &bundle)?;
+    let reachable = lookup.reachable_from(Some(packed_commit), &mut cache::Never)?;
+    assert_eq!(
+        lookup.ids(&reachable).count(),
+        bundle.index.num_objects() as usize,
+        "all packed objects are reachable from the last packed commit"
+    );
+    Ok(())
+}
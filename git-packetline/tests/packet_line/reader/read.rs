@@ -77,6 +77,7 @@ fn read_pack_with_progress_extraction() -> crate::Result {
         pack_read,
         pack::data::input::Mode::Verify,
         pack::data::input::EntryDataMode::Ignore,
+        git_hash::Kind::Sha1,
     )?;
     let size = pack_entries.size_hint().0 - 1;
     let last = pack_entries.skip(size).next().expect("last entry")?;
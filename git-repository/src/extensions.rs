@@ -0,0 +1,100 @@
+use git_config::{file::GitConfig, parser::parse_from_bytes, values::Boolean};
+use quick_error::quick_error;
+use std::{
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        IoOpen(err: std::io::Error, path: PathBuf) {
+            display("Could not read the configuration at '{}'", path.display())
+            source(err)
+        }
+        Parse(message: String, path: PathBuf) {
+            display("Could not parse the configuration at '{}': {}", path.display(), message)
+        }
+        RepositoryFormatVersion(version: String) {
+            display("Unsupported repository format version: '{}'", version)
+        }
+    }
+}
+
+/// Return the value of `extensions.<name>` in the configuration of the repository at `git_dir`, or `None` if it isn't set or
+/// there is no configuration.
+///
+/// Like git, extensions are ignored if `core.repositoryFormatVersion` is 0, except for the few which were in use before
+/// the format version was introduced, like `extensions.partialClone`.
+pub fn value(git_dir: impl AsRef<Path>, name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let path = git_dir.as_ref().join("config");
+    let bytes = match read(&path)? {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+    let config = GitConfig::try_from(&bytes).map_err(|err| Error::Parse(err.to_string(), path.clone()))?;
+    extension(&config, name)
+}
+
+/// Return the name of the promisor remote if the repository at `git_dir` is a partial clone, or `None` otherwise.
+///
+/// The promisor remote is configured by `extensions.partialClone`, or by `remote.<name>.promisor` as written by newer
+/// versions of git. Objects missing in a partial clone can be fetched from the promisor remote on demand.
+pub fn partial_clone(git_dir: impl AsRef<Path>) -> Result<Option<String>, Error> {
+    let path = git_dir.as_ref().join("config");
+    let bytes = match read(&path)? {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+    let parser = parse_from_bytes(&bytes).map_err(|err| Error::Parse(err.to_string(), path.clone()))?;
+    let remotes: Vec<_> = parser
+        .sections()
+        .iter()
+        .filter(|section| section.section_header.name.0.eq_ignore_ascii_case("remote"))
+        .filter_map(|section| {
+            section
+                .section_header
+                .subsection_name
+                .as_ref()
+                .map(|name| name.to_string())
+        })
+        .collect();
+    let config = GitConfig::from(parser);
+    if let Some(name) = extension(&config, "partialclone")? {
+        return Ok(Some(String::from_utf8_lossy(&name).into_owned()));
+    }
+    Ok(remotes.into_iter().find(|name| {
+        matches!(
+            config.value::<Boolean<'_>>("remote", Some(name), "promisor"),
+            Ok(Boolean::True(_))
+        )
+    }))
+}
+
+fn read(path: &Path) -> Result<Option<Vec<u8>>, Error> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(Error::IoOpen(err, path.to_owned())),
+    }
+}
+
+/// Extensions git understands in repositories with `core.repositoryFormatVersion` 0 for historical reasons.
+const FORMAT_VERSION_0_EXTENSIONS: &[&str] = &["noop", "preciousobjects", "partialclone", "worktreeconfig"];
+
+fn extension(config: &GitConfig<'_>, name: &str) -> Result<Option<Vec<u8>>, Error> {
+    let value = match config.get_raw_value("extensions", None, name) {
+        Ok(value) => value.into_owned(),
+        Err(_) => return Ok(None),
+    };
+    let format_version = config
+        .get_raw_value("core", None, "repositoryformatversion")
+        .map(|version| String::from_utf8_lossy(&version).trim().to_owned())
+        .unwrap_or_else(|_| "0".into());
+    match format_version.as_str() {
+        "0" if FORMAT_VERSION_0_EXTENSIONS.contains(&name) => Ok(Some(value)),
+        "0" => Ok(None),
+        "1" => Ok(Some(value)),
+        _ => Err(Error::RepositoryFormatVersion(format_version)),
+    }
+}
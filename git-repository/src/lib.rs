@@ -1,4 +1,8 @@
 #![forbid(unsafe_code)]
 #![deny(rust_2018_idioms)]
 
+pub mod extensions;
 pub mod init;
+pub mod object_format;
+pub mod repository;
+pub mod tips;
@@ -0,0 +1,28 @@
+use crate::extensions;
+use quick_error::quick_error;
+use std::path::Path;
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Extensions(err: extensions::Error) {
+            display("Could not read the object format extension")
+            from()
+            source(err)
+        }
+        UnknownObjectFormat(name: String) {
+            display("Unknown object format: '{}'", name)
+        }
+    }
+}
+
+/// Return the kind of hash used for the objects of the repository at `git_dir`, as configured by `extensions.objectFormat`.
+///
+/// Repositories without configuration or without the extension use `Sha1`.
+pub fn from_git_dir(git_dir: impl AsRef<Path>) -> Result<git_hash::Kind, Error> {
+    match extensions::value(git_dir, "objectformat")? {
+        Some(name) => git_hash::Kind::from_name(&name)
+            .ok_or_else(|| Error::UnknownObjectFormat(String::from_utf8_lossy(&name).into_owned())),
+        None => Ok(git_hash::Kind::Sha1),
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{extensions, object_format};
+use git_hash::ObjectId;
+use git_odb::linked;
+use quick_error::quick_error;
+use std::path::{Path, PathBuf};
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        ObjectFormat(err: object_format::Error) {
+            display("Could not determine the object format of the repository")
+            from()
+            source(err)
+        }
+        Extensions(err: extensions::Error) {
+            display("Could not read the partial clone configuration")
+            from()
+            source(err)
+        }
+        ObjectDatabase(err: linked::init::Error) {
+            display("Could not open the object database")
+            from()
+            source(err)
+        }
+    }
+}
+
+/// A repository along with its object database, whose objects are identified by hashes of the configured object format.
+pub struct Repository {
+    /// The `.git` directory of the repository.
+    pub git_dir: PathBuf,
+    /// The kind of hash used for all objects, as configured by `extensions.objectFormat`.
+    pub object_hash: git_hash::Kind,
+    /// The name of the remote to fetch missing objects from if the repository is a partial clone, or `None` otherwise.
+    pub promisor_remote: Option<String>,
+    /// The object database in the `objects` directory, including its alternates.
+    pub odb: linked::Db,
+}
+
+impl Repository {
+    /// Open the repository at `git_dir`, the `.git` directory of a repository or a bare repository.
+    ///
+    /// Fails if the object format is unknown, as objects couldn't be read or written correctly.
+    pub fn open(git_dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let git_dir = git_dir.as_ref();
+        let object_hash = object_format::from_git_dir(git_dir)?;
+        let promisor_remote = extensions::partial_clone(git_dir)?;
+        let odb = linked::Db::at(git_dir.join("objects"))?;
+        Ok(Repository {
+            git_dir: git_dir.to_owned(),
+            object_hash,
+            promisor_remote,
+            odb,
+        })
+    }
+
+    /// Turn the object database into one which fetches missing objects from the [promisor remote][Repository::promisor_remote],
+    /// or return `None` if the repository isn't a partial clone.
+    ///
+    /// `make_fetch` is called with the name of the promisor remote to create the callback fetching missing objects from it.
+    pub fn into_promisor<F, E>(
+        self,
+        make_fetch: impl FnOnce(&str) -> F,
+        options: linked::promisor::Options,
+    ) -> Option<linked::Promisor<F>>
+    where
+        F: FnMut(&[ObjectId]) -> Result<(), E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let remote = self.promisor_remote?;
+        Some(linked::Promisor::new(self.odb, make_fetch(&remote), options))
+    }
+}
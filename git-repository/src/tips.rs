@@ -0,0 +1,281 @@
+use crate::object_format;
+use git_hash::ObjectId;
+use quick_error::quick_error;
+use std::{
+    convert::TryInto,
+    path::{Path, PathBuf},
+};
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        Io(err: std::io::Error, path: PathBuf) {
+            display("Could not read '{}'", path.display())
+            source(err)
+        }
+        Corrupt(message: String, path: PathBuf) {
+            display("'{}' is corrupt: {}", path.display(), message)
+        }
+        ObjectFormat(err: object_format::Error) {
+            display("Could not determine the object format of the repository")
+            from()
+            source(err)
+        }
+    }
+}
+
+/// Return the ids of all objects which keep other objects alive in the repository at `git_dir`, sorted and without
+/// duplicates, as needed to prune unreachable objects.
+///
+/// These are the objects pointed to by `HEAD` and all references, loose or packed, all entries of their reflogs, and all
+/// objects in the index along with the trees it caches. The `HEAD`, reflogs and index of linked worktrees are included
+/// as well. Submodule commits in the index belong to other repositories and are skipped.
+///
+/// Note that objects in the reflogs may not exist anymore.
+pub fn from_git_dir(git_dir: impl AsRef<Path>) -> Result<Vec<ObjectId>, Error> {
+    let git_dir = git_dir.as_ref();
+    let hash_kind = object_format::from_git_dir(git_dir)?;
+    let mut tips = Vec::new();
+
+    add_ref(&git_dir.join("HEAD"), &mut tips)?;
+    for path in files(&git_dir.join("refs"))? {
+        if path.extension() != Some("lock".as_ref()) {
+            add_ref(&path, &mut tips)?;
+        }
+    }
+    add_packed_refs(&git_dir.join("packed-refs"), &mut tips)?;
+    add_reflogs(&git_dir.join("logs"), &mut tips)?;
+    add_index(&git_dir.join("index"), hash_kind, &mut tips)?;
+
+    let worktrees = git_dir.join("worktrees");
+    if worktrees.is_dir() {
+        let entries = std::fs::read_dir(&worktrees).map_err(|err| Error::Io(err, worktrees.clone()))?;
+        for entry in entries {
+            let worktree = entry.map_err(|err| Error::Io(err, worktrees.clone()))?.path();
+            add_ref(&worktree.join("HEAD"), &mut tips)?;
+            add_reflogs(&worktree.join("logs"), &mut tips)?;
+            add_index(&worktree.join("index"), hash_kind, &mut tips)?;
+        }
+    }
+
+    tips.sort();
+    tips.dedup();
+    Ok(tips)
+}
+
+fn read(path: &Path) -> Result<Option<Vec<u8>>, Error> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(Error::Io(err, path.to_owned())),
+    }
+}
+
+/// Return the paths of all files in `dir` and its subdirectories, or nothing if it doesn't exist.
+fn files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut out = Vec::new();
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(Error::Io(err, dir)),
+        };
+        for entry in entries {
+            let entry = entry.map_err(|err| Error::Io(err, dir.clone()))?;
+            let file_type = entry.file_type().map_err(|err| Error::Io(err, entry.path()))?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                out.push(entry.path());
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn parse_hex(hex: &[u8], path: &Path) -> Result<ObjectId, Error> {
+    ObjectId::from_hex(hex).map_err(|err| Error::Corrupt(err.to_string(), path.to_owned()))
+}
+
+/// Add the object pointed to by the reference at `path` unless it is a symbolic reference.
+fn add_ref(path: &Path, tips: &mut Vec<ObjectId>) -> Result<(), Error> {
+    let content = match read(path)? {
+        Some(content) => content,
+        None => return Ok(()),
+    };
+    let content = trim(&content);
+    if !content.starts_with(b"ref:") {
+        tips.push(parse_hex(content, path)?);
+    }
+    Ok(())
+}
+
+fn add_packed_refs(path: &Path, tips: &mut Vec<ObjectId>) -> Result<(), Error> {
+    let content = match read(path)? {
+        Some(content) => content,
+        None => return Ok(()),
+    };
+    for line in content.split(|b| *b == b'\n').map(trim) {
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+        let hex = match line.strip_prefix(b"^") {
+            Some(peeled) => peeled,
+            None => line
+                .split(|b| *b == b' ')
+                .next()
+                .expect("split yields at least one item"),
+        };
+        tips.push(parse_hex(hex, path)?);
+    }
+    Ok(())
+}
+
+/// Add the previous and new object of all entries in all reflogs in `logs_dir`, except for null ids marking creation
+/// and deletion of references.
+fn add_reflogs(logs_dir: &Path, tips: &mut Vec<ObjectId>) -> Result<(), Error> {
+    for path in files(logs_dir)? {
+        let content = read(&path)?.unwrap_or_default();
+        for line in content.split(|b| *b == b'\n').filter(|line| !line.is_empty()) {
+            let mut tokens = line.splitn(3, |b| *b == b' ');
+            for hex in tokens.by_ref().take(2) {
+                let id = parse_hex(hex, &path)?;
+                if id != id.kind().null() {
+                    tips.push(id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |pos| pos + 1);
+    &bytes[start..end]
+}
+
+/// The size of the stat information, `ctime`, `mtime`, `dev`, `ino`, `mode`, `uid`, `gid` and `size`, of each index entry.
+const INDEX_STAT_LEN: usize = 40;
+const INDEX_FLAG_EXTENDED: u16 = 0x4000;
+const MODE_MASK: u32 = 0o170000;
+const MODE_SUBMODULE: u32 = 0o160000;
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes[..4].try_into().expect("4 bytes"))
+}
+
+/// Add the objects of all entries of the index at `path` and all trees cached in it, following a split index to its
+/// shared index.
+fn add_index(path: &Path, hash_kind: git_hash::Kind, tips: &mut Vec<ObjectId>) -> Result<(), Error> {
+    let data = match read(path)? {
+        Some(data) => data,
+        None => return Ok(()),
+    };
+    let corrupt = |message: &str| Error::Corrupt(message.into(), path.to_owned());
+    let hash_len = hash_kind.len_in_bytes();
+    let id_at = |ofs: usize| match hash_kind {
+        git_hash::Kind::Sha1 => ObjectId::from_20_bytes(&data[ofs..][..hash_len]),
+        git_hash::Kind::Sha256 => ObjectId::from_32_bytes(&data[ofs..][..hash_len]),
+    };
+    if data.len() < 12 + hash_len || &data[..4] != b"DIRC" {
+        return Err(corrupt("the index header is invalid"));
+    }
+    let version = be_u32(&data[4..]);
+    if !(2..=4).contains(&version) {
+        return Err(corrupt(&format!("index version {} is unsupported", version)));
+    }
+    let num_entries = be_u32(&data[8..]);
+    let end = data.len() - hash_len;
+
+    let mut ofs = 12;
+    for _ in 0..num_entries {
+        let flags_ofs = ofs + INDEX_STAT_LEN + hash_len;
+        if flags_ofs + 2 > end {
+            return Err(corrupt("the index is truncated"));
+        }
+        let mode = be_u32(&data[ofs + 24..]);
+        let id = id_at(ofs + INDEX_STAT_LEN);
+        let flags = u16::from_be_bytes([data[flags_ofs], data[flags_ofs + 1]]);
+        let mut path_ofs = flags_ofs + 2;
+        if version >= 3 && flags & INDEX_FLAG_EXTENDED != 0 {
+            path_ofs += 2;
+        }
+        if version == 4 {
+            // Skip the variable-length number of bytes to remove from the previous path.
+            while matches!(data.get(path_ofs), Some(b) if b & 0x80 != 0) {
+                path_ofs += 1;
+            }
+            path_ofs += 1;
+        }
+        let path_len = data
+            .get(path_ofs..end)
+            .and_then(|path| path.iter().position(|b| *b == 0))
+            .ok_or_else(|| corrupt("the index is truncated"))?;
+        ofs = if version == 4 {
+            path_ofs + path_len + 1
+        } else {
+            // Entries are padded with 1 to 8 null bytes to a multiple of 8 bytes.
+            ofs + ((path_ofs - ofs + path_len + 8) & !7)
+        };
+        if mode & MODE_MASK != MODE_SUBMODULE {
+            tips.push(id);
+        }
+    }
+
+    while ofs + 8 <= end {
+        let signature = &data[ofs..ofs + 4];
+        let size = be_u32(&data[ofs + 4..]) as usize;
+        let extension = data
+            .get(ofs + 8..ofs + 8 + size)
+            .filter(|_| ofs + 8 + size <= end)
+            .ok_or_else(|| corrupt("an index extension is truncated"))?;
+        match signature {
+            b"TREE" => {
+                add_cache_tree(extension, hash_kind, tips).ok_or_else(|| corrupt("the cached trees are invalid"))?
+            }
+            b"link" if extension.len() >= hash_len => {
+                let shared_index = id_at(ofs + 8);
+                if shared_index != hash_kind.null() {
+                    add_index(
+                        &path.with_file_name(format!("sharedindex.{}", shared_index)),
+                        hash_kind,
+                        tips,
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        ofs += 8 + size;
+    }
+    Ok(())
+}
+
+/// Add the ids of all valid trees cached in the `TREE` index extension `data`, or return `None` if it is malformed.
+fn add_cache_tree(mut data: &[u8], hash_kind: git_hash::Kind, tips: &mut Vec<ObjectId>) -> Option<()> {
+    let hash_len = hash_kind.len_in_bytes();
+    while !data.is_empty() {
+        let path_end = data.iter().position(|b| *b == 0)?;
+        data = &data[path_end + 1..];
+        let counts_end = data.iter().position(|b| *b == b'\n')?;
+        // Invalidated trees have a negative entry count and no id.
+        let is_valid = !data[..counts_end].starts_with(b"-");
+        data = &data[counts_end + 1..];
+        if is_valid {
+            let id = data.get(..hash_len)?;
+            tips.push(match hash_kind {
+                git_hash::Kind::Sha1 => ObjectId::from_20_bytes(id),
+                git_hash::Kind::Sha256 => ObjectId::from_32_bytes(id),
+            });
+            data = &data[hash_len..];
+        }
+    }
+    Some(())
+}
@@ -0,0 +1,20 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q source
+(cd source
+  git config commit.gpgsign false
+  git config uploadpack.allowFilter true
+  echo content > file
+  git add .
+  git commit -q -m "initial"
+  git rev-parse HEAD:file > ../omitted-blob
+)
+
+git clone -q --no-checkout --filter=blob:none "file://$PWD/source" partial
+
+git init -q --bare v0-partial-clone.git
+git -C v0-partial-clone.git config extensions.partialClone origin
+
+git init -q --bare promisor-remote.git
+git -C promisor-remote.git config remote.upstream.promisor true
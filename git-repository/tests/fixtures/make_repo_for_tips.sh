@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+echo "first" > file
+git add file
+git commit -q -m "first"
+git tag -a -m "annotated" annotated
+git pack-refs --all
+
+echo "second" > file
+git commit -q -am "second"
+git rev-parse HEAD > reflog-only-commit
+git reset -q --hard HEAD~1
+git branch -q loose-branch
+
+echo "staged" > staged
+git add staged
+git rev-parse :staged > staged-blob
+git write-tree > cached-tree
+
+echo "unreferenced" | git hash-object -w --stdin > unreferenced-blob
+
+git rev-parse HEAD annotated annotated^{} > ref-targets
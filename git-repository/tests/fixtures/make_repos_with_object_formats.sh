@@ -0,0 +1,12 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q --bare sha1.git
+git init -q --bare --object-format=sha256 sha256.git
+
+git init -q --bare unknown.git
+git -C unknown.git config core.repositoryFormatVersion 1
+git -C unknown.git config extensions.objectFormat sha512
+
+git init -q --bare v0-with-extensions.git
+git -C v0-with-extensions.git config extensions.objectFormat sha256
@@ -0,0 +1,39 @@
+use git_repository::{object_format, repository, repository::Repository};
+use git_testtools::scripted_fixture_repo_read_only;
+
+#[test]
+fn object_hash_is_taken_from_the_object_format() -> crate::Result {
+    let repos = scripted_fixture_repo_read_only("make_repos_with_object_formats.sh")?;
+    for (name, expected) in &[
+        ("sha1.git", git_hash::Kind::Sha1),
+        ("sha256.git", git_hash::Kind::Sha256),
+    ] {
+        let repo = Repository::open(repos.join(name))?;
+        assert_eq!(repo.object_hash, *expected, "{}", name);
+        assert_eq!(repo.git_dir, repos.join(name));
+        assert!(repo.odb.dbs[0].packs.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn unknown_object_formats_are_rejected() -> crate::Result {
+    let repos = scripted_fixture_repo_read_only("make_repos_with_object_formats.sh")?;
+    assert!(matches!(
+        Repository::open(repos.join("unknown.git")),
+        Err(repository::Error::ObjectFormat(object_format::Error::UnknownObjectFormat(name))) if name == "sha512"
+    ));
+    Ok(())
+}
+
+#[test]
+fn extensions_are_ignored_without_repository_format_version_1() -> crate::Result {
+    let repos = scripted_fixture_repo_read_only("make_repos_with_object_formats.sh")?;
+    let repo = Repository::open(repos.join("v0-with-extensions.git"))?;
+    assert_eq!(
+        repo.object_hash,
+        git_hash::Kind::Sha1,
+        "like git, extensions.objectFormat is ignored in repositories of format version 0"
+    );
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::hex_to_id;
+use git_odb::pack;
+use git_repository::repository::Repository;
+use git_testtools::scripted_fixture_repo_read_only;
+use std::cell::RefCell;
+
+#[test]
+fn the_promisor_remote_is_read_from_the_configuration() -> crate::Result {
+    let repos = scripted_fixture_repo_read_only("make_partial_clones.sh")?;
+    for (git_dir, expected) in &[
+        (repos.join("partial").join(".git"), Some("origin")),
+        (repos.join("v0-partial-clone.git"), Some("origin")),
+        (repos.join("promisor-remote.git"), Some("upstream")),
+        (repos.join("source").join(".git"), None),
+    ] {
+        let repo = Repository::open(git_dir)?;
+        assert_eq!(repo.promisor_remote.as_deref(), *expected, "{}", git_dir.display());
+    }
+    Ok(())
+}
+
+#[test]
+fn missing_objects_are_fetched_from_the_promisor_remote() -> crate::Result {
+    let repos = scripted_fixture_repo_read_only("make_partial_clones.sh")?;
+    let blob = hex_to_id(std::fs::read_to_string(repos.join("omitted-blob"))?.trim());
+    let calls = RefCell::new(Vec::new());
+    let promisor = Repository::open(repos.join("partial").join(".git"))?
+        .into_promisor(
+            |remote| {
+                let (remote, calls) = (remote.to_owned(), &calls);
+                move |ids: &[git_hash::ObjectId]| {
+                    calls.borrow_mut().push((remote.clone(), ids.to_vec()));
+                    Ok::<_, std::io::Error>(())
+                }
+            },
+            Default::default(),
+        )
+        .expect("a partial clone");
+
+    assert!(
+        promisor.find(blob, &mut Vec::new(), &mut pack::cache::Never)?.is_none(),
+        "the fetch didn't provide the object"
+    );
+    assert_eq!(calls.into_inner(), vec![("origin".to_string(), vec![blob])]);
+    Ok(())
+}
+
+#[test]
+fn repositories_without_promisor_remote_have_no_promisor() -> crate::Result {
+    let repos = scripted_fixture_repo_read_only("make_partial_clones.sh")?;
+    let promisor = Repository::open(repos.join("source").join(".git"))?.into_promisor(
+        |_remote| |_ids: &[git_hash::ObjectId]| Ok::<_, std::io::Error>(()),
+        Default::default(),
+    );
+    assert!(promisor.is_none());
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::hex_to_id;
+use git_repository::tips;
+use git_testtools::scripted_fixture_repo_read_only;
+use std::path::Path;
+
+fn ids_in(path: impl AsRef<Path>) -> crate::Result<Vec<git_hash::ObjectId>> {
+    Ok(std::fs::read_to_string(path)?.lines().map(hex_to_id).collect())
+}
+
+#[test]
+fn refs_reflogs_and_index_objects_are_tips() -> crate::Result {
+    let repo = scripted_fixture_repo_read_only("make_repo_for_tips.sh")?;
+    let tips = tips::from_git_dir(repo.join(".git"))?;
+    for file in &["ref-targets", "reflog-only-commit", "staged-blob", "cached-tree"] {
+        for id in ids_in(repo.join(file))? {
+            assert!(tips.contains(&id), "{} from '{}' is a tip", id, file);
+        }
+    }
+    for id in ids_in(repo.join("unreferenced-blob"))? {
+        assert!(!tips.contains(&id), "objects which aren't referenced aren't tips");
+    }
+    assert!(tips.windows(2).all(|w| w[0] < w[1]), "tips are sorted and unique");
+    Ok(())
+}
+
+#[test]
+fn repositories_without_refs_or_index_have_no_tips() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    git_repository::init::repository(Some(tmp.path().to_owned()))?;
+    assert!(tips::from_git_dir(tmp.path().join(".git"))?.is_empty());
+    Ok(())
+}
@@ -21,6 +21,11 @@ pub mod ancestors {
                 source(err)
                 from()
             }
+            CommitGraphParent(err: git_commitgraph::file::commit::Error) {
+                display("The parents of a commit could not be obtained from the commit-graph")
+                source(err)
+                from()
+            }
         }
     }
 
@@ -44,6 +49,7 @@ pub mod ancestors {
     pub struct Ancestors<Find, StateMut> {
         find: Find,
         state: StateMut,
+        cache: Option<git_commitgraph::Graph>,
     }
 
     impl<Find, StateMut> Ancestors<Find, StateMut>
@@ -70,7 +76,18 @@ pub mod ancestors {
                 state.next.extend(tips.into_iter().map(Into::into));
                 state.seen.extend(state.next.iter().cloned());
             }
-            Ancestors { find, state }
+            Ancestors {
+                find,
+                state,
+                cache: None,
+            }
+        }
+
+        /// Use the given commit-graph, if present, to obtain the parents of all commits it contains instead of
+        /// looking them up with `find`. Commits not contained in the commit-graph are still looked up with `find`.
+        pub fn with_commit_graph(mut self, graph: Option<git_commitgraph::Graph>) -> Self {
+            self.cache = graph;
+            self
         }
     }
 
@@ -85,6 +102,21 @@ pub mod ancestors {
             let state = self.state.borrow_mut();
             let res = state.next.pop_front();
             if let Some(oid) = res {
+                if let Some(graph) = self.cache.as_ref() {
+                    if let Some(commit) = graph.commit_by_id(oid) {
+                        for pos in commit.iter_parents() {
+                            let id = match pos {
+                                Ok(pos) => graph.id_at(pos).to_owned(),
+                                Err(err) => return Some(Err(err.into())),
+                            };
+                            let was_inserted = state.seen.insert(id);
+                            if was_inserted {
+                                state.next.push_back(id);
+                            }
+                        }
+                        return Some(Ok(oid));
+                    }
+                }
                 match (self.find)(&oid, &mut state.buf) {
                     Some(mut commit_iter) => {
                         if let Some(Err(decode_tree_err)) = commit_iter.next() {
@@ -112,3 +144,13 @@ pub mod ancestors {
 }
 #[doc(inline)]
 pub use ancestors::Ancestors;
+
+///
+pub mod walk;
+#[doc(inline)]
+pub use walk::Walk;
+
+///
+pub mod reachability;
+#[doc(inline)]
+pub use reachability::Reachability;
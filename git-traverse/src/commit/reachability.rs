@@ -0,0 +1,304 @@
+use git_hash::{oid, ObjectId};
+use git_object::immutable;
+use quick_error::quick_error;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+quick_error! {
+    /// The error returned by queries of [Reachability].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NotFound{oid: ObjectId} {
+            display("The object {} could not be found", oid)
+        }
+        ObjectDecode(err: immutable::object::decode::Error) {
+            display("An object could not be decoded")
+            source(err)
+            from()
+        }
+        CommitGraphParent(err: git_commitgraph::file::commit::Error) {
+            display("The parents or the generation of a commit could not be obtained from the commit-graph")
+            source(err)
+            from()
+        }
+    }
+}
+
+const PARENT1: u8 = 1 << 0;
+const PARENT2: u8 = 1 << 1;
+const STALE: u8 = 1 << 2;
+const RESULT: u8 = 1 << 3;
+
+/// The generation of commits not contained in the commit-graph.
+const GENERATION_INFINITY: u64 = u64::MAX;
+
+struct Info {
+    parents: Vec<ObjectId>,
+    /// Either the corrected commit date or the topological level, depending on what all commits in the commit-graph have.
+    generation: u64,
+    committer_time: u32,
+}
+
+/// Answers reachability queries like [merge bases][Reachability::merge_bases()] or [ancestry][Reachability::is_ancestor()]
+/// similar to `git merge-base`.
+///
+/// All commits seen during queries are cached, making repeated queries on the same portion of the commit graph cheaper.
+/// If a commit-graph is [provided][Reachability::with_commit_graph()], its generation numbers are used to avoid
+/// walking further than needed. Corrected commit dates are preferred if all of its files have them, as unlike topological
+/// levels they also allow to prune walks early in histories with skewed committer clocks.
+pub struct Reachability<Find> {
+    find: Find,
+    cache: Option<git_commitgraph::Graph>,
+    use_corrected_commit_dates: bool,
+    buf: Vec<u8>,
+    commits: HashMap<ObjectId, Info>,
+    flags: HashMap<ObjectId, u8>,
+}
+
+impl<Find> Reachability<Find>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<immutable::CommitIter<'a>>,
+{
+    /// Create a new instance which uses `find` to lookup commits, see [`Ancestors::new()`][super::Ancestors::new()] for details.
+    pub fn new(find: Find) -> Self {
+        Reachability {
+            find,
+            cache: None,
+            use_corrected_commit_dates: false,
+            buf: Vec::new(),
+            commits: HashMap::new(),
+            flags: HashMap::new(),
+        }
+    }
+
+    /// Use the given commit-graph, if present, to obtain parents and generation numbers of all commits it contains.
+    /// Commits not contained in the commit-graph are still looked up with `find`.
+    pub fn with_commit_graph(mut self, graph: Option<git_commitgraph::Graph>) -> Self {
+        self.use_corrected_commit_dates = graph.as_ref().map(|g| g.has_generation_data()).unwrap_or(false);
+        self.cache = graph;
+        self.commits.clear();
+        self
+    }
+
+    /// Return true if `ancestor` is reachable from `descendant`, which is also the case if both are the same commit,
+    /// similar to `git merge-base --is-ancestor`.
+    pub fn is_ancestor(
+        &mut self,
+        ancestor: impl Into<ObjectId>,
+        descendant: impl Into<ObjectId>,
+    ) -> Result<bool, Error> {
+        let ancestor = ancestor.into();
+        self.is_reachable_from_any(ancestor, &[descendant.into()])
+    }
+
+    /// Return the best common ancestor between `one` and any of `others`, similar to `git merge-base one others…`,
+    /// or `None` if there is no common ancestor.
+    pub fn merge_base(&mut self, one: impl Into<ObjectId>, others: &[ObjectId]) -> Result<Option<ObjectId>, Error> {
+        Ok(self.merge_bases(one, others)?.into_iter().next())
+    }
+
+    /// Return all best common ancestors between `one` and any of `others`, similar to `git merge-base --all one others…`.
+    ///
+    /// None of the returned commits is reachable from any of the others, and they are sorted by committer date, newest first.
+    pub fn merge_bases(&mut self, one: impl Into<ObjectId>, others: &[ObjectId]) -> Result<Vec<ObjectId>, Error> {
+        let one = one.into();
+        if others.contains(&one) {
+            return Ok(vec![one]);
+        }
+        let common = self.paint_down_to_common(one, others)?;
+        let mut bases: Vec<_> = common.into_iter().filter(|id| self.flags[id] & STALE == 0).collect();
+        self.flags.clear();
+        bases.sort_by_key(|id| Reverse(self.commits[id].committer_time));
+        self.remove_redundant(bases)
+    }
+
+    /// Return all best common ancestors of all `commits`, similar to `git merge-base --octopus --all commits…`.
+    pub fn octopus_merge_bases(&mut self, commits: &[ObjectId]) -> Result<Vec<ObjectId>, Error> {
+        let (first, rest) = match commits.split_first() {
+            Some(v) => v,
+            None => return Ok(Vec::new()),
+        };
+        let mut bases = vec![*first];
+        for commit in rest {
+            let mut next_bases = Vec::new();
+            for base in bases {
+                for id in self.merge_bases(*commit, &[base])? {
+                    if !next_bases.contains(&id) {
+                        next_bases.push(id);
+                    }
+                }
+            }
+            bases = next_bases;
+        }
+        self.remove_redundant(bases)
+    }
+
+    /// Return all `commits` that can't be reached from any of the other `commits`, in the order they were given and without
+    /// duplicates, similar to `git merge-base --independent`.
+    pub fn independent(&mut self, commits: &[ObjectId]) -> Result<Vec<ObjectId>, Error> {
+        let mut unique = Vec::with_capacity(commits.len());
+        for id in commits {
+            if !unique.contains(id) {
+                unique.push(*id);
+            }
+        }
+        self.remove_redundant(unique)
+    }
+
+    /// Remove all `commits` which are reachable from any other, keeping the order of the remaining ones.
+    fn remove_redundant(&mut self, commits: Vec<ObjectId>) -> Result<Vec<ObjectId>, Error> {
+        if commits.len() < 2 {
+            return Ok(commits);
+        }
+        let mut redundant = vec![false; commits.len()];
+        for (index, id) in commits.iter().enumerate() {
+            let others: Vec<_> = commits
+                .iter()
+                .enumerate()
+                .filter(|(other_index, _)| *other_index != index && !redundant[*other_index])
+                .map(|(_, id)| *id)
+                .collect();
+            redundant[index] = self.is_reachable_from_any(*id, &others)?;
+        }
+        Ok(commits
+            .into_iter()
+            .zip(redundant)
+            .filter(|(_, redundant)| !redundant)
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    /// Return true if `target` can be reached from any of `sources`, without walking past commits with a generation lower
+    /// than the one of `target`.
+    fn is_reachable_from_any(&mut self, target: ObjectId, sources: &[ObjectId]) -> Result<bool, Error> {
+        let min_generation = self.info(target)?.generation;
+        let mut seen = HashSet::new();
+        let mut queue: Vec<ObjectId> = sources.to_vec();
+        while let Some(id) = queue.pop() {
+            if id == target {
+                return Ok(true);
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            let info = self.info(id)?;
+            if info.generation < min_generation {
+                continue;
+            }
+            let parents = info.parents.clone();
+            queue.extend(parents.into_iter().filter(|id| !seen.contains(id)));
+        }
+        Ok(false)
+    }
+
+    /// Walk down from `one` and all `others` until commits reachable from both sides are found, and return them.
+    ///
+    /// Found commits will be flagged as `RESULT`, and `STALE` if they are reachable from another found commit,
+    /// leaving all flags in place for the caller to inspect.
+    fn paint_down_to_common(&mut self, one: ObjectId, others: &[ObjectId]) -> Result<Vec<ObjectId>, Error> {
+        self.flags.clear();
+        let mut seq = 0;
+        let mut queue = BinaryHeap::new();
+        // Commits may be queued more than once, each time they receive new flags from another child.
+        let mut num_queued = HashMap::<ObjectId, usize>::new();
+        let mut num_queued_non_stale = 0;
+        for (id, flags) in std::iter::once((one, PARENT1)).chain(others.iter().map(|id| (*id, PARENT2))) {
+            *self.flags.entry(id).or_default() |= flags;
+            let info = self.info(id)?;
+            queue.push((info.generation, info.committer_time, Reverse(seq), id));
+            seq += 1;
+            *num_queued.entry(id).or_default() += 1;
+            num_queued_non_stale += 1;
+        }
+
+        let mut result = Vec::new();
+        while num_queued_non_stale != 0 {
+            let (_, _, _, id) = queue.pop().expect("non-empty queue");
+            *num_queued.get_mut(&id).expect("queued commits are counted") -= 1;
+            if self.flags[&id] & STALE == 0 {
+                num_queued_non_stale -= 1;
+            }
+            let mut flags = self.flags[&id] & (PARENT1 | PARENT2 | STALE);
+            if flags == PARENT1 | PARENT2 {
+                let commit_flags = self.flags.get_mut(&id).expect("queued commits are flagged");
+                if *commit_flags & RESULT == 0 {
+                    *commit_flags |= RESULT;
+                    result.push(id);
+                }
+                flags |= STALE;
+            }
+            let parents = self.info(id)?.parents.clone();
+            for parent_id in parents {
+                let parent_flags = self.flags.entry(parent_id).or_default();
+                if *parent_flags & flags == flags {
+                    continue;
+                }
+                let parent_queued = num_queued.entry(parent_id).or_default();
+                if *parent_flags & STALE == 0 {
+                    if flags & STALE == 0 {
+                        num_queued_non_stale += 1;
+                    } else {
+                        num_queued_non_stale -= *parent_queued;
+                    }
+                }
+                *parent_queued += 1;
+                *parent_flags |= flags;
+                let info = self.info(parent_id)?;
+                queue.push((info.generation, info.committer_time, Reverse(seq), parent_id));
+                seq += 1;
+            }
+        }
+        Ok(result)
+    }
+
+    fn info(&mut self, id: ObjectId) -> Result<&Info, Error> {
+        if !self.commits.contains_key(&id) {
+            let info = match self
+                .cache
+                .as_ref()
+                .and_then(|graph| graph.commit_by_id(id).map(|commit| (graph, commit)))
+            {
+                Some((graph, commit)) => Info {
+                    parents: commit
+                        .iter_parents()
+                        .map(|pos| pos.map(|pos| graph.id_at(pos).to_owned()))
+                        .collect::<Result<_, _>>()?,
+                    generation: if self.use_corrected_commit_dates {
+                        commit
+                            .corrected_commit_date()?
+                            .expect("all commits in the graph to have corrected commit dates")
+                    } else {
+                        u64::from(commit.generation())
+                    },
+                    committer_time: commit.committer_timestamp() as u32,
+                },
+                None => {
+                    let commit_iter = (self.find)(&id, &mut self.buf).ok_or(Error::NotFound { oid: id })?;
+                    let mut info = Info {
+                        parents: Vec::new(),
+                        generation: GENERATION_INFINITY,
+                        committer_time: 0,
+                    };
+                    for token in commit_iter {
+                        match token? {
+                            immutable::commit::iter::Token::Tree { .. }
+                            | immutable::commit::iter::Token::Author { .. } => {}
+                            immutable::commit::iter::Token::Parent { id } => info.parents.push(id),
+                            immutable::commit::iter::Token::Committer { signature } => {
+                                info.committer_time = signature.time.time;
+                                break;
+                            }
+                            _a_token_past_the_committer => break,
+                        }
+                    }
+                    info
+                }
+            };
+            self.commits.insert(id, info);
+        }
+        Ok(&self.commits[&id])
+    }
+}
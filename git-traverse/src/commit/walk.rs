@@ -0,0 +1,487 @@
+use git_hash::{oid, ObjectId};
+use git_object::immutable;
+use quick_error::quick_error;
+use std::{
+    borrow::BorrowMut,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+quick_error! {
+    /// The error is part of the item returned by the [Walk] iterator.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NotFound{oid: ObjectId} {
+            display("The object {} could not be found", oid)
+        }
+        ObjectDecode(err: immutable::object::decode::Error) {
+            display("An object could not be decoded")
+            source(err)
+            from()
+        }
+        CommitGraphParent(err: git_commitgraph::file::commit::Error) {
+            display("The parents of a commit could not be obtained from the commit-graph")
+            source(err)
+            from()
+        }
+    }
+}
+
+/// The order in which commits are returned by the [Walk].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Sorting {
+    /// Commits are returned by their committer date, newest first, without making any guarantees about the order of parents and their children.
+    ///
+    /// This is the default order of `git rev-list` and allows commits to be returned while they are discovered, unless
+    /// [hidden tips][Options::hidden] are used.
+    CommitterDate,
+    /// No parent is returned before all of its children, and commits of different lines of history are not intermixed.
+    ///
+    /// This is the equivalent of `git rev-list --topo-order`.
+    Topological,
+    /// No parent is returned before all of its children, otherwise commits are returned by their committer date, newest first.
+    ///
+    /// This is the equivalent of `git rev-list --date-order`.
+    TopologicalByCommitterDate,
+    /// No parent is returned before all of its children, otherwise commits are returned by their author date, newest first.
+    ///
+    /// This is the equivalent of `git rev-list --author-date-order`. As the commit-graph doesn't store author dates, it is
+    /// not used with this order and all commits are looked up instead.
+    TopologicalByAuthorDate,
+}
+
+impl Default for Sorting {
+    fn default() -> Self {
+        Sorting::CommitterDate
+    }
+}
+
+impl Sorting {
+    fn is_topological(&self) -> bool {
+        !matches!(self, Sorting::CommitterDate)
+    }
+}
+
+/// Options for use in [Walk::new()].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The order in which to return commits.
+    pub sorting: Sorting,
+    /// If true, only the first parent of each commit will be followed, similar to `git rev-list --first-parent`.
+    pub first_parent: bool,
+    /// Commits reachable from these tips will not be returned, like `^hidden` or `hidden..tip` in `git rev-list`.
+    pub hidden: Vec<ObjectId>,
+    /// If set, no more than the given amount of commits will be returned.
+    pub max_count: Option<usize>,
+    /// If set, commits whose committer time in seconds since epoch is older than the given one will not be returned,
+    /// and their parents will not be traversed.
+    pub since: Option<u32>,
+    /// If set, commits whose committer time in seconds since epoch is newer than the given one will not be returned,
+    /// but their parents are still traversed.
+    pub until: Option<u32>,
+}
+
+const HIDDEN: u8 = 1 << 0;
+const PROCESSED: u8 = 1 << 1;
+
+struct Info {
+    parents: Vec<ObjectId>,
+    committer_time: u32,
+    author_time: u32,
+    /// The order in which the commit was discovered.
+    seq: usize,
+    flags: u8,
+}
+
+/// The state used and potentially shared by multiple graph walks.
+#[derive(Default)]
+pub struct State {
+    buf: Vec<u8>,
+    commits: HashMap<ObjectId, Info>,
+    /// Commits to process, by generation number if [ordered by it][State::by_generation], then by committer time,
+    /// newest first, and by insertion order if the time is equal.
+    queue: BinaryHeap<(u32, u32, Reverse<usize>, ObjectId)>,
+    /// If true, commits are queued by their generation number before their committer time. This is the case when limiting
+    /// the walk with a commit-graph, and assures no commit is processed before any of its descendants even if committer
+    /// clocks are skewed.
+    by_generation: bool,
+    /// The amount of queued commits which aren't hidden.
+    num_queued_visible: usize,
+    seq: usize,
+    /// Commits ready to be returned, if the walk was prepared ahead of time.
+    out: VecDeque<ObjectId>,
+    returned: usize,
+    initialized: bool,
+}
+
+impl State {
+    fn clear(&mut self) {
+        self.buf.clear();
+        self.commits.clear();
+        self.queue.clear();
+        self.by_generation = false;
+        self.num_queued_visible = 0;
+        self.seq = 0;
+        self.out.clear();
+        self.returned = 0;
+        self.initialized = false;
+    }
+}
+
+/// A `rev-list`-style iterator over the ancestors of one or more starting commits, with configurable [Sorting]
+/// and [limits][Options].
+pub struct Walk<Find, StateMut> {
+    find: Find,
+    state: StateMut,
+    tips: Vec<ObjectId>,
+    options: Options,
+    cache: Option<git_commitgraph::Graph>,
+    is_done: bool,
+}
+
+impl<Find, StateMut> Walk<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<immutable::CommitIter<'a>>,
+    StateMut: BorrowMut<State>,
+{
+    /// Create a new instance.
+    ///
+    /// * `tips` - the starting points of the walk, each commit they lead to will only be returned once.
+    /// * `options` - control the [order][Sorting] in which commits are returned and which commits are returned at all.
+    /// * `state` - all state used for the walk. If multiple walks are performed, allocations can be minimized by reusing
+    ///   this state.
+    /// * `find` - a way to lookup commits by their id, see [`Ancestors::new()`][super::Ancestors::new()] for details.
+    ///
+    /// Note that [hidden tips][Options::hidden] and all [topological orders][Sorting] require the graph to be walked
+    /// before the first commit can be returned.
+    pub fn new(
+        tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+        options: Options,
+        mut state: StateMut,
+        find: Find,
+    ) -> Self {
+        state.borrow_mut().clear();
+        Walk {
+            find,
+            state,
+            tips: tips.into_iter().map(Into::into).collect(),
+            options,
+            cache: None,
+            is_done: false,
+        }
+    }
+
+    /// Use the given commit-graph, if present, to obtain parents and committer dates of all commits it contains instead of
+    /// looking them up with `find`. Commits not contained in the commit-graph are still looked up with `find`, as are
+    /// all commits if they are [sorted by author date][Sorting::TopologicalByAuthorDate], which bypasses the commit-graph
+    /// entirely.
+    ///
+    /// When walks are [limited][Options::hidden] or [sorted topologically][Sorting], generation numbers are used to
+    /// process commits before their ancestors, which stops walking the graph as soon as all remaining commits are hidden,
+    /// without being misled by skewed committer clocks.
+    pub fn with_commit_graph(mut self, graph: Option<git_commitgraph::Graph>) -> Self {
+        self.cache = graph;
+        self
+    }
+}
+
+impl<Find, StateMut> Walk<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<immutable::CommitIter<'a>>,
+    StateMut: BorrowMut<State>,
+{
+    fn is_limited(&self) -> bool {
+        self.options.sorting.is_topological() || !self.options.hidden.is_empty()
+    }
+
+    fn initialize(&mut self) -> Result<(), Error> {
+        let graph = commit_graph(&self.cache, self.options.sorting);
+        let is_limited = self.is_limited();
+        let state = self.state.borrow_mut();
+        state.initialized = true;
+        state.by_generation = is_limited && graph.is_some();
+        for id in self.options.hidden.iter() {
+            enqueue(&mut self.find, graph, state, *id, HIDDEN)?;
+        }
+        for id in self.tips.iter() {
+            enqueue(&mut self.find, graph, state, *id, 0)?;
+        }
+        if is_limited {
+            self.limit()?;
+            if self.options.sorting.is_topological() {
+                self.sort_topologically();
+            }
+        }
+        Ok(())
+    }
+
+    /// Walk the graph until all commits reachable from non-hidden tips are known, and put them into the output queue.
+    fn limit(&mut self) -> Result<(), Error> {
+        let graph = commit_graph(&self.cache, self.options.sorting);
+        let state = self.state.borrow_mut();
+        let mut collected = Vec::new();
+        while state.num_queued_visible != 0 {
+            let (_, _, _, id) = state.queue.pop().expect("non-empty queue");
+            let info = state.commits.get_mut(&id).expect("queued commits are known");
+            info.flags |= PROCESSED;
+            let flags = info.flags;
+            if flags & HIDDEN == 0 {
+                state.num_queued_visible -= 1;
+            }
+            if flags & HIDDEN == 0 && self.options.since.map_or(false, |since| info.committer_time < since) {
+                continue;
+            }
+            let parents = parents_to_follow(&info.parents, self.options.first_parent).to_vec();
+            for parent_id in parents {
+                if state.commits.contains_key(&parent_id) {
+                    if flags & HIDDEN != 0 {
+                        mark_hidden(state, parent_id, self.options.first_parent);
+                    }
+                } else {
+                    enqueue(&mut self.find, graph, state, parent_id, flags & HIDDEN)?;
+                }
+            }
+            if flags & HIDDEN == 0 {
+                collected.push(id);
+            }
+        }
+        state.queue.clear();
+        state.num_queued_visible = 0;
+        let commits = &state.commits;
+        if state.by_generation {
+            collected.sort_by_key(|id| (Reverse(commits[id].committer_time), commits[id].seq));
+        }
+        state
+            .out
+            .extend(collected.into_iter().filter(|id| commits[id].flags & HIDDEN == 0));
+        Ok(())
+    }
+
+    /// Reorder the output queue so that no parent is returned before its children.
+    fn sort_topologically(&mut self) {
+        let state = self.state.borrow_mut();
+        let first_parent = self.options.first_parent;
+        let mut in_degree: HashMap<ObjectId, usize> = state.out.iter().map(|id| (*id, 0)).collect();
+        for id in state.out.iter() {
+            for parent_id in parents_to_follow(&state.commits[id].parents, first_parent) {
+                if let Some(count) = in_degree.get_mut(parent_id) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let sorting = self.options.sorting;
+        let time_of = |info: &Info| match sorting {
+            Sorting::TopologicalByAuthorDate => info.author_time,
+            _ => info.committer_time,
+        };
+        let mut seq = 0;
+        let mut stack = Vec::new();
+        let mut queue = BinaryHeap::new();
+        let mut push = |id: ObjectId, stack: &mut Vec<ObjectId>, queue: &mut BinaryHeap<_>| {
+            if sorting == Sorting::Topological {
+                stack.push(id);
+            } else {
+                queue.push((time_of(&state.commits[&id]), Reverse(seq), id));
+                seq += 1;
+            }
+        };
+        for id in state.out.iter().filter(|id| in_degree[*id] == 0) {
+            push(*id, &mut stack, &mut queue);
+        }
+        // Tips are returned in the order they were discovered in.
+        stack.reverse();
+
+        let mut sorted = VecDeque::with_capacity(state.out.len());
+        while let Some(id) = stack.pop().or_else(|| queue.pop().map(|(_, _, id)| id)) {
+            for parent_id in parents_to_follow(&state.commits[&id].parents, first_parent) {
+                if let Some(count) = in_degree.get_mut(parent_id) {
+                    *count -= 1;
+                    if *count == 0 {
+                        push(*parent_id, &mut stack, &mut queue);
+                    }
+                }
+            }
+            sorted.push_back(id);
+        }
+        state.out = sorted;
+    }
+
+    /// Produce the next commit of an unlimited walk, which is done in committer date order while the graph is traversed.
+    fn next_unlimited(&mut self) -> Result<Option<ObjectId>, Error> {
+        let graph = commit_graph(&self.cache, self.options.sorting);
+        let state = self.state.borrow_mut();
+        while let Some((_, _, _, id)) = state.queue.pop() {
+            let info = state.commits.get_mut(&id).expect("queued commits are known");
+            info.flags |= PROCESSED;
+            state.num_queued_visible -= 1;
+            if self.options.since.map_or(false, |since| info.committer_time < since) {
+                continue;
+            }
+            let parents = parents_to_follow(&info.parents, self.options.first_parent).to_vec();
+            for parent_id in parents {
+                if !state.commits.contains_key(&parent_id) {
+                    enqueue(&mut self.find, graph, state, parent_id, 0)?;
+                }
+            }
+            return Ok(Some(id));
+        }
+        Ok(None)
+    }
+
+    fn next_commit(&mut self) -> Result<Option<ObjectId>, Error> {
+        if !self.state.borrow_mut().initialized {
+            self.initialize()?;
+        }
+        if self.is_limited() {
+            Ok(self.state.borrow_mut().out.pop_front())
+        } else {
+            self.next_unlimited()
+        }
+    }
+}
+
+impl<Find, StateMut> Iterator for Walk<Find, StateMut>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<immutable::CommitIter<'a>>,
+    StateMut: BorrowMut<State>,
+{
+    type Item = Result<ObjectId, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done
+            || self
+                .options
+                .max_count
+                .map_or(false, |max| self.state.borrow_mut().returned >= max)
+        {
+            return None;
+        }
+        loop {
+            match self.next_commit() {
+                Ok(Some(id)) => {
+                    let state = self.state.borrow_mut();
+                    if self
+                        .options
+                        .until
+                        .map_or(false, |until| state.commits[&id].committer_time > until)
+                    {
+                        continue;
+                    }
+                    state.returned += 1;
+                    return Some(Ok(id));
+                }
+                Ok(None) => return None,
+                Err(err) => {
+                    self.is_done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// The commit-graph to use for lookups, unless it lacks information needed for `sorting`.
+fn commit_graph(cache: &Option<git_commitgraph::Graph>, sorting: Sorting) -> Option<&git_commitgraph::Graph> {
+    match sorting {
+        Sorting::TopologicalByAuthorDate => None,
+        _ => cache.as_ref(),
+    }
+}
+
+fn parents_to_follow(parents: &[ObjectId], first_parent: bool) -> &[ObjectId] {
+    if first_parent && !parents.is_empty() {
+        &parents[..1]
+    } else {
+        parents
+    }
+}
+
+/// Mark `id` and all of its known ancestors as hidden.
+fn mark_hidden(state: &mut State, id: ObjectId, first_parent: bool) {
+    let mut next = vec![id];
+    while let Some(id) = next.pop() {
+        let info = match state.commits.get_mut(&id) {
+            Some(info) if info.flags & HIDDEN == 0 => info,
+            _ => continue,
+        };
+        info.flags |= HIDDEN;
+        // Unprocessed commits are still queued and will pass the flag on to their parents once processed.
+        if info.flags & PROCESSED != 0 {
+            next.extend_from_slice(parents_to_follow(&info.parents, first_parent));
+        } else {
+            state.num_queued_visible -= 1;
+        }
+    }
+}
+
+/// Lookup the commit with `id` and queue it for processing, or merely add `flags` if it was seen before.
+fn enqueue<Find>(
+    find: &mut Find,
+    graph: Option<&git_commitgraph::Graph>,
+    state: &mut State,
+    id: ObjectId,
+    flags: u8,
+) -> Result<(), Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<immutable::CommitIter<'a>>,
+{
+    if let Some(info) = state.commits.get_mut(&id) {
+        if info.flags & (HIDDEN | PROCESSED) == 0 && flags & HIDDEN != 0 {
+            state.num_queued_visible -= 1;
+        }
+        info.flags |= flags;
+        return Ok(());
+    }
+    let mut info = Info {
+        parents: Vec::new(),
+        committer_time: 0,
+        author_time: 0,
+        seq: state.seq,
+        flags,
+    };
+    let generation = match graph.and_then(|graph| graph.commit_by_id(id).map(|commit| (graph, commit))) {
+        Some((graph, commit)) => {
+            for pos in commit.iter_parents() {
+                info.parents.push(graph.id_at(pos?).to_owned());
+            }
+            info.committer_time = commit.committer_timestamp() as u32;
+            commit.generation()
+        }
+        None => {
+            decode_commit(find, state, id, &mut info)?;
+            git_commitgraph::GENERATION_NUMBER_INFINITY
+        }
+    };
+    let generation = if state.by_generation { generation } else { 0 };
+    state
+        .queue
+        .push((generation, info.committer_time, Reverse(state.seq), id));
+    state.seq += 1;
+    if flags & HIDDEN == 0 {
+        state.num_queued_visible += 1;
+    }
+    state.commits.insert(id, info);
+    Ok(())
+}
+
+fn decode_commit<Find>(find: &mut Find, state: &mut State, id: ObjectId, info: &mut Info) -> Result<(), Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<immutable::CommitIter<'a>>,
+{
+    let commit_iter = find(&id, &mut state.buf).ok_or(Error::NotFound { oid: id })?;
+    for token in commit_iter {
+        match token? {
+            immutable::commit::iter::Token::Tree { .. } => {}
+            immutable::commit::iter::Token::Parent { id } => info.parents.push(id),
+            immutable::commit::iter::Token::Author { signature } => info.author_time = signature.time.time,
+            immutable::commit::iter::Token::Committer { signature } => {
+                info.committer_time = signature.time.time;
+                break;
+            }
+            _a_token_past_the_committer => break,
+        }
+    }
+    Ok(())
+}
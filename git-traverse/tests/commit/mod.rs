@@ -70,6 +70,34 @@ mod ancestor {
         )
     }
 
+    #[test]
+    fn with_commit_graph_lookups_are_not_needed() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only_with_args(
+            "make_traversal_repo_for_commits_with_dates.sh",
+            Some("commit-graph"),
+        )?;
+        let objects_dir = dir.join(".git").join("objects");
+        let graph = git_commitgraph::Graph::from_info_dir(objects_dir.join("info"))?;
+        let db = Db::at(objects_dir)?;
+        let tips = Some(hex_to_id("183c5c828625a9989263d06a3232cd58437de399"));
+
+        let expected: Result<Vec<_>, _> =
+            commit::Ancestors::new(tips, commit::ancestors::State::default(), |oid, buf| {
+                db.find_existing_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+            })
+            .collect();
+        let actual: Result<Vec<_>, _> =
+            commit::Ancestors::new(tips, commit::ancestors::State::default(), |oid, _buf| {
+                unreachable!("{} should have been found in the commit-graph", oid)
+            })
+            .with_commit_graph(Some(graph))
+            .collect();
+        let expected = expected?;
+        assert_eq!(expected.len(), 8, "all commits reachable from main");
+        assert_eq!(actual?, expected);
+        Ok(())
+    }
+
     #[test]
     fn multiple_tips() -> crate::Result {
         check_traversal_with_shared_reference(
@@ -88,3 +116,421 @@ mod ancestor {
         )
     }
 }
+
+mod walk {
+    use crate::hex_to_id;
+    use git_hash::ObjectId;
+    use git_odb::{linked::Db, pack, FindExt};
+    use git_traverse::commit::{
+        self,
+        walk::{Options, Sorting},
+    };
+
+    /// The kinds of repositories to run each walk on.
+    pub const COMMIT_GRAPH_MODES: &[Option<&str>] = &[None, Some("partial-commit-graph"), Some("commit-graph")];
+
+    pub fn db_and_graph(
+        commit_graph_mode: Option<&'static str>,
+    ) -> crate::Result<(Db, Option<git_commitgraph::Graph>)> {
+        let dir = git_testtools::scripted_fixture_repo_read_only_with_args(
+            "make_traversal_repo_for_commits_with_dates.sh",
+            commit_graph_mode,
+        )?;
+        let objects_dir = dir.join(".git").join("objects");
+        let graph = commit_graph_mode
+            .map(|_| git_commitgraph::Graph::from_info_dir(objects_dir.join("info")))
+            .transpose()?;
+        Ok((Db::at(objects_dir)?, graph))
+    }
+
+    const MAIN: &str = "183c5c828625a9989263d06a3232cd58437de399";
+    const BRANCH2: &str = "c143e1805b93a0ebfff390cef5f64fd768473796";
+
+    pub fn by_message(message: &str) -> ObjectId {
+        hex_to_id(match message {
+            "m1b1" => MAIN,
+            "b2c1" => BRANCH2,
+            "c5" => "6cad410e64d80d983816a583f1b458d78bdeccc7",
+            "c4" => "2a0579ef9ca71a9b06314b820edca4392c5b0383",
+            "b1c2" => "e6e96e0336f085f1fa3f2208fddf19b1ed56035f",
+            "c3" => "c58a22bccd42c8e15f79a592c62ac02272ff23dc",
+            "b1c1" => "0735a8c68d53b69e6a6e3f76920dca733c6af839",
+            "c2" => "0e2a4a57b9d4496b16673d3f730c31e9c504f791",
+            "c1" => "f12f78484da5117bb87150f627d74f4394912c0b",
+            _ => unreachable!("unknown commit message: {}", message),
+        })
+    }
+
+    pub fn skewed_clocks(message: &str) -> ObjectId {
+        hex_to_id(match message {
+            "m" => "6fe4873564f40675e92ad10270a10fda4d0879e9",
+            "b2" => "afdf4d5e8e1662d52ec4477f8657061d5324fe5c",
+            "b1" => "04a863070550c15d174dd1f5559820299e5301c6",
+            "a3" => "e4e6acdb0bf767cb683a7978261215c85f4f7be7",
+            "a2" => "00e02ab1cee15cfa4dd7fc5704ac0a63b6ec8490",
+            "a1" => "0c1b94bf0c911c094b69313070a0e92618af508e",
+            "base" => "d2bdfcc113bb2a017548d81b65e405da6df4864a",
+            _ => unreachable!("unknown commit message: {}", message),
+        })
+    }
+
+    fn check_walk(tips: &[&str], options: Options, expected: &[&str]) -> crate::Result {
+        let expected: Vec<_> = expected.iter().copied().map(by_message).collect();
+        for commit_graph_mode in COMMIT_GRAPH_MODES {
+            let (db, graph) = db_and_graph(*commit_graph_mode)?;
+            // A complete commit-graph makes object lookups unnecessary, unless dates are needed that it doesn't store.
+            let lookups_allowed =
+                *commit_graph_mode != Some("commit-graph") || options.sorting == Sorting::TopologicalByAuthorDate;
+            let oids: Result<Vec<_>, _> = commit::Walk::new(
+                tips.iter().copied().map(hex_to_id),
+                options.clone(),
+                commit::walk::State::default(),
+                move |oid, buf| {
+                    assert!(lookups_allowed, "unexpected lookup of {}", oid);
+                    db.find_existing_commit_iter(oid, buf, &mut pack::cache::Never).ok()
+                },
+            )
+            .with_commit_graph(graph)
+            .collect();
+            assert_eq!(oids?, expected, "commit-graph mode: {:?}", commit_graph_mode);
+        }
+        Ok(())
+    }
+
+    fn sorted(sorting: Sorting) -> Options {
+        Options {
+            sorting,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn committer_date() -> crate::Result {
+        check_walk(
+            &[MAIN, BRANCH2],
+            sorted(Sorting::CommitterDate),
+            &["m1b1", "b2c1", "c5", "c4", "b1c2", "c3", "b1c1", "c2", "c1"],
+        )
+    }
+
+    #[test]
+    fn topological() -> crate::Result {
+        check_walk(
+            &[MAIN, BRANCH2],
+            sorted(Sorting::Topological),
+            &["m1b1", "b1c2", "b1c1", "c5", "b2c1", "c4", "c3", "c2", "c1"],
+        )
+    }
+
+    #[test]
+    fn topological_by_committer_date() -> crate::Result {
+        check_walk(
+            &[MAIN, BRANCH2],
+            sorted(Sorting::TopologicalByCommitterDate),
+            &["m1b1", "b2c1", "c5", "c4", "b1c2", "c3", "b1c1", "c2", "c1"],
+        )
+    }
+
+    #[test]
+    fn topological_by_author_date() -> crate::Result {
+        check_walk(
+            &[MAIN, BRANCH2],
+            sorted(Sorting::TopologicalByAuthorDate),
+            &["m1b1", "b2c1", "b1c2", "b1c1", "c5", "c4", "c3", "c2", "c1"],
+        )
+    }
+
+    #[test]
+    fn first_parent() -> crate::Result {
+        check_walk(
+            &[MAIN],
+            Options {
+                sorting: Sorting::Topological,
+                first_parent: true,
+                ..Default::default()
+            },
+            &["m1b1", "c5", "c4", "c3", "c2", "c1"],
+        )
+    }
+
+    #[test]
+    fn hidden_tips() -> crate::Result {
+        for sorting in &[Sorting::CommitterDate, Sorting::TopologicalByCommitterDate] {
+            check_walk(
+                &[MAIN],
+                Options {
+                    sorting: *sorting,
+                    hidden: vec![hex_to_id(BRANCH2)],
+                    ..Default::default()
+                },
+                &["m1b1", "c5", "b1c2", "b1c1"],
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn hiding_a_tip_hides_everything() -> crate::Result {
+        check_walk(
+            &[BRANCH2],
+            Options {
+                hidden: vec![hex_to_id(MAIN)],
+                ..Default::default()
+            },
+            &["b2c1"],
+        )?;
+        check_walk(
+            &[MAIN],
+            Options {
+                hidden: vec![hex_to_id(MAIN)],
+                ..Default::default()
+            },
+            &[],
+        )
+    }
+
+    #[test]
+    fn hidden_tips_with_skewed_committer_clocks() -> crate::Result {
+        for commit_graph_mode in &COMMIT_GRAPH_MODES[1..] {
+            let dir = git_testtools::scripted_fixture_repo_read_only_with_args(
+                "make_repo_with_skewed_clocks.sh",
+                *commit_graph_mode,
+            )?;
+            let objects_dir = dir.join(".git").join("objects");
+            let db = Db::at(&objects_dir)?;
+            for (sorting, expected) in &[
+                (Sorting::CommitterDate, ["a2", "m", "b2", "a3", "a1"]),
+                (Sorting::TopologicalByCommitterDate, ["m", "b2", "a3", "a2", "a1"]),
+            ] {
+                let oids: Result<Vec<_>, _> = commit::Walk::new(
+                    Some(skewed_clocks("m")),
+                    Options {
+                        sorting: *sorting,
+                        hidden: vec![skewed_clocks("b1")],
+                        ..Default::default()
+                    },
+                    commit::walk::State::default(),
+                    |oid, buf| db.find_existing_commit_iter(oid, buf, &mut pack::cache::Never).ok(),
+                )
+                .with_commit_graph(Some(git_commitgraph::Graph::from_info_dir(objects_dir.join("info"))?))
+                .collect();
+                assert_eq!(
+                    oids?,
+                    expected.iter().copied().map(skewed_clocks).collect::<Vec<_>>(),
+                    "generation numbers assure 'base' is hidden by 'b1' even though it is newer, commit-graph mode: {:?}",
+                    commit_graph_mode
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn max_count_since_and_until() -> crate::Result {
+        check_walk(
+            &[MAIN],
+            Options {
+                max_count: Some(3),
+                since: Some(1000000100),
+                until: Some(1000000600),
+                ..Default::default()
+            },
+            &["c5", "c4", "b1c2"],
+        )?;
+        check_walk(
+            &[MAIN],
+            Options {
+                since: Some(1000000300),
+                ..Default::default()
+            },
+            &["m1b1", "c5", "c4", "b1c2", "c3"],
+        )
+    }
+}
+
+mod reachability {
+    use crate::hex_to_id;
+    use git_hash::ObjectId;
+    use git_odb::{linked::Db, pack, FindExt};
+    use git_traverse::commit::Reachability;
+
+    use super::walk::{by_message, db_and_graph, skewed_clocks, COMMIT_GRAPH_MODES};
+
+    type FindFn =
+        Box<dyn for<'a> FnMut(&git_hash::oid, &'a mut Vec<u8>) -> Option<git_object::immutable::CommitIter<'a>>>;
+
+    fn for_each_reachability(
+        fixture: &str,
+        mut check: impl FnMut(&mut Reachability<FindFn>) -> crate::Result,
+    ) -> crate::Result {
+        for commit_graph_mode in COMMIT_GRAPH_MODES {
+            let (db, graph) = match fixture {
+                "dates" => db_and_graph(*commit_graph_mode)?,
+                _ => {
+                    let (script, mode) = match fixture {
+                        "skewed-clocks" => ("make_repo_with_skewed_clocks.sh", *commit_graph_mode),
+                        _ => (
+                            "make_repo_with_criss_cross_merges.sh",
+                            commit_graph_mode.filter(|mode| *mode == "commit-graph"),
+                        ),
+                    };
+                    let dir = git_testtools::scripted_fixture_repo_read_only_with_args(script, mode)?;
+                    let objects_dir = dir.join(".git").join("objects");
+                    let graph = mode
+                        .map(|_| git_commitgraph::Graph::from_info_dir(objects_dir.join("info")))
+                        .transpose()?;
+                    (Db::at(objects_dir)?, graph)
+                }
+            };
+            let find: FindFn =
+                Box::new(move |oid, buf| db.find_existing_commit_iter(oid, buf, &mut pack::cache::Never).ok());
+            let mut reachability = Reachability::new(find).with_commit_graph(graph);
+            check(&mut reachability)?;
+        }
+        Ok(())
+    }
+
+    fn criss_cross(message: &str) -> ObjectId {
+        hex_to_id(match message {
+            "b2" => "588779a67746b7975609216af6e4898e43f1ed3b",
+            "a2" => "093833298da705f46cd96730884a7724fb0c7758",
+            "b1" => "ce68affd84a270f496932c2285f0508a8fb1d2d3",
+            "a1" => "0c1b94bf0c911c094b69313070a0e92618af508e",
+            "base" => "d2bdfcc113bb2a017548d81b65e405da6df4864a",
+            _ => unreachable!("unknown commit message: {}", message),
+        })
+    }
+
+    #[test]
+    fn is_ancestor() -> crate::Result {
+        for_each_reachability("dates", |r| {
+            assert!(r.is_ancestor(by_message("c4"), by_message("m1b1"))?);
+            assert!(r.is_ancestor(by_message("b1c1"), by_message("m1b1"))?);
+            assert!(r.is_ancestor(by_message("c1"), by_message("b2c1"))?);
+            assert!(
+                r.is_ancestor(by_message("m1b1"), by_message("m1b1"))?,
+                "commits are their own ancestors"
+            );
+            assert!(!r.is_ancestor(by_message("b2c1"), by_message("m1b1"))?);
+            assert!(!r.is_ancestor(by_message("m1b1"), by_message("c4"))?);
+            assert!(!r.is_ancestor(by_message("b1c2"), by_message("b2c1"))?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn merge_bases() -> crate::Result {
+        for_each_reachability("dates", |r| {
+            assert_eq!(
+                r.merge_bases(by_message("b2c1"), &[by_message("b1c2")])?,
+                vec![by_message("c2")]
+            );
+            assert_eq!(
+                r.merge_base(by_message("b2c1"), &[by_message("m1b1")])?,
+                Some(by_message("c4"))
+            );
+            assert_eq!(
+                r.merge_bases(by_message("c3"), &[by_message("m1b1")])?,
+                vec![by_message("c3")],
+                "an ancestor is its own merge-base"
+            );
+            assert_eq!(
+                r.merge_bases(by_message("b2c1"), &[by_message("b1c2"), by_message("c5")])?,
+                vec![by_message("c4")],
+                "the merge-base with any of the others"
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn merge_bases_of_criss_cross_merges() -> crate::Result {
+        for_each_reachability("criss-cross", |r| {
+            assert_eq!(
+                r.merge_bases(criss_cross("a2"), &[criss_cross("b2")])?,
+                vec![criss_cross("b1"), criss_cross("a1")],
+                "all merge-bases, newest first"
+            );
+            assert_eq!(
+                r.merge_base(criss_cross("a2"), &[criss_cross("b2")])?,
+                Some(criss_cross("b1"))
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn octopus_merge_bases() -> crate::Result {
+        for_each_reachability("dates", |r| {
+            assert_eq!(
+                r.octopus_merge_bases(&[by_message("b2c1"), by_message("b1c2"), by_message("c5")])?,
+                vec![by_message("c2")]
+            );
+            Ok(())
+        })?;
+        for_each_reachability("criss-cross", |r| {
+            assert_eq!(
+                r.octopus_merge_bases(&[criss_cross("a2"), criss_cross("b2"), criss_cross("b1")])?,
+                vec![criss_cross("b1")]
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn independent() -> crate::Result {
+        for_each_reachability("dates", |r| {
+            assert_eq!(
+                r.independent(&[
+                    by_message("m1b1"),
+                    by_message("b2c1"),
+                    by_message("c5"),
+                    by_message("b2c1"),
+                    by_message("c4"),
+                    by_message("b1c2"),
+                ])?,
+                vec![by_message("m1b1"), by_message("b2c1")]
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn queries_with_skewed_committer_clocks() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only_with_args(
+            "make_repo_with_skewed_clocks.sh",
+            Some("commit-graph"),
+        )?;
+        assert!(
+            git_commitgraph::Graph::from_info_dir(dir.join(".git").join("objects").join("info"))?.has_generation_data(),
+            "the commit-graph has corrected commit dates to prune walks with"
+        );
+
+        for_each_reachability("skewed-clocks", |r| {
+            assert!(r.is_ancestor(skewed_clocks("a2"), skewed_clocks("m"))?);
+            assert!(r.is_ancestor(skewed_clocks("b1"), skewed_clocks("b2"))?);
+            assert!(r.is_ancestor(skewed_clocks("base"), skewed_clocks("a3"))?);
+            assert!(!r.is_ancestor(skewed_clocks("b1"), skewed_clocks("a3"))?);
+            assert!(!r.is_ancestor(skewed_clocks("a2"), skewed_clocks("b2"))?);
+            assert!(!r.is_ancestor(skewed_clocks("m"), skewed_clocks("a2"))?);
+            assert_eq!(
+                r.merge_bases(skewed_clocks("a3"), &[skewed_clocks("b2")])?,
+                vec![skewed_clocks("base")]
+            );
+            assert_eq!(
+                r.merge_bases(skewed_clocks("m"), &[skewed_clocks("b1")])?,
+                vec![skewed_clocks("b1")]
+            );
+            assert_eq!(
+                r.independent(&[
+                    skewed_clocks("a1"),
+                    skewed_clocks("b2"),
+                    skewed_clocks("a3"),
+                    skewed_clocks("b1")
+                ])?,
+                vec![skewed_clocks("b2"), skewed_clocks("a3")]
+            );
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,37 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local message=${1:?first argument is the commit message}
+  local date=${2:?second argument is the commit date}
+  GIT_COMMITTER_DATE="$date +0000" GIT_AUTHOR_DATE="$date +0000" git commit -q --allow-empty -m "$message"
+}
+
+function merge_at() {
+  local branch=${1:?first argument is the branch to merge}
+  local message=${2:?second argument is the commit message}
+  local date=${3:?third argument is the commit date}
+  GIT_COMMITTER_DATE="$date +0000" GIT_AUTHOR_DATE="$date +0000" git merge -q --no-ff "$branch" -m "$message"
+}
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+commit_at base 1000000000
+
+git checkout -q -b a
+commit_at a1 1000000100
+
+git checkout -q -b b main
+commit_at b1 1000000200
+
+git checkout -q -b a2 a
+merge_at b a2 1000000300
+
+git checkout -q -b b2 b
+merge_at a b2 1000000400
+
+if [ "${1:-}" = "commit-graph" ]; then
+  git commit-graph write --no-progress --reachable
+fi
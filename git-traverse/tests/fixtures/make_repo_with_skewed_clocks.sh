@@ -0,0 +1,38 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local message=${1:?first argument is the commit message}
+  local date=${2:?second argument is the commit date}
+  GIT_COMMITTER_DATE="$date +0000" GIT_AUTHOR_DATE="$date +0000" git commit -q --allow-empty -m "$message"
+}
+
+git init -q
+git config commit.gpgsign false
+git config commitGraph.generationVersion 2
+
+git checkout -q -b main
+commit_at base 1000000000
+
+git checkout -q -b a
+commit_at a1 1000000100
+# a committer clock far in the future
+commit_at a2 2000000000
+commit_at a3 1000000300
+
+git checkout -q -b b main
+# a committer clock in the past
+commit_at b1 900000000
+
+if [ "${1:-}" = "partial-commit-graph" ]; then
+  git commit-graph write --no-progress --reachable
+fi
+
+commit_at b2 1000000400
+
+git checkout -q -b m a
+GIT_COMMITTER_DATE="1000000500 +0000" GIT_AUTHOR_DATE="1000000500 +0000" git merge -q --no-ff b -m m
+
+if [ "${1:-}" = "commit-graph" ]; then
+  git commit-graph write --no-progress --reachable
+fi
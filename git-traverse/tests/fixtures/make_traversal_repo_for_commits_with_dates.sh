@@ -0,0 +1,39 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function commit_at() {
+  local message=${1:?first argument is the commit message}
+  local committer_date=${2:?second argument is the commit date}
+  local author_date=${3:-$committer_date}
+  GIT_COMMITTER_DATE="$committer_date +0000" GIT_AUTHOR_DATE="$author_date +0000" git commit -q --allow-empty -m "$message"
+}
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+commit_at c1 1000000000
+commit_at c2 1000000100
+
+git checkout -q -b branch1
+commit_at b1c1 1000000200 1000000600
+commit_at b1c2 1000000400 1000000500
+
+git checkout -q main
+commit_at c3 1000000300
+commit_at c4 1000000500
+
+if [ "${1:-}" = "partial-commit-graph" ]; then
+  git commit-graph write --no-progress --reachable
+fi
+
+git checkout -q -b branch2
+commit_at b2c1 1000000700
+
+git checkout -q main
+commit_at c5 1000000600 1000000100
+GIT_COMMITTER_DATE="1000000800 +0000" GIT_AUTHOR_DATE="1000000800 +0000" git merge -q --no-ff branch1 -m m1b1
+
+if [ "${1:-}" = "commit-graph" ]; then
+  git commit-graph write --no-progress --reachable
+fi
@@ -0,0 +1,171 @@
+use crate::OutputFormat;
+use anyhow::{anyhow, Context as AnyhowContext, Result};
+use git_features::progress::Progress;
+use git_hash::ObjectId;
+use git_object::bstr::ByteSlice;
+use git_odb::linked::{self, fsck::Outcome};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+/// A general purpose context for many operations provided here
+pub struct Context<W1: io::Write, W2: io::Write> {
+    /// The way the outcome is written to `out`
+    pub format: OutputFormat,
+    /// A stream to which to output operation results
+    pub out: W1,
+    /// A stream to which to output errors
+    pub err: W2,
+    /// If set, don't use more than this amount of threads.
+    /// Otherwise, usually use as many threads as there are logical cores.
+    /// A value of 0 is interpreted as no-limit
+    pub thread_limit: Option<usize>,
+}
+
+/// Check the objects of the repository at `path` for problems and verify they are connected, starting at `HEAD`, all references
+/// and the entries of their reflogs.
+///
+/// `path` may be the `.git` directory or the working tree containing it.
+pub fn repository<W1, W2>(
+    path: impl AsRef<Path>,
+    progress: impl Progress,
+    Context {
+        format,
+        mut out,
+        err: _err,
+        thread_limit,
+    }: Context<W1, W2>,
+) -> Result<Outcome>
+where
+    W1: io::Write,
+    W2: io::Write,
+{
+    let git_dir = git_dir(path.as_ref());
+    let repo = git_repository::repository::Repository::open(&git_dir).with_context(|| "Could not open repository")?;
+    let tips = tips(&git_dir, repo.object_hash).with_context(|| "Could not read references")?;
+    let outcome = repo.odb.fsck(tips, progress, linked::fsck::Options { thread_limit })?;
+
+    match format {
+        OutputFormat::Human => print_outcome(&mut out, &outcome)?,
+        #[cfg(feature = "serde1")]
+        OutputFormat::Json => serde_json::to_writer_pretty(out, &outcome)?,
+    }
+
+    if outcome.has_errors() {
+        Err(anyhow!("The object database has errors"))
+    } else {
+        Ok(outcome)
+    }
+}
+
+fn git_dir(path: &Path) -> PathBuf {
+    let dot_git = path.join(".git");
+    if dot_git.is_dir() {
+        dot_git
+    } else {
+        path.to_owned()
+    }
+}
+
+/// Return the objects pointed to by `HEAD`, all loose and packed references and all reflog entries.
+fn tips(git_dir: &Path, object_format: git_hash::Kind) -> io::Result<Vec<ObjectId>> {
+    let mut tips = Vec::new();
+    let mut push_hex = |hex: &[u8]| {
+        if let Ok(id) = ObjectId::from_hex(hex) {
+            if id.kind() == object_format && id != object_format.null() {
+                tips.push(id);
+            }
+        }
+    };
+
+    let mut ref_files = vec![git_dir.join("HEAD")];
+    collect_files(&git_dir.join("refs"), &mut ref_files)?;
+    for path in ref_files {
+        push_hex(std::fs::read(path)?.trim_end_with(|c| c.is_ascii_whitespace()));
+    }
+    match std::fs::read(git_dir.join("packed-refs")) {
+        Ok(packed) => {
+            for line in packed.lines() {
+                let line = line.strip_prefix(b"^").unwrap_or(line);
+                if let Some(hex) = line.get(..object_format.len_in_hex()) {
+                    push_hex(hex);
+                }
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    let mut log_files = Vec::new();
+    collect_files(&git_dir.join("logs"), &mut log_files)?;
+    for path in log_files {
+        for line in std::fs::read(path)?.lines() {
+            let mut ids = line.splitn(3, |b| *b == b' ');
+            if let (Some(previous), Some(new)) = (ids.next(), ids.next()) {
+                push_hex(previous);
+                push_hex(new);
+            }
+        }
+    }
+    Ok(tips)
+}
+
+fn collect_files(directory: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), out)?;
+        } else {
+            out.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+fn print_outcome(out: &mut impl io::Write, outcome: &Outcome) -> io::Result<()> {
+    for id in &outcome.missing_tips {
+        writeln!(out, "invalid reference target {}", id)?;
+    }
+    for link in &outcome.broken_links {
+        writeln!(
+            out,
+            "broken link from {} {} to {} {}",
+            link.from_kind, link.from, link.expected_kind, link.to
+        )?;
+    }
+    for malformed in &outcome.malformed {
+        for problem in &malformed.problems {
+            let severity = match problem.severity() {
+                git_odb::fsck::Severity::Error => "error",
+                git_odb::fsck::Severity::Warning => "warning",
+            };
+            writeln!(out, "{} in {} {}: {}", severity, malformed.kind, malformed.id, problem)?;
+        }
+    }
+    for id in &outcome.missing {
+        writeln!(out, "missing {}", id)?;
+    }
+    for (id, kind) in &outcome.dangling {
+        writeln!(out, "dangling {} {}", kind, id)?;
+    }
+    if !outcome.promised.is_empty() {
+        writeln!(
+            out,
+            "{} objects are missing but promised by the promisor remote",
+            outcome.promised.len()
+        )?;
+    }
+    writeln!(
+        out,
+        "checked {} objects, {} of which are unreachable",
+        outcome.num_objects, outcome.num_unreachable
+    )
+}
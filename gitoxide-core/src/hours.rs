@@ -56,17 +56,13 @@ where
     )
     .with_context(|| "Could not decode file contents as hex-encoded hash")?;
 
-    let repo_objects_dir = {
-        let mut d = repo_git_dir;
-        d.push("objects");
-        d
-    };
+    let repo = git_repository::repository::Repository::open(&repo_git_dir)?;
 
     let all_commits = {
         let start = Instant::now();
         let mut count_commits = progress.add_child("Traverse commit graph");
         count_commits.init(None, git_features::progress::count("Commit"));
-        let db = git_odb::linked::Db::at(&repo_objects_dir)?;
+        let db = &repo.odb;
         let mut pack_cache = git_odb::pack::cache::Never;
         let mut commits = Vec::<Vec<u8>>::default();
         for (idx, commit) in commit::Ancestors::new(Some(commit_id), commit::ancestors::State::default(), |oid, buf| {
@@ -38,6 +38,7 @@ mod protocol;
 pub use protocol::Protocol;
 
 pub mod commitgraph;
+pub mod fsck;
 #[cfg(feature = "estimate-hours")]
 pub mod hours;
 #[cfg(feature = "organize")]
@@ -78,6 +78,7 @@ pub fn from_pack(
         thread_limit: ctx.thread_limit,
         iteration_mode: ctx.iteration_mode.into(),
         index_kind: pack::index::Version::default(),
+        hash_kind: git_hash::Kind::Sha1,
     };
     let out = ctx.out;
     let format = ctx.format;
@@ -85,11 +86,25 @@ pub fn from_pack(
         Some(pack) => {
             let pack_len = pack.metadata()?.len();
             let pack_file = fs::File::open(pack)?;
-            pack::Bundle::write_to_directory_eagerly(pack_file, Some(pack_len), directory, progress, options)
+            pack::Bundle::write_to_directory_eagerly(
+                pack_file,
+                Some(pack_len),
+                directory,
+                progress,
+                None::<git_odb::linked::Db>,
+                options,
+            )
         }
         None => {
             let stdin = io::stdin();
-            pack::Bundle::write_to_directory_eagerly(stdin, None, directory, progress, options)
+            pack::Bundle::write_to_directory_eagerly(
+                stdin,
+                None,
+                directory,
+                progress,
+                None::<git_odb::linked::Db>,
+                options,
+            )
         }
     }
     .with_context(|| "Failed to write pack and index")?;
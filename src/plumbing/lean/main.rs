@@ -134,5 +134,19 @@ pub fn main() -> Result<()> {
             )
             .map(|_| ())
         }
+        SubCommands::Fsck(options::Fsck { path }) => {
+            let (_handle, progress) = prepare(verbose, "fsck", core::fsck::PROGRESS_RANGE);
+            core::fsck::repository(
+                path,
+                DoOrDiscard::from(progress),
+                core::fsck::Context {
+                    format: core::OutputFormat::Human,
+                    out: stdout(),
+                    err: stderr(),
+                    thread_limit,
+                },
+            )
+            .map(|_| ())
+        }
     }
 }
@@ -33,6 +33,7 @@ pub enum SubCommands {
     RemoteRefList(RemoteRefList),
     PackReceive(PackReceive),
     CommitGraphVerify(CommitGraphVerify),
+    Fsck(Fsck),
 }
 
 /// Create an index from a packfile.
@@ -202,3 +203,12 @@ pub struct CommitGraphVerify {
     #[argh(switch, short = 's')]
     pub statistics: bool,
 }
+
+/// Check the objects of a repository for problems and verify their connectivity
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "fsck")]
+pub struct Fsck {
+    /// the path to the repository or its '.git' directory.
+    #[argh(positional, default = "PathBuf::from(\".\")")]
+    pub path: PathBuf,
+}
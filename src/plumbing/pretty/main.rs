@@ -170,6 +170,26 @@ pub fn main() -> Result<()> {
             },
         )
         .map(|_| ()),
+        Subcommands::Fsck { path } => prepare_and_run(
+            "fsck",
+            verbose,
+            progress,
+            progress_keep_open,
+            core::fsck::PROGRESS_RANGE,
+            move |progress, out, err| {
+                core::fsck::repository(
+                    path,
+                    git_features::progress::DoOrDiscard::from(progress),
+                    core::fsck::Context {
+                        format,
+                        out,
+                        err,
+                        thread_limit,
+                    },
+                )
+            },
+        )
+        .map(|_| ()),
     }?;
     Ok(())
 }
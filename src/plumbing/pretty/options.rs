@@ -197,4 +197,12 @@ pub enum Subcommands {
         #[clap(long, short = 's')]
         statistics: bool,
     },
+    /// Check the objects of a repository for problems and verify their connectivity, similar to `git fsck`
+    #[clap(setting = AppSettings::ColoredHelp)]
+    #[clap(setting = AppSettings::DisableVersion)]
+    Fsck {
+        /// The path to the repository or its '.git' directory.
+        #[clap(parse(from_os_str), default_value = ".")]
+        path: PathBuf,
+    },
 }